@@ -1,8 +1,9 @@
 use crate::{ActionUndo, ActionUndoType, BaseTaskAction, TaskAction, impl_taskaction_from_base};
 use bee_core::Printer;
+use bee_core::config::get_config;
 use bee_core::task::{Task, TaskData, TaskProperties, TaskStatus};
 
-use log::info;
+use log::{debug, info};
 
 #[derive(Default)]
 pub struct AddTaskAction {
@@ -13,7 +14,19 @@ impl TaskAction for AddTaskAction {
     impl_taskaction_from_base!();
     fn do_action(&mut self, printer: &dyn Printer) -> Result<(), String> {
         info!("Performing AddTaskAction");
-        let props = TaskProperties::from(&self.base.arguments)?;
+        // The default properties of the active context come first, so that the
+        // arguments override them
+        let mut arguments = Vec::new();
+        if let Some(name) = self.base.get_context()
+            && let Some(context) = get_config().contexts.get(name)
+            && !context.defaults.is_empty()
+        {
+            debug!("Adding the defaults of context '{}'", name);
+            arguments.push(context.defaults.clone());
+        }
+        arguments.extend(self.base.arguments.iter().cloned());
+        let props =
+            TaskProperties::from_with_clock(&arguments, self.base.tasks.get_clock().clone())?;
 
         // Clone here to avoid having multiple mutable borrows
         let new_task: Task = self
@@ -35,10 +48,8 @@ impl TaskAction for AddTaskAction {
             ));
         }
 
-        self.base.undos.push(ActionUndo {
-            action_type: ActionUndoType::Add,
-            tasks: vec![new_task.to_owned()],
-        });
+        self.base
+            .push_undo(ActionUndoType::Add, "add", vec![new_task.to_owned()]);
         Ok(())
     }
 }
@@ -46,7 +57,8 @@ impl TaskAction for AddTaskAction {
 impl AddTaskAction {
     pub fn get_command_description() -> String {
         r#"Add a new task
-<arguments> will define the task's summary, and potentially its properties as well
+<arguments> will define the task's summary, and potentially its properties as well.
+The default properties of the active context are added to them.
 "#
         .to_string()
     }
@@ -43,10 +43,8 @@ impl TaskAction for AnnotateTaskAction {
                 }
             }
         }
-        self.base.undos.push(ActionUndo {
-            action_type: super::ActionUndoType::Modify,
-            tasks: undos,
-        });
+        self.base
+            .push_undo(super::ActionUndoType::Modify, "annotate", undos);
         Ok(())
     }
 }
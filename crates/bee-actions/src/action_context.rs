@@ -0,0 +1,184 @@
+use crate::{ActionUndo, BaseTaskAction, TaskAction, impl_taskaction_from_base};
+
+use bee_core::Printer;
+use bee_core::config::get_config;
+use bee_core::task::TaskData;
+
+use log::info;
+
+/// Show, list, set or clear the active context. The new active context is
+/// kept in the base action, the caller is responsible for persisting it.
+#[derive(Default)]
+pub struct ContextTaskAction {
+    pub base: BaseTaskAction,
+}
+
+impl TaskAction for ContextTaskAction {
+    impl_taskaction_from_base!();
+    fn do_action(&mut self, printer: &dyn Printer) -> Result<(), String> {
+        info!("Performing ContextTaskAction");
+        let arguments = self.base.arguments.clone();
+        let arguments: Vec<&str> = arguments.iter().map(|a| a.as_str()).collect();
+        match arguments.as_slice() {
+            [] | ["show"] => printer.show_information_message(&self.describe_active_context()),
+            ["list"] => printer.show_information_message(&self.list_contexts()),
+            ["clear"] | ["none"] => {
+                self.base.set_context(None);
+                printer.show_information_message("Context cleared.");
+            }
+            ["set", name] | [name] => {
+                if !get_config().contexts.contains_key(*name) {
+                    return Err(format!(
+                        "The context '{}' is not defined. Contexts are defined in the \
+                        [core.context] section of the configuration.",
+                        name
+                    ));
+                }
+                self.base.set_context(Some(name.to_string()));
+                printer.show_information_message(&format!("Context '{}' set.", name));
+            }
+            _ => {
+                return Err(format!(
+                    "Invalid arguments for context: '{}'.",
+                    self.base.arguments.join(" ")
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ContextTaskAction {
+    pub fn get_command_description() -> String {
+        r#"Manage the active context. The filter of the active context is added to the
+filter of every command, and its default properties are given to the added tasks.
+Contexts are defined in the [core.context] section of the configuration.
+<arguments> can be:
+- nothing or 'show' to show the active context
+- 'list' to list the contexts
+- '<name>' or 'set <name>' to activate a context
+- 'clear' or 'none' to deactivate the active context
+<filter> will be ignored.
+"#
+        .to_string()
+    }
+
+    fn describe_active_context(&self) -> String {
+        match self.base.get_context() {
+            Some(name) => match get_config().contexts.get(name) {
+                Some(context) => format!("Context '{}' is active: {}", name, context.filter),
+                None => format!("Context '{}' is active but is not defined.", name),
+            },
+            None => "No context is active.".to_string(),
+        }
+    }
+
+    fn list_contexts(&self) -> String {
+        let contexts = &get_config().contexts;
+        if contexts.is_empty() {
+            return "No context is defined.".to_string();
+        }
+        contexts
+            .iter()
+            .map(|(name, context)| {
+                let marker = if self.base.get_context().as_deref() == Some(name.as_str()) {
+                    "*"
+                } else {
+                    " "
+                };
+                let mut line = format!("{} {}: {}", marker, name, context.filter);
+                if !context.defaults.is_empty() {
+                    line += &format!(" (adds '{}')", context.defaults);
+                }
+                line
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use all_asserts::*;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    use super::*;
+    use bee_core::config::ReportConfig;
+    use bee_core::task::Task;
+
+    #[derive(Default)]
+    struct MockPrinter {
+        messages: RefCell<Vec<String>>,
+    }
+
+    impl Printer for MockPrinter {
+        fn show_help(
+            &self,
+            _help_section_description: &HashMap<String, String>,
+        ) -> Result<(), String> {
+            Ok(())
+        }
+        fn print_task_info(&self, _task: &Task, _: Vec<&Task>) -> Result<(), String> {
+            Ok(())
+        }
+        fn print_raw(&self, _: &str) {}
+        fn show_information_message(&self, message: &str) {
+            self.messages.borrow_mut().push(message.to_owned());
+        }
+        fn error(&self, _: &str) {}
+
+        fn print_list_of_tasks(&self, _: Vec<&Task>, _: &ReportConfig) -> Result<(), String> {
+            Err("Not implemented".to_string())
+        }
+    }
+
+    fn run(context: Option<&str>, arguments: &[&str]) -> (Result<(), String>, ContextTaskAction) {
+        let mut action = ContextTaskAction::default();
+        action.base.set_context(context.map(|c| c.to_string()));
+        action.base.arguments = arguments.iter().map(|a| a.to_string()).collect();
+        let result = action.do_action(&MockPrinter::default());
+        (result, action)
+    }
+
+    #[test]
+    fn test_clear_context() {
+        let (result, action) = run(Some("work"), &["clear"]);
+        assert_true!(result.is_ok());
+        assert_eq!(action.get_context(), &None);
+
+        let (result, action) = run(Some("work"), &["none"]);
+        assert_true!(result.is_ok());
+        assert_eq!(action.get_context(), &None);
+    }
+
+    #[test]
+    fn test_set_undefined_context() {
+        let (result, action) = run(Some("work"), &["undefined"]);
+        assert_true!(result.is_err());
+        assert_eq!(action.get_context(), &Some("work".to_string()));
+
+        let (result, _) = run(None, &["set", "undefined"]);
+        assert_true!(result.is_err());
+
+        let (result, _) = run(None, &["set", "home", "work"]);
+        assert_true!(result.is_err());
+    }
+
+    #[test]
+    fn test_show_context() {
+        let printer = MockPrinter::default();
+        let mut action = ContextTaskAction::default();
+        action.do_action(&printer).unwrap();
+        action.base.set_context(Some("undefined".to_string()));
+        action.base.arguments = vec!["show".to_string()];
+        action.do_action(&printer).unwrap();
+        assert_eq!(
+            printer.messages.into_inner(),
+            vec![
+                "No context is active.".to_string(),
+                "Context 'undefined' is active but is not defined.".to_string(),
+            ]
+        );
+    }
+}
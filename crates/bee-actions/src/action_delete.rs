@@ -50,33 +50,12 @@ impl TaskAction for DeleteTaskAction {
             }
         }
         if !undos.is_empty() {
-            let mut extra_uuids: Vec<Uuid> = [
-                undos
-                    .values()
-                    .flat_map(|t| t.get_extra_uuid())
-                    .collect::<Vec<_>>(),
-                self.base
-                    .tasks
-                    .get_extra_tasks()
-                    .keys()
-                    .map(|uuid| uuid.to_owned())
-                    .collect::<Vec<_>>(),
-            ]
-            .concat();
-            extra_uuids.sort_unstable();
-            extra_uuids.dedup();
-            for uuid in extra_uuids {
-                if let Some(task) = self.base.tasks.get_extra_tasks().get(&uuid) {
-                    // Do not overwrite the tasks if they're already in the undos
-                    if undos.contains_key(&uuid) {
-                        undos.insert(uuid.to_owned(), task.to_owned());
-                    }
-                }
-            }
-            self.base.undos.push(ActionUndo {
-                action_type: super::ActionUndoType::Modify,
-                tasks: undos.into_values().collect(),
-            });
+            self.base.add_linked_tasks_to_undos(&mut undos);
+            self.base.push_undo(
+                super::ActionUndoType::Modify,
+                "delete",
+                undos.into_values().collect(),
+            );
         }
         Ok(())
     }
@@ -113,7 +92,7 @@ mod tests {
         ) -> Result<(), String> {
             Ok(())
         }
-        fn print_task_info(&self, _task: &Task) -> Result<(), String> {
+        fn print_task_info(&self, _task: &Task, _: Vec<&Task>) -> Result<(), String> {
             Ok(())
         }
         fn print_raw(&self, _: &str) {}
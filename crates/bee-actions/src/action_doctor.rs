@@ -0,0 +1,130 @@
+use log::info;
+
+use crate::{ActionUndo, ActionUndoType, BaseTaskAction, TaskAction, impl_taskaction_from_base};
+
+use bee_core::Printer;
+use bee_core::task::{Task, TaskData};
+
+/// Check that the stored tasks are consistent, and repair what can be repaired.
+/// The tasks are given to this action as they are stored, without being checked.
+#[derive(Default)]
+pub struct DoctorTaskAction {
+    pub base: BaseTaskAction,
+}
+
+impl TaskAction for DoctorTaskAction {
+    impl_taskaction_from_base!();
+    fn do_action(&mut self, printer: &dyn Printer) -> Result<(), String> {
+        info!("Performing DoctorTaskAction");
+        let tasks_before = self.base.tasks.clone();
+        let repairs = self.base.tasks.repair_dangling_links();
+        for repair in &repairs {
+            printer.show_information_message(&format!("Repaired: {}", repair));
+        }
+
+        // Whatever is still wrong cannot be repaired automatically
+        self.base.tasks.clone().upkeep().map_err(|e| {
+            format!(
+                "{}\nThis problem cannot be repaired automatically, the data file has to be fixed by hand.",
+                e
+            )
+        })?;
+
+        if repairs.is_empty() {
+            printer.show_information_message("No problem found.");
+            return Ok(());
+        }
+
+        let changed_tasks: Vec<Task> = tasks_before
+            .get_task_map()
+            .values()
+            .filter(|t| self.base.tasks.get_task_map().get(t.get_uuid()) != Some(*t))
+            .cloned()
+            .collect();
+        self.base
+            .push_undo(ActionUndoType::Modify, "doctor", changed_tasks);
+        if repairs.len() == 1 {
+            printer.show_information_message("Repaired 1 problem.");
+        } else {
+            printer.show_information_message(&format!("Repaired {} problems.", repairs.len()));
+        }
+        Ok(())
+    }
+}
+
+impl DoctorTaskAction {
+    pub fn get_command_description() -> String {
+        r#"Check that the tasks are consistent, and repair them.
+The dependencies on tasks that do not exist are removed.
+Both the <filter> and the <arguments> are ignored.
+"#
+        .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use all_asserts::*;
+    use std::collections::HashMap;
+
+    use super::*;
+    use bee_core::config::ReportConfig;
+    use bee_core::task::{TaskProperties, TaskStatus};
+    use uuid::Uuid;
+
+    struct MockPrinter;
+
+    impl Printer for MockPrinter {
+        fn show_help(
+            &self,
+            _help_section_description: &HashMap<String, String>,
+        ) -> Result<(), String> {
+            Ok(())
+        }
+        fn print_task_info(&self, _task: &Task, _: Vec<&Task>) -> Result<(), String> {
+            Ok(())
+        }
+        fn print_raw(&self, _: &str) {}
+        fn show_information_message(&self, _message: &str) {}
+        fn error(&self, _: &str) {}
+
+        fn print_list_of_tasks(&self, _: Vec<&Task>, _: &ReportConfig) -> Result<(), String> {
+            Err("Not implemented".to_string())
+        }
+    }
+
+    #[test]
+    fn test_doctor_repairs_dangling_dependency() {
+        let mut tasks = TaskData::default();
+        tasks
+            .add_task(
+                &TaskProperties::from(&["broken task".to_owned()]).unwrap(),
+                TaskStatus::Pending,
+            )
+            .unwrap();
+        let mut json = serde_json::to_value(&tasks).unwrap();
+        json[0]["depends_on"] = serde_json::json!([Uuid::new_v4()]);
+        let tasks: TaskData = serde_json::from_value(json).unwrap();
+        assert_true!(tasks.clone().upkeep().is_err());
+
+        let mut action = DoctorTaskAction::default();
+        action.base.tasks = tasks;
+        action.do_action(&MockPrinter).unwrap();
+
+        let repaired = action.get_tasks().to_vec()[0];
+        assert_true!(repaired.get_depends().is_empty());
+        assert_eq!(action.get_undos().len(), 1);
+        assert_eq!(action.get_undos()[0].tasks.len(), 1);
+        assert_true!(action.get_tasks().clone().upkeep().is_ok());
+
+        // Nothing is left to repair
+        let mut action = DoctorTaskAction {
+            base: BaseTaskAction {
+                tasks: action.get_tasks().clone(),
+                ..Default::default()
+            },
+        };
+        action.do_action(&MockPrinter).unwrap();
+        assert_true!(action.get_undos().is_empty());
+    }
+}
@@ -4,6 +4,7 @@ use uuid::Uuid;
 use crate::{ActionUndo, BaseTaskAction, TaskAction, impl_taskaction_from_base};
 
 use bee_core::Printer;
+use bee_core::config::get_config;
 
 use bee_core::task::{Task, TaskData};
 use std::collections::HashMap;
@@ -17,22 +18,56 @@ impl TaskAction for DoneTaskAction {
     impl_taskaction_from_base!();
     fn do_action(&mut self, p: &dyn Printer) -> Result<(), String> {
         info!("Performing DoneTaskAction");
+        self.complete_tasks(p, get_config().complete_parents)
+    }
+}
+
+impl DoneTaskAction {
+    pub fn get_command_description() -> String {
+        r#"Complete a task
+If 'complete_parents' is set in the configuration, a task is completed as well
+once all of its subtasks are completed.
+<arguments> are ignored for this action
+"#
+        .to_string()
+    }
+
+    /// Complete the tasks, along with the parents whose subtasks are then all
+    /// completed if @complete_parents is set
+    fn complete_tasks(&mut self, p: &dyn Printer, complete_parents: bool) -> Result<(), String> {
         if self.base.tasks.get_task_map().is_empty() {
             p.show_information_message(" No task to complete.");
             return Ok(());
         }
 
         let mut undos: HashMap<Uuid, Task> = HashMap::default();
-        let uuids_to_complete: Vec<Uuid> = self
+        let mut uuids_to_complete: Vec<Uuid> = self
             .base
             .tasks
             .get_task_map()
             .keys()
             .map(|u| u.to_owned())
             .collect();
-        for uuid in uuids_to_complete {
+        while let Some(uuid) = uuids_to_complete.pop() {
             let task_before = self.base.tasks.get_task_map().get(&uuid).unwrap().clone();
-            self.base.tasks.task_done(&uuid);
+            if let Some(next_instance) = self.base.tasks.task_done(&uuid).cloned() {
+                // Its ID is only known once the tasks are renumbered, after the
+                // completed task has lost its own
+                p.show_information_message(&format!(
+                    "Created task {} as the next instance of recurring task '{}'{}.",
+                    next_instance.get_uuid(),
+                    next_instance.get_summary(),
+                    match next_instance.get_date_due() {
+                        Some(due) => format!(", due {}", due.format("%Y-%m-%d")),
+                        None => String::new(),
+                    }
+                ));
+                // The next instance didn't exist before this action. Undoing
+                // it should then delete it.
+                let mut next_instance_before = next_instance.to_owned();
+                next_instance_before.delete(self.base.tasks.get_clock().now());
+                undos.insert(next_instance.get_uuid().to_owned(), next_instance_before);
+            }
             let t = self
                 .base
                 .tasks
@@ -54,50 +89,29 @@ impl TaskAction for DoneTaskAction {
                     p.show_information_message(&format!("Completed Task '{}'.", t.get_summary()));
                 }
             }
+
+            if complete_parents
+                && let Some(parent) = self.base.tasks.get_parent_to_complete(&uuid)
+                && !uuids_to_complete.contains(&parent)
+            {
+                // The parent is usually only loaded because it is linked to the task
+                self.base.tasks.promote_extra_task(&parent);
+                uuids_to_complete.push(parent);
+            }
         }
 
         if !undos.is_empty() {
-            let mut extra_uuids: Vec<Uuid> = [
-                undos
-                    .values()
-                    .flat_map(|t| t.get_extra_uuid())
-                    .collect::<Vec<_>>(),
-                self.base
-                    .tasks
-                    .get_extra_tasks()
-                    .keys()
-                    .map(|uuid| uuid.to_owned())
-                    .collect::<Vec<_>>(),
-            ]
-            .concat();
-            extra_uuids.sort_unstable();
-            extra_uuids.dedup();
-            for uuid in extra_uuids {
-                if let Some(task) = self.base.tasks.get_extra_tasks().get(&uuid) {
-                    // Do not overwrite the tasks if they're already in the undos
-                    if undos.contains_key(&uuid) {
-                        undos.insert(uuid.to_owned(), task.to_owned());
-                    }
-                }
-            }
-            self.base.undos.push(ActionUndo {
-                action_type: super::ActionUndoType::Modify,
-                tasks: undos.into_values().collect(),
-            });
+            self.base.add_linked_tasks_to_undos(&mut undos);
+            self.base.push_undo(
+                super::ActionUndoType::Modify,
+                "done",
+                undos.into_values().collect(),
+            );
         }
         Ok(())
     }
 }
 
-impl DoneTaskAction {
-    pub fn get_command_description() -> String {
-        r#"Complete a task
-<arguments> are ignored for this action
-"#
-        .to_string()
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use all_asserts::*;
@@ -120,7 +134,7 @@ mod tests {
         ) -> Result<(), String> {
             Ok(())
         }
-        fn print_task_info(&self, _task: &Task) -> Result<(), String> {
+        fn print_task_info(&self, _task: &Task, _: Vec<&Task>) -> Result<(), String> {
             Ok(())
         }
         fn print_raw(&self, _: &str) {}
@@ -187,6 +201,143 @@ mod tests {
         assert_eq!(action.base.undos.first().unwrap().tasks.len(), 2);
     }
 
+    #[test]
+    fn test_do_action_with_recurring_task() {
+        init();
+        let mut action = DoneTaskAction::default();
+        let printer = MockPrinter;
+
+        let mut tasks = TaskData::default();
+        let task = tasks
+            .add_task(
+                &TaskProperties::from(&["water the plants recur:weekly due:today".to_owned()])
+                    .unwrap(),
+                TaskStatus::Pending,
+            )
+            .unwrap()
+            .clone();
+        action.base.tasks = tasks;
+
+        let result = action.do_action(&printer);
+        assert!(result.is_ok());
+
+        let task_map = action.base.tasks.get_task_map();
+        assert_eq!(task_map.len(), 2);
+        let next_instance = task_map
+            .values()
+            .find(|t| t.get_uuid() != task.get_uuid())
+            .unwrap();
+        assert_eq!(next_instance.get_status(), &TaskStatus::Pending);
+        assert_eq!(next_instance.get_summary(), task.get_summary());
+        assert_eq!(
+            next_instance.get_recurrence_template(),
+            &Some(task.get_uuid().to_owned())
+        );
+
+        // Undoing the action should delete the new instance
+        assert_eq!(action.base.undos.len(), 1);
+        let undo_tasks = &action.base.undos.first().unwrap().tasks;
+        assert_eq!(undo_tasks.len(), 2);
+        assert_eq!(
+            undo_tasks
+                .iter()
+                .find(|t| t.get_uuid() == next_instance.get_uuid())
+                .unwrap()
+                .get_status(),
+            &TaskStatus::Deleted
+        );
+    }
+
+    #[test]
+    fn test_do_action_undo_captures_linked_tasks() {
+        init();
+        let mut tasks = TaskData::default();
+        let mut uuids: Vec<Uuid> = Vec::new();
+        for summary in ["first", "second", "third"] {
+            let mut arguments = summary.to_owned();
+            if let Some(previous) = uuids.last() {
+                arguments += &format!(" depends:{}", previous);
+            }
+            let task = tasks
+                .add_task(
+                    &TaskProperties::from(&[arguments]).unwrap(),
+                    TaskStatus::Pending,
+                )
+                .unwrap();
+            uuids.push(*task.get_uuid());
+        }
+        tasks.upkeep().unwrap();
+
+        // Only the first task is selected, the others are loaded as extra tasks
+        let filter = bee_core::filters::from(&[uuids[0].to_string()]).unwrap();
+        let mut action = DoneTaskAction::default();
+        action.base.tasks = tasks.filter(&filter);
+        assert_eq!(action.base.tasks.get_extra_tasks().len(), 2);
+        action.do_action(&MockPrinter).unwrap();
+
+        // The second task stops depending on the first one once written, so
+        // undoing must restore it too. The third one is left untouched.
+        let mut undo_uuids: Vec<Uuid> = action.base.undos[0]
+            .tasks
+            .iter()
+            .map(|t| *t.get_uuid())
+            .collect();
+        undo_uuids.sort_by_key(|uuid| uuids.iter().position(|u| u == uuid));
+        assert_eq!(undo_uuids, vec![uuids[0], uuids[1]]);
+    }
+
+    #[test]
+    fn test_do_action_completes_parents() {
+        init();
+        let mut tasks = TaskData::default();
+        let mut add_task = |arguments: String| {
+            *tasks
+                .add_task(
+                    &TaskProperties::from(&[arguments]).unwrap(),
+                    TaskStatus::Pending,
+                )
+                .unwrap()
+                .get_uuid()
+        };
+        let parent = add_task("release".to_owned());
+        let first = add_task(format!("build parent:{}", parent));
+        let second = add_task(format!("document parent:{}", parent));
+        tasks.upkeep().unwrap();
+
+        let complete = |tasks: &TaskData, uuid: &Uuid, complete_parents: bool| {
+            let filter = bee_core::filters::from(&[uuid.to_string()]).unwrap();
+            let mut action = DoneTaskAction::default();
+            action.base.tasks = tasks.filter(&filter);
+            action
+                .complete_tasks(&MockPrinter, complete_parents)
+                .unwrap();
+            action
+        };
+
+        // The parent is left alone if the option isn't set
+        let action = complete(&tasks, &first, false);
+        assert_false!(action.base.tasks.get_task_map().contains_key(&parent));
+
+        // It is completed once its last subtask is completed
+        let action = complete(&tasks, &first, true);
+        assert_false!(action.base.tasks.get_task_map().contains_key(&parent));
+        tasks.set_task(action.base.tasks.get_task_map()[&first].to_owned());
+        let action = complete(&tasks, &second, true);
+        assert_eq!(
+            action.base.tasks.get_task_map()[&parent].get_status(),
+            &TaskStatus::Completed
+        );
+        let undo_tasks = &action.base.undos[0].tasks;
+        assert_eq!(
+            undo_tasks
+                .iter()
+                .find(|t| t.get_uuid() == &parent)
+                .unwrap()
+                .get_status(),
+            &TaskStatus::Pending
+        );
+    }
+
     #[test]
     fn test_get_command_description() {
         assert_false!(DoneTaskAction::get_command_description().is_empty());
@@ -18,16 +18,90 @@ pub struct EditTaskAction {
 use tempfile::Builder;
 use uuid::Uuid;
 
-fn create_and_edit_json_file(input_serialised_tasks: &str) -> Result<TaskData, String> {
+const TASK_MARKER: &str = "<!-- bee:task -->";
+const DESCRIPTION_MARKER: &str = "<!-- bee:description -->";
+
+const FILE_HEADER: &str = "<!--
+Edit the tasks below. Each task is a JSON block followed by its description,
+written in markdown until the next task. Leave a description empty to remove it.
+Do not modify the markers nor the UUIDs.
+-->
+";
+
+/// Write the tasks as a markdown document. The description of a task is taken
+/// out of its JSON so it can be edited as plain markdown.
+fn tasks_to_markdown(tasks: &TaskData) -> Result<String, String> {
+    let serde_json::Value::Array(tasks) = serde_json::to_value(tasks).map_err(|e| e.to_string())?
+    else {
+        return Err("The tasks could not be serialised.".to_string());
+    };
+
+    let mut output = FILE_HEADER.to_string();
+    for mut task in tasks {
+        let description = task
+            .as_object_mut()
+            .and_then(|task| task.remove("description"))
+            .and_then(|description| description.as_str().map(|d| d.to_owned()))
+            .unwrap_or_default();
+        output += &format!(
+            "\n{}\n```json\n{}\n```\n{}\n{}\n",
+            TASK_MARKER,
+            serde_json::to_string_pretty(&task).map_err(|e| e.to_string())?,
+            DESCRIPTION_MARKER,
+            description
+        );
+    }
+    Ok(output)
+}
+
+/// Read back the tasks written by `tasks_to_markdown`
+fn tasks_from_markdown(input: &str) -> Result<TaskData, String> {
+    let mut tasks: Vec<serde_json::Value> = Vec::new();
+    // Everything before the first task is the header
+    for (index, section) in input.split(TASK_MARKER).enumerate().skip(1) {
+        let (json, description) = section.split_once(DESCRIPTION_MARKER).ok_or(format!(
+            "The description marker of the task #{} is missing.",
+            index
+        ))?;
+        let json = json
+            .trim()
+            .strip_prefix("```json")
+            .and_then(|json| json.strip_suffix("```"))
+            .ok_or(format!(
+                "The JSON of the task #{} must be in a ```json block.",
+                index
+            ))?;
+
+        let mut task: serde_json::Value = serde_json::from_str(json)
+            .map_err(|e| format!("Could not parse the task #{}: {}", index, e))?;
+        let description = description.trim();
+        task.as_object_mut()
+            .ok_or(format!("The task #{} must be a JSON object.", index))?
+            .insert(
+                "description".to_string(),
+                if description.is_empty() {
+                    serde_json::Value::Null
+                } else {
+                    serde_json::Value::String(description.to_owned())
+                },
+            );
+        tasks.push(task);
+    }
+
+    serde_json::from_value(serde_json::Value::Array(tasks))
+        .map_err(|e| format!("Could not parse the modified file: {}", e))
+}
+
+fn create_and_edit_file(input_tasks: &TaskData) -> Result<TaskData, String> {
     // Create a temporary file path
     let mut temp_file = Builder::new()
-        .suffix(".json")
+        .suffix(".md")
         .tempfile()
         .map_err(|e| e.to_string())?;
 
-    // Write some initial JSON content to the file
+    // Write the tasks to the file
     temp_file
-        .write_all(input_serialised_tasks.as_bytes())
+        .write_all(tasks_to_markdown(input_tasks)?.as_bytes())
         .map_err(|e| format!("Failed to write to file: {}", e))?;
 
     // Determine the editor to use
@@ -43,22 +117,23 @@ fn create_and_edit_json_file(input_serialised_tasks: &str) -> Result<TaskData, S
         return Err("Editor exited with an error".to_string());
     }
 
-    let task_data: TaskData = serde_json::from_str(
+    tasks_from_markdown(
         &fs::read_to_string(temp_file).map_err(|e| format!("Failed to read file: {}", e))?,
     )
-    .map_err(|e| format!("Could not parse the modified file: {}", e))?;
-
-    Ok(task_data)
 }
 
 /// This will construct a TaskProperties that will only contain the fields
-/// that we are allowing to be modified (summary, annotations, tags)
+/// that we are allowing to be modified (summary, description, annotations, tags)
 fn get_task_property(old_task: &Task, new_task: &Task) -> TaskProperties {
     let mut props = TaskProperties::default();
     if new_task.get_summary() != old_task.get_summary() {
         props.set_summary(new_task.get_summary());
     }
 
+    if new_task.get_description() != old_task.get_description() {
+        props.set_description(new_task.get_description().as_deref().unwrap_or_default());
+    }
+
     if new_task.get_tags() != old_task.get_tags() {
         let to_add: Vec<String> = new_task
             .get_tags()
@@ -141,10 +216,11 @@ Don't do that."
         }
 
         if !undos.is_empty() {
-            self.base.undos.push(ActionUndo {
-                action_type: super::ActionUndoType::Modify,
-                tasks: undos.into_values().collect(),
-            });
+            self.base.push_undo(
+                super::ActionUndoType::Modify,
+                "edit",
+                undos.into_values().collect(),
+            );
         }
 
         Ok(())
@@ -155,9 +231,7 @@ impl TaskAction for EditTaskAction {
     impl_taskaction_from_base!();
 
     fn do_action(&mut self, printer: &dyn Printer) -> Result<(), String> {
-        let new_tasks = create_and_edit_json_file(
-            &serde_json::to_string_pretty(self.base.get_tasks()).unwrap(),
-        )?;
+        let new_tasks = create_and_edit_file(self.base.get_tasks())?;
 
         self.do_action_impl(printer, new_tasks)
     }
@@ -166,8 +240,11 @@ impl TaskAction for EditTaskAction {
 impl EditTaskAction {
     pub fn get_command_description() -> String {
         r#"Edit one or more tasks using an editor.
+The tasks are shown in a markdown file, each one as a JSON block followed by its
+description in markdown.
 Only some fields can be edited (although all show in the JSON). The editable fields are:
 - Summary
+- description
 - annotations
 - tags
 
@@ -198,7 +275,7 @@ mod tests {
         ) -> Result<(), String> {
             Ok(())
         }
-        fn print_task_info(&self, _task: &Task) -> Result<(), String> {
+        fn print_task_info(&self, _task: &Task, _: Vec<&Task>) -> Result<(), String> {
             Ok(())
         }
         fn print_raw(&self, _: &str) {}
@@ -349,6 +426,71 @@ mod tests {
         assert_eq!(action.base.undos.len(), 0);
     }
 
+    #[test]
+    fn test_markdown_round_trip() {
+        let mut tasks = TaskData::default();
+        let mut props = TaskProperties::from(&["a task +tag".to_owned()]).unwrap();
+        props.set_description("# Notes\n\n- first\n- second");
+        let task1 = tasks.add_task(&props, TaskStatus::Pending).unwrap().clone();
+        let task2 = tasks
+            .add_task(
+                &TaskProperties::from(&["another task".to_owned()]).unwrap(),
+                TaskStatus::Pending,
+            )
+            .unwrap()
+            .clone();
+
+        let markdown = tasks_to_markdown(&tasks).unwrap();
+        assert_true!(markdown.contains("\n# Notes\n\n- first\n- second\n"));
+        assert_false!(markdown.contains("\"description\""));
+
+        let parsed = tasks_from_markdown(&markdown).unwrap();
+        assert_eq!(parsed.get_task_map().get(task1.get_uuid()), Some(&task1));
+        assert_eq!(parsed.get_task_map().get(task2.get_uuid()), Some(&task2));
+    }
+
+    #[test]
+    fn test_markdown_invalid() {
+        assert_true!(tasks_from_markdown("<!-- bee:task -->\n{}\n").is_err());
+        assert_true!(
+            tasks_from_markdown("<!-- bee:task -->\n{}\n<!-- bee:description -->\n").is_err()
+        );
+        assert_true!(tasks_from_markdown("").unwrap().get_task_map().is_empty());
+    }
+
+    #[test]
+    fn test_do_action_description() {
+        let mut action = EditTaskAction::default();
+        let printer = MockPrinter;
+
+        let mut old_tasks = TaskData::default();
+        let task1 = old_tasks
+            .add_task(
+                &TaskProperties::from(&["a task".to_owned()]).unwrap(),
+                TaskStatus::Pending,
+            )
+            .unwrap()
+            .clone();
+        action.base.tasks = old_tasks.clone();
+
+        let markdown = tasks_to_markdown(&old_tasks).unwrap().replace(
+            "<!-- bee:description -->\n",
+            "<!-- bee:description -->\nSome *details*\n",
+        );
+        let result = action.do_action_impl(&printer, tasks_from_markdown(&markdown).unwrap());
+        assert_true!(result.is_ok());
+
+        let task = action
+            .base
+            .tasks
+            .get_task_map()
+            .get(task1.get_uuid())
+            .unwrap();
+        assert_eq!(task.get_description().as_deref(), Some("Some *details*"));
+        assert_eq!(task.get_summary(), task1.get_summary());
+        assert_eq!(action.base.undos.len(), 1);
+    }
+
     #[test]
     fn test_get_command_description() {
         assert_false!(EditTaskAction::get_command_description().is_empty());
@@ -1,7 +1,8 @@
 use crate::{ActionUndo, BaseTaskAction, TaskAction, impl_taskaction_from_base};
 use bee_core::Printer;
 
-use bee_core::task::TaskData;
+use bee_core::task::{Task, TaskData, UrgencyTerm};
+use serde::Serialize;
 
 #[derive(Default)]
 pub struct ExportTaskAction {
@@ -11,19 +12,143 @@ pub struct ExportTaskAction {
 impl TaskAction for ExportTaskAction {
     impl_taskaction_from_base!();
     fn do_action(&mut self, printer: &dyn Printer) -> Result<(), String> {
-        printer.show_information_message(
-            &serde_json::to_string_pretty(self.base.get_tasks()).unwrap(),
-        );
+        let mut urgency_breakdown = false;
+        for argument in &self.base.arguments {
+            match argument.as_str() {
+                "urgency:breakdown" => urgency_breakdown = true,
+                _ => {
+                    return Err(format!(
+                        "Unknown argument '{}' for export, expected 'urgency:breakdown'.",
+                        argument
+                    ));
+                }
+            }
+        }
+        let output = if urgency_breakdown {
+            serde_json::to_string_pretty(&with_urgency_breakdown(self.base.get_tasks()))
+        } else {
+            serde_json::to_string_pretty(self.base.get_tasks())
+        };
+        printer.show_information_message(&output.unwrap());
         Ok(())
     }
 }
 
+/// A task as it is exported, followed by the terms of its urgency
+#[derive(Serialize)]
+struct TaskWithUrgencyBreakdown<'a> {
+    #[serde(flatten)]
+    task: &'a Task,
+    urgency_breakdown: &'a Vec<UrgencyTerm>,
+}
+
+/// The tasks in the order they are exported in, along with the terms of their urgency
+fn with_urgency_breakdown(tasks: &TaskData) -> Vec<TaskWithUrgencyBreakdown<'_>> {
+    let mut tasks: Vec<&Task> = tasks.get_task_map().values().collect();
+    tasks.sort_by_key(|t| (*t.get_date_created(), *t.get_uuid()));
+    tasks
+        .into_iter()
+        .map(|task| TaskWithUrgencyBreakdown {
+            task,
+            urgency_breakdown: task.get_urgency_terms(),
+        })
+        .collect()
+}
+
 impl ExportTaskAction {
     pub fn get_command_description() -> String {
         r#"Print the tasks as JSON format.
 This is useful for scripting access to Bee.
-Both <filters> and <arguments> are treated as filter
+Both <filters> and <arguments> are treated as filter.
+'urgency:breakdown' adds the terms of the urgency of each task as 'urgency_breakdown',
+e.g. 'bee export +work urgency:breakdown'.
 "#
         .to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use all_asserts::*;
+    use serde_json::Value;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    use super::*;
+    use bee_core::config::ReportConfig;
+    use bee_core::task::{TaskProperties, TaskStatus};
+
+    #[derive(Default)]
+    struct MockPrinter {
+        messages: RefCell<Vec<String>>,
+    }
+
+    impl Printer for MockPrinter {
+        fn show_help(
+            &self,
+            _help_section_description: &HashMap<String, String>,
+        ) -> Result<(), String> {
+            Ok(())
+        }
+        fn print_task_info(&self, _task: &Task, _: Vec<&Task>) -> Result<(), String> {
+            Ok(())
+        }
+        fn print_raw(&self, _: &str) {}
+        fn show_information_message(&self, message: &str) {
+            self.messages.borrow_mut().push(message.to_owned());
+        }
+        fn error(&self, _: &str) {}
+
+        fn print_list_of_tasks(&self, _: Vec<&Task>, _: &ReportConfig) -> Result<(), String> {
+            Err("Not implemented".to_string())
+        }
+    }
+
+    /// The exported tasks, as JSON values
+    fn export(arguments: &[&str]) -> Result<Vec<Value>, String> {
+        let mut tasks = TaskData::default();
+        tasks
+            .add_task(
+                &TaskProperties::from(&["write report priority:H".to_owned()]).unwrap(),
+                TaskStatus::Pending,
+            )
+            .unwrap();
+        tasks.upkeep().unwrap();
+
+        let mut action = ExportTaskAction::default();
+        action.base.tasks = tasks;
+        action.base.arguments = arguments.iter().map(|a| a.to_string()).collect();
+        let printer = MockPrinter::default();
+        action.do_action(&printer)?;
+        let messages = printer.messages.into_inner();
+        Ok(serde_json::from_str(&messages[0]).unwrap())
+    }
+
+    #[test]
+    fn test_export() {
+        let tasks = export(&[]).unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0]["summary"], "write report");
+        assert_false!(
+            tasks[0]
+                .as_object()
+                .unwrap()
+                .contains_key("urgency_breakdown")
+        );
+    }
+
+    #[test]
+    fn test_export_urgency_breakdown() {
+        let tasks = export(&["urgency:breakdown"]).unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0]["summary"], "write report");
+        let breakdown = tasks[0]["urgency_breakdown"].as_array().unwrap();
+        assert_true!(
+            breakdown
+                .iter()
+                .any(|term| term["name"] == "priority" && term["value"] == "H")
+        );
+
+        assert_true!(export(&["urgency:total"]).is_err());
+    }
+}
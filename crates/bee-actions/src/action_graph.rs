@@ -0,0 +1,202 @@
+use log::info;
+use uuid::Uuid;
+
+use crate::{ActionUndo, BaseTaskAction, TaskAction, impl_taskaction_from_base};
+
+use bee_core::Printer;
+use bee_core::task::{Task, TaskData, TaskStatus};
+
+use std::collections::{BTreeSet, HashMap};
+
+/// Print the dependencies between the tasks as a Graphviz graph
+#[derive(Default)]
+pub struct GraphTaskAction {
+    pub base: BaseTaskAction,
+}
+
+impl TaskAction for GraphTaskAction {
+    impl_taskaction_from_base!();
+    fn do_action(&mut self, printer: &dyn Printer) -> Result<(), String> {
+        info!("Performing GraphTaskAction");
+        printer.show_information_message(&to_dot(&self.base.tasks));
+        Ok(())
+    }
+}
+
+impl GraphTaskAction {
+    pub fn get_command_description() -> String {
+        r#"Print the tasks matched by <filter> and their dependencies as a Graphviz graph,
+in the DOT language. An arrow goes from a task to each task it depends on.
+The tasks that are not matched by <filter> but are linked to the matched ones
+are drawn with a dashed border.
+The graph can be rendered with e.g. 'bee graph | dot -Tsvg > tasks.svg'.
+<arguments> are treated as filters.
+"#
+        .to_string()
+    }
+}
+
+/// The graph of the tasks of @tasks and of the extra tasks they are directly
+/// linked to, in the DOT language
+fn to_dot(tasks: &TaskData) -> String {
+    let is_shown = |uuid: &Uuid| tasks.get_task_map().contains_key(uuid);
+    let mut nodes: Vec<(&Task, bool)> = tasks
+        .get_task_map()
+        .values()
+        .map(|task| (task, true))
+        .chain(
+            tasks
+                .get_extra_tasks()
+                .values()
+                .filter(|extra| !is_shown(extra.get_uuid()))
+                .filter(|extra| {
+                    extra
+                        .get_depends()
+                        .iter()
+                        .chain(extra.get_blocking())
+                        .any(is_shown)
+                })
+                .map(|extra| (extra, false)),
+        )
+        .collect();
+    nodes.sort_by_key(|(task, _)| (*task.get_date_created(), *task.get_uuid()));
+    let node_positions: HashMap<&Uuid, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(position, (task, _))| (task.get_uuid(), position))
+        .collect();
+
+    // A dependency is stored on both of its ends, it is only drawn once
+    let mut edges = BTreeSet::new();
+    for (task, _) in &nodes {
+        let links = task
+            .get_depends()
+            .iter()
+            .map(|dependency| (task.get_uuid(), dependency))
+            .chain(
+                task.get_blocking()
+                    .iter()
+                    .map(|blocked| (blocked, task.get_uuid())),
+            );
+        for (from, to) in links {
+            if let (Some(from), Some(to)) = (node_positions.get(from), node_positions.get(to)) {
+                edges.insert((*from, *to));
+            }
+        }
+    }
+
+    let mut dot = "digraph tasks {\n    node [shape=box];\n".to_string();
+    for (task, matched) in &nodes {
+        dot += &format!(
+            "    \"{}\" [label=\"{}\"{}];\n",
+            task.get_uuid(),
+            escape(&get_label(task)),
+            get_node_style(task, *matched)
+        );
+    }
+    for (from, to) in edges {
+        dot += &format!(
+            "    \"{}\" -> \"{}\";\n",
+            nodes[from].0.get_uuid(),
+            nodes[to].0.get_uuid()
+        );
+    }
+    dot += "}";
+    dot
+}
+
+fn get_label(task: &Task) -> String {
+    match task.get_id() {
+        Some(id) => format!("{}: {}", id, task.get_summary()),
+        None => task.get_summary().to_owned(),
+    }
+}
+
+/// Attributes of the node of @task, depending on its status. The tasks that
+/// are not @matched by the filter have a dashed border.
+fn get_node_style(task: &Task, matched: bool) -> String {
+    let mut styles = Vec::new();
+    let mut attributes = Vec::new();
+    match task.get_status() {
+        TaskStatus::Pending => (),
+        TaskStatus::Active => {
+            styles.push("filled");
+            attributes.push("fillcolor=\"lightblue\"");
+        }
+        TaskStatus::Completed => {
+            styles.push("filled");
+            attributes.push("fillcolor=\"palegreen\"");
+        }
+        TaskStatus::Deleted => {
+            styles.push("filled");
+            attributes.push("fillcolor=\"lightgrey\"");
+            attributes.push("fontcolor=\"grey40\"");
+        }
+    }
+    if !matched {
+        styles.push("dashed");
+    }
+    let mut out = String::new();
+    if !styles.is_empty() {
+        out += &format!(", style=\"{}\"", styles.join(","));
+    }
+    for attribute in attributes {
+        out += &format!(", {}", attribute);
+    }
+    out
+}
+
+/// Escape @value to be used in a quoted DOT string
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use all_asserts::*;
+
+    use super::*;
+    use bee_core::task::TaskProperties;
+
+    fn add_task(tasks: &mut TaskData, arguments: String) -> Uuid {
+        *tasks
+            .add_task(
+                &TaskProperties::from(&[arguments]).unwrap(),
+                TaskStatus::Pending,
+            )
+            .unwrap()
+            .get_uuid()
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let mut tasks = TaskData::default();
+        let design = add_task(&mut tasks, "design \"v2\"".to_owned());
+        let build = add_task(&mut tasks, format!("build depends:{}", design));
+        let release = add_task(&mut tasks, format!("release depends:{}", build));
+        add_task(&mut tasks, "unrelated".to_owned());
+        let mut props = TaskProperties::default();
+        props.set_active_status(true);
+        tasks.apply(&build, &props).unwrap();
+        tasks.upkeep().unwrap();
+
+        let filter = bee_core::filters::from(&[build.to_string()]).unwrap();
+        let dot = to_dot(&tasks.filter(&filter));
+        assert_eq!(
+            dot,
+            format!(
+                "digraph tasks {{\n    node [shape=box];\n\
+                \x20   \"{design}\" [label=\"1: design \\\"v2\\\"\", style=\"dashed\"];\n\
+                \x20   \"{build}\" [label=\"2: build\", style=\"filled\", fillcolor=\"lightblue\"];\n\
+                \x20   \"{release}\" [label=\"3: release\", style=\"dashed\"];\n\
+                \x20   \"{build}\" -> \"{design}\";\n\
+                \x20   \"{release}\" -> \"{build}\";\n\
+                }}"
+            )
+        );
+        assert_false!(dot.contains("unrelated"));
+    }
+}
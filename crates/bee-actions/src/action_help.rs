@@ -28,6 +28,25 @@ Basic usage is:
 bee <filter> <action_name> <arguments>
 
 - <filter> restricts the tasks you will be applying the action onto.
+  Its terms can be combined with 'not', 'and', 'or' and 'xor', from the one binding
+  the tightest to the loosest. Two terms without an operator are combined with 'and',
+  and parentheses can be used to group terms, e.g. 'not (+home or project:garden)'.
+  Words search the summary, the annotations and the description of the tasks.
+  'summary:', 'annotation:' and 'description:' search a given field, and '/regex/',
+  'summary~:regex', 'annotation~:regex' or 'description~:regex' match a regular
+  expression. 'project.is:' matches a project but not its sub-projects.
+  'priority:H' matches a priority (H, M, L or none) and 'priority.above:M' the
+  tasks with a higher priority.
+  Tasks with a 'wait:' date in the future match 'status:waiting' until then. The
+  actions printing a report ('list', 'tree' and 'graph') leave them out, unless the
+  filter asks for 'status:waiting' or the report sets 'hide_waiting = false', as the
+  'waiting' report does, e.g. 'bee waiting'. The other actions reach them as any
+  other task. 'wait:none', 'due:none' and 'scheduled:none' remove these dates.
+  The user defined attributes declared in the '[core.uda]' section of the configuration
+  are set and matched with '<name>:<value>', e.g. 'estimate:2h'. 'none' unsets them, and
+  '<name>.above:' and '<name>.below:' compare their values.
+  The filters of the report, the default one unless a report is named, and the filter
+  of the active context, set with the 'context' action, are added to it.
 - <action_name> defines what action is going to be performed on those tasks.
   This can be omitted. If it is omitted, then the default action is used.
   The default action is 'list' (which lists all the tasks matching the filter).
@@ -0,0 +1,420 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    io::{self, Read},
+};
+
+use crate::{ActionUndo, ActionUndoType, BaseTaskAction, TaskAction, impl_taskaction_from_base};
+use bee_core::Printer;
+use bee_core::task::{
+    ImportOutcome, ImportedTask, Project, TaskAnnotation, TaskData, TaskPriority, TaskStatus,
+};
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use log::info;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+/// Fields computed by Taskwarrior, that are not worth keeping
+const COMPUTED_FIELDS: [&str; 2] = ["id", "urgency"];
+
+/// A task as printed by `task export`
+#[derive(Deserialize)]
+struct TaskwarriorTask {
+    uuid: Uuid,
+    description: String,
+    status: String,
+    entry: String,
+    end: Option<String>,
+    due: Option<String>,
+    wait: Option<String>,
+    scheduled: Option<String>,
+    project: Option<String>,
+    priority: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    annotations: Vec<TaskwarriorAnnotation>,
+    /// Either a list of UUIDs, or a comma separated string for older versions
+    depends: Option<Value>,
+    #[serde(flatten)]
+    other: Map<String, Value>,
+}
+
+#[derive(Deserialize)]
+struct TaskwarriorAnnotation {
+    entry: String,
+    description: String,
+}
+
+#[derive(Default)]
+pub struct ImportTaskAction {
+    pub base: BaseTaskAction,
+}
+
+impl TaskAction for ImportTaskAction {
+    impl_taskaction_from_base!();
+    fn do_action(&mut self, printer: &dyn Printer) -> Result<(), String> {
+        info!("Performing ImportTaskAction");
+        let content = match self.base.arguments.as_slice() {
+            [] => {
+                let mut content = String::new();
+                io::stdin()
+                    .read_to_string(&mut content)
+                    .map_err(|e| format!("Failed to read the standard input: {}", e))?;
+                content
+            }
+            [path] => fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read file '{}': {}", path, e))?,
+            _ => return Err("Too many arguments for import: expected a single file.".to_string()),
+        };
+
+        let tw_tasks = parse_export(&content)?;
+        let mut known_uuids: HashSet<Uuid> = tw_tasks.iter().map(|t| t.uuid).collect();
+        known_uuids.extend(self.base.tasks.get_task_map().keys());
+        known_uuids.extend(self.base.tasks.get_extra_tasks().keys());
+
+        let mut created = Vec::new();
+        let mut updated = Vec::new();
+        let mut unchanged = 0;
+        for tw_task in tw_tasks {
+            let imported = to_imported_task(tw_task, &known_uuids)?;
+            let uuid = imported.uuid;
+            match self.base.tasks.import_task(imported) {
+                ImportOutcome::Created => {
+                    created.push(self.base.tasks.get_owned(&uuid).unwrap());
+                }
+                ImportOutcome::Updated(before) => updated.push(*before),
+                ImportOutcome::Unchanged => unchanged += 1,
+            }
+        }
+
+        printer.show_information_message(&format!(
+            "Imported {} tasks: {} created, {} updated, {} unchanged.",
+            created.len() + updated.len() + unchanged,
+            created.len(),
+            updated.len(),
+            unchanged
+        ));
+
+        if !created.is_empty() {
+            self.base.push_undo(ActionUndoType::Add, "import", created);
+        }
+        if !updated.is_empty() {
+            self.base
+                .push_undo(ActionUndoType::Modify, "import", updated);
+        }
+        Ok(())
+    }
+}
+
+impl ImportTaskAction {
+    pub fn get_command_description() -> String {
+        r#"Import tasks exported from Taskwarrior with 'task export'
+<arguments> is the exported JSON file. The standard input is read when it is omitted.
+Tasks keep their UUID, so importing them again updates them instead of creating duplicates.
+Fields that have no equivalent in bee are kept with the task.
+<filter> will be ignored.
+"#
+        .to_string()
+    }
+}
+
+/// Parse the output of `task export`, which is either a JSON array or, for older
+/// versions of Taskwarrior, one JSON object per line
+fn parse_export(content: &str) -> Result<Vec<TaskwarriorTask>, String> {
+    let parse_error =
+        |e: serde_json::Error| format!("Could not parse the Taskwarrior export: {}", e);
+
+    if content.trim_start().starts_with('[') {
+        return serde_json::from_str(content).map_err(parse_error);
+    }
+    content
+        .lines()
+        .map(|line| line.trim().trim_end_matches(','))
+        .filter(|line| !line.is_empty())
+        .map(|line| serde_json::from_str(line).map_err(parse_error))
+        .collect()
+}
+
+/// Parse a Taskwarrior date, e.g. `20230525T192524Z`
+fn parse_date(value: &str) -> Result<DateTime<Local>, String> {
+    if let Ok(date) = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%SZ") {
+        return Ok(Utc.from_utc_datetime(&date).with_timezone(&Local));
+    }
+    DateTime::parse_from_rfc3339(value)
+        .map(|date| date.with_timezone(&Local))
+        .map_err(|_| format!("Invalid Taskwarrior date '{}'", value))
+}
+
+/// Convert @tw_task to a bee task. The dependencies on tasks that are not part of
+/// @known_uuids are kept as an imported field.
+fn to_imported_task(
+    tw_task: TaskwarriorTask,
+    known_uuids: &HashSet<Uuid>,
+) -> Result<ImportedTask, String> {
+    let mut imported_fields = BTreeMap::new();
+    for (name, value) in &tw_task.other {
+        if !COMPUTED_FIELDS.contains(&name.as_str()) {
+            imported_fields.insert(name.to_owned(), value.to_string());
+        }
+    }
+
+    let status = match tw_task.status.as_str() {
+        "pending" if tw_task.other.contains_key("start") => TaskStatus::Active,
+        "pending" => TaskStatus::Pending,
+        "completed" => TaskStatus::Completed,
+        "deleted" => TaskStatus::Deleted,
+        // The wait date of the task is enough for it to be waiting in bee
+        "waiting" => TaskStatus::Pending,
+        "recurring" => {
+            imported_fields.insert("status".to_owned(), Value::from(tw_task.status).to_string());
+            TaskStatus::Pending
+        }
+        _ => {
+            return Err(format!(
+                "Task {} has an unknown Taskwarrior status '{}'",
+                tw_task.uuid, tw_task.status
+            ));
+        }
+    };
+
+    // Taskwarrior priorities can be customised, only the ones bee knows are converted
+    let priority = match &tw_task.priority {
+        Some(value) => match TaskPriority::from_string(value) {
+            Ok(priority) => Some(priority),
+            Err(_) => {
+                imported_fields.insert(
+                    "priority".to_owned(),
+                    Value::from(value.as_str()).to_string(),
+                );
+                None
+            }
+        },
+        None => None,
+    };
+
+    let depends: Vec<Uuid> = match &tw_task.depends {
+        Some(Value::Array(values)) => values
+            .iter()
+            .filter_map(|v| v.as_str())
+            .map(|v| v.to_owned())
+            .collect::<Vec<String>>(),
+        Some(Value::String(values)) => values.split(',').map(|v| v.to_owned()).collect(),
+        _ => Vec::new(),
+    }
+    .iter()
+    .map(|v| {
+        Uuid::parse_str(v.trim())
+            .map_err(|_| format!("Task {} depends on an invalid UUID '{}'", tw_task.uuid, v))
+    })
+    .collect::<Result<_, _>>()?;
+    let (depends_on, unknown_depends): (Vec<Uuid>, Vec<Uuid>) = depends
+        .into_iter()
+        .partition(|uuid| known_uuids.contains(uuid));
+    if !unknown_depends.is_empty() {
+        imported_fields.insert(
+            "depends".to_owned(),
+            serde_json::to_string(&unknown_depends).unwrap(),
+        );
+    }
+
+    Ok(ImportedTask {
+        uuid: tw_task.uuid,
+        summary: tw_task.description,
+        status,
+        date_created: parse_date(&tw_task.entry)?,
+        date_completed: tw_task.end.as_deref().map(parse_date).transpose()?,
+        date_due: tw_task.due.as_deref().map(parse_date).transpose()?,
+        date_wait: tw_task.wait.as_deref().map(parse_date).transpose()?,
+        date_scheduled: tw_task.scheduled.as_deref().map(parse_date).transpose()?,
+        project: tw_task.project.map(Project::from),
+        priority,
+        tags: tw_task.tags,
+        annotations: tw_task
+            .annotations
+            .iter()
+            .map(|a| Ok(TaskAnnotation::new(&a.description, parse_date(&a.entry)?)))
+            .collect::<Result<_, String>>()?,
+        depends_on,
+        imported_fields,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use all_asserts::*;
+    use std::{collections::HashMap, io::Write};
+
+    use super::*;
+    use bee_core::config::ReportConfig;
+    use bee_core::task::Task;
+
+    struct MockPrinter;
+
+    impl Printer for MockPrinter {
+        fn show_help(
+            &self,
+            _help_section_description: &HashMap<String, String>,
+        ) -> Result<(), String> {
+            Ok(())
+        }
+        fn print_task_info(&self, _task: &Task, _: Vec<&Task>) -> Result<(), String> {
+            Ok(())
+        }
+        fn print_raw(&self, _: &str) {}
+        fn show_information_message(&self, _message: &str) {}
+        fn error(&self, _: &str) {}
+
+        fn print_list_of_tasks(&self, _: Vec<&Task>, _: &ReportConfig) -> Result<(), String> {
+            Err("Not implemented".to_string())
+        }
+    }
+
+    const EXPORT: &str = r#"[
+{"id":1,"description":"write report","entry":"20230525T192524Z","modified":"20230526T080000Z","project":"work.bee","status":"pending","tags":["main"],"uuid":"5e3a3a4e-1d8b-4f6c-9d0b-3c1c1c1c1c01","priority":"H","urgency":8.2,"depends":["5e3a3a4e-1d8b-4f6c-9d0b-3c1c1c1c1c02","5e3a3a4e-1d8b-4f6c-9d0b-3c1c1c1c1c09"]},
+{"id":0,"description":"collect data","end":"20230601T100000Z","entry":"20230520T090000Z","status":"completed","uuid":"5e3a3a4e-1d8b-4f6c-9d0b-3c1c1c1c1c02","annotations":[{"entry":"20230521T090000Z","description":"asked the team"}]},
+{"id":2,"description":"read book","entry":"20230520T090000Z","start":"20230522T090000Z","status":"pending","due":"20230701T220000Z","uuid":"5e3a3a4e-1d8b-4f6c-9d0b-3c1c1c1c1c03"}
+]"#;
+
+    fn import(tasks: TaskData, content: &str) -> ImportTaskAction {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+
+        let mut action = ImportTaskAction::default();
+        action.base.tasks = tasks;
+        action.base.arguments = vec![file.path().to_string_lossy().into_owned()];
+        action.do_action(&MockPrinter).unwrap();
+        action
+    }
+
+    #[test]
+    fn test_import_fields() {
+        let action = import(TaskData::default(), EXPORT);
+        let tasks = action.get_tasks().get_task_map();
+        assert_eq!(tasks.len(), 3);
+
+        let uuid = |last: &str| {
+            Uuid::parse_str(&format!("5e3a3a4e-1d8b-4f6c-9d0b-3c1c1c1c1c{}", last)).unwrap()
+        };
+        let report = &tasks[&uuid("01")];
+        assert_eq!(report.get_summary(), "write report");
+        assert_eq!(report.get_status(), &TaskStatus::Pending);
+        assert_eq!(
+            report.get_project().as_ref().unwrap().get_name(),
+            "work.bee"
+        );
+        assert_eq!(report.get_tags(), &vec!["main".to_owned()]);
+        assert_eq!(
+            report.get_date_created(),
+            &Utc.with_ymd_and_hms(2023, 5, 25, 19, 25, 24).unwrap()
+        );
+        // Only the dependencies on imported tasks are kept as dependencies
+        assert_eq!(report.get_depends(), &vec![uuid("02")]);
+        let fields = report.get_imported_fields();
+        assert_eq!(report.get_priority(), &Some(TaskPriority::High));
+        assert_false!(fields.contains_key("priority"));
+        assert_eq!(fields["modified"], "\"20230526T080000Z\"");
+        assert_eq!(fields["depends"], format!("[\"{}\"]", uuid("09")));
+        assert_false!(fields.contains_key("urgency"));
+        assert_false!(fields.contains_key("id"));
+
+        let data = &tasks[&uuid("02")];
+        assert_eq!(data.get_status(), &TaskStatus::Completed);
+        assert_true!(data.get_date_completed().is_some());
+        assert_eq!(data.get_annotations()[0].get_value(), "asked the team");
+
+        let book = &tasks[&uuid("03")];
+        assert_eq!(book.get_status(), &TaskStatus::Active);
+        assert_true!(book.get_date_due().is_some());
+
+        assert_eq!(action.get_undos().len(), 1);
+        assert_eq!(action.get_undos()[0].action_type, ActionUndoType::Add);
+        assert_eq!(action.get_undos()[0].tasks.len(), 3);
+    }
+
+    #[test]
+    fn test_import_is_idempotent() {
+        let action = import(TaskData::default(), EXPORT);
+        let tasks = action.get_tasks().to_owned();
+
+        let action = import(tasks.to_owned(), EXPORT);
+        assert_eq!(action.get_tasks().get_task_map(), tasks.get_task_map());
+        assert_true!(action.get_undos().is_empty());
+
+        // Only the modified task gets updated
+        let action = import(tasks, &EXPORT.replace("read book", "read two books"));
+        assert_eq!(action.get_tasks().get_task_map().len(), 3);
+        assert_eq!(action.get_undos().len(), 1);
+        assert_eq!(action.get_undos()[0].action_type, ActionUndoType::Modify);
+        assert_eq!(action.get_undos()[0].tasks[0].get_summary(), "read book");
+    }
+
+    #[test]
+    fn test_import_ids_with_same_entry() {
+        let content = r#"[
+{"description":"first","entry":"20230525T192524Z","status":"pending","uuid":"5e3a3a4e-1d8b-4f6c-9d0b-3c1c1c1c1c04"},
+{"description":"second","entry":"20230525T192524Z","status":"pending","uuid":"5e3a3a4e-1d8b-4f6c-9d0b-3c1c1c1c1c01"},
+{"description":"third","entry":"20230525T192524Z","status":"pending","uuid":"5e3a3a4e-1d8b-4f6c-9d0b-3c1c1c1c1c03"},
+{"description":"fourth","entry":"20230525T192524Z","status":"pending","uuid":"5e3a3a4e-1d8b-4f6c-9d0b-3c1c1c1c1c02"}
+]"#;
+        let id_to_uuid = |tasks: &TaskData| -> BTreeMap<usize, Uuid> {
+            tasks
+                .get_task_map()
+                .values()
+                .filter_map(|t| Some((t.get_id()?, *t.get_uuid())))
+                .collect()
+        };
+        let mut tasks = import(TaskData::default(), content).get_tasks().to_owned();
+        tasks.upkeep().unwrap();
+        let expected = id_to_uuid(&tasks);
+        assert_eq!(
+            expected.keys().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3, 4]
+        );
+
+        for _ in 0..10 {
+            tasks.upkeep().unwrap();
+            assert_eq!(id_to_uuid(&tasks), expected);
+        }
+
+        // The IDs are kept once the tasks are stored and read again, and when
+        // importing the same tasks again
+        let mut tasks: TaskData =
+            serde_json::from_str(&serde_json::to_string(&tasks).unwrap()).unwrap();
+        tasks.upkeep().unwrap();
+        assert_eq!(id_to_uuid(&tasks), expected);
+
+        let mut tasks = import(tasks, content).get_tasks().to_owned();
+        tasks.upkeep().unwrap();
+        assert_eq!(id_to_uuid(&tasks), expected);
+    }
+
+    #[test]
+    fn test_parse_export_lines() {
+        let content = r#"{"description":"first","entry":"20230525T192524Z","status":"waiting","wait":"20990101T000000Z","uuid":"5e3a3a4e-1d8b-4f6c-9d0b-3c1c1c1c1c01"}
+{"description":"second","entry":"20230525T192524Z","status":"pending","uuid":"5e3a3a4e-1d8b-4f6c-9d0b-3c1c1c1c1c02","depends":"5e3a3a4e-1d8b-4f6c-9d0b-3c1c1c1c1c01"}
+"#;
+        let tw_tasks = parse_export(content).unwrap();
+        assert_eq!(tw_tasks.len(), 2);
+
+        let known_uuids = tw_tasks.iter().map(|t| t.uuid).collect();
+        let mut imported: Vec<ImportedTask> = tw_tasks
+            .into_iter()
+            .map(|t| to_imported_task(t, &known_uuids).unwrap())
+            .collect();
+        let second = imported.pop().unwrap();
+        let first = imported.pop().unwrap();
+        assert_eq!(first.status, TaskStatus::Pending);
+        assert_eq!(
+            first.date_wait,
+            Some(parse_date("20990101T000000Z").unwrap())
+        );
+        assert_false!(first.imported_fields.contains_key("status"));
+        assert_eq!(second.depends_on, vec![first.uuid]);
+
+        assert_true!(parse_export("not json").is_err());
+        assert_true!(parse_date("2023-05-25").is_err());
+    }
+}
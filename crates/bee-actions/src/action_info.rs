@@ -11,8 +11,21 @@ pub struct InfoTaskAction {
 impl TaskAction for InfoTaskAction {
     impl_taskaction_from_base!();
     fn do_action(&mut self, printer: &dyn Printer) -> Result<(), String> {
-        for task in self.base.get_tasks().to_vec() {
-            printer.print_task_info(task)?;
+        let tasks = self.base.get_tasks();
+        for task in tasks.to_vec() {
+            // The subtasks are loaded along with the task, as extra tasks when they
+            // are not matched by the filter
+            let subtasks = task
+                .get_subtasks()
+                .iter()
+                .filter_map(|uuid| {
+                    tasks
+                        .get_task_map()
+                        .get(uuid)
+                        .or_else(|| tasks.get_extra_tasks().get(uuid))
+                })
+                .collect();
+            printer.print_task_info(task, subtasks)?;
         }
         Ok(())
     }
@@ -20,6 +20,8 @@ impl ListTaskAction {
     pub fn get_command_description() -> String {
         r#"Show a list of tasks matched by <filter>
 <arguments> are treated as filters.
+'sort:<fields>' sorts the tasks by the given fields instead of the ones of the
+report, e.g. 'sort:due+,urgency-'.
 "#
         .to_string()
     }
@@ -1,4 +1,4 @@
-use log::{debug, info};
+use log::info;
 use uuid::Uuid;
 
 use crate::{ActionUndo, BaseTaskAction, TaskAction, impl_taskaction_from_base};
@@ -16,7 +16,10 @@ impl TaskAction for ModifyTaskAction {
     impl_taskaction_from_base!();
     fn do_action(&mut self, p: &dyn Printer) -> Result<(), String> {
         info!("Performing ModifyTaskAction");
-        let props = TaskProperties::from(&self.base.arguments)?;
+        let props = TaskProperties::from_with_clock(
+            &self.base.arguments,
+            self.base.tasks.get_clock().clone(),
+        )?;
         let mut undos: HashMap<Uuid, Task> = HashMap::default();
 
         let uuids_to_modify: Vec<Uuid> = self
@@ -53,35 +56,12 @@ impl TaskAction for ModifyTaskAction {
             }
         }
         if !undos.is_empty() {
-            let mut extra_uuids: Vec<Uuid> = [
-                undos
-                    .values()
-                    .flat_map(|t| t.get_extra_uuid())
-                    .collect::<Vec<_>>(),
-                self.base
-                    .tasks
-                    .get_extra_tasks()
-                    .keys()
-                    .map(|uuid| uuid.to_owned())
-                    .collect::<Vec<_>>(),
-            ]
-            .concat();
-
-            debug!("extra UUID when modifying: {:?}", extra_uuids);
-            extra_uuids.sort_unstable();
-            extra_uuids.dedup();
-            for uuid in extra_uuids {
-                if let Some(task) = self.base.tasks.get_extra_tasks().get(&uuid) {
-                    // Do not overwrite the tasks if they're already in the undos
-                    if undos.contains_key(&uuid) {
-                        undos.insert(uuid.to_owned(), task.to_owned());
-                    }
-                }
-            }
-            self.base.undos.push(ActionUndo {
-                action_type: super::ActionUndoType::Modify,
-                tasks: undos.into_values().collect(),
-            });
+            self.base.add_linked_tasks_to_undos(&mut undos);
+            self.base.push_undo(
+                super::ActionUndoType::Modify,
+                "modify",
+                undos.into_values().collect(),
+            );
         }
         Ok(())
     }
@@ -117,7 +97,7 @@ mod tests {
         ) -> Result<(), String> {
             Ok(())
         }
-        fn print_task_info(&self, _task: &Task) -> Result<(), String> {
+        fn print_task_info(&self, _task: &Task, _: Vec<&Task>) -> Result<(), String> {
             Ok(())
         }
         fn print_raw(&self, _: &str) {}
@@ -0,0 +1,150 @@
+use crate::{
+    ActionUndo, ActionUndoType, BaseTaskAction, TaskAction, action_undo::parse_count,
+    impl_taskaction_from_base,
+};
+
+use bee_core::Printer;
+
+use bee_core::task::TaskData;
+
+#[derive(Default)]
+pub struct RedoTaskAction {
+    pub base: BaseTaskAction,
+}
+
+impl TaskAction for RedoTaskAction {
+    impl_taskaction_from_base!();
+    fn do_action(&mut self, printer: &dyn Printer) -> Result<(), String> {
+        let count = parse_count(&self.base.arguments, "redo")?;
+        let to_redo: Vec<usize> = (0..self.base.undos.len())
+            .filter(|idx| self.base.undos[*idx].undone)
+            .take(count)
+            .collect();
+        if to_redo.is_empty() {
+            printer.show_information_message("There is nothing to redo.");
+            return Ok(());
+        }
+
+        for idx in to_redo {
+            let current_undo = self.base.undos[idx].to_owned();
+            let tasks_after = match current_undo.action_type {
+                // The created task is the state right after the action
+                ActionUndoType::Add if current_undo.tasks_after.is_empty() => &current_undo.tasks,
+                _ => &current_undo.tasks_after,
+            };
+            if tasks_after.is_empty() {
+                return Err(format!(
+                    "Unable to redo {}: the state of its tasks after it was done is unknown.",
+                    current_undo.describe()
+                ));
+            }
+
+            for t in tasks_after {
+                self.base.tasks.set_task(t.to_owned());
+            }
+            self.base.undos[idx].undone = false;
+            printer.show_information_message(&format!("Redid {}", current_undo.describe()));
+        }
+        Ok(())
+    }
+}
+
+impl RedoTaskAction {
+    pub fn get_command_description() -> String {
+        r#"Redo the last undone operations
+<arguments> can be the number of operations to redo (1 by default).
+<filter> will be ignored.
+"#
+        .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use all_asserts::*;
+    use std::collections::HashMap;
+
+    use super::*;
+    use bee_core::config::ReportConfig;
+    use bee_core::task::{Task, TaskProperties, TaskStatus};
+
+    use crate::action_add::AddTaskAction;
+    use crate::action_undo::UndoTaskAction;
+
+    struct MockPrinter;
+
+    impl Printer for MockPrinter {
+        fn show_help(
+            &self,
+            _help_section_description: &HashMap<String, String>,
+        ) -> Result<(), String> {
+            Ok(())
+        }
+        fn print_task_info(&self, _task: &Task, _: Vec<&Task>) -> Result<(), String> {
+            Ok(())
+        }
+        fn print_raw(&self, _: &str) {}
+        fn show_information_message(&self, _message: &str) {}
+        fn error(&self, _: &str) {}
+
+        fn print_list_of_tasks(&self, _: Vec<&Task>, _: &ReportConfig) -> Result<(), String> {
+            Err("Not implemented".to_string())
+        }
+    }
+
+    #[test]
+    fn test_redo_add() {
+        let mut action = AddTaskAction::default();
+        action.base.arguments = vec!["new task".to_owned()];
+        action.do_action(&MockPrinter).unwrap();
+        let tasks = action.base.tasks;
+        let undos = action.base.undos;
+        let uuid = *undos[0].tasks[0].get_uuid();
+
+        let mut action = UndoTaskAction::default();
+        action.base.tasks = tasks;
+        action.base.tasks.set_undos(&undos[0].tasks);
+        action.base.undos = undos;
+        action.do_action(&MockPrinter).unwrap();
+        assert_eq!(
+            action.base.tasks.get_task_map()[&uuid].get_status(),
+            &TaskStatus::Deleted
+        );
+
+        let mut redo_action = RedoTaskAction::default();
+        redo_action.base.tasks = action.base.tasks;
+        redo_action.base.undos = action.base.undos;
+        redo_action.do_action(&MockPrinter).unwrap();
+        assert_eq!(
+            redo_action.base.tasks.get_task_map()[&uuid].get_status(),
+            &TaskStatus::Pending
+        );
+        assert_false!(redo_action.base.undos[0].undone);
+
+        // There is nothing left to redo
+        redo_action.do_action(&MockPrinter).unwrap();
+        assert_false!(redo_action.base.undos[0].undone);
+    }
+
+    #[test]
+    fn test_redo_unknown_state() {
+        let mut tasks = TaskData::default();
+        let task = tasks
+            .add_task(
+                &TaskProperties::from(&["a task".to_owned()]).unwrap(),
+                TaskStatus::Pending,
+            )
+            .unwrap()
+            .to_owned();
+
+        let mut action = RedoTaskAction::default();
+        action.base.tasks = tasks;
+        action.base.undos = vec![ActionUndo {
+            action_type: ActionUndoType::Modify,
+            tasks: vec![task],
+            undone: true,
+            ..Default::default()
+        }];
+        assert_true!(action.do_action(&MockPrinter).is_err());
+    }
+}
@@ -50,10 +50,11 @@ impl TaskAction for StartTaskAction {
             p.show_information_message(&format!("Started task '{}'.", t.get_summary()));
         }
         if !undos.is_empty() {
-            self.base.undos.push(ActionUndo {
-                action_type: super::ActionUndoType::Modify,
-                tasks: undos.into_values().collect(),
-            });
+            self.base.push_undo(
+                super::ActionUndoType::Modify,
+                "start",
+                undos.into_values().collect(),
+            );
         }
         Ok(())
     }
@@ -88,7 +89,7 @@ mod tests {
         ) -> Result<(), String> {
             Ok(())
         }
-        fn print_task_info(&self, _task: &Task) -> Result<(), String> {
+        fn print_task_info(&self, _task: &Task, _: Vec<&Task>) -> Result<(), String> {
             Ok(())
         }
         fn print_raw(&self, _: &str) {}
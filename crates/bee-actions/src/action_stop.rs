@@ -46,10 +46,11 @@ impl TaskAction for StopTaskAction {
             p.show_information_message(&format!("Stopped task '{}'.", t.get_summary()));
         }
         if !undos.is_empty() {
-            self.base.undos.push(ActionUndo {
-                action_type: super::ActionUndoType::Modify,
-                tasks: undos.into_values().collect(),
-            });
+            self.base.push_undo(
+                super::ActionUndoType::Modify,
+                "stop",
+                undos.into_values().collect(),
+            );
         }
         Ok(())
     }
@@ -58,6 +59,7 @@ impl TaskAction for StopTaskAction {
 impl StopTaskAction {
     pub fn get_command_description() -> String {
         r#"Removes the 'ACTIVE' status of a task back to 'PENDING'. If a task was not 'ACTIVE', it has no effect.
+The time spent since the task was started is tracked, see 'timesheet'.
 <arguments> are ignored.
 "#
         .to_string()
@@ -84,7 +86,7 @@ mod tests {
         ) -> Result<(), String> {
             Ok(())
         }
-        fn print_task_info(&self, _task: &Task) -> Result<(), String> {
+        fn print_task_info(&self, _task: &Task, _: Vec<&Task>) -> Result<(), String> {
             Ok(())
         }
         fn print_raw(&self, _: &str) {}
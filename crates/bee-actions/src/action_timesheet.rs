@@ -0,0 +1,346 @@
+use chrono::{DateTime, Days, Local, NaiveDate, NaiveTime, TimeDelta, TimeZone};
+use log::info;
+
+use crate::{ActionUndo, BaseTaskAction, TaskAction, impl_taskaction_from_base};
+
+use bee_core::Printer;
+use bee_core::clock::Clock;
+use bee_core::task::{TaskData, TaskStatus, format_duration, parse_date_expression};
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Name of the group of the tasks without a project or without tags
+const NO_GROUP: &str = "(none)";
+
+/// Summarize the time during which the tasks were active, per day, per project
+/// and per tag, over a range of dates.
+#[derive(Default)]
+pub struct TimesheetTaskAction {
+    pub base: BaseTaskAction,
+}
+
+/// Time tracked over a range of dates
+#[derive(Debug, Default, PartialEq)]
+struct Timesheet {
+    days: BTreeMap<NaiveDate, TimeDelta>,
+    projects: BTreeMap<String, TimeDelta>,
+    tags: BTreeMap<String, TimeDelta>,
+    total: TimeDelta,
+}
+
+impl TaskAction for TimesheetTaskAction {
+    impl_taskaction_from_base!();
+    fn do_action(&mut self, printer: &dyn Printer) -> Result<(), String> {
+        info!("Performing TimesheetTaskAction");
+        let clock = self.base.tasks.get_clock().clone();
+        let (from, to) = parse_range(&self.base.arguments, clock.clone())?;
+        let timesheet = Timesheet::new(&self.base.tasks, from, to, clock.now());
+
+        let mut output = format!(
+            "Timesheet from {} to {}",
+            from.format("%Y-%m-%d %H:%M"),
+            to.format("%Y-%m-%d %H:%M")
+        );
+        if timesheet.total.is_zero() {
+            output += "\n\nNo time was tracked.";
+            printer.print_raw(&output);
+            return Ok(());
+        }
+
+        let days: Vec<(String, TimeDelta)> = timesheet
+            .days
+            .iter()
+            .map(|(day, time)| (day.format("%Y-%m-%d %a").to_string(), *time))
+            .collect();
+        output += &format_section("Days", &days);
+        output += &format_section("Projects", &into_rows(timesheet.projects));
+        output += &format_section("Tags", &into_rows(timesheet.tags));
+        output += &format!("\n\nTotal: {}", format_time(timesheet.total));
+        printer.print_raw(&output);
+        Ok(())
+    }
+}
+
+impl TimesheetTaskAction {
+    pub fn get_command_description() -> String {
+        r#"Summarize the time spent on the tasks per day, per project and per tag.
+The time spent on a task is tracked from the moment it is started to the moment it
+is stopped or completed.
+<arguments> can be:
+- 'from:<date>' to start the timesheet at this date, a week ago by default
+- 'to:<date>' to end the timesheet at this date, now by default
+The dates are date expressions, e.g. 'from:yesterday' or 'from:2 weeks ago'.
+<filter> will be ignored, all the tasks but the deleted ones are taken into account.
+"#
+        .to_string()
+    }
+}
+
+impl Timesheet {
+    /// Time tracked on the @tasks between @from and @to. The tasks that are still
+    /// active are tracked until @now.
+    fn new(
+        tasks: &TaskData,
+        from: DateTime<Local>,
+        to: DateTime<Local>,
+        now: DateTime<Local>,
+    ) -> Timesheet {
+        let mut timesheet = Timesheet::default();
+        for task in tasks.get_task_map().values() {
+            if task.get_status() == &TaskStatus::Deleted {
+                continue;
+            }
+
+            let mut task_time = TimeDelta::zero();
+            for interval in task.get_intervals() {
+                for (day, time) in interval.split_by_day(from, to, now) {
+                    *timesheet.days.entry(day).or_default() += time;
+                    task_time += time;
+                }
+            }
+            if task_time.is_zero() {
+                continue;
+            }
+
+            let project = match task.get_project() {
+                Some(project) => project.get_name().to_owned(),
+                None => NO_GROUP.to_string(),
+            };
+            *timesheet.projects.entry(project).or_default() += task_time;
+            if task.get_tags().is_empty() {
+                *timesheet.tags.entry(NO_GROUP.to_string()).or_default() += task_time;
+            }
+            for tag in task.get_tags() {
+                *timesheet.tags.entry(tag.to_owned()).or_default() += task_time;
+            }
+            timesheet.total += task_time;
+        }
+        timesheet
+    }
+}
+
+/// Read the range of dates from the @arguments, given as `from:<date>` and
+/// `to:<date>`. A date expression can be made of several arguments, it is relative
+/// to the time given by @clock.
+fn parse_range(
+    arguments: &[String],
+    clock: Arc<dyn Clock>,
+) -> Result<(DateTime<Local>, DateTime<Local>), String> {
+    let mut from: Option<String> = None;
+    let mut to: Option<String> = None;
+    let mut current: Option<&mut String> = None;
+    for word in arguments.iter().flat_map(|a| a.split_whitespace()) {
+        if let Some(value) = word.strip_prefix("from:") {
+            current = Some(from.insert(value.to_owned()));
+        } else if let Some(value) = word.strip_prefix("to:") {
+            current = Some(to.insert(value.to_owned()));
+        } else if let Some(value) = current.as_mut() {
+            value.push(' ');
+            value.push_str(word);
+        } else {
+            return Err(format!(
+                "Invalid argument '{}' for timesheet, expected 'from:<date>' or 'to:<date>'.",
+                word
+            ));
+        }
+    }
+
+    let parse = |value: &str| {
+        parse_date_expression(value, clock.clone())
+            .map_err(|e| format!("Invalid date '{}' for timesheet: {}", value, e))
+    };
+    let from = match from {
+        Some(value) => parse(&value)?,
+        None => a_week_ago(clock.now()),
+    };
+    let to = match to {
+        Some(value) => parse(&value)?,
+        None => clock.now(),
+    };
+    if from >= to {
+        return Err(format!(
+            "The start of the timesheet ({}) must be before its end ({}).",
+            from.format("%Y-%m-%d %H:%M"),
+            to.format("%Y-%m-%d %H:%M")
+        ));
+    }
+    Ok((from, to))
+}
+
+/// The start of the day six days before @now, so that the default timesheet
+/// covers a whole week including today
+fn a_week_ago(now: DateTime<Local>) -> DateTime<Local> {
+    let day = now.date_naive().checked_sub_days(Days::new(6)).unwrap();
+    Local
+        .from_local_datetime(&day.and_time(NaiveTime::MIN))
+        .earliest()
+        .unwrap_or(now)
+}
+
+fn into_rows(times: BTreeMap<String, TimeDelta>) -> Vec<(String, TimeDelta)> {
+    times.into_iter().collect()
+}
+
+fn format_time(time: TimeDelta) -> String {
+    format_duration(time.num_seconds())
+}
+
+fn format_section(title: &str, rows: &[(String, TimeDelta)]) -> String {
+    let width = rows.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    let mut section = format!("\n\n{}:", title);
+    for (name, time) in rows {
+        section += &format!("\n    {:width$}  {}", name, format_time(*time));
+    }
+    section
+}
+
+#[cfg(test)]
+mod tests {
+    use all_asserts::*;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    use super::*;
+    use bee_core::clock::FixedClock;
+    use bee_core::config::ReportConfig;
+    use bee_core::task::{Task, TaskProperties};
+
+    #[derive(Default)]
+    struct MockPrinter {
+        output: RefCell<Vec<String>>,
+    }
+
+    impl Printer for MockPrinter {
+        fn show_help(
+            &self,
+            _help_section_description: &HashMap<String, String>,
+        ) -> Result<(), String> {
+            Ok(())
+        }
+        fn print_task_info(&self, _task: &Task, _: Vec<&Task>) -> Result<(), String> {
+            Ok(())
+        }
+        fn print_raw(&self, message: &str) {
+            self.output.borrow_mut().push(message.to_owned());
+        }
+        fn show_information_message(&self, _message: &str) {}
+        fn error(&self, _: &str) {}
+
+        fn print_list_of_tasks(&self, _: Vec<&Task>, _: &ReportConfig) -> Result<(), String> {
+            Err("Not implemented".to_string())
+        }
+    }
+
+    fn local_time(day: u32, hour: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2024, 3, day, hour, 0, 0)
+            .single()
+            .unwrap()
+    }
+
+    /// Interval of time given as (day, start hour, end hour)
+    type Interval = (u32, u32, u32);
+
+    /// Tasks with the given properties, status and intervals of time
+    fn tracked_tasks(tasks: &[(&str, TaskStatus, &[Interval])]) -> TaskData {
+        let mut data = TaskData::default();
+        let mut intervals_by_uuid = HashMap::new();
+        for (properties, status, intervals) in tasks {
+            let task = data
+                .add_task(
+                    &TaskProperties::from(&[properties.to_string()]).unwrap(),
+                    status.to_owned(),
+                )
+                .unwrap();
+            intervals_by_uuid.insert(task.get_uuid().to_string(), intervals);
+        }
+        let mut json = serde_json::to_value(&data).unwrap();
+        for task in json.as_array_mut().unwrap() {
+            let intervals = intervals_by_uuid[task["uuid"].as_str().unwrap()];
+            let intervals: Vec<serde_json::Value> = intervals
+                .iter()
+                .map(|(day, start, end)| {
+                    serde_json::json!({
+                        "start": local_time(*day, *start),
+                        "end": local_time(*day, *end),
+                    })
+                })
+                .collect();
+            task["intervals"] = serde_json::json!(intervals);
+        }
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_timesheet() {
+        let tasks = tracked_tasks(&[
+            (
+                "write report project:work +writing",
+                TaskStatus::Completed,
+                &[(4, 9, 11), (5, 14, 15)],
+            ),
+            (
+                "review +writing +review",
+                TaskStatus::Pending,
+                &[(5, 9, 10)],
+            ),
+            ("exercise", TaskStatus::Pending, &[(1, 8, 9), (5, 18, 19)]),
+            ("mistake", TaskStatus::Deleted, &[(5, 10, 12)]),
+        ]);
+        let timesheet =
+            Timesheet::new(&tasks, local_time(4, 0), local_time(6, 0), local_time(7, 0));
+
+        let day = |day| NaiveDate::from_ymd_opt(2024, 3, day).unwrap();
+        assert_eq!(
+            timesheet.days,
+            BTreeMap::from([(day(4), TimeDelta::hours(2)), (day(5), TimeDelta::hours(3))])
+        );
+        assert_eq!(
+            timesheet.projects,
+            BTreeMap::from([
+                ("(none)".to_string(), TimeDelta::hours(2)),
+                ("work".to_string(), TimeDelta::hours(3)),
+            ])
+        );
+        assert_eq!(
+            timesheet.tags,
+            BTreeMap::from([
+                ("(none)".to_string(), TimeDelta::hours(1)),
+                ("review".to_string(), TimeDelta::hours(1)),
+                ("writing".to_string(), TimeDelta::hours(4)),
+            ])
+        );
+        assert_eq!(timesheet.total, TimeDelta::hours(5));
+    }
+
+    #[test]
+    fn test_parse_range() {
+        let clock: Arc<dyn Clock> = Arc::new(FixedClock::new(local_time(7, 14)));
+        let (from, to) = parse_range(&[], clock.clone()).unwrap();
+        assert_eq!(from, local_time(1, 0));
+        assert_eq!(to, local_time(7, 14));
+
+        let (from, to) = parse_range(
+            &["from:2".to_string(), "days ago to:today".to_string()],
+            clock.clone(),
+        )
+        .unwrap();
+        assert_eq!(from, local_time(5, 14));
+        assert_eq!(to, local_time(7, 0));
+
+        let parse = |argument: &str| parse_range(&[argument.to_string()], clock.clone());
+        assert_true!(parse("yesterday").is_err());
+        assert_true!(parse("from:someday").is_err());
+        assert_true!(parse("from:today to:yesterday").is_err());
+    }
+
+    #[test]
+    fn test_do_action() {
+        let printer = MockPrinter::default();
+        let mut action = TimesheetTaskAction::default();
+        action.do_action(&printer).unwrap();
+        assert_true!(printer.output.borrow()[0].ends_with("No time was tracked."));
+        assert_true!(action.get_undos().is_empty());
+    }
+}
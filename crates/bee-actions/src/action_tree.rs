@@ -0,0 +1,153 @@
+use log::info;
+use uuid::Uuid;
+
+use crate::{ActionUndo, BaseTaskAction, TaskAction, impl_taskaction_from_base};
+
+use bee_core::task::{Task, TaskData};
+use bee_core::{Printer, TaskTreeNode};
+
+use std::collections::HashMap;
+
+/// Show the tasks as a forest, where each task is followed by the tasks it
+/// depends on.
+#[derive(Default)]
+pub struct TreeTaskAction {
+    pub base: BaseTaskAction,
+}
+
+impl TaskAction for TreeTaskAction {
+    impl_taskaction_from_base!();
+    fn do_action(&mut self, printer: &dyn Printer) -> Result<(), String> {
+        info!("Performing TreeTaskAction");
+        let roots = build_dependency_forest(self.base.tasks.get_task_map());
+        printer.print_tree_of_tasks(roots, &self.base.report)
+    }
+}
+
+impl TreeTaskAction {
+    pub fn get_command_description() -> String {
+        r#"Show the tasks matched by <filter> as a tree of dependencies.
+Each task is followed by the tasks it depends on, which are indented below it.
+A task that several tasks depend on is shown under each of them.
+<arguments> are treated as filters.
+"#
+        .to_string()
+    }
+}
+
+/// The trees of dependencies between @tasks. The roots are the tasks that no
+/// other task of @tasks depends on. The dependencies on tasks that are not part
+/// of @tasks are left out.
+fn build_dependency_forest(tasks: &HashMap<Uuid, Task>) -> Vec<TaskTreeNode<'_>> {
+    let mut roots: Vec<&Task> = tasks
+        .values()
+        .filter(|t| !t.get_blocking().iter().any(|uuid| tasks.contains_key(uuid)))
+        .collect();
+    sort_siblings(&mut roots);
+    roots
+        .into_iter()
+        .map(|task| build_node(tasks, task, &mut Vec::new()))
+        .collect()
+}
+
+/// The tree under @task. @ancestors are the tasks above it, they are never shown
+/// again under it in case the stored dependencies form a cycle.
+fn build_node<'a>(
+    tasks: &'a HashMap<Uuid, Task>,
+    task: &'a Task,
+    ancestors: &mut Vec<Uuid>,
+) -> TaskTreeNode<'a> {
+    ancestors.push(*task.get_uuid());
+    let mut children: Vec<&Task> = task
+        .get_depends()
+        .iter()
+        .filter(|uuid| !ancestors.contains(uuid))
+        .filter_map(|uuid| tasks.get(uuid))
+        .collect();
+    sort_siblings(&mut children);
+    let children = children
+        .into_iter()
+        .map(|child| build_node(tasks, child, ancestors))
+        .collect();
+    ancestors.pop();
+    TaskTreeNode { task, children }
+}
+
+/// Most urgent tasks first, as in the lists of tasks
+fn sort_siblings(tasks: &mut [&Task]) {
+    tasks.sort();
+    tasks.reverse();
+}
+
+#[cfg(test)]
+mod tests {
+    use all_asserts::*;
+
+    use super::*;
+    use bee_core::task::{TaskProperties, TaskStatus};
+
+    /// Summaries of the tasks of the tree, prefixed by their depth
+    fn flatten(nodes: &[TaskTreeNode], depth: usize, out: &mut Vec<String>) {
+        for node in nodes {
+            out.push(format!("{}{}", "-".repeat(depth), node.task.get_summary()));
+            flatten(&node.children, depth + 1, out);
+        }
+    }
+
+    fn add_task(tasks: &mut TaskData, arguments: String) -> Uuid {
+        *tasks
+            .add_task(
+                &TaskProperties::from(&[arguments]).unwrap(),
+                TaskStatus::Pending,
+            )
+            .unwrap()
+            .get_uuid()
+    }
+
+    #[test]
+    fn test_build_dependency_forest() {
+        let mut tasks = TaskData::default();
+        let design = add_task(&mut tasks, "design".to_owned());
+        let build = add_task(&mut tasks, format!("build depends:{}", design));
+        let document = add_task(&mut tasks, format!("document depends:{}", design));
+        add_task(
+            &mut tasks,
+            format!("release depends:{} depends:{}", build, document),
+        );
+        add_task(&mut tasks, "unrelated".to_owned());
+        tasks.upkeep().unwrap();
+
+        let forest = build_dependency_forest(tasks.get_task_map());
+        let mut summaries = Vec::new();
+        flatten(&forest, 0, &mut summaries);
+        summaries.sort();
+        assert_eq!(
+            summaries,
+            vec![
+                "--design",
+                "--design",
+                "-build",
+                "-document",
+                "release",
+                "unrelated"
+            ]
+        );
+        assert_eq!(forest.len(), 2);
+    }
+
+    #[test]
+    fn test_build_dependency_forest_partial() {
+        let mut tasks = TaskData::default();
+        let design = add_task(&mut tasks, "design".to_owned());
+        let build = add_task(&mut tasks, format!("build depends:{}", design));
+        tasks.upkeep().unwrap();
+
+        // The task depended on is not part of the tasks to show
+        let mut shown = tasks.get_task_map().clone();
+        shown.remove(&design);
+        let forest = build_dependency_forest(&shown);
+        assert_eq!(forest.len(), 1);
+        assert_eq!(forest[0].task.get_uuid(), &build);
+        assert_true!(forest[0].children.is_empty());
+    }
+}
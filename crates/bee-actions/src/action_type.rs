@@ -4,16 +4,29 @@ use strum::{Display, EnumIter, IntoEnumIterator};
 
 use crate::{
     BaseTaskAction, TaskAction, action_add::AddTaskAction, action_annotate::AnnotateTaskAction,
-    action_cmd::CmdTaskAction, action_delete::DeleteTaskAction, action_done::DoneTaskAction,
-    action_edit::EditTaskAction, action_export::ExportTaskAction, action_help::HelpTaskAction,
-    action_info::InfoTaskAction, action_list::ListTaskAction, action_modify::ModifyTaskAction,
-    action_start::StartTaskAction, action_stop::StopTaskAction, action_undo::UndoTaskAction,
+    action_cmd::CmdTaskAction, action_context::ContextTaskAction, action_delete::DeleteTaskAction,
+    action_doctor::DoctorTaskAction, action_done::DoneTaskAction, action_edit::EditTaskAction,
+    action_export::ExportTaskAction, action_graph::GraphTaskAction, action_help::HelpTaskAction,
+    action_import::ImportTaskAction, action_info::InfoTaskAction, action_list::ListTaskAction,
+    action_modify::ModifyTaskAction, action_redo::RedoTaskAction, action_start::StartTaskAction,
+    action_stop::StopTaskAction, action_timesheet::TimesheetTaskAction,
+    action_tree::TreeTaskAction, action_undo::UndoTaskAction,
 };
 
 pub struct ActionTypeData {
     pub parsed_string: Vec<String>,
     pub use_arguments_as_filter: bool,
     pub documentation_string: String,
+    /// The action needs all the tasks, whatever the filters given by the user
+    pub load_all_tasks: bool,
+    /// The action needs all the tasks as they are stored, even if they are not
+    /// consistent with each other
+    pub load_raw_tasks: bool,
+    /// Names of the `<name>:<value>` arguments given to the action, when its other
+    /// arguments are used as filters
+    pub options: Vec<String>,
+    /// The action prints its tasks as a report, which may leave out the waiting ones
+    pub prints_report: bool,
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Display, EnumIter)]
@@ -21,16 +34,23 @@ pub enum ActionType {
     Add,
     Annotate,
     Command,
+    Context,
     Delete,
+    Doctor,
     Done,
     Edit,
     Export,
+    Graph,
     Help,
+    Import,
     Info,
     List,
     Modify,
+    Redo,
     Start,
     Stop,
+    Timesheet,
+    Tree,
     Undo,
 }
 
@@ -43,16 +63,23 @@ impl ActionType {
             ActionType::Add => (),
             ActionType::Annotate => (),
             ActionType::Command => (),
+            ActionType::Context => (),
             ActionType::Delete => (),
+            ActionType::Doctor => (),
             ActionType::Done => (),
             ActionType::Edit => (),
             ActionType::Export => (),
+            ActionType::Graph => (),
             ActionType::Help => (),
+            ActionType::Import => (),
             ActionType::Info => (),
             ActionType::List => (),
             ActionType::Modify => (),
+            ActionType::Redo => (),
             ActionType::Start => (),
             ActionType::Stop => (),
+            ActionType::Timesheet => (),
+            ActionType::Tree => (),
             ActionType::Undo => (),
         }
         let mut map = HashMap::new();
@@ -66,6 +93,10 @@ impl ActionType {
                             parsed_string: vec!["add".to_string()],
                             use_arguments_as_filter: false,
                             documentation_string: AddTaskAction::get_command_description(),
+                            load_all_tasks: false,
+                            load_raw_tasks: false,
+                            options: Vec::new(),
+                            prints_report: false,
                         },
                     );
                 }
@@ -76,6 +107,10 @@ impl ActionType {
                             parsed_string: vec!["annotate".to_string()],
                             use_arguments_as_filter: false,
                             documentation_string: { AnnotateTaskAction::get_command_description() },
+                            load_all_tasks: false,
+                            load_raw_tasks: false,
+                            options: Vec::new(),
+                            prints_report: false,
                         },
                     );
                 }
@@ -86,6 +121,24 @@ impl ActionType {
                             parsed_string: vec!["_cmd".to_string()],
                             use_arguments_as_filter: false,
                             documentation_string: CmdTaskAction::get_command_description(),
+                            load_all_tasks: false,
+                            load_raw_tasks: false,
+                            options: Vec::new(),
+                            prints_report: false,
+                        },
+                    );
+                }
+                ActionType::Context => {
+                    map.insert(
+                        action_type,
+                        ActionTypeData {
+                            parsed_string: vec!["context".to_string()],
+                            use_arguments_as_filter: false,
+                            documentation_string: ContextTaskAction::get_command_description(),
+                            load_all_tasks: false,
+                            load_raw_tasks: false,
+                            options: Vec::new(),
+                            prints_report: false,
                         },
                     );
                 }
@@ -96,6 +149,24 @@ impl ActionType {
                             parsed_string: vec!["delete".to_string()],
                             use_arguments_as_filter: false,
                             documentation_string: DeleteTaskAction::get_command_description(),
+                            load_all_tasks: false,
+                            load_raw_tasks: false,
+                            options: Vec::new(),
+                            prints_report: false,
+                        },
+                    );
+                }
+                ActionType::Doctor => {
+                    map.insert(
+                        action_type,
+                        ActionTypeData {
+                            parsed_string: vec!["doctor".to_string()],
+                            use_arguments_as_filter: false,
+                            documentation_string: DoctorTaskAction::get_command_description(),
+                            load_all_tasks: true,
+                            load_raw_tasks: true,
+                            options: Vec::new(),
+                            prints_report: false,
                         },
                     );
                 }
@@ -106,6 +177,10 @@ impl ActionType {
                             parsed_string: vec!["done".to_string()],
                             use_arguments_as_filter: false,
                             documentation_string: DoneTaskAction::get_command_description(),
+                            load_all_tasks: false,
+                            load_raw_tasks: false,
+                            options: Vec::new(),
+                            prints_report: false,
                         },
                     );
                 }
@@ -116,6 +191,10 @@ impl ActionType {
                             parsed_string: vec!["edit".to_string()],
                             use_arguments_as_filter: true,
                             documentation_string: EditTaskAction::get_command_description(),
+                            load_all_tasks: false,
+                            load_raw_tasks: false,
+                            options: Vec::new(),
+                            prints_report: false,
                         },
                     );
                 }
@@ -126,6 +205,24 @@ impl ActionType {
                             parsed_string: vec!["export".to_string()],
                             use_arguments_as_filter: true,
                             documentation_string: ExportTaskAction::get_command_description(),
+                            load_all_tasks: false,
+                            load_raw_tasks: false,
+                            options: vec!["urgency".to_string()],
+                            prints_report: false,
+                        },
+                    );
+                }
+                ActionType::Graph => {
+                    map.insert(
+                        action_type,
+                        ActionTypeData {
+                            parsed_string: vec!["graph".to_string()],
+                            use_arguments_as_filter: true,
+                            documentation_string: GraphTaskAction::get_command_description(),
+                            load_all_tasks: false,
+                            load_raw_tasks: false,
+                            options: Vec::new(),
+                            prints_report: true,
                         },
                     );
                 }
@@ -136,6 +233,24 @@ impl ActionType {
                             parsed_string: vec!["help".to_string()],
                             use_arguments_as_filter: false,
                             documentation_string: HelpTaskAction::get_command_description(),
+                            load_all_tasks: false,
+                            load_raw_tasks: false,
+                            options: Vec::new(),
+                            prints_report: false,
+                        },
+                    );
+                }
+                ActionType::Import => {
+                    map.insert(
+                        action_type,
+                        ActionTypeData {
+                            parsed_string: vec!["import".to_string()],
+                            use_arguments_as_filter: false,
+                            documentation_string: ImportTaskAction::get_command_description(),
+                            load_all_tasks: true,
+                            load_raw_tasks: false,
+                            options: Vec::new(),
+                            prints_report: false,
                         },
                     );
                 }
@@ -146,6 +261,10 @@ impl ActionType {
                             parsed_string: vec!["info".to_string()],
                             use_arguments_as_filter: true,
                             documentation_string: InfoTaskAction::get_command_description(),
+                            load_all_tasks: false,
+                            load_raw_tasks: false,
+                            options: Vec::new(),
+                            prints_report: false,
                         },
                     );
                 }
@@ -156,6 +275,10 @@ impl ActionType {
                             parsed_string: vec!["list".to_string()],
                             use_arguments_as_filter: true,
                             documentation_string: ListTaskAction::get_command_description(),
+                            load_all_tasks: false,
+                            load_raw_tasks: false,
+                            options: Vec::new(),
+                            prints_report: true,
                         },
                     );
                 }
@@ -166,6 +289,24 @@ impl ActionType {
                             parsed_string: vec!["modify".to_string(), "mod".to_string()],
                             use_arguments_as_filter: false,
                             documentation_string: ModifyTaskAction::get_command_description(),
+                            load_all_tasks: false,
+                            load_raw_tasks: false,
+                            options: Vec::new(),
+                            prints_report: false,
+                        },
+                    );
+                }
+                ActionType::Redo => {
+                    map.insert(
+                        action_type,
+                        ActionTypeData {
+                            parsed_string: vec!["redo".to_string()],
+                            use_arguments_as_filter: false,
+                            documentation_string: RedoTaskAction::get_command_description(),
+                            load_all_tasks: false,
+                            load_raw_tasks: false,
+                            options: Vec::new(),
+                            prints_report: false,
                         },
                     );
                 }
@@ -176,6 +317,10 @@ impl ActionType {
                             parsed_string: vec!["start".to_string()],
                             use_arguments_as_filter: true,
                             documentation_string: StartTaskAction::get_command_description(),
+                            load_all_tasks: false,
+                            load_raw_tasks: false,
+                            options: Vec::new(),
+                            prints_report: false,
                         },
                     );
                 }
@@ -186,6 +331,38 @@ impl ActionType {
                             parsed_string: vec!["stop".to_string()],
                             use_arguments_as_filter: true,
                             documentation_string: StopTaskAction::get_command_description(),
+                            load_all_tasks: false,
+                            load_raw_tasks: false,
+                            options: Vec::new(),
+                            prints_report: false,
+                        },
+                    );
+                }
+                ActionType::Timesheet => {
+                    map.insert(
+                        action_type,
+                        ActionTypeData {
+                            parsed_string: vec!["timesheet".to_string()],
+                            use_arguments_as_filter: false,
+                            documentation_string: TimesheetTaskAction::get_command_description(),
+                            load_all_tasks: true,
+                            load_raw_tasks: false,
+                            options: Vec::new(),
+                            prints_report: false,
+                        },
+                    );
+                }
+                ActionType::Tree => {
+                    map.insert(
+                        action_type,
+                        ActionTypeData {
+                            parsed_string: vec!["tree".to_string()],
+                            use_arguments_as_filter: true,
+                            documentation_string: TreeTaskAction::get_command_description(),
+                            load_all_tasks: false,
+                            load_raw_tasks: false,
+                            options: Vec::new(),
+                            prints_report: true,
                         },
                     );
                 }
@@ -196,6 +373,10 @@ impl ActionType {
                             parsed_string: vec!["undo".to_string()],
                             use_arguments_as_filter: false,
                             documentation_string: UndoTaskAction::get_command_description(),
+                            load_all_tasks: false,
+                            load_raw_tasks: false,
+                            options: Vec::new(),
+                            prints_report: false,
                         },
                     );
                 }
@@ -235,19 +416,26 @@ impl ActionType {
             ActionType::Add => Box::new(AddTaskAction::default()),
             ActionType::Annotate => Box::new(AnnotateTaskAction::default()),
             ActionType::Command => Box::new(CmdTaskAction::default()),
+            ActionType::Context => Box::new(ContextTaskAction::default()),
             ActionType::Delete => Box::new(DeleteTaskAction::default()),
+            ActionType::Doctor => Box::new(DoctorTaskAction::default()),
             ActionType::Done => Box::new(DoneTaskAction::default()),
             ActionType::Edit => Box::new(EditTaskAction::default()),
             ActionType::Export => Box::new(ExportTaskAction::default()),
+            ActionType::Graph => Box::new(GraphTaskAction::default()),
             ActionType::Help => Box::new(HelpTaskAction {
                 base: BaseTaskAction::default(),
                 command_descriptions: Self::get_command_descriptions(),
             }),
+            ActionType::Import => Box::new(ImportTaskAction::default()),
             ActionType::Info => Box::new(InfoTaskAction::default()),
             ActionType::List => Box::new(ListTaskAction::default()),
             ActionType::Modify => Box::new(ModifyTaskAction::default()),
+            ActionType::Redo => Box::new(RedoTaskAction::default()),
             ActionType::Start => Box::new(StartTaskAction::default()),
             ActionType::Stop => Box::new(StopTaskAction::default()),
+            ActionType::Timesheet => Box::new(TimesheetTaskAction::default()),
+            ActionType::Tree => Box::new(TreeTaskAction::default()),
             ActionType::Undo => Box::new(UndoTaskAction::default()),
         }
     }
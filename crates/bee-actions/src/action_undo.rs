@@ -11,30 +11,285 @@ pub struct UndoTaskAction {
 
 impl TaskAction for UndoTaskAction {
     impl_taskaction_from_base!();
-    fn do_action(&mut self, _: &dyn Printer) -> Result<(), String> {
-        let undos = &self.base.undos;
-        for current_undo in undos.iter().rev() {
+    fn do_action(&mut self, printer: &dyn Printer) -> Result<(), String> {
+        if self.base.arguments == ["--list"] {
+            printer.show_information_message(&self.get_history());
+            return Ok(());
+        }
+
+        let count = parse_count(&self.base.arguments, "undo")?;
+        let to_undo: Vec<usize> = (0..self.base.undos.len())
+            .rev()
+            .filter(|idx| !self.base.undos[*idx].undone)
+            .take(count)
+            .collect();
+        if to_undo.is_empty() {
+            printer.show_information_message("There is nothing to undo.");
+            return Ok(());
+        }
+
+        for idx in to_undo {
+            let current_undo = self.base.undos[idx].to_owned();
             for t in &current_undo.tasks {
                 if !self.get_tasks().get_undos().contains_key(t.get_uuid()) {
                     return Err(format!("Could not find task to undo: {}", t.get_uuid()));
                 }
 
                 match current_undo.action_type {
-                    ActionUndoType::Add => self.base.tasks.task_delete(t.get_uuid()),
+                    ActionUndoType::Add => {
+                        // The task might have been filtered out when loading the tasks
+                        let mut task =
+                            self.base.tasks.get_owned(t.get_uuid()).unwrap_or_else(|| {
+                                current_undo
+                                    .tasks_after
+                                    .iter()
+                                    .find(|after| after.get_uuid() == t.get_uuid())
+                                    .unwrap_or(t)
+                                    .to_owned()
+                            });
+                        task.delete(self.base.tasks.get_clock().now());
+                        self.base.tasks.set_task(task);
+                    }
                     ActionUndoType::Modify => self.base.tasks.set_task(t.to_owned()),
                 }
             }
+            self.base.undos[idx].undone = true;
+            printer.show_information_message(&format!("Undid {}", current_undo.describe()));
         }
-        self.base.undos.clear();
         Ok(())
     }
 }
 
 impl UndoTaskAction {
     pub fn get_command_description() -> String {
-        r#"Undo the last operation
-Both <filter> and <arguments> will be ignored.
+        r#"Undo the last operations
+<arguments> can be the number of operations to undo (1 by default), or '--list'
+to show the operations that can be undone and redone.
+<filter> will be ignored.
 "#
         .to_string()
     }
+
+    fn get_history(&self) -> String {
+        let format_entries = |undos: Vec<&ActionUndo>| -> Vec<String> {
+            undos
+                .iter()
+                .enumerate()
+                .map(|(idx, undo)| format!("  {}  {}", idx + 1, undo.describe()))
+                .collect()
+        };
+
+        let undoable: Vec<&ActionUndo> =
+            self.base.undos.iter().filter(|u| !u.undone).rev().collect();
+        let redoable: Vec<&ActionUndo> = self.base.undos.iter().filter(|u| u.undone).collect();
+
+        let mut lines = Vec::new();
+        if undoable.is_empty() {
+            lines.push("There is nothing to undo.".to_string());
+        } else {
+            lines.push("Operations that can be undone, most recent first:".to_string());
+            lines.extend(format_entries(undoable));
+        }
+        if !redoable.is_empty() {
+            lines.push("Operations that can be redone:".to_string());
+            lines.extend(format_entries(redoable));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Read how many operations to undo or redo from the arguments of the action
+pub(crate) fn parse_count(arguments: &[String], action_name: &str) -> Result<usize, String> {
+    match arguments {
+        [] => Ok(1),
+        [count] => match count.parse::<usize>() {
+            Ok(count) if count > 0 => Ok(count),
+            _ => Err(format!(
+                "Invalid argument '{}' for {}: expected a number of operations.",
+                count, action_name
+            )),
+        },
+        _ => Err(format!(
+            "Too many arguments for {}: expected a number of operations.",
+            action_name
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use all_asserts::*;
+    use std::collections::HashMap;
+
+    use super::*;
+    use bee_core::config::ReportConfig;
+    use bee_core::task::{Task, TaskProperties, TaskStatus};
+
+    use crate::action_done::DoneTaskAction;
+    use crate::action_modify::ModifyTaskAction;
+
+    struct MockPrinter;
+
+    impl Printer for MockPrinter {
+        fn show_help(
+            &self,
+            _help_section_description: &HashMap<String, String>,
+        ) -> Result<(), String> {
+            Ok(())
+        }
+        fn print_task_info(&self, _task: &Task, _: Vec<&Task>) -> Result<(), String> {
+            Ok(())
+        }
+        fn print_raw(&self, _: &str) {}
+        fn show_information_message(&self, _message: &str) {}
+        fn error(&self, _: &str) {}
+
+        fn print_list_of_tasks(&self, _: Vec<&Task>, _: &ReportConfig) -> Result<(), String> {
+            Err("Not implemented".to_string())
+        }
+    }
+
+    /// Modify all the tasks of @tasks with @arguments, returning the resulting tasks
+    /// and the updated undo log
+    fn modify(
+        tasks: TaskData,
+        undos: Vec<ActionUndo>,
+        arguments: &str,
+    ) -> (TaskData, Vec<ActionUndo>) {
+        let mut action = ModifyTaskAction::default();
+        action.base.tasks = tasks;
+        action.base.undos = undos;
+        action.base.arguments = vec![arguments.to_owned()];
+        action.do_action(&MockPrinter).unwrap();
+        (action.base.tasks, action.base.undos)
+    }
+
+    fn undo(tasks: TaskData, undos: Vec<ActionUndo>, arguments: &[&str]) -> UndoTaskAction {
+        let mut action = UndoTaskAction::default();
+        action.base.tasks = tasks;
+        for undo in &undos {
+            action.base.tasks.set_undos(&undo.tasks);
+        }
+        action.base.undos = undos;
+        action.base.arguments = arguments.iter().map(|a| a.to_string()).collect();
+        action.do_action(&MockPrinter).unwrap();
+        action
+    }
+
+    #[test]
+    fn test_undo_multiple_operations() {
+        let mut tasks = TaskData::default();
+        let uuid = *tasks
+            .add_task(
+                &TaskProperties::from(&["first summary".to_owned()]).unwrap(),
+                TaskStatus::Pending,
+            )
+            .unwrap()
+            .get_uuid();
+
+        let (tasks, undos) = modify(tasks, Vec::new(), "second summary");
+        let (tasks, undos) = modify(tasks, undos, "third summary");
+        assert_eq!(undos.len(), 2);
+        assert_true!(undos.iter().all(|u| u.time.is_some()));
+
+        let action = undo(tasks, undos, &["2"]);
+        assert_eq!(
+            action.get_tasks().get_task_map()[&uuid].get_summary(),
+            "first summary"
+        );
+        assert_true!(action.get_undos().iter().all(|u| u.undone));
+
+        // Nothing is left to undo
+        let action = undo(
+            action.get_tasks().to_owned(),
+            action.get_undos().to_owned(),
+            &[],
+        );
+        assert_eq!(
+            action.get_tasks().get_task_map()[&uuid].get_summary(),
+            "first summary"
+        );
+    }
+
+    #[test]
+    fn test_undo_drops_redo_on_new_operation() {
+        let mut tasks = TaskData::default();
+        tasks
+            .add_task(
+                &TaskProperties::from(&["first summary".to_owned()]).unwrap(),
+                TaskStatus::Pending,
+            )
+            .unwrap();
+
+        let (tasks, undos) = modify(tasks, Vec::new(), "second summary");
+        let (tasks, undos) = modify(tasks, undos, "third summary");
+        let action = undo(tasks, undos, &[]);
+        assert_eq!(action.get_undos().iter().filter(|u| u.undone).count(), 1);
+
+        let (_, undos) = modify(
+            action.get_tasks().to_owned(),
+            action.get_undos().to_owned(),
+            "fourth summary",
+        );
+        assert_eq!(undos.len(), 2);
+        assert_false!(undos.iter().any(|u| u.undone));
+    }
+
+    #[test]
+    fn test_undo_list() {
+        let mut tasks = TaskData::default();
+        tasks
+            .add_task(
+                &TaskProperties::from(&["first summary".to_owned()]).unwrap(),
+                TaskStatus::Pending,
+            )
+            .unwrap();
+        let (tasks, undos) = modify(tasks, Vec::new(), "second summary");
+        let (tasks, undos) = modify(tasks, undos, "third summary");
+        let action = undo(tasks, undos, &[]);
+
+        let history = action.get_history();
+        let lines: Vec<&str> = history.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_true!(lines[1].starts_with("  1  "));
+        assert_true!(lines[1].ends_with("modify  'first summary'"));
+        assert_true!(lines[3].ends_with("modify  'second summary'"));
+    }
+
+    #[test]
+    fn test_undo_list_action_names() {
+        let mut tasks = TaskData::default();
+        tasks
+            .add_task(
+                &TaskProperties::from(&["first summary".to_owned()]).unwrap(),
+                TaskStatus::Pending,
+            )
+            .unwrap();
+        let (tasks, undos) = modify(tasks, Vec::new(), "second summary");
+        let mut done = DoneTaskAction::default();
+        done.base.tasks = tasks;
+        done.base.undos = undos;
+        done.do_action(&MockPrinter).unwrap();
+        let action = undo(done.base.tasks, done.base.undos, &["--list"]);
+
+        let history = action.get_history();
+        let lines: Vec<&str> = history.lines().collect();
+        assert_true!(lines[1].ends_with("done  'second summary'"));
+        assert_true!(lines[2].ends_with("modify  'first summary'"));
+
+        // The actions logged without their name are described by their type
+        let undo: ActionUndo =
+            serde_json::from_str(r#"{"action_type": "Modify", "tasks": []}"#).unwrap();
+        assert_eq!(undo.action_name, "");
+        assert_eq!(undo.describe(), "unknown time  modify  ");
+    }
+
+    #[test]
+    fn test_parse_count() {
+        assert_eq!(parse_count(&[], "undo"), Ok(1));
+        assert_eq!(parse_count(&["3".to_owned()], "undo"), Ok(3));
+        assert_true!(parse_count(&["0".to_owned()], "undo").is_err());
+        assert_true!(parse_count(&["foo".to_owned()], "undo").is_err());
+        assert_true!(parse_count(&["1".to_owned(), "2".to_owned()], "undo").is_err());
+    }
 }
@@ -2,19 +2,87 @@ use bee_core::filters;
 use bee_core::filters::Filter;
 use log::debug;
 
+use bee_core::clock::{Clock, SystemClock};
 use bee_core::config::{ReportConfig, get_config};
+use bee_core::sort::parse_sort_keys;
 
 use std::collections::HashMap;
+use std::sync::Arc;
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct Parser {
     command_parsers: Vec<ParsedCommand>,
+    /// Name of the context whose filter is added to the filter of every command
+    active_context: Option<String>,
+    /// Gives the time the dates of the filters are relative to
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Parser {
+            command_parsers: Vec::default(),
+            active_context: None,
+            clock: Arc::new(SystemClock),
+        }
+    }
 }
 
 impl Parser {
     pub fn register_command_parser(&mut self, command_parser: ParsedCommand) {
         self.command_parsers.push(command_parser);
     }
+
+    pub fn set_active_context(&mut self, context: Option<String>) {
+        self.active_context = context;
+    }
+
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    fn parse_filter(&self, values: &[String]) -> Result<Box<dyn Filter>, String> {
+        filters::from_with_clock(values, self.clock.clone())
+    }
+
+    /// Filter of the active context, or an empty filter if there is none
+    fn get_context_filter(&self) -> Result<Box<dyn Filter>, String> {
+        let Some(name) = &self.active_context else {
+            return Ok(filters::new_empty());
+        };
+        let context = get_config()
+            .contexts
+            .get(name)
+            .ok_or(format!("The context '{}' is not defined.", name))?;
+        debug!("Using the filter of context '{}'", name);
+        self.parse_filter(std::slice::from_ref(&context.filter))
+            .map_err(|e| format!("Invalid filter for the context '{}': {}", name, e))
+    }
+
+    /// Filter of a command: its @values, the filters of @report and the filter of the
+    /// active context. The waiting tasks are left out when the command prints @report
+    /// and the report hides them, unless the filter asks for them.
+    fn get_command_filter(
+        &self,
+        values: &[String],
+        report: &ReportConfig,
+        prints_report: bool,
+    ) -> Result<Box<dyn Filter>, String> {
+        let filter = filters::and(
+            filters::and(
+                self.parse_filter(values)?,
+                self.parse_filter(&report.filters)?,
+            ),
+            self.get_context_filter()?,
+        );
+        if prints_report && report.hide_waiting && !filters::mentions_waiting(&filter) {
+            return Ok(filters::and(
+                filter,
+                self.parse_filter(&["not status:waiting".to_string()])?,
+            ));
+        }
+        Ok(filter)
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -25,6 +93,15 @@ pub struct ParsedCommand {
     pub filters: Box<dyn Filter>,
     pub arguments: Vec<String>,
     pub arguments_as_filters: bool,
+    /// The filters are ignored and all the tasks are loaded
+    pub load_all_tasks: bool,
+    /// All the tasks are loaded as they are stored, without checking them
+    pub load_raw_tasks: bool,
+    /// Names of the `<name>:<value>` arguments that are given to the command
+    /// instead of being used as filters
+    pub options: Vec<String>,
+    /// The command prints its tasks as a report, which may leave out the waiting ones
+    pub prints_report: bool,
     pub report_kind: ReportConfig,
 }
 
@@ -39,6 +116,8 @@ impl Parser {
         let arguments = if args.len() > 1 { &args[1..] } else { &[] };
 
         let mut report_kind = get_config().get_default_report().clone();
+        // Given by `sort:`, it replaces the sort of whichever report is used
+        let mut sort = None;
         let mut filters = Vec::new();
         let mut command_args = Vec::new();
 
@@ -50,6 +129,20 @@ impl Parser {
                         report_kind = report.clone();
                         continue;
                     }
+                    // The other commands take `sort:` as one of their arguments
+                    if parsed_command.arguments_as_filters
+                        && let Some(keys) = remaining_arg.strip_prefix("sort:")
+                    {
+                        sort = Some(parse_sort_keys(keys)?);
+                        continue;
+                    }
+                    if parsed_command.arguments_as_filters
+                        && let Some((name, _)) = remaining_arg.split_once(':')
+                        && parsed_command.options.iter().any(|option| option == name)
+                    {
+                        parsed_command.arguments.push(remaining_arg.clone());
+                        continue;
+                    }
                     command_args.push(remaining_arg.clone());
                 }
                 if parsed_command.arguments_as_filters {
@@ -57,10 +150,11 @@ impl Parser {
                 } else {
                     parsed_command.arguments = command_args;
                 }
-                parsed_command.filters = filters::and(
-                    filters::from(&filters)?,
-                    filters::from(&report_kind.filters)?,
-                );
+                parsed_command.filters =
+                    self.get_command_filter(&filters, &report_kind, parsed_command.prints_report)?;
+                if let Some(sort) = sort {
+                    report_kind.sort = sort;
+                }
                 parsed_command.report_kind = report_kind;
                 return Ok(parsed_command.clone());
             }
@@ -71,19 +165,212 @@ impl Parser {
                 report_kind = report.clone();
                 continue;
             }
+            if let Some(keys) = arg.strip_prefix("sort:") {
+                sort = Some(parse_sort_keys(keys)?);
+                continue;
+            }
 
             filters.push(arg.clone());
         }
 
-        let command_filters = filters::and(
-            filters::from(&filters)?,
-            filters::from(&report_kind.filters)?,
-        );
+        let command_filters = self.get_command_filter(&filters, &report_kind, true)?;
+        if let Some(sort) = sort {
+            report_kind.sort = sort;
+        }
         Ok(ParsedCommand {
             filters: command_filters,
             command: "list".to_string(),
+            prints_report: true,
             report_kind,
             ..Default::default()
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use all_asserts::*;
+    use bee_core::clock::FixedClock;
+    use chrono::Local;
+
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        std::iter::once("bee")
+            .chain(line.split_whitespace())
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_no_active_context() {
+        let clock: Arc<dyn Clock> = Arc::new(FixedClock::new(Local::now()));
+        let mut parser = Parser::default();
+        parser.set_clock(clock.clone());
+        let command = parser.parse_command_line_arguments(args("+home")).unwrap();
+        assert_eq!(command.command, "list");
+        assert_eq!(
+            &command.filters,
+            &filters::and(
+                filters::and(
+                    filters::from_with_clock(&["+home".to_string()], clock.clone()).unwrap(),
+                    filters::from_with_clock(
+                        &get_config().get_default_report().filters,
+                        clock.clone()
+                    )
+                    .unwrap(),
+                ),
+                filters::from_with_clock(&["not status:waiting".to_string()], clock).unwrap(),
+            )
+        );
+    }
+
+    #[test]
+    fn test_sort_override() {
+        let mut parser = Parser::default();
+        for command in ["list", "modify"] {
+            parser.register_command_parser(ParsedCommand {
+                command: command.to_string(),
+                arguments_as_filters: command == "list",
+                ..Default::default()
+            });
+        }
+        let sort = |command: &ParsedCommand| -> Vec<String> {
+            command
+                .report_kind
+                .sort
+                .iter()
+                .map(|key| key.to_string())
+                .collect()
+        };
+
+        let command = parser.parse_command_line_arguments(args("+home")).unwrap();
+        assert_eq!(sort(&command), vec!["urgency-", "created-"]);
+
+        let command = parser
+            .parse_command_line_arguments(args("sort:due-,project+ +home"))
+            .unwrap();
+        assert_eq!(command.command, "list");
+        assert_eq!(sort(&command), vec!["due-", "project+"]);
+
+        let command = parser
+            .parse_command_line_arguments(args("+home list sort:due-"))
+            .unwrap();
+        assert_eq!(sort(&command), vec!["due-"]);
+        assert_true!(command.arguments.is_empty());
+
+        // The arguments of the other commands are left alone
+        let command = parser
+            .parse_command_line_arguments(args("1 modify sort:due-"))
+            .unwrap();
+        assert_eq!(command.arguments, vec!["sort:due-"]);
+
+        assert_true!(
+            parser
+                .parse_command_line_arguments(args("sort:due"))
+                .is_err()
+        );
+        assert_true!(
+            parser
+                .parse_command_line_arguments(args("list sort:someday+"))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_reports_hide_waiting_tasks() {
+        let clock: Arc<dyn Clock> = Arc::new(FixedClock::new(Local::now()));
+        let mut parser = Parser::default();
+        parser.set_clock(clock.clone());
+        for command in ["list", "info"] {
+            parser.register_command_parser(ParsedCommand {
+                command: command.to_string(),
+                arguments_as_filters: true,
+                prints_report: command == "list",
+                ..Default::default()
+            });
+        }
+        let expected = |values: &[&str]| -> Box<dyn Filter> {
+            let values: Vec<String> = values.iter().map(|s| s.to_string()).collect();
+            filters::from_with_clock(&values, clock.clone()).unwrap()
+        };
+        let default_filters = get_config().get_default_report().filters.join(" ");
+
+        // Only the commands printing the report leave out the waiting tasks
+        let command = parser
+            .parse_command_line_arguments(args("+home list"))
+            .unwrap();
+        assert_eq!(
+            &command.filters,
+            &filters::and(
+                filters::and(expected(&["+home"]), expected(&[&default_filters])),
+                expected(&["not status:waiting"]),
+            )
+        );
+        let command = parser.parse_command_line_arguments(args("+home")).unwrap();
+        assert_true!(command.prints_report);
+        assert_true!(filters::mentions_waiting(&command.filters));
+
+        let command = parser
+            .parse_command_line_arguments(args("+home info"))
+            .unwrap();
+        assert_eq!(
+            &command.filters,
+            &filters::and(expected(&["+home"]), expected(&[&default_filters]))
+        );
+
+        // Unless the filter asks for them
+        let command = parser
+            .parse_command_line_arguments(args("status:waiting list"))
+            .unwrap();
+        assert_eq!(
+            &command.filters,
+            &filters::and(expected(&["status:waiting"]), expected(&[&default_filters]))
+        );
+
+        // Or the report shows them
+        let command = parser
+            .parse_command_line_arguments(args("waiting +home list"))
+            .unwrap();
+        assert_eq!(
+            &command.filters,
+            &filters::and(expected(&["+home"]), expected(&["status:waiting"]))
+        );
+    }
+
+    #[test]
+    fn test_command_options() {
+        let clock: Arc<dyn Clock> = Arc::new(FixedClock::new(Local::now()));
+        let mut parser = Parser::default();
+        parser.set_clock(clock.clone());
+        parser.register_command_parser(ParsedCommand {
+            command: "export".to_string(),
+            arguments_as_filters: true,
+            options: vec!["urgency".to_string()],
+            ..Default::default()
+        });
+
+        let command = parser
+            .parse_command_line_arguments(args("export +home urgency:breakdown"))
+            .unwrap();
+        assert_eq!(command.arguments, vec!["urgency:breakdown"]);
+        assert_eq!(
+            &command.filters,
+            &filters::and(
+                filters::from_with_clock(&["+home".to_string()], clock.clone()).unwrap(),
+                filters::from_with_clock(&get_config().get_default_report().filters, clock)
+                    .unwrap(),
+            )
+        );
+
+        let command = parser.parse_command_line_arguments(args("export")).unwrap();
+        assert_true!(command.arguments.is_empty());
+    }
+
+    #[test]
+    fn test_undefined_active_context() {
+        let mut parser = Parser::default();
+        parser.set_active_context(Some("undefined".to_string()));
+        assert_true!(parser.parse_command_line_arguments(args("+home")).is_err());
+    }
+}
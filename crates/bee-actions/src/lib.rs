@@ -5,20 +5,32 @@ mod action_type;
 mod action_add;
 mod action_annotate;
 mod action_cmd;
+mod action_context;
 mod action_delete;
+mod action_doctor;
 mod action_done;
 mod action_edit;
 mod action_export;
+mod action_graph;
 mod action_help;
+mod action_import;
 mod action_info;
 mod action_list;
 mod action_modify;
+mod action_redo;
 mod action_start;
 mod action_stop;
+mod action_timesheet;
+mod action_tree;
 mod action_undo;
 
 use action_type::ActionType;
+use chrono::{DateTime, Local};
+use log::debug;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use uuid::Uuid;
 
 use crate::command_parser::ParsedCommand;
 use bee_core::{
@@ -34,6 +46,15 @@ pub enum ActionUndoType {
     Modify,
 }
 
+impl fmt::Display for ActionUndoType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ActionUndoType::Add => write!(f, "add"),
+            ActionUndoType::Modify => write!(f, "modify"),
+        }
+    }
+}
+
 pub trait TaskAction {
     /// This is the main execution of the action. This is where it will affect
     /// the tasks it targets or call the printer
@@ -57,12 +78,58 @@ pub trait TaskAction {
     /// Set the report this action should use. This is important
     /// to decide how the printer should behave in some cases
     fn set_report(&mut self, report: ReportConfig);
+
+    /// Set the name of the active context
+    fn set_context(&mut self, context: Option<String>);
+
+    /// Name of the active context, which the action might have changed
+    fn get_context(&self) -> &Option<String>;
 }
 
 #[derive(Default, Serialize, Deserialize, Clone, Debug)]
 pub struct ActionUndo {
     pub action_type: ActionUndoType,
+    /// Name of the action that was done, e.g. `done`. This is empty for the actions
+    /// logged before it was recorded, which are described by their type.
+    #[serde(default)]
+    pub action_name: String,
+    /// State of the tasks before the action. For an `Add`, this is the created task.
     pub tasks: Vec<Task>,
+
+    /// State of the tasks right after the action, restored when redoing it
+    #[serde(default)]
+    pub tasks_after: Vec<Task>,
+
+    /// When the action was done. This is unknown for the actions logged before
+    /// it was recorded.
+    #[serde(default)]
+    pub time: Option<DateTime<Local>>,
+
+    /// Undone actions stay in the log so that they can be redone, until a
+    /// new action is done.
+    #[serde(default)]
+    pub undone: bool,
+}
+
+impl ActionUndo {
+    /// Short description of the action, with the summary of the tasks it changed
+    pub fn describe(&self) -> String {
+        let time = match self.time {
+            Some(time) => time.format("%Y-%m-%d %H:%M:%S").to_string(),
+            None => "unknown time".to_string(),
+        };
+        let summaries: Vec<String> = self
+            .tasks
+            .iter()
+            .map(|t| format!("'{}'", t.get_summary()))
+            .collect();
+        let name = if self.action_name.is_empty() {
+            self.action_type.to_string()
+        } else {
+            self.action_name.clone()
+        };
+        format!("{}  {}  {}", time, name, summaries.join(", "))
+    }
 }
 
 #[derive(Default)]
@@ -76,6 +143,10 @@ impl ActionRegistry {
                 v.push(ParsedCommand {
                     command: alias.to_string(),
                     arguments_as_filters: data.use_arguments_as_filter,
+                    load_all_tasks: data.load_all_tasks,
+                    load_raw_tasks: data.load_raw_tasks,
+                    options: data.options.clone(),
+                    prints_report: data.prints_report,
                     ..Default::default()
                 })
             }
@@ -97,6 +168,7 @@ pub struct BaseTaskAction {
     undos: Vec<ActionUndo>,
     arguments: Vec<String>,
     report: ReportConfig,
+    context: Option<String>,
 }
 
 impl BaseTaskAction {
@@ -112,6 +184,14 @@ impl BaseTaskAction {
         self.tasks = tasks;
     }
 
+    pub fn set_context(&mut self, context: Option<String>) {
+        self.context = context;
+    }
+
+    pub fn get_context(&self) -> &Option<String> {
+        &self.context
+    }
+
     pub fn get_tasks(&self) -> &TaskData {
         &self.tasks
     }
@@ -123,6 +203,63 @@ impl BaseTaskAction {
     pub fn get_undos(&self) -> &Vec<ActionUndo> {
         &self.undos
     }
+
+    /// Add to @undos, which maps the tasks changed by an action to their state before
+    /// it, the tasks linked to them that were left unchanged. Writing the tasks
+    /// updates their links, e.g. when a task they depend on is completed, so they
+    /// must be restored as well when undoing the action.
+    pub fn add_linked_tasks_to_undos(&self, undos: &mut HashMap<Uuid, Task>) {
+        let mut linked_uuids: Vec<Uuid> = undos
+            .values()
+            .chain(
+                undos
+                    .keys()
+                    .filter_map(|uuid| self.tasks.get_task_map().get(uuid)),
+            )
+            .flat_map(|t| t.get_extra_uuid())
+            .collect();
+        linked_uuids.sort_unstable();
+        linked_uuids.dedup();
+        debug!("Tasks linked to the changed tasks: {:?}", linked_uuids);
+
+        for uuid in linked_uuids {
+            if undos.contains_key(&uuid) {
+                continue;
+            }
+            if let Some(task) = self
+                .tasks
+                .get_task_map()
+                .get(&uuid)
+                .or_else(|| self.tasks.get_extra_tasks().get(&uuid))
+            {
+                undos.insert(uuid, task.to_owned());
+            }
+        }
+    }
+
+    /// Log the action @action_name done on @tasks, given in the state they were before
+    /// the action. The actions that were undone cannot be redone after that.
+    pub fn push_undo(&mut self, action_type: ActionUndoType, action_name: &str, tasks: Vec<Task>) {
+        let tasks_after = tasks
+            .iter()
+            .filter_map(|t| {
+                self.tasks
+                    .get_task_map()
+                    .get(t.get_uuid())
+                    .or_else(|| self.tasks.get_extra_tasks().get(t.get_uuid()))
+                    .cloned()
+            })
+            .collect();
+        self.undos.retain(|undo| !undo.undone);
+        self.undos.push(ActionUndo {
+            action_type,
+            action_name: action_name.to_string(),
+            tasks,
+            tasks_after,
+            time: Some(self.tasks.get_clock().now()),
+            undone: false,
+        });
+    }
 }
 
 mod macros {
@@ -147,6 +284,12 @@ mod macros {
             fn set_arguments(&mut self, arguments: Vec<String>) {
                 self.base.set_arguments(arguments);
             }
+            fn set_context(&mut self, context: Option<String>) {
+                self.base.set_context(context);
+            }
+            fn get_context(&self) -> &Option<String> {
+                self.base.get_context()
+            }
         };
     }
 }
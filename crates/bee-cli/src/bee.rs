@@ -2,13 +2,21 @@ mod cli;
 mod config;
 mod table;
 
-use bee_actions::{ActionRegistry, command_parser::Parser};
+use bee_actions::{
+    ActionRegistry,
+    command_parser::{ParsedCommand, Parser},
+};
 use bee_core::{
     Printer,
+    clock::{Clock, clock_from_env},
+    config::{StorageBackend, get_config},
     filters::{self, Filter},
     task::TaskProperties,
 };
-use bee_storage::storage::{JsonStore, Store};
+use bee_storage::{
+    sqlite_store::SqliteStore,
+    storage::{JsonStore, Store, load_active_context, lock_data, write_active_context},
+};
 
 use crate::{
     cli::SimpleTaskTextPrinter,
@@ -17,17 +25,21 @@ use crate::{
 
 use log::{debug, trace};
 use std::process::exit;
+use std::sync::Arc;
 
-fn get_section_filters() -> Result<Option<Box<dyn Filter>>, String> {
+fn get_section_filters(clock: &Arc<dyn Clock>) -> Result<Option<Box<dyn Filter>>, String> {
     let mut report_filter = filters::new_empty();
     let section_config = &get_cli_config().section;
-    if let Some(session_type) = &section_config.section_type {
-        if *session_type == SectionType::Filters {
-            for filter in section_config.filters.values() {
-                report_filter = filters::or(report_filter, filters::from(filter)?);
-            }
-            return Ok(Some(report_filter));
+    if let Some(session_type) = &section_config.section_type
+        && *session_type == SectionType::Filters
+    {
+        for filter in section_config.filters.values() {
+            report_filter = filters::or(
+                report_filter,
+                filters::from_with_clock(filter, clock.clone())?,
+            );
         }
+        return Ok(Some(report_filter));
     }
 
     Ok(None)
@@ -35,12 +47,51 @@ fn get_section_filters() -> Result<Option<Box<dyn Filter>>, String> {
 
 fn main() {
     env_logger::init();
-    let undo_count = 1;
 
     match config::load_config() {
         Ok(_) => {}
         Err(msg) => {
-            SimpleTaskTextPrinter.error(&msg);
+            SimpleTaskTextPrinter::default().error(&msg);
+            exit(1);
+        }
+    }
+
+    let clock = match clock_from_env() {
+        Ok(clock) => clock,
+        Err(msg) => {
+            SimpleTaskTextPrinter::default().error(&msg);
+            exit(1);
+        }
+    };
+    let printer = SimpleTaskTextPrinter::new(clock.clone());
+
+    // Held until the end of the process, so that no other bee process writes
+    // the tasks while this command runs
+    let _lock = match lock_data() {
+        Ok(lock) => lock,
+        Err(e) => {
+            printer.error(&e.to_string());
+            exit(1);
+        }
+    };
+
+    let mut active_context = match load_active_context() {
+        Ok(res) => res,
+        Err(e) => {
+            printer.error(&e.to_string());
+            exit(1);
+        }
+    };
+    if let Some(name) = &active_context
+        && !get_config().contexts.contains_key(name)
+    {
+        printer.show_information_message(&format!(
+            "The active context '{}' is not defined anymore, it has been cleared.",
+            name
+        ));
+        active_context = None;
+        if let Err(e) = write_active_context(None) {
+            printer.error(&e.to_string());
             exit(1);
         }
     }
@@ -49,18 +100,20 @@ fn main() {
     for cmd in ActionRegistry::get_parsed_commands() {
         arg_parser.register_command_parser(cmd);
     }
+    arg_parser.set_active_context(active_context.clone());
+    arg_parser.set_clock(clock.clone());
 
     let mut command = match arg_parser.parse_command_line_arguments(std::env::args().collect()) {
         Ok(res) => res,
         Err(msg) => {
-            SimpleTaskTextPrinter.error(&msg);
+            printer.error(&msg);
             exit(1);
         }
     };
-    let section_filters = match get_section_filters() {
+    let section_filters = match get_section_filters(&clock) {
         Ok(res) => res,
         Err(msg) => {
-            SimpleTaskTextPrinter.error(&msg);
+            printer.error(&msg);
             exit(1);
         }
     };
@@ -68,7 +121,31 @@ fn main() {
         command.filters = filters::or(command.filters.clone(), f);
     }
 
-    let undos = JsonStore::load_undos(undo_count);
+    match get_config().storage {
+        StorageBackend::Json => run_command::<JsonStore>(command, active_context, clock, &printer),
+        StorageBackend::Sqlite => {
+            run_command::<SqliteStore>(command, active_context, clock, &printer)
+        }
+    }
+}
+
+/// Load the tasks targeted by @command from the store, run its action and
+/// write the result back, along with the context if the action changed it.
+/// @clock gives the time of the changes.
+fn run_command<S: Store>(
+    mut command: ParsedCommand,
+    active_context: Option<String>,
+    clock: Arc<dyn Clock>,
+    printer: &SimpleTaskTextPrinter,
+) {
+    let undo_count = get_config().undo_depth;
+    let undos = match S::load_undos(undo_count) {
+        Ok(undos) => undos,
+        Err(e) => {
+            printer.error(&e.to_string());
+            exit(1);
+        }
+    };
     let undos_uuid: Vec<uuid::Uuid> = undos
         .iter()
         .flat_map(|x| x.tasks.iter().map(|y| *y.get_uuid()))
@@ -81,21 +158,31 @@ fn main() {
     let mut props: Option<TaskProperties> = None;
 
     if !command.arguments_as_filters {
-        match TaskProperties::from(&command.arguments) {
+        match TaskProperties::from_with_clock(&command.arguments, clock.clone()) {
             Ok(props_from_args) => {
                 props = Some(props_from_args);
             }
             Err(msg) => {
-                SimpleTaskTextPrinter.error(&msg);
+                printer.error(&msg);
                 exit(1);
             }
         }
     }
 
-    let mut tasks = match JsonStore::load_tasks(Some(&command.filters), props) {
+    let filters = if command.load_all_tasks {
+        None
+    } else {
+        Some(&command.filters)
+    };
+    let loaded_tasks = if command.load_raw_tasks {
+        S::load_raw_tasks()
+    } else {
+        S::load_tasks(filters, props, clock)
+    };
+    let mut tasks = match loaded_tasks {
         Ok(t) => t,
-        Err(msg) => {
-            SimpleTaskTextPrinter.error(&msg);
+        Err(e) => {
+            printer.error(&e.to_string());
             exit(1);
         }
     };
@@ -108,20 +195,31 @@ fn main() {
     let mut action = ActionRegistry::get_action_from_command_parser(&command);
     action.set_tasks(tasks);
     action.set_undos(undos);
-    match action.do_action(&SimpleTaskTextPrinter) {
+    action.set_context(active_context.clone());
+    match action.do_action(printer) {
         Ok(_) => {}
         Err(msg) => {
-            SimpleTaskTextPrinter.error(&msg);
+            printer.error(&msg);
             exit(1);
         }
     }
 
-    match JsonStore::write_tasks(action.get_tasks()) {
+    match S::write_tasks(action.get_tasks()) {
         Ok(_) => (),
-        Err(msg) => {
-            SimpleTaskTextPrinter.error(&msg);
+        Err(e) => {
+            printer.error(&e.to_string());
             exit(1);
         }
     };
-    JsonStore::log_undo(undo_count, action.get_undos().to_owned());
+    if let Err(e) = S::log_undo(undo_count, action.get_undos().to_owned(), undo_count) {
+        printer.error(&e.to_string());
+        exit(1);
+    }
+
+    if action.get_context() != &active_context
+        && let Err(e) = write_active_context(action.get_context().as_deref())
+    {
+        printer.error(&e.to_string());
+        exit(1);
+    }
 }
@@ -5,24 +5,34 @@ use crate::{
 
 use crate::config::SectionType;
 use bee_core::{
-    Printer,
-    config::ReportConfig,
+    Printer, TaskTreeNode,
+    clock::{Clock, SystemClock},
+    config::{ReportConfig, get_config},
     filters,
-    task::{Task, TaskStatus},
+    sort::compare_tasks,
+    task::{LinkType, Task, TaskStatus, UrgencyTerm, format_duration},
 };
 use chrono::{DateTime, Local};
 use colored::{ColoredString, Colorize};
 use indexmap::IndexMap;
 use log::{debug, trace};
 use serde_json::Value;
-use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::io::{self, Write};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Time elapsed from @t to @now, in the largest unit that makes sense. The time
+/// left until @t is prefixed with "in " when @t is in the future, e.g. "in 18h".
+fn format_relative_time(t: DateTime<Local>, now: DateTime<Local>) -> String {
+    if t > now {
+        return format!("in {}", format_duration_unit(t.signed_duration_since(now)));
+    }
+    format_duration_unit(now.signed_duration_since(t))
+}
 
-fn format_relative_time(t: DateTime<Local>) -> String {
-    let now = Local::now();
-    let diff = now.signed_duration_since(t);
-
+/// @diff in the largest unit that makes sense, e.g. "3d"
+fn format_duration_unit(diff: chrono::TimeDelta) -> String {
     let seconds = diff.num_seconds();
     let minutes = diff.num_minutes();
     let hours = diff.num_hours();
@@ -48,7 +58,90 @@ fn format_relative_time(t: DateTime<Local>) -> String {
     }
 }
 
-pub struct SimpleTaskTextPrinter;
+/// Table of the terms an urgency is made of, with the coefficient of each term,
+/// what it matched and how much it adds
+fn format_urgency_terms(terms: &[UrgencyTerm]) -> Result<String, String> {
+    let mut output = Vec::new();
+    let mut tbl = Table::new(
+        &vec![
+            "Coefficient".to_string(),
+            "Value".to_string(),
+            "Urgency".to_string(),
+        ],
+        &mut output,
+    )?;
+    for term in terms {
+        tbl.add_row(
+            vec![
+                term.name.to_owned(),
+                term.value.to_owned().unwrap_or_default(),
+                format!("{:+}", term.amount),
+            ],
+            None,
+        )?;
+    }
+    tbl.print();
+    String::from_utf8(output)
+        .map(|table| table.trim_end().to_owned())
+        .map_err(|e| e.to_string())
+}
+
+fn format_status(status: &TaskStatus) -> ColoredString {
+    let name = status.to_string().to_uppercase();
+    match status {
+        TaskStatus::Active => name.green(),
+        TaskStatus::Pending => name.blue(),
+        TaskStatus::Completed => name.green(),
+        TaskStatus::Deleted => name.bright_red(),
+    }
+}
+
+/// The subtasks of @task, one per line, with how much of them is completed.
+/// @subtasks are the ones that could be found, the others only show their UUID.
+fn format_subtasks(task: &Task, subtasks: &[&Task]) -> String {
+    let mut output = format!(
+        "\nSubtasks:\t{}",
+        format!("{}% completed", task.get_progress().unwrap_or_default()).bold()
+    );
+    for uuid in task.get_subtasks() {
+        output += "\n    ";
+        match subtasks.iter().find(|t| t.get_uuid() == uuid) {
+            // The tasks that are done have no ID anymore
+            Some(subtask) => {
+                let identifier = match subtask.get_id() {
+                    Some(id) => id.to_string(),
+                    None => uuid.to_string()[..8].to_string(),
+                };
+                output += format!(
+                    "{} - {} ({})",
+                    identifier.bold(),
+                    subtask.get_summary(),
+                    format_status(subtask.get_status())
+                )
+                .as_str();
+            }
+            None => output += format!("{} - not found", uuid.to_string().bold()).as_str(),
+        }
+    }
+    output
+}
+
+pub struct SimpleTaskTextPrinter {
+    /// Gives the time the ages and the tracked times are computed at
+    clock: Arc<dyn Clock>,
+}
+
+impl SimpleTaskTextPrinter {
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
+        SimpleTaskTextPrinter { clock }
+    }
+}
+
+impl Default for SimpleTaskTextPrinter {
+    fn default() -> Self {
+        SimpleTaskTextPrinter::new(Arc::new(SystemClock))
+    }
+}
 
 // Return the style that should be applied to a Task
 fn get_style_for_task(task: &Task) -> Result<Option<StyledText>, String> {
@@ -108,21 +201,6 @@ struct RowTask {
     row: Vec<String>,
 }
 
-impl PartialOrd for RowTask {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl Ord for RowTask {
-    fn cmp(&self, other: &Self) -> Ordering {
-        match self.task.cmp(&other.task) {
-            Ordering::Equal => unreachable!("Two rows have the same task"),
-            other => other,
-        }
-    }
-}
-
 impl Printer for SimpleTaskTextPrinter {
     fn show_help(&self, help_section_description: &HashMap<String, String>) -> Result<(), String> {
         let mut tbl = Table::new(
@@ -142,15 +220,14 @@ impl Printer for SimpleTaskTextPrinter {
         Ok(())
     }
 
-    fn print_task_info(&self, task: &Task) -> Result<(), String> {
-        let status = match task.get_status() {
-            TaskStatus::Active => task.get_status().to_string().to_uppercase().green(),
-            TaskStatus::Pending => task.get_status().to_string().to_uppercase().blue(),
-            TaskStatus::Completed => task.get_status().to_string().to_uppercase().green(),
-            TaskStatus::Deleted => task.get_status().to_string().to_uppercase().bright_red(),
-        };
+    fn print_task_info(&self, task: &Task, subtasks: Vec<&Task>) -> Result<(), String> {
         let mut output_str = String::default();
-        output_str += format!("\n{} - {}", status, task.get_summary()).as_str();
+        output_str += format!(
+            "\n{} - {}",
+            format_status(task.get_status()),
+            task.get_summary()
+        )
+        .as_str();
 
         output_str += format!(
             "\n\nIDs:\t\t{}{}",
@@ -170,6 +247,10 @@ impl Printer for SimpleTaskTextPrinter {
             output_str += format!("\nTags:\t\t{}", task.get_tags().join(" ").bold()).as_str();
         }
 
+        if let Some(priority) = task.get_priority() {
+            output_str += format!("\nPriority:\t{}", priority.to_string().bold()).as_str();
+        }
+
         output_str += "\n";
 
         output_str += format!(
@@ -201,6 +282,48 @@ impl Printer for SimpleTaskTextPrinter {
             .as_str();
         }
 
+        if let Some(wait_date) = task.get_date_wait() {
+            output_str += format!(
+                "\nWait:\t\t{}",
+                wait_date.format("%Y-%m-%d %H:%M").to_string().bold()
+            )
+            .as_str();
+        }
+
+        if let Some(scheduled_date) = task.get_date_scheduled() {
+            output_str += format!(
+                "\nScheduled:\t{}",
+                scheduled_date.format("%Y-%m-%d %H:%M").to_string().bold()
+            )
+            .as_str();
+        }
+
+        if let Some(recurrence) = task.get_recurrence() {
+            output_str += format!("\nRecurrence:\t{}", recurrence.to_string().bold()).as_str();
+        }
+
+        if let Some(template) = task.get_recurrence_template() {
+            output_str += format!("\nRecurs from:\t{}", template.to_string().bold()).as_str();
+        }
+
+        if !task.get_intervals().is_empty() {
+            output_str += format!(
+                "\nTracked:\t{}",
+                format_duration(task.get_tracked_time(self.clock.now()).num_seconds()).bold()
+            )
+            .as_str();
+        }
+
+        if let Some(description) = task.get_description() {
+            output_str += "\n\nDescription:";
+            for line in description.lines() {
+                output_str += "\n";
+                if !line.is_empty() {
+                    output_str += format!("    {}", line).as_str();
+                }
+            }
+        }
+
         if !task.get_annotations().is_empty() {
             output_str += "\n\nAnnotations:";
         }
@@ -213,14 +336,38 @@ impl Printer for SimpleTaskTextPrinter {
             .as_str();
         }
 
-        if !task.get_depends().is_empty() || !task.get_depends().is_empty() {
+        // Symmetric links are shown the same way from both of their ends
+        let mut links: Vec<(&str, Vec<Uuid>)> = Vec::new();
+        for kind in LinkType::ALL {
+            if kind.is_symmetric() {
+                links.push((
+                    kind.label(),
+                    [task.get_links(kind), task.get_backlinks(kind)].concat(),
+                ));
+            } else {
+                links.push((kind.label(), task.get_links(kind).to_vec()));
+                links.push((kind.inverse_label(), task.get_backlinks(kind).to_vec()));
+            }
+        }
+        links.retain(|(_, uuids)| !uuids.is_empty());
+
+        if !links.is_empty() || task.get_parent().is_some() || !task.get_subtasks().is_empty() {
             output_str += "\n";
         }
 
-        if !task.get_depends().is_empty() {
+        if let Some(parent) = task.get_parent() {
+            output_str += format!("\nParent:\t\t{}", parent.to_string().bold()).as_str();
+        }
+
+        if !task.get_subtasks().is_empty() {
+            output_str += format_subtasks(task, &subtasks).as_str();
+        }
+
+        for (label, uuids) in links {
             output_str += format!(
-                "\nDepends:\t{}",
-                task.get_depends()
+                "\n{}:\t{}",
+                label,
+                uuids
                     .iter()
                     .map(|uuid| uuid.to_string())
                     .collect::<Vec<String>>()
@@ -230,17 +377,36 @@ impl Printer for SimpleTaskTextPrinter {
             .as_str();
         }
 
-        if !task.get_blocking().is_empty() {
+        if !task.get_udas().is_empty() {
+            output_str += "\n\nAttributes:";
+        }
+        for (name, value) in task.get_udas() {
+            let value = match get_config().udas.get(name) {
+                Some(definition) => definition.format_value(value),
+                None => value.to_owned(),
+            };
+            output_str += format!("\n    {}: {}", name.bold(), value).as_str();
+        }
+
+        if !task.get_imported_fields().is_empty() {
+            output_str += "\n\nImported fields:";
+        }
+        for (name, value) in task.get_imported_fields() {
+            output_str += format!("\n    {}: {}", name.bold(), value).as_str();
+        }
+
+        if !task.get_urgency_terms().is_empty() {
             output_str += format!(
-                "\nBlocking:\t{}",
-                task.get_blocking()
+                "\n\nUrgency:\t{}\n",
+                task.get_urgency_terms()
                     .iter()
-                    .map(|uuid| uuid.to_string())
-                    .collect::<Vec<String>>()
-                    .join(" ")
+                    .map(|term| term.amount)
+                    .sum::<i64>()
+                    .to_string()
                     .bold()
             )
             .as_str();
+            output_str += format_urgency_terms(task.get_urgency_terms())?.as_str();
         }
 
         if !task.get_history().is_empty() {
@@ -269,6 +435,15 @@ impl Printer for SimpleTaskTextPrinter {
         self.print_list_of_tasks_impl(tasks, report_kind, &mut writer)
     }
 
+    fn print_tree_of_tasks(
+        &self,
+        roots: Vec<TaskTreeNode>,
+        report_kind: &ReportConfig,
+    ) -> Result<(), String> {
+        let mut writer = io::stdout();
+        self.print_tree_of_tasks_impl(roots, report_kind, &mut writer)
+    }
+
     fn show_information_message(&self, message: &str) {
         println!("{}", message);
     }
@@ -302,22 +477,43 @@ impl SimpleTaskTextPrinter {
             let mut row_fields: Vec<String> = Vec::default();
             for field in &report_kind.columns {
                 match field.as_str() {
-                    "date_created" | "date_completed" | "date_due" => {
+                    "date_created" | "date_completed" | "date_due" | "date_wait"
+                    | "date_scheduled" => {
                         if let Some(date_str) = t.get_field(field).as_str() {
                             let local_date: DateTime<Local> = DateTime::from(
                                 DateTime::parse_from_rfc3339(date_str).ok().unwrap(),
                             );
-                            row_fields.push(format_relative_time(local_date))
+                            row_fields.push(format_relative_time(local_date, self.clock.now()))
                         } else {
                             row_fields.push("".to_owned());
                         }
                     }
+                    "tracked" => {
+                        if t.get_intervals().is_empty() {
+                            row_fields.push("".to_string());
+                        } else {
+                            row_fields.push(format_duration(
+                                t.get_tracked_time(self.clock.now()).num_seconds(),
+                            ));
+                        }
+                    }
+                    "progress" => match t.get_progress() {
+                        Some(progress) => row_fields.push(format!("{}%", progress)),
+                        None => row_fields.push("".to_string()),
+                    },
                     "project" => {
                         match t.get_project() {
                             Some(proj) => row_fields.push(proj.get_name().to_owned()),
                             None => row_fields.push("".to_string()),
                         };
                     }
+                    _ if get_config().udas.contains_key(field) => {
+                        let value = match t.get_udas().get(field) {
+                            Some(value) => get_config().udas[field].format_value(value),
+                            None => "".to_string(),
+                        };
+                        row_fields.push(value);
+                    }
                     "summary" => {
                         let mut out_str = t.get_summary().to_owned();
                         t.get_annotations().iter().for_each(|ann| {
@@ -422,7 +618,7 @@ impl SimpleTaskTextPrinter {
                             "Parsing filter for section... section_name='{}'",
                             filter_name
                         );
-                        let filter_task = filters::from(filter_str)?;
+                        let filter_task = filters::from_with_clock(filter_str, self.clock.clone())?;
                         let mut remaining_task_to_row = Vec::new();
                         for row in rows.drain(0..) {
                             if filter_task.validate_task(&row.task) {
@@ -496,8 +692,7 @@ impl SimpleTaskTextPrinter {
 
         let mut tbl = Table::new(&header_names, writer)?;
         if let Some(rows) = group_on_value.get_mut(&empty_key) {
-            rows.sort();
-            rows.reverse();
+            rows.sort_by(|lhs, rhs| compare_tasks(&lhs.task, &rhs.task, &report_kind.sort));
 
             tbl.add_section("".to_string());
 
@@ -515,8 +710,7 @@ impl SimpleTaskTextPrinter {
                 debug!("Dropping section {} because it is empty!", section_name);
                 continue;
             }
-            rows.sort();
-            rows.reverse();
+            rows.sort_by(|lhs, rhs| compare_tasks(&lhs.task, &rhs.task, &report_kind.sort));
 
             tbl.add_section(section_name.to_string());
 
@@ -528,6 +722,81 @@ impl SimpleTaskTextPrinter {
         tbl.print();
         Ok(())
     }
+
+    fn print_tree_of_tasks_impl<W: Write>(
+        &self,
+        roots: Vec<TaskTreeNode>,
+        report_kind: &ReportConfig,
+        writer: &mut W,
+    ) -> Result<(), String> {
+        let nodes = flatten_tree(&roots);
+        let tasks = nodes.iter().map(|(task, _, _)| *task).collect();
+        let mut rows: Vec<RowTask> = self.build_row_task_objects(tasks, report_kind);
+
+        if rows.is_empty() {
+            return writeln!(writer, "No task to show.").map_err(|e| e.to_string());
+        }
+
+        // The tree guides are drawn in front of the summary, or of the first
+        // column if the report has no summary
+        let tree_column = report_kind
+            .columns
+            .iter()
+            .position(|c| c == "summary")
+            .unwrap_or(0);
+        for (row_task, (_, guide, indent)) in rows.iter_mut().zip(&nodes) {
+            if let Some(value) = row_task.row.get_mut(tree_column) {
+                let lines: Vec<String> = value
+                    .lines()
+                    .enumerate()
+                    .map(|(i, line)| match i {
+                        0 => format!("{}{}", guide, line),
+                        _ => format!("{}{}", indent, line),
+                    })
+                    .collect();
+                *value = lines.join("\n");
+            }
+        }
+
+        let (rows, header_names) = self.remove_unused_columns(rows, report_kind);
+
+        // The rows are kept in the order of the tree
+        let mut tbl = Table::new(&header_names, writer)?;
+        tbl.add_section("".to_string());
+        for row_task in rows {
+            tbl.add_row(row_task.row, get_style_for_task(&row_task.task)?)
+                .unwrap();
+        }
+        tbl.print();
+        Ok(())
+    }
+}
+
+/// Depth-first list of the tasks under the @roots, each with the guide drawn in
+/// front of its first line and the indentation of its other lines
+fn flatten_tree<'a>(roots: &[TaskTreeNode<'a>]) -> Vec<(&'a Task, String, String)> {
+    let mut out = Vec::new();
+    for root in roots {
+        out.push((root.task, String::new(), String::new()));
+        flatten_children(&root.children, "", &mut out);
+    }
+    out
+}
+
+/// Add the tasks under @nodes to @out, @prefix is the indentation of their parent
+fn flatten_children<'a>(
+    nodes: &[TaskTreeNode<'a>],
+    prefix: &str,
+    out: &mut Vec<(&'a Task, String, String)>,
+) {
+    for (i, node) in nodes.iter().enumerate() {
+        let (guide, indent) = match i + 1 == nodes.len() {
+            true => (format!("{}└─ ", prefix), format!("{}   ", prefix)),
+            false => (format!("{}├─ ", prefix), format!("{}│  ", prefix)),
+        };
+        out.push((node.task, guide, indent.clone()));
+        flatten_children(&node.children, &indent, out);
+    }
 }
 
 fn print_value(value: &Value) -> String {
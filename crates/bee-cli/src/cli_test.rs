@@ -40,15 +40,38 @@ fn test_format_relative_time() {
     ];
 
     for (name, input, expected) in tests {
-        let got = format_relative_time(input);
+        let got = format_relative_time(input, now);
         assert_eq!(expected, got, "Failed test for {}", name);
     }
 }
 
+#[test]
+fn test_format_relative_time_future() {
+    let now = Local::now();
+
+    let tests = vec![
+        (
+            "Seconds",
+            now + Duration::try_seconds(30).unwrap(),
+            "in 30s",
+        ),
+        ("Hours", now + Duration::try_hours(18).unwrap(), "in 18h"),
+        ("Days", now + Duration::try_days(6).unwrap(), "in 6d"),
+        ("Weeks", now + Duration::try_days(15).unwrap(), "in 2w"),
+        ("Years", now + Duration::try_days(730).unwrap(), "in 2y"),
+    ];
+
+    for (name, input, expected) in tests {
+        let got = format_relative_time(input, now);
+        assert_eq!(expected, got, "Failed test for {}", name);
+    }
+    assert_eq!(format_relative_time(now, now), "0s");
+}
+
 #[test]
 fn test_print_table_no_task() {
     let mut buffer = Cursor::new(Vec::new());
-    SimpleTaskTextPrinter
+    SimpleTaskTextPrinter::default()
         .print_list_of_tasks_impl(Vec::new(), &ReportConfig::default(), &mut buffer)
         .unwrap();
     assert_eq!(
@@ -56,3 +79,175 @@ fn test_print_table_no_task() {
         "No task to show.\n"
     )
 }
+
+#[test]
+fn test_format_urgency_terms() {
+    colored::control::set_override(false);
+    let table = format_urgency_terms(&[
+        UrgencyTerm {
+            name: "priority".to_string(),
+            value: Some("H".to_string()),
+            amount: 6,
+        },
+        UrgencyTerm {
+            name: "depends".to_string(),
+            value: Some("2".to_string()),
+            amount: -2,
+        },
+    ])
+    .unwrap();
+    let lines: Vec<&str> = table.lines().map(|line| line.trim_end()).collect();
+    assert_eq!(
+        lines,
+        vec![
+            "Coefficient Value Urgency",
+            "priority    H     +6",
+            "depends     2     -2",
+        ]
+    );
+}
+
+#[test]
+fn test_print_tree_no_task() {
+    let mut buffer = Cursor::new(Vec::new());
+    SimpleTaskTextPrinter::default()
+        .print_tree_of_tasks_impl(Vec::new(), &ReportConfig::default(), &mut buffer)
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(buffer.into_inner()).unwrap(),
+        "No task to show.\n"
+    )
+}
+
+#[test]
+fn test_flatten_tree() {
+    use bee_core::task::{TaskData, TaskProperties};
+    let mut data = TaskData::default();
+    for summary in ["release", "build", "design", "document"] {
+        data.add_task(
+            &TaskProperties::from(&[summary.to_string()]).unwrap(),
+            TaskStatus::Pending,
+        )
+        .unwrap();
+    }
+    let task = |summary: &str| {
+        data.get_task_map()
+            .values()
+            .find(|t| t.get_summary() == summary)
+            .unwrap()
+    };
+    let node = |summary: &str, children| TaskTreeNode {
+        task: task(summary),
+        children,
+    };
+    let roots = vec![node(
+        "release",
+        vec![
+            node("build", vec![node("design", Vec::new())]),
+            node("document", Vec::new()),
+        ],
+    )];
+    let guides: Vec<(&str, String, String)> = flatten_tree(&roots)
+        .into_iter()
+        .map(|(task, guide, indent)| (task.get_summary(), guide, indent))
+        .collect();
+    assert_eq!(
+        guides,
+        vec![
+            ("release", "".to_string(), "".to_string()),
+            ("build", "├─ ".to_string(), "│  ".to_string()),
+            ("design", "│  └─ ".to_string(), "│     ".to_string()),
+            ("document", "└─ ".to_string(), "   ".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_print_table_sorted() {
+    use bee_core::clock::FixedClock;
+    use bee_core::sort::parse_sort_keys;
+    use bee_core::task::{TaskData, TaskProperties};
+    colored::control::set_override(false);
+
+    // Tasks created at the same time with the same urgency used to be impossible to order
+    let mut data = TaskData::default();
+    data.set_clock(Arc::new(FixedClock::new(Local::now())));
+    for summary in ["b", "a", "c"] {
+        data.add_task(
+            &TaskProperties::from(&[summary.to_string()]).unwrap(),
+            TaskStatus::Pending,
+        )
+        .unwrap();
+    }
+    data.upkeep().unwrap();
+    let tasks: Vec<&Task> = data.get_task_map().values().collect();
+
+    let print = |sort: &str| {
+        let report = ReportConfig {
+            columns: vec!["summary".to_string()],
+            column_names: vec!["Summary".to_string()],
+            sort: parse_sort_keys(sort).unwrap(),
+            ..ReportConfig::default()
+        };
+        let mut buffer = Cursor::new(Vec::new());
+        SimpleTaskTextPrinter::default()
+            .print_list_of_tasks_impl(tasks.clone(), &report, &mut buffer)
+            .unwrap();
+        String::from_utf8(buffer.into_inner())
+            .unwrap()
+            .lines()
+            .skip(1)
+            .map(|line| line.trim_end().to_string())
+            .collect::<Vec<String>>()
+    };
+    assert_eq!(print("summary+"), vec!["a", "b", "c"]);
+    assert_eq!(print("summary-"), vec!["c", "b", "a"]);
+    assert_eq!(print("urgency-,created-").len(), 3);
+}
+
+#[test]
+fn test_format_subtasks() {
+    use bee_core::task::{TaskData, TaskProperties};
+    colored::control::set_override(false);
+    let mut data = TaskData::default();
+    let mut add = |value: String| {
+        *data
+            .add_task(
+                &TaskProperties::from(&[value]).unwrap(),
+                TaskStatus::Pending,
+            )
+            .unwrap()
+            .get_uuid()
+    };
+    let parent = add("move out".to_string());
+    let boxes = add(format!("buy boxes parent:{}", parent));
+    let pack = add(format!("pack parent:{}", parent));
+    data.task_done(&boxes);
+    data.upkeep().unwrap();
+
+    let task = data.get_task_map()[&parent].to_owned();
+    let subtasks: Vec<&Task> = [boxes, pack]
+        .iter()
+        .map(|uuid| &data.get_task_map()[uuid])
+        .collect();
+    let output = format_subtasks(&task, &subtasks);
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines[1], "Subtasks:\t50% completed");
+    assert_eq!(
+        lines[2..],
+        [
+            format!("    {} - buy boxes (COMPLETED)", &boxes.to_string()[..8]),
+            format!(
+                "    {} - pack (PENDING)",
+                data.get_task_map()[&pack].get_id().unwrap()
+            ),
+        ]
+    );
+
+    // A subtask that is not loaded is still listed
+    let output = format_subtasks(&task, &subtasks[1..]);
+    assert_eq!(
+        output.lines().nth(2).unwrap(),
+        format!("    {} - not found", boxes)
+    );
+}
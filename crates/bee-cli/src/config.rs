@@ -20,12 +20,13 @@ pub struct Config {
 
 impl Config {
     fn validate(&self) -> Result<(), String> {
-        if let Some(section_type) = &self.section.section_type {
-            if section_type == &SectionType::Filters && self.section.filters.is_empty() {
-                return Err("Configuration: Section: The section configuration type is \
+        if let Some(section_type) = &self.section.section_type
+            && section_type == &SectionType::Filters
+            && self.section.filters.is_empty()
+        {
+            return Err("Configuration: Section: The section configuration type is \
                                'filters' but no filter was provided."
-                    .to_string());
-            }
+                .to_string());
         }
 
         Ok(())
@@ -74,13 +75,6 @@ fn default_section_header_bg() -> (u8, u8, u8) {
     (26, 26, 26)
 }
 
-#[derive(Deserialize, Debug, PartialEq)]
-pub struct CoeffientField {
-    pub field: String,
-    pub value: Option<String>,
-    pub coefficient: i64,
-}
-
 #[derive(Deserialize, Debug, PartialEq)]
 pub struct ColourField {
     pub field: String,
@@ -212,8 +206,10 @@ where
 impl Config {
     pub fn get_primary_colour_fg(&self) -> (u8, u8, u8) {
         for c in &self.colour_fields {
-            if c.field == "primary_colour" && c.fg.is_some() {
-                return c.fg.unwrap();
+            if c.field == "primary_colour"
+                && let Some(colour) = c.fg
+            {
+                return colour;
             }
         }
         (220, 220, 220)
@@ -221,8 +217,10 @@ impl Config {
 
     pub fn get_primary_colour_bg(&self) -> (u8, u8, u8) {
         for c in &self.colour_fields {
-            if c.field == "primary_colour" && c.bg.is_some() {
-                return c.bg.unwrap();
+            if c.field == "primary_colour"
+                && let Some(colour) = c.bg
+            {
+                return colour;
             }
         }
         (89, 89, 89)
@@ -230,8 +228,10 @@ impl Config {
 
     pub fn get_secondary_colour_fg(&self) -> (u8, u8, u8) {
         for c in &self.colour_fields {
-            if c.field == "secondary_colour" && c.fg.is_some() {
-                return c.fg.unwrap();
+            if c.field == "secondary_colour"
+                && let Some(colour) = c.fg
+            {
+                return colour;
             }
         }
         (220, 220, 220)
@@ -239,8 +239,10 @@ impl Config {
 
     pub fn get_secondary_colour_bg(&self) -> (u8, u8, u8) {
         for c in &self.colour_fields {
-            if c.field == "secondary_colour" && c.bg.is_some() {
-                return c.bg.unwrap();
+            if c.field == "secondary_colour"
+                && let Some(colour) = c.bg
+            {
+                return colour;
             }
         }
         (38, 38, 38)
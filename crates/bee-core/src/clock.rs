@@ -0,0 +1,76 @@
+use std::env;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use chrono::{DateTime, Local, TimeZone};
+
+use crate::parser::parse_iso_date;
+
+/// Name of the environment variable fixing the current time, see `clock_from_env`
+pub const NOW_ENV_VARIABLE: &str = "BEE_NOW";
+
+/// Where the current time comes from. Everything that depends on the current time
+/// (history entries, urgency, relative dates, ...) asks a clock for it, so that a
+/// fixed one can be used to get reproducible results.
+pub trait Clock: Debug + Send + Sync {
+    fn now(&self) -> DateTime<Local>;
+}
+
+/// The clock of the system
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+}
+
+/// A clock that is stopped at a given time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FixedClock {
+    time: DateTime<Local>,
+}
+
+impl FixedClock {
+    pub fn new(time: DateTime<Local>) -> FixedClock {
+        FixedClock { time }
+    }
+
+    /// Parse the time as an RFC 3339 date (`2026-11-03T14:00:00+01:00`) or as a
+    /// local date such as `2026-11-03` or `2026-11-03T14:00`
+    pub fn from_string(input: &str) -> Result<FixedClock, String> {
+        let time = match DateTime::parse_from_rfc3339(input) {
+            Ok(time) => time.with_timezone(&Local),
+            Err(_) => parse_iso_date(input)
+                .and_then(|time| Local.from_local_datetime(&time).earliest())
+                .ok_or(format!(
+                    "Invalid time '{}'. Expected a date such as '2026-11-03', \
+                    '2026-11-03T14:00' or '2026-11-03T14:00:00+01:00'",
+                    input
+                ))?,
+        };
+        Ok(FixedClock::new(time))
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Local> {
+        self.time
+    }
+}
+
+/// The clock to use: a fixed clock if the `BEE_NOW` environment variable is set,
+/// the system clock otherwise
+pub fn clock_from_env() -> Result<Arc<dyn Clock>, String> {
+    match env::var(NOW_ENV_VARIABLE) {
+        Ok(value) if !value.is_empty() => FixedClock::from_string(&value)
+            .map(|clock| Arc::new(clock) as Arc<dyn Clock>)
+            .map_err(|e| format!("Invalid value for {}: {}", NOW_ENV_VARIABLE, e)),
+        _ => Ok(Arc::new(SystemClock)),
+    }
+}
+
+#[cfg(test)]
+#[path = "clock_test.rs"]
+mod clock_test;
@@ -0,0 +1,36 @@
+use all_asserts::assert_true;
+use chrono::{FixedOffset, Local, TimeZone};
+
+use super::*;
+
+#[test]
+fn test_fixed_clock() {
+    let time = Local.with_ymd_and_hms(2026, 11, 3, 14, 0, 0).unwrap();
+    let clock = FixedClock::new(time);
+    assert_eq!(clock.now(), time);
+    assert_eq!(clock.now(), clock.now());
+}
+
+#[test]
+fn test_fixed_clock_from_string() {
+    assert_eq!(
+        FixedClock::from_string("2026-11-03").unwrap().now(),
+        Local.with_ymd_and_hms(2026, 11, 3, 0, 0, 0).unwrap()
+    );
+    assert_eq!(
+        FixedClock::from_string("2026-11-03T14:00").unwrap().now(),
+        Local.with_ymd_and_hms(2026, 11, 3, 14, 0, 0).unwrap()
+    );
+    assert_eq!(
+        FixedClock::from_string("2026-11-03T14:00:00+02:00")
+            .unwrap()
+            .now(),
+        FixedOffset::east_opt(2 * 3600)
+            .unwrap()
+            .with_ymd_and_hms(2026, 11, 3, 14, 0, 0)
+            .unwrap()
+    );
+
+    assert_true!(FixedClock::from_string("tomorrow").is_err());
+    assert_true!(FixedClock::from_string("2026-13-03").is_err());
+}
@@ -1,13 +1,16 @@
 use log::debug;
 use once_cell::sync::Lazy;
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
 
 use serde::Deserialize;
 
+use crate::sort::SortKey;
+use crate::task::UdaDefinition;
+
 #[derive(Deserialize, Debug, PartialEq)]
 pub struct Config {
     #[serde(default = "default_report_name")]
@@ -20,10 +23,39 @@ pub struct Config {
     #[serde(default)]
     #[serde(rename = "coefficients")]
     pub coefficients: Vec<CoeffientField>,
+
+    /// Backend used to store the tasks and the undo log
+    #[serde(default)]
+    pub storage: StorageBackend,
+
+    /// How many operations are kept in the undo log
+    #[serde(default = "default_undo_depth")]
+    pub undo_depth: usize,
+
+    /// Complete a task once all of its subtasks are completed
+    #[serde(default)]
+    pub complete_parents: bool,
+
+    /// User defined attributes (UDA), by name
+    #[serde(default)]
+    #[serde(rename = "uda")]
+    pub udas: BTreeMap<String, UdaDefinition>,
+
+    /// Contexts that can be activated with the 'context' action, by name
+    #[serde(default)]
+    #[serde(rename = "context")]
+    pub contexts: BTreeMap<String, ContextConfig>,
+}
+
+fn default_undo_depth() -> usize {
+    100
 }
 
 fn default_report_map() -> HashMap<String, ReportConfig> {
-    HashMap::from([(DEFAULT_REPORT_NAME.to_string(), ReportConfig::default())])
+    HashMap::from([
+        (DEFAULT_REPORT_NAME.to_string(), ReportConfig::default()),
+        (WAITING_REPORT_NAME.to_string(), ReportConfig::waiting()),
+    ])
 }
 
 fn default_report_name() -> String {
@@ -36,6 +68,11 @@ impl Default for Config {
             default_report: default_report_name(),
             report_map: default_report_map(),
             coefficients: Vec::new(),
+            storage: StorageBackend::default(),
+            undo_depth: default_undo_depth(),
+            complete_parents: false,
+            udas: BTreeMap::new(),
+            contexts: BTreeMap::new(),
         }
     }
 }
@@ -55,11 +92,34 @@ impl Config {
     }
 }
 
+#[derive(Deserialize, Debug, PartialEq, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    /// Every task is kept in a single JSON file
+    #[default]
+    Json,
+    /// Tasks are kept in an SQLite database, with indexes on the commonly filtered fields
+    Sqlite,
+}
+
 #[derive(Deserialize, Debug, PartialEq, Default)]
 pub struct CoeffientField {
     pub field: String,
     pub value: Option<String>,
     pub coefficient: i64,
+    /// Number of days over which the coefficient of `due` and `age` builds up
+    pub window: Option<i64>,
+    /// Highest urgency the `due` coefficient gives to an overdue task
+    pub overdue_max: Option<i64>,
+}
+
+/// A named filter which, once activated, restricts every command to the tasks it matches
+#[derive(Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct ContextConfig {
+    pub filter: String,
+    /// Properties given to the tasks added while the context is active, e.g. `project:work`
+    #[serde(default)]
+    pub defaults: String,
 }
 
 #[derive(Deserialize, Debug, PartialEq, Clone)]
@@ -68,6 +128,25 @@ pub struct ReportConfig {
     pub columns: Vec<String>,
     pub column_names: Vec<String>,
     pub default: bool,
+    /// Fields the tasks are sorted by, e.g. `["due+", "urgency-"]`
+    #[serde(default = "default_sort")]
+    pub sort: Vec<SortKey>,
+    /// The actions printing the report leave out the waiting tasks, unless its
+    /// filter or the one of the command line asks for `status:waiting`
+    #[serde(default = "default_hide_waiting")]
+    pub hide_waiting: bool,
+}
+
+fn default_hide_waiting() -> bool {
+    true
+}
+
+/// The most urgent tasks first, then the most recent ones
+pub(crate) fn default_sort() -> Vec<SortKey> {
+    ["urgency-", "created-"]
+        .iter()
+        .map(|key| SortKey::from_string(key).unwrap())
+        .collect()
 }
 
 impl Default for ReportConfig {
@@ -83,6 +162,28 @@ impl Default for ReportConfig {
                 .iter()
                 .map(|&s| s.to_string())
                 .collect(),
+            sort: default_sort(),
+            hide_waiting: default_hide_waiting(),
+        }
+    }
+}
+
+impl ReportConfig {
+    /// Report listing the tasks hidden until their wait date
+    fn waiting() -> Self {
+        ReportConfig {
+            default: false,
+            filters: vec!["status:waiting".to_string()],
+            columns: ["id", "date_wait", "summary", "tags", "urgency"]
+                .iter()
+                .map(|&s| s.to_string())
+                .collect(),
+            column_names: ["ID", "Wait", "Summary", "Tags", "Urgency"]
+                .iter()
+                .map(|&s| s.to_string())
+                .collect(),
+            sort: default_sort(),
+            hide_waiting: false,
         }
     }
 }
@@ -100,6 +201,10 @@ static CONFIG: Lazy<Result<Config, String>> = Lazy::new(|| match load_config() {
 
 const DEFAULT_REPORT_NAME: &str = "__default";
 
+/// Name of the report that is always available to show the waiting tasks,
+/// unless the configuration defines its own
+const WAITING_REPORT_NAME: &str = "waiting";
+
 pub fn load_config() -> Result<Config, String> {
     match find_config_file() {
         Some(file) => {
@@ -130,6 +235,11 @@ fn load_config_from_string(content: &str) -> Result<Config, String> {
         return Err("Configuration file found but the [core] section is missing.".to_string());
     };
 
+    for (name, uda) in &config.udas {
+        uda.validate(name)
+            .map_err(|e| format!("Unable to parse the [core.uda] section. {}", e))?;
+    }
+
     for (name, report) in &config.report_map {
         if report.default {
             config.default_report = name.clone();
@@ -141,6 +251,10 @@ fn load_config_from_string(content: &str) -> Result<Config, String> {
             .report_map
             .insert(DEFAULT_REPORT_NAME.to_string(), ReportConfig::default());
     }
+    config
+        .report_map
+        .entry(WAITING_REPORT_NAME.to_string())
+        .or_insert_with(ReportConfig::waiting);
     Ok(config)
 }
 
@@ -162,11 +276,11 @@ pub fn find_config_file() -> Option<PathBuf> {
             Cow::Owned(v) => v.to_string(),
         };
 
-        if let Ok(full_path) = Path::new(&expanded_path).canonicalize() {
-            if full_path.exists() {
-                debug!("Found config file {}", expanded_path);
-                return Some(full_path);
-            }
+        if let Ok(full_path) = Path::new(&expanded_path).canonicalize()
+            && full_path.exists()
+        {
+            debug!("Found config file {}", expanded_path);
+            return Some(full_path);
         }
     }
 
@@ -175,9 +289,10 @@ pub fn find_config_file() -> Option<PathBuf> {
 
 #[cfg(test)]
 mod test {
-    use all_asserts::assert_true;
+    use all_asserts::{assert_false, assert_true};
 
     use super::*;
+    use crate::task::UdaType;
 
     // Test that this doesn't panic
     #[test]
@@ -198,6 +313,134 @@ debug = true
         assert_true!(_result.is_ok());
     }
 
+    #[test]
+    fn test_load_config_storage_backend() {
+        let config = load_config_from_string("[core]").unwrap();
+        assert_eq!(config.storage, StorageBackend::Json);
+
+        let content = r#"
+[core]
+storage = "sqlite"
+        "#;
+        let config = load_config_from_string(content).unwrap();
+        assert_eq!(config.storage, StorageBackend::Sqlite);
+
+        assert_true!(load_config_from_string("[core]\nstorage = \"csv\"").is_err());
+    }
+
+    #[test]
+    fn test_load_config_undo_depth() {
+        let config = load_config_from_string("[core]").unwrap();
+        assert_eq!(config.undo_depth, 100);
+
+        let config = load_config_from_string("[core]\nundo_depth = 5").unwrap();
+        assert_eq!(config.undo_depth, 5);
+    }
+
+    #[test]
+    fn test_load_config_complete_parents() {
+        let config = load_config_from_string("[core]").unwrap();
+        assert_false!(config.complete_parents);
+
+        let config = load_config_from_string("[core]\ncomplete_parents = true").unwrap();
+        assert_true!(config.complete_parents);
+    }
+
+    #[test]
+    fn test_load_config_udas() {
+        let content = r#"
+[core.uda.estimate]
+type = "duration"
+
+[core.uda.size]
+type = "enum"
+values = ["S", "M", "L"]
+        "#;
+        let config = load_config_from_string(content).unwrap();
+        assert_eq!(config.udas.len(), 2);
+        assert_eq!(config.udas["estimate"].uda_type, UdaType::Duration);
+        assert_eq!(config.udas["size"].values, vec!["S", "M", "L"]);
+
+        assert_true!(load_config_from_string("[core.uda.size]\ntype = \"enum\"").is_err());
+        assert_true!(load_config_from_string("[core.uda.due]\ntype = \"date\"").is_err());
+        assert_true!(load_config_from_string("[core.uda.cost]\ntype = \"money\"").is_err());
+    }
+
+    #[test]
+    fn test_load_config_contexts() {
+        let content = r#"
+[core.context.work]
+filter = "+work or project:work"
+defaults = "project:work"
+
+[core.context.home]
+filter = "+home"
+        "#;
+        let config = load_config_from_string(content).unwrap();
+        assert_eq!(config.contexts.len(), 2);
+        assert_eq!(config.contexts["work"].filter, "+work or project:work");
+        assert_eq!(config.contexts["work"].defaults, "project:work");
+        assert_eq!(config.contexts["home"].defaults, "");
+
+        assert_true!(load_config_from_string("[core.context.work]\ndefaults = \"+work\"").is_err());
+    }
+
+    #[test]
+    fn test_load_config_report_sort() {
+        let content = r#"
+[core.report.next]
+filters = []
+columns = ["id", "summary"]
+column_names = ["ID", "Summary"]
+default = false
+sort = ["due+", "project+", "urgency-"]
+"#;
+        let config = load_config_from_string(content).unwrap();
+        let sort: Vec<String> = config.report_map["next"]
+            .sort
+            .iter()
+            .map(|key| key.to_string())
+            .collect();
+        assert_eq!(sort, vec!["due+", "project+", "urgency-"]);
+        assert_eq!(config.get_default_report().sort, default_sort());
+
+        assert_true!(load_config_from_string(&content.replace("due+", "due")).is_err());
+        assert_true!(load_config_from_string(&content.replace("due+", "someday+")).is_err());
+    }
+
+    #[test]
+    fn test_load_config_report_hide_waiting() {
+        let content = r#"
+[core.report.all]
+filters = []
+columns = ["id", "summary"]
+column_names = ["ID", "Summary"]
+default = false
+"#;
+        let config = load_config_from_string(content).unwrap();
+        assert_true!(config.report_map["all"].hide_waiting);
+        assert_true!(config.get_default_report().hide_waiting);
+        assert_false!(config.report_map[WAITING_REPORT_NAME].hide_waiting);
+
+        let config = load_config_from_string(&format!("{}hide_waiting = false", content)).unwrap();
+        assert_false!(config.report_map["all"].hide_waiting);
+    }
+
+    #[test]
+    fn test_waiting_report_exists() {
+        let config = Config::default();
+        assert_eq!(
+            config.get_report(WAITING_REPORT_NAME),
+            Some(&ReportConfig::waiting())
+        );
+
+        let config = load_config_from_string("[core]").unwrap();
+        assert_eq!(
+            config.get_report(WAITING_REPORT_NAME),
+            Some(&ReportConfig::waiting())
+        );
+    }
+
     #[test]
     fn test_get_default_report_exists() {
         let config = Config::default();
@@ -2,22 +2,28 @@ mod filters_impl;
 
 mod parser;
 
+use crate::clock::{Clock, SystemClock};
 use crate::lexer::Lexer;
-use crate::task::Task;
+use crate::task::{Task, TaskStatus};
 use parser::FilterParser;
 
+use chrono::{DateTime, Days, Local};
 use log::{debug, error};
 use std::{
     any::Any,
     collections::HashMap,
     fmt::{Debug, Display},
+    sync::Arc,
 };
 use uuid::Uuid;
 
 use filters_impl::{
-    AndFilter, DateCreatedFilter, DateDueFilter, DateEndFilter, DependsOnFilter, FilterKind,
-    FilterKindGetter, OrFilter, ProjectFilter, RootFilter, StatusFilter, StringFilter, TagFilter,
-    TaskIdFilter, UuidFilter, XorFilter,
+    AndFilter, DateCreatedFilter, DateDueFilter, DateDueFilterType, DateEndFilter,
+    DateScheduledFilter, DateWaitFilter, DependsOnFilter, FilterKind, FilterKindGetter,
+    HasSubtasksFilter, LinkedFilter, NotFilter, OrFilter, ParentFilter, PriorityFilter,
+    ProjectFilter, ProjectIsFilter, RecurrenceFilter, RegexFilter, RootFilter, StatusFilter,
+    StringFilter, TagFilter, TaskIdFilter, TextFilter, UdaFilter, UuidFilter, WaitingFilter,
+    XorFilter,
 };
 
 #[allow(private_bounds)]
@@ -81,8 +87,16 @@ pub fn or(lhs: Box<dyn Filter>, rhs: Box<dyn Filter>) -> Box<dyn Filter> {
 }
 
 pub fn from(values: &[String]) -> Result<Box<dyn Filter>, String> {
+    from_with_clock(values, Arc::new(SystemClock))
+}
+
+/// Parse a filter, the relative dates being relative to the time of @clock
+pub fn from_with_clock(
+    values: &[String],
+    clock: Arc<dyn Clock>,
+) -> Result<Box<dyn Filter>, String> {
     let lexer = Lexer::new(values.join(" "));
-    let mut parser = FilterParser::new(lexer);
+    let mut parser = FilterParser::new(lexer).with_clock(clock);
     let f = parser.parse_filter()?;
     debug!("Parsed filter:\n{}", f);
     Ok(f)
@@ -92,6 +106,13 @@ pub fn new_empty() -> Box<dyn Filter> {
     Default::default()
 }
 
+/// Whether a term of @filter is the 'waiting' virtual status, which means the
+/// waiting tasks were asked for
+#[allow(clippy::borrowed_box)]
+pub fn mentions_waiting(filter: &Box<dyn Filter>) -> bool {
+    filter.iter().any(|f| f.get_kind() == FilterKind::Waiting)
+}
+
 #[allow(clippy::borrowed_box)]
 fn downcast_and_compare<T: Filter + PartialEq>(
     self_filter: &Box<dyn Filter>,
@@ -147,17 +168,169 @@ impl PartialEq for Box<dyn Filter> {
             FilterKind::And => downcast_and_compare::<AndFilter>(self, other),
             FilterKind::Or => downcast_and_compare::<OrFilter>(self, other),
             FilterKind::Xor => downcast_and_compare::<XorFilter>(self, other),
+            FilterKind::Not => downcast_and_compare::<NotFilter>(self, other),
             FilterKind::String => downcast_and_compare::<StringFilter>(self, other),
+            FilterKind::Text => downcast_and_compare::<TextFilter>(self, other),
+            FilterKind::Regex => downcast_and_compare::<RegexFilter>(self, other),
             FilterKind::Status => downcast_and_compare::<StatusFilter>(self, other),
             FilterKind::Project => downcast_and_compare::<ProjectFilter>(self, other),
+            FilterKind::ProjectIs => downcast_and_compare::<ProjectIsFilter>(self, other),
             FilterKind::Tag => downcast_and_compare::<TagFilter>(self, other),
             FilterKind::Uuid => downcast_and_compare::<UuidFilter>(self, other),
             FilterKind::TaskId => downcast_and_compare::<TaskIdFilter>(self, other),
             FilterKind::DependsOn => downcast_and_compare::<DependsOnFilter>(self, other),
+            FilterKind::Linked => downcast_and_compare::<LinkedFilter>(self, other),
+            FilterKind::Parent => downcast_and_compare::<ParentFilter>(self, other),
+            FilterKind::HasSubtasks => downcast_and_compare::<HasSubtasksFilter>(self, other),
+            FilterKind::Recurrence => downcast_and_compare::<RecurrenceFilter>(self, other),
+            FilterKind::Priority => downcast_and_compare::<PriorityFilter>(self, other),
+            FilterKind::Uda => downcast_and_compare::<UdaFilter>(self, other),
             FilterKind::DateEnd => downcast_and_compare::<DateEndFilter>(self, other),
             FilterKind::DateCreated => downcast_and_compare::<DateCreatedFilter>(self, other),
             FilterKind::DateDue => downcast_and_compare::<DateDueFilter>(self, other),
+            FilterKind::DateWait => downcast_and_compare::<DateWaitFilter>(self, other),
+            FilterKind::DateScheduled => downcast_and_compare::<DateScheduledFilter>(self, other),
+            FilterKind::Waiting => downcast_and_compare::<WaitingFilter>(self, other),
+        }
+    }
+}
+
+/// Simplified view of a filter, restricted to the task fields a storage backend
+/// is expected to index. It lets a backend select candidate tasks on its own,
+/// for instance by turning it into a database query.
+///
+/// An `IndexedFilter` matches a superset of the tasks matched by the filter it was
+/// built from, so the tasks it selects must still be validated against the
+/// original filter.
+#[derive(Clone, Debug, PartialEq)]
+pub enum IndexedFilter {
+    /// Matches every task
+    All,
+    And(Vec<IndexedFilter>),
+    Or(Vec<IndexedFilter>),
+    Status(TaskStatus),
+    /// Matches the tasks whose project starts with the given name
+    Project(String),
+    Tag {
+        name: String,
+        include: bool,
+    },
+    Id(usize),
+    Uuid(Uuid),
+    /// Matches the tasks due strictly before the given time
+    DueBefore(DateTime<Local>),
+    /// Matches the tasks due at or after the given time
+    DueAfter(DateTime<Local>),
+}
+
+/// Build the `IndexedFilter` selecting the candidate tasks of @filter.
+/// The parts of the filter that cannot be expressed with indexed fields are
+/// relaxed to `IndexedFilter::All`.
+#[allow(clippy::borrowed_box)]
+pub fn to_indexed(filter: &Box<dyn Filter>) -> IndexedFilter {
+    match filter.get_kind() {
+        FilterKind::And => {
+            let and_filter = filter.as_any().downcast_ref::<AndFilter>().unwrap();
+            let children: Vec<IndexedFilter> = and_filter
+                .children
+                .iter()
+                .map(to_indexed)
+                .filter(|child| child != &IndexedFilter::All)
+                .collect();
+            match children.len() {
+                0 => IndexedFilter::All,
+                1 => children.into_iter().next().unwrap(),
+                _ => IndexedFilter::And(children),
+            }
+        }
+        FilterKind::Or => {
+            let or_filter = filter.as_any().downcast_ref::<OrFilter>().unwrap();
+            let children: Vec<IndexedFilter> = or_filter.children.iter().map(to_indexed).collect();
+            if children.is_empty() || children.contains(&IndexedFilter::All) {
+                IndexedFilter::All
+            } else {
+                IndexedFilter::Or(children)
+            }
+        }
+        FilterKind::Not => {
+            let not_filter = filter.as_any().downcast_ref::<NotFilter>().unwrap();
+            // The relaxed form of any other filter matches more tasks than the
+            // filter does, so its negation would match fewer
+            match not_filter.child.as_any().downcast_ref::<TagFilter>() {
+                Some(tag_filter) => IndexedFilter::Tag {
+                    name: tag_filter.tag_name.to_owned(),
+                    include: !tag_filter.include,
+                },
+                None => IndexedFilter::All,
+            }
+        }
+        FilterKind::Status => {
+            let status_filter = filter.as_any().downcast_ref::<StatusFilter>().unwrap();
+            IndexedFilter::Status(status_filter.status.to_owned())
+        }
+        FilterKind::Waiting => IndexedFilter::Or(vec![
+            IndexedFilter::Status(TaskStatus::Pending),
+            IndexedFilter::Status(TaskStatus::Active),
+        ]),
+        FilterKind::Project => {
+            let project_filter = filter.as_any().downcast_ref::<ProjectFilter>().unwrap();
+            IndexedFilter::Project(project_filter.name.get_name().to_owned())
+        }
+        FilterKind::ProjectIs => {
+            let project_filter = filter.as_any().downcast_ref::<ProjectIsFilter>().unwrap();
+            IndexedFilter::Project(project_filter.name.get_name().to_owned())
+        }
+        FilterKind::Tag => {
+            let tag_filter = filter.as_any().downcast_ref::<TagFilter>().unwrap();
+            IndexedFilter::Tag {
+                name: tag_filter.tag_name.to_owned(),
+                include: tag_filter.include,
+            }
+        }
+        FilterKind::TaskId => {
+            let id_filter = filter.as_any().downcast_ref::<TaskIdFilter>().unwrap();
+            IndexedFilter::Id(id_filter.id)
+        }
+        FilterKind::Uuid => {
+            let uuid_filter = filter.as_any().downcast_ref::<UuidFilter>().unwrap();
+            IndexedFilter::Uuid(uuid_filter.uuid)
+        }
+        FilterKind::DateDue => {
+            let due_filter = filter.as_any().downcast_ref::<DateDueFilter>().unwrap();
+            match due_filter.type_when {
+                DateDueFilterType::Before => IndexedFilter::DueBefore(due_filter.time),
+                DateDueFilterType::After => IndexedFilter::DueAfter(due_filter.time),
+                DateDueFilterType::Day => {
+                    // Every time of that day is within a day of the filter time
+                    match (
+                        due_filter.time.checked_sub_days(Days::new(1)),
+                        due_filter.time.checked_add_days(Days::new(1)),
+                    ) {
+                        (Some(start), Some(end)) => IndexedFilter::And(vec![
+                            IndexedFilter::DueAfter(start),
+                            IndexedFilter::DueBefore(end),
+                        ]),
+                        _ => IndexedFilter::All,
+                    }
+                }
+            }
         }
+        FilterKind::Root
+        | FilterKind::Xor
+        | FilterKind::String
+        | FilterKind::Text
+        | FilterKind::Regex
+        | FilterKind::DateEnd
+        | FilterKind::DateCreated
+        | FilterKind::DateWait
+        | FilterKind::DateScheduled
+        | FilterKind::DependsOn
+        | FilterKind::Linked
+        | FilterKind::Parent
+        | FilterKind::HasSubtasks
+        | FilterKind::Recurrence
+        | FilterKind::Priority
+        | FilterKind::Uda => IndexedFilter::All,
     }
 }
 
@@ -1,12 +1,13 @@
 use chrono::{DateTime, Local};
 use log::{debug, trace, warn};
-use serde::{Deserialize, Serialize};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::HashMap;
-use std::{any::Any, fmt};
+use std::{any::Any, cmp::Ordering, fmt};
 use uuid::Uuid;
 
 use super::{CloneFilter, Filter};
-use crate::task::{Project, Task, TaskStatus};
+use crate::task::{LinkType, Project, Recurrence, Task, TaskPriority, TaskStatus, UdaDefinition};
 
 #[derive(PartialEq, Debug)]
 pub enum FilterKind {
@@ -15,15 +16,28 @@ pub enum FilterKind {
     Root,
     Status,
     Project,
+    ProjectIs,
     DateEnd,
     DateCreated,
     DateDue,
+    DateWait,
+    DateScheduled,
+    Waiting,
     String,
+    Text,
+    Regex,
     Tag,
     TaskId,
     DependsOn,
+    Linked,
+    Parent,
+    HasSubtasks,
+    Recurrence,
+    Priority,
+    Uda,
     Uuid,
     Xor,
+    Not,
 }
 
 impl fmt::Display for FilterKind {
@@ -34,15 +48,28 @@ impl fmt::Display for FilterKind {
             FilterKind::Root => write!(f, "Root"),
             FilterKind::Status => write!(f, "Status"),
             FilterKind::Project => write!(f, "Project"),
+            FilterKind::ProjectIs => write!(f, "ProjectIs"),
             FilterKind::DateEnd => write!(f, "DateEnd"),
             FilterKind::DateCreated => write!(f, "DateCreated"),
             FilterKind::DateDue => write!(f, "DateDue"),
+            FilterKind::DateWait => write!(f, "DateWait"),
+            FilterKind::DateScheduled => write!(f, "DateScheduled"),
+            FilterKind::Waiting => write!(f, "Waiting"),
             FilterKind::String => write!(f, "String"),
+            FilterKind::Text => write!(f, "Text"),
+            FilterKind::Regex => write!(f, "Regex"),
             FilterKind::Tag => write!(f, "Tag"),
             FilterKind::TaskId => write!(f, "TaskId"),
             FilterKind::DependsOn => write!(f, "DependsOn"),
+            FilterKind::Linked => write!(f, "Linked"),
+            FilterKind::Parent => write!(f, "Parent"),
+            FilterKind::HasSubtasks => write!(f, "HasSubtasks"),
+            FilterKind::Recurrence => write!(f, "Recurrence"),
+            FilterKind::Priority => write!(f, "Priority"),
+            FilterKind::Uda => write!(f, "Uda"),
             FilterKind::Uuid => write!(f, "Uuid"),
             FilterKind::Xor => write!(f, "Xor"),
+            FilterKind::Not => write!(f, "Not"),
         }
     }
 }
@@ -73,16 +100,29 @@ impl_display_and_debug!(
     OrFilter,
     RootFilter,
     ProjectFilter,
+    ProjectIsFilter,
     StatusFilter,
     DateEndFilter,
     DateCreatedFilter,
     DateDueFilter,
+    DateWaitFilter,
+    DateScheduledFilter,
+    WaitingFilter,
     StringFilter,
+    TextFilter,
+    RegexFilter,
     TagFilter,
     TaskIdFilter,
     DependsOnFilter,
+    LinkedFilter,
+    ParentFilter,
+    HasSubtasksFilter,
+    RecurrenceFilter,
+    PriorityFilter,
+    UdaFilter,
     UuidFilter,
-    XorFilter
+    XorFilter,
+    NotFilter
 );
 
 fn indent_string(input: &str, indent: usize) -> String {
@@ -290,6 +330,68 @@ impl CloneFilter for XorFilter {
     }
 }
 
+/// Matches the tasks that its child doesn't match
+#[derive(Deserialize, Serialize)]
+pub struct NotFilter {
+    pub child: Box<dyn Filter>,
+}
+
+// Neither deriving it nor using `==` compiles here: comparing two
+// `Box<dyn Filter>` fields tries to move the right-hand side (rust-lang/rust#31740)
+impl PartialEq for NotFilter {
+    fn eq(&self, other: &Self) -> bool {
+        PartialEq::eq(&self.child, &other.child)
+    }
+}
+
+#[typetag::serde]
+impl Filter for NotFilter {
+    fn validate_task(&self, task: &Task) -> bool {
+        !self.child.validate_task(task)
+    }
+
+    fn add_children(&mut self, _child: Box<dyn Filter>) {
+        unreachable!("Trying to add a child to a NotFilter");
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn convert_id_to_uuid(&mut self, id_to_uuid: &HashMap<usize, Uuid>) {
+        self.child.convert_id_to_uuid(id_to_uuid);
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = &dyn Filter> + '_> {
+        Box::new(std::iter::once(self as &dyn Filter).chain(self.child.iter()))
+    }
+}
+
+impl FilterKindGetter for NotFilter {
+    fn get_kind(&self) -> FilterKind {
+        FilterKind::Not
+    }
+}
+
+impl NotFilter {
+    fn format_helper(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:\n{}",
+            self.get_kind(),
+            indent_string(&self.child.to_string(), 4)
+        )
+    }
+}
+
+impl CloneFilter for NotFilter {
+    fn clone_box(&self) -> Box<dyn Filter> {
+        Box::new(NotFilter {
+            child: self.child.to_owned(),
+        })
+    }
+}
+
 #[derive(PartialEq, Deserialize, Serialize)]
 pub struct OrFilter {
     pub children: Vec<Box<dyn Filter>>,
@@ -365,6 +467,8 @@ impl CloneFilter for OrFilter {
     }
 }
 
+/// Case insensitive search of a word in the summary, the annotations or the
+/// description of the task
 #[derive(PartialEq, Deserialize, Serialize)]
 pub struct StringFilter {
     pub value: String,
@@ -373,9 +477,8 @@ pub struct StringFilter {
 #[typetag::serde]
 impl Filter for StringFilter {
     fn validate_task(&self, task: &Task) -> bool {
-        task.get_summary()
-            .to_lowercase()
-            .contains(&self.value.to_lowercase())
+        let value = self.value.to_lowercase();
+        TextField::Any.matches(task, |text| text.to_lowercase().contains(&value))
     }
 
     fn add_children(&mut self, _: Box<dyn Filter>) {
@@ -539,27 +642,28 @@ impl CloneFilter for DateDueFilter {
     }
 }
 
+/// Filter on the date until which a task is waiting
 #[derive(PartialEq, Eq, Deserialize, Serialize)]
-pub struct DateEndFilter {
+pub struct DateWaitFilter {
     pub time: DateTime<Local>,
-    pub before: bool,
+    pub type_when: DateDueFilterType,
 }
 
 #[typetag::serde]
-impl Filter for DateEndFilter {
+impl Filter for DateWaitFilter {
     fn validate_task(&self, task: &Task) -> bool {
-        if let Some(d) = task.get_date_completed() {
-            if self.before {
-                debug!("Checking task has end before");
-                return d < &self.time;
-            }
-            return d >= &self.time;
+        match task.get_date_wait() {
+            Some(date) => match self.type_when {
+                DateDueFilterType::Day => date.date_naive() == self.time.date_naive(),
+                DateDueFilterType::Before => date < &self.time,
+                DateDueFilterType::After => date >= &self.time,
+            },
+            None => false,
         }
-        false
     }
 
     fn add_children(&mut self, _: Box<dyn Filter>) {
-        unreachable!("Trying to add a child to a DateEndFilter");
+        unreachable!("Trying to add a child to a DateWaitFilter");
     }
 
     fn as_any(&self) -> &dyn Any {
@@ -573,44 +677,54 @@ impl Filter for DateEndFilter {
     }
 }
 
-impl FilterKindGetter for DateEndFilter {
+impl FilterKindGetter for DateWaitFilter {
     fn get_kind(&self) -> FilterKind {
-        FilterKind::DateEnd
+        FilterKind::DateWait
     }
 }
 
-impl DateEndFilter {
+impl DateWaitFilter {
     fn format_helper(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let before = if self.before { "before" } else { "after" };
-        write!(f, "{}: {}: {}", self.get_kind(), before, &self.time)
+        let type_when = match self.type_when {
+            DateDueFilterType::Day => "day",
+            DateDueFilterType::Before => "before",
+            DateDueFilterType::After => "after",
+        };
+        write!(f, "{}: {}: {}", self.get_kind(), type_when, &self.time)
     }
 }
 
-impl CloneFilter for DateEndFilter {
+impl CloneFilter for DateWaitFilter {
     fn clone_box(&self) -> Box<dyn Filter> {
-        Box::new(DateEndFilter {
+        Box::new(DateWaitFilter {
             time: self.time.to_owned(),
-            before: self.before.to_owned(),
+            type_when: self.type_when.to_owned(),
         })
     }
 }
 
-#[derive(PartialEq, Deserialize, Serialize)]
-pub struct ProjectFilter {
-    pub name: Project,
+/// Filter on the date a task is scheduled for
+#[derive(PartialEq, Eq, Deserialize, Serialize)]
+pub struct DateScheduledFilter {
+    pub time: DateTime<Local>,
+    pub type_when: DateDueFilterType,
 }
 
 #[typetag::serde]
-impl Filter for ProjectFilter {
+impl Filter for DateScheduledFilter {
     fn validate_task(&self, task: &Task) -> bool {
-        if let Some(p) = task.get_project() {
-            return p.get_name().starts_with(self.name.get_name().as_str());
+        match task.get_date_scheduled() {
+            Some(date) => match self.type_when {
+                DateDueFilterType::Day => date.date_naive() == self.time.date_naive(),
+                DateDueFilterType::Before => date < &self.time,
+                DateDueFilterType::After => date >= &self.time,
+            },
+            None => false,
         }
-        false
     }
 
     fn add_children(&mut self, _: Box<dyn Filter>) {
-        unreachable!("Trying to add a child to a ProjectFilter");
+        unreachable!("Trying to add a child to a DateScheduledFilter");
     }
 
     fn as_any(&self) -> &dyn Any {
@@ -624,39 +738,48 @@ impl Filter for ProjectFilter {
     }
 }
 
-impl FilterKindGetter for ProjectFilter {
+impl FilterKindGetter for DateScheduledFilter {
     fn get_kind(&self) -> FilterKind {
-        FilterKind::Project
+        FilterKind::DateScheduled
     }
 }
 
-impl ProjectFilter {
+impl DateScheduledFilter {
     fn format_helper(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}: {}", self.get_kind(), &self.name)
+        let type_when = match self.type_when {
+            DateDueFilterType::Day => "day",
+            DateDueFilterType::Before => "before",
+            DateDueFilterType::After => "after",
+        };
+        write!(f, "{}: {}: {}", self.get_kind(), type_when, &self.time)
     }
 }
 
-impl CloneFilter for ProjectFilter {
+impl CloneFilter for DateScheduledFilter {
     fn clone_box(&self) -> Box<dyn Filter> {
-        Box::new(ProjectFilter {
-            name: self.name.to_owned(),
+        Box::new(DateScheduledFilter {
+            time: self.time.to_owned(),
+            type_when: self.type_when.to_owned(),
         })
     }
 }
 
-#[derive(PartialEq, Deserialize, Serialize)]
-pub struct StatusFilter {
-    pub status: TaskStatus,
+/// Match the pending and active tasks whose wait date is not reached yet.
+/// This is the 'waiting' virtual status.
+#[derive(PartialEq, Eq, Deserialize, Serialize)]
+pub struct WaitingFilter {
+    /// The time the wait dates are compared to
+    pub now: DateTime<Local>,
 }
 
 #[typetag::serde]
-impl Filter for StatusFilter {
+impl Filter for WaitingFilter {
     fn validate_task(&self, task: &Task) -> bool {
-        &self.status == task.get_status()
+        task.is_waiting(self.now)
     }
 
     fn add_children(&mut self, _: Box<dyn Filter>) {
-        unreachable!("Trying to add a child to a StatusFilter");
+        unreachable!("Trying to add a child to a WaitingFilter");
     }
 
     fn as_any(&self) -> &dyn Any {
@@ -670,43 +793,45 @@ impl Filter for StatusFilter {
     }
 }
 
-impl FilterKindGetter for StatusFilter {
+impl FilterKindGetter for WaitingFilter {
     fn get_kind(&self) -> FilterKind {
-        FilterKind::Status
+        FilterKind::Waiting
     }
 }
 
-impl StatusFilter {
+impl WaitingFilter {
     fn format_helper(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}: {}", self.get_kind(), &self.status)
+        write!(f, "{}", self.get_kind())
     }
 }
 
-impl CloneFilter for StatusFilter {
+impl CloneFilter for WaitingFilter {
     fn clone_box(&self) -> Box<dyn Filter> {
-        Box::new(StatusFilter {
-            status: self.status.to_owned(),
-        })
+        Box::new(WaitingFilter { now: self.now })
     }
 }
 
-#[derive(PartialEq, Deserialize, Serialize)]
-pub struct TagFilter {
-    pub include: bool,
-    pub tag_name: String,
+#[derive(PartialEq, Eq, Deserialize, Serialize)]
+pub struct DateEndFilter {
+    pub time: DateTime<Local>,
+    pub before: bool,
 }
 
 #[typetag::serde]
-impl Filter for TagFilter {
+impl Filter for DateEndFilter {
     fn validate_task(&self, task: &Task) -> bool {
-        if self.include {
-            return task.get_tags().iter().any(|t| t == &self.tag_name);
+        if let Some(d) = task.get_date_completed() {
+            if self.before {
+                debug!("Checking task has end before");
+                return d < &self.time;
+            }
+            return d >= &self.time;
         }
-        task.get_tags().iter().all(|t| t != &self.tag_name)
+        false
     }
 
     fn add_children(&mut self, _: Box<dyn Filter>) {
-        unreachable!("Trying to add a child to a TagFilter");
+        unreachable!("Trying to add a child to a DateEndFilter");
     }
 
     fn as_any(&self) -> &dyn Any {
@@ -720,41 +845,84 @@ impl Filter for TagFilter {
     }
 }
 
-impl FilterKindGetter for TagFilter {
+impl FilterKindGetter for DateEndFilter {
     fn get_kind(&self) -> FilterKind {
-        FilterKind::Tag
+        FilterKind::DateEnd
     }
 }
 
-impl TagFilter {
+impl DateEndFilter {
     fn format_helper(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let include_str = if self.include { "include" } else { "exclude" };
-        write!(f, "{}: {}={}", self.get_kind(), &self.tag_name, include_str,)
+        let before = if self.before { "before" } else { "after" };
+        write!(f, "{}: {}: {}", self.get_kind(), before, &self.time)
     }
 }
 
-impl CloneFilter for TagFilter {
+impl CloneFilter for DateEndFilter {
     fn clone_box(&self) -> Box<dyn Filter> {
-        Box::new(TagFilter {
-            include: self.include.to_owned(),
-            tag_name: self.tag_name.to_owned(),
+        Box::new(DateEndFilter {
+            time: self.time.to_owned(),
+            before: self.before.to_owned(),
         })
     }
 }
 
+/// The text of a task a `TextFilter` or a `RegexFilter` looks into
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub enum TextField {
+    Summary,
+    Annotation,
+    Description,
+    /// Either the summary, one of the annotations or the description
+    Any,
+}
+
+impl TextField {
+    fn matches(&self, task: &Task, predicate: impl Fn(&str) -> bool) -> bool {
+        let in_summary = || predicate(task.get_summary());
+        let in_annotations = || {
+            task.get_annotations()
+                .iter()
+                .any(|ann| predicate(ann.get_value()))
+        };
+        let in_description = || task.get_description().as_deref().is_some_and(&predicate);
+        match self {
+            TextField::Summary => in_summary(),
+            TextField::Annotation => in_annotations(),
+            TextField::Description => in_description(),
+            TextField::Any => in_summary() || in_annotations() || in_description(),
+        }
+    }
+}
+
+impl fmt::Display for TextField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextField::Summary => write!(f, "summary"),
+            TextField::Annotation => write!(f, "annotation"),
+            TextField::Description => write!(f, "description"),
+            TextField::Any => write!(f, "any"),
+        }
+    }
+}
+
+/// Case insensitive search of a text in a field of the task
 #[derive(PartialEq, Deserialize, Serialize)]
-pub struct UuidFilter {
-    pub uuid: Uuid,
+pub struct TextFilter {
+    pub field: TextField,
+    pub value: String,
 }
 
 #[typetag::serde]
-impl Filter for UuidFilter {
+impl Filter for TextFilter {
     fn validate_task(&self, task: &Task) -> bool {
-        &self.uuid == task.get_uuid()
+        let value = self.value.to_lowercase();
+        self.field
+            .matches(task, |text| text.to_lowercase().contains(&value))
     }
 
     fn add_children(&mut self, _: Box<dyn Filter>) {
-        unreachable!("Trying to add a child to a UuidFilter");
+        unreachable!("Trying to add a child to a TextFilter");
     }
 
     fn as_any(&self) -> &dyn Any {
@@ -768,42 +936,82 @@ impl Filter for UuidFilter {
     }
 }
 
-impl FilterKindGetter for UuidFilter {
+impl FilterKindGetter for TextFilter {
     fn get_kind(&self) -> FilterKind {
-        FilterKind::Uuid
+        FilterKind::Text
     }
 }
 
-impl UuidFilter {
+impl TextFilter {
     fn format_helper(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}: {}", self.get_kind(), &self.uuid,)
+        write!(f, "{}: {}={}", self.get_kind(), self.field, &self.value)
     }
 }
 
-impl CloneFilter for UuidFilter {
+impl CloneFilter for TextFilter {
     fn clone_box(&self) -> Box<dyn Filter> {
-        Box::new(UuidFilter {
-            uuid: self.uuid.to_owned(),
+        Box::new(TextFilter {
+            field: self.field,
+            value: self.value.to_owned(),
         })
     }
 }
 
+/// Case insensitive regular expression, (de)serialised as its pattern
+#[derive(Clone, Debug)]
+pub struct FilterRegex(Regex);
+
+impl FilterRegex {
+    pub fn new(pattern: &str) -> Result<FilterRegex, String> {
+        RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .build()
+            .map(FilterRegex)
+            .map_err(|e| format!("Invalid regular expression '{}': {}", pattern, e))
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    fn is_match(&self, text: &str) -> bool {
+        self.0.is_match(text)
+    }
+}
+
+impl PartialEq for FilterRegex {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Serialize for FilterRegex {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for FilterRegex {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        FilterRegex::new(&pattern).map_err(serde::de::Error::custom)
+    }
+}
+
 #[derive(PartialEq, Deserialize, Serialize)]
-pub struct TaskIdFilter {
-    pub id: usize,
+pub struct RegexFilter {
+    pub field: TextField,
+    pub regex: FilterRegex,
 }
 
 #[typetag::serde]
-impl Filter for TaskIdFilter {
+impl Filter for RegexFilter {
     fn validate_task(&self, task: &Task) -> bool {
-        if let Some(task_id) = task.get_id() {
-            return self.id == task_id;
-        }
-        false
+        self.field.matches(task, |text| self.regex.is_match(text))
     }
 
     fn add_children(&mut self, _: Box<dyn Filter>) {
-        unreachable!("Trying to add a child to a TaskIdFilter");
+        unreachable!("Trying to add a child to a RegexFilter");
     }
 
     fn as_any(&self) -> &dyn Any {
@@ -817,94 +1025,567 @@ impl Filter for TaskIdFilter {
     }
 }
 
-impl FilterKindGetter for TaskIdFilter {
+impl FilterKindGetter for RegexFilter {
     fn get_kind(&self) -> FilterKind {
-        FilterKind::TaskId
+        FilterKind::Regex
     }
 }
 
-impl TaskIdFilter {
+impl RegexFilter {
     fn format_helper(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}: {}", self.get_kind(), &self.id,)
+        write!(
+            f,
+            "{}: {}=/{}/",
+            self.get_kind(),
+            self.field,
+            self.regex.as_str()
+        )
     }
 }
 
-impl CloneFilter for TaskIdFilter {
+impl CloneFilter for RegexFilter {
     fn clone_box(&self) -> Box<dyn Filter> {
-        Box::new(TaskIdFilter {
-            id: self.id.to_owned(),
+        Box::new(RegexFilter {
+            field: self.field,
+            regex: self.regex.to_owned(),
         })
     }
 }
 
+/// Matches the tasks of exactly this project, unlike `ProjectFilter` that also
+/// matches its sub-projects
 #[derive(PartialEq, Deserialize, Serialize)]
-pub struct DependsOnFilter {
-    pub id: Option<usize>,
-    pub uuid: Option<Uuid>,
+pub struct ProjectIsFilter {
+    pub name: Project,
 }
 
 #[typetag::serde]
-impl Filter for DependsOnFilter {
+impl Filter for ProjectIsFilter {
     fn validate_task(&self, task: &Task) -> bool {
-        if let Some(uuid) = &self.uuid {
-            return task.get_depends().contains(uuid);
-        }
-
-        unreachable!("Trying to validate a DependsOn filter without a UUID");
+        task.get_project().as_ref() == Some(&self.name)
     }
 
     fn add_children(&mut self, _: Box<dyn Filter>) {
-        unreachable!("Trying to add a child to a DependsOnFilter");
+        unreachable!("Trying to add a child to a ProjectIsFilter");
     }
 
     fn as_any(&self) -> &dyn Any {
         self
     }
 
-    fn convert_id_to_uuid(&mut self, id_to_uuid: &HashMap<usize, Uuid>) {
-        if self.uuid.is_some() {
-            debug!("DependsOnFilter already has a UUID, no need to update it.");
-            return;
-        }
-
-        if let Some(id) = &self.id {
-            if let Some(uuid) = id_to_uuid.get(id) {
-                self.uuid = Some(uuid.to_owned());
-            } else {
-                warn!(
-                    "Trying to map id {} in DependsOnFilter but couldn't find a matching UUID",
-                    id
-                );
-            }
-        }
-    }
+    fn convert_id_to_uuid(&mut self, _id_to_uuid: &HashMap<usize, Uuid>) {}
 
     fn iter(&self) -> Box<dyn Iterator<Item = &dyn Filter> + '_> {
         Box::new(std::iter::once(self as &dyn Filter))
     }
 }
 
-impl FilterKindGetter for DependsOnFilter {
+impl FilterKindGetter for ProjectIsFilter {
     fn get_kind(&self) -> FilterKind {
-        FilterKind::DependsOn
+        FilterKind::ProjectIs
     }
 }
 
-impl DependsOnFilter {
+impl ProjectIsFilter {
     fn format_helper(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let uuid_string = if let Some(uuid) = &self.uuid {
-            uuid.to_string()
-        } else {
-            "None".to_string()
-        };
+        write!(f, "{}: {}", self.get_kind(), self.name)
+    }
+}
 
-        let id_string = if let Some(id) = &self.id {
-            id.to_string()
-        } else {
-            "None".to_string()
-        };
-        write!(
-            f,
+impl CloneFilter for ProjectIsFilter {
+    fn clone_box(&self) -> Box<dyn Filter> {
+        Box::new(ProjectIsFilter {
+            name: self.name.to_owned(),
+        })
+    }
+}
+
+#[derive(PartialEq, Deserialize, Serialize)]
+pub struct ProjectFilter {
+    pub name: Project,
+}
+
+#[typetag::serde]
+impl Filter for ProjectFilter {
+    fn validate_task(&self, task: &Task) -> bool {
+        if let Some(p) = task.get_project() {
+            return p.get_name().starts_with(self.name.get_name().as_str());
+        }
+        false
+    }
+
+    fn add_children(&mut self, _: Box<dyn Filter>) {
+        unreachable!("Trying to add a child to a ProjectFilter");
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn convert_id_to_uuid(&mut self, _id_to_uuid: &HashMap<usize, Uuid>) {}
+
+    fn iter(&self) -> Box<dyn Iterator<Item = &dyn Filter> + '_> {
+        Box::new(std::iter::once(self as &dyn Filter))
+    }
+}
+
+impl FilterKindGetter for ProjectFilter {
+    fn get_kind(&self) -> FilterKind {
+        FilterKind::Project
+    }
+}
+
+impl ProjectFilter {
+    fn format_helper(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.get_kind(), &self.name)
+    }
+}
+
+impl CloneFilter for ProjectFilter {
+    fn clone_box(&self) -> Box<dyn Filter> {
+        Box::new(ProjectFilter {
+            name: self.name.to_owned(),
+        })
+    }
+}
+
+#[derive(PartialEq, Deserialize, Serialize)]
+pub struct StatusFilter {
+    pub status: TaskStatus,
+}
+
+#[typetag::serde]
+impl Filter for StatusFilter {
+    fn validate_task(&self, task: &Task) -> bool {
+        &self.status == task.get_status()
+    }
+
+    fn add_children(&mut self, _: Box<dyn Filter>) {
+        unreachable!("Trying to add a child to a StatusFilter");
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn convert_id_to_uuid(&mut self, _id_to_uuid: &HashMap<usize, Uuid>) {}
+
+    fn iter(&self) -> Box<dyn Iterator<Item = &dyn Filter> + '_> {
+        Box::new(std::iter::once(self as &dyn Filter))
+    }
+}
+
+impl FilterKindGetter for StatusFilter {
+    fn get_kind(&self) -> FilterKind {
+        FilterKind::Status
+    }
+}
+
+impl StatusFilter {
+    fn format_helper(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.get_kind(), &self.status)
+    }
+}
+
+impl CloneFilter for StatusFilter {
+    fn clone_box(&self) -> Box<dyn Filter> {
+        Box::new(StatusFilter {
+            status: self.status.to_owned(),
+        })
+    }
+}
+
+#[derive(PartialEq, Deserialize, Serialize)]
+pub struct TagFilter {
+    pub include: bool,
+    pub tag_name: String,
+}
+
+#[typetag::serde]
+impl Filter for TagFilter {
+    fn validate_task(&self, task: &Task) -> bool {
+        if self.include {
+            return task.get_tags().iter().any(|t| t == &self.tag_name);
+        }
+        task.get_tags().iter().all(|t| t != &self.tag_name)
+    }
+
+    fn add_children(&mut self, _: Box<dyn Filter>) {
+        unreachable!("Trying to add a child to a TagFilter");
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn convert_id_to_uuid(&mut self, _id_to_uuid: &HashMap<usize, Uuid>) {}
+
+    fn iter(&self) -> Box<dyn Iterator<Item = &dyn Filter> + '_> {
+        Box::new(std::iter::once(self as &dyn Filter))
+    }
+}
+
+impl FilterKindGetter for TagFilter {
+    fn get_kind(&self) -> FilterKind {
+        FilterKind::Tag
+    }
+}
+
+impl TagFilter {
+    fn format_helper(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let include_str = if self.include { "include" } else { "exclude" };
+        write!(f, "{}: {}={}", self.get_kind(), &self.tag_name, include_str,)
+    }
+}
+
+impl CloneFilter for TagFilter {
+    fn clone_box(&self) -> Box<dyn Filter> {
+        Box::new(TagFilter {
+            include: self.include.to_owned(),
+            tag_name: self.tag_name.to_owned(),
+        })
+    }
+}
+
+#[derive(PartialEq, Deserialize, Serialize)]
+pub struct UuidFilter {
+    pub uuid: Uuid,
+}
+
+#[typetag::serde]
+impl Filter for UuidFilter {
+    fn validate_task(&self, task: &Task) -> bool {
+        &self.uuid == task.get_uuid()
+    }
+
+    fn add_children(&mut self, _: Box<dyn Filter>) {
+        unreachable!("Trying to add a child to a UuidFilter");
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn convert_id_to_uuid(&mut self, _id_to_uuid: &HashMap<usize, Uuid>) {}
+
+    fn iter(&self) -> Box<dyn Iterator<Item = &dyn Filter> + '_> {
+        Box::new(std::iter::once(self as &dyn Filter))
+    }
+}
+
+impl FilterKindGetter for UuidFilter {
+    fn get_kind(&self) -> FilterKind {
+        FilterKind::Uuid
+    }
+}
+
+impl UuidFilter {
+    fn format_helper(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.get_kind(), &self.uuid,)
+    }
+}
+
+impl CloneFilter for UuidFilter {
+    fn clone_box(&self) -> Box<dyn Filter> {
+        Box::new(UuidFilter {
+            uuid: self.uuid.to_owned(),
+        })
+    }
+}
+
+#[derive(PartialEq, Deserialize, Serialize)]
+pub struct TaskIdFilter {
+    pub id: usize,
+}
+
+#[typetag::serde]
+impl Filter for TaskIdFilter {
+    fn validate_task(&self, task: &Task) -> bool {
+        if let Some(task_id) = task.get_id() {
+            return self.id == task_id;
+        }
+        false
+    }
+
+    fn add_children(&mut self, _: Box<dyn Filter>) {
+        unreachable!("Trying to add a child to a TaskIdFilter");
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn convert_id_to_uuid(&mut self, _id_to_uuid: &HashMap<usize, Uuid>) {}
+
+    fn iter(&self) -> Box<dyn Iterator<Item = &dyn Filter> + '_> {
+        Box::new(std::iter::once(self as &dyn Filter))
+    }
+}
+
+impl FilterKindGetter for TaskIdFilter {
+    fn get_kind(&self) -> FilterKind {
+        FilterKind::TaskId
+    }
+}
+
+impl TaskIdFilter {
+    fn format_helper(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.get_kind(), &self.id,)
+    }
+}
+
+impl CloneFilter for TaskIdFilter {
+    fn clone_box(&self) -> Box<dyn Filter> {
+        Box::new(TaskIdFilter {
+            id: self.id.to_owned(),
+        })
+    }
+}
+
+#[derive(PartialEq, Deserialize, Serialize)]
+pub struct DependsOnFilter {
+    pub id: Option<usize>,
+    pub uuid: Option<Uuid>,
+}
+
+#[typetag::serde]
+impl Filter for DependsOnFilter {
+    fn validate_task(&self, task: &Task) -> bool {
+        if let Some(uuid) = &self.uuid {
+            return task.get_depends().contains(uuid);
+        }
+
+        unreachable!("Trying to validate a DependsOn filter without a UUID");
+    }
+
+    fn add_children(&mut self, _: Box<dyn Filter>) {
+        unreachable!("Trying to add a child to a DependsOnFilter");
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn convert_id_to_uuid(&mut self, id_to_uuid: &HashMap<usize, Uuid>) {
+        if self.uuid.is_some() {
+            debug!("DependsOnFilter already has a UUID, no need to update it.");
+            return;
+        }
+
+        if let Some(id) = &self.id {
+            if let Some(uuid) = id_to_uuid.get(id) {
+                self.uuid = Some(uuid.to_owned());
+            } else {
+                warn!(
+                    "Trying to map id {} in DependsOnFilter but couldn't find a matching UUID",
+                    id
+                );
+            }
+        }
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = &dyn Filter> + '_> {
+        Box::new(std::iter::once(self as &dyn Filter))
+    }
+}
+
+impl FilterKindGetter for DependsOnFilter {
+    fn get_kind(&self) -> FilterKind {
+        FilterKind::DependsOn
+    }
+}
+
+impl DependsOnFilter {
+    fn format_helper(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let uuid_string = if let Some(uuid) = &self.uuid {
+            uuid.to_string()
+        } else {
+            "None".to_string()
+        };
+
+        let id_string = if let Some(id) = &self.id {
+            id.to_string()
+        } else {
+            "None".to_string()
+        };
+        write!(
+            f,
+            "{}: id({}), uuid({})",
+            self.get_kind(),
+            id_string,
+            uuid_string
+        )
+    }
+}
+
+impl CloneFilter for DependsOnFilter {
+    fn clone_box(&self) -> Box<dyn Filter> {
+        Box::new(DependsOnFilter {
+            id: self.id.to_owned(),
+            uuid: self.uuid.to_owned(),
+        })
+    }
+}
+
+/// Match the tasks having a link of the given type to the task with the given ID
+/// or UUID. Symmetric links match from both of their ends.
+#[derive(PartialEq, Deserialize, Serialize)]
+pub struct LinkedFilter {
+    pub kind: LinkType,
+    pub id: Option<usize>,
+    pub uuid: Option<Uuid>,
+}
+
+#[typetag::serde]
+impl Filter for LinkedFilter {
+    fn validate_task(&self, task: &Task) -> bool {
+        if let Some(uuid) = &self.uuid {
+            return task.is_linked_to(self.kind, uuid);
+        }
+
+        // The ID doesn't match any task, so no task can be linked to it
+        false
+    }
+
+    fn add_children(&mut self, _: Box<dyn Filter>) {
+        unreachable!("Trying to add a child to a LinkedFilter");
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn convert_id_to_uuid(&mut self, id_to_uuid: &HashMap<usize, Uuid>) {
+        if self.uuid.is_some() {
+            return;
+        }
+
+        if let Some(id) = &self.id {
+            if let Some(uuid) = id_to_uuid.get(id) {
+                self.uuid = Some(uuid.to_owned());
+            } else {
+                warn!(
+                    "Trying to map id {} in LinkedFilter but couldn't find a matching UUID",
+                    id
+                );
+            }
+        }
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = &dyn Filter> + '_> {
+        Box::new(std::iter::once(self as &dyn Filter))
+    }
+}
+
+impl FilterKindGetter for LinkedFilter {
+    fn get_kind(&self) -> FilterKind {
+        FilterKind::Linked
+    }
+}
+
+impl LinkedFilter {
+    fn format_helper(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let uuid_string = if let Some(uuid) = &self.uuid {
+            uuid.to_string()
+        } else {
+            "None".to_string()
+        };
+
+        let id_string = if let Some(id) = &self.id {
+            id.to_string()
+        } else {
+            "None".to_string()
+        };
+        write!(
+            f,
+            "{}: {}, id({}), uuid({})",
+            self.get_kind(),
+            self.kind,
+            id_string,
+            uuid_string
+        )
+    }
+}
+
+impl CloneFilter for LinkedFilter {
+    fn clone_box(&self) -> Box<dyn Filter> {
+        Box::new(LinkedFilter {
+            kind: self.kind,
+            id: self.id.to_owned(),
+            uuid: self.uuid.to_owned(),
+        })
+    }
+}
+
+/// Match the subtasks of the task with the given ID or UUID
+#[derive(PartialEq, Deserialize, Serialize)]
+pub struct ParentFilter {
+    pub id: Option<usize>,
+    pub uuid: Option<Uuid>,
+}
+
+#[typetag::serde]
+impl Filter for ParentFilter {
+    fn validate_task(&self, task: &Task) -> bool {
+        if let Some(uuid) = &self.uuid {
+            return task.get_parent().as_ref() == Some(uuid);
+        }
+
+        // The ID doesn't match any task, so no task can be its subtask
+        false
+    }
+
+    fn add_children(&mut self, _: Box<dyn Filter>) {
+        unreachable!("Trying to add a child to a ParentFilter");
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn convert_id_to_uuid(&mut self, id_to_uuid: &HashMap<usize, Uuid>) {
+        if self.uuid.is_some() {
+            return;
+        }
+
+        if let Some(id) = &self.id {
+            if let Some(uuid) = id_to_uuid.get(id) {
+                self.uuid = Some(uuid.to_owned());
+            } else {
+                warn!(
+                    "Trying to map id {} in ParentFilter but couldn't find a matching UUID",
+                    id
+                );
+            }
+        }
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = &dyn Filter> + '_> {
+        Box::new(std::iter::once(self as &dyn Filter))
+    }
+}
+
+impl FilterKindGetter for ParentFilter {
+    fn get_kind(&self) -> FilterKind {
+        FilterKind::Parent
+    }
+}
+
+impl ParentFilter {
+    fn format_helper(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let uuid_string = if let Some(uuid) = &self.uuid {
+            uuid.to_string()
+        } else {
+            "None".to_string()
+        };
+
+        let id_string = if let Some(id) = &self.id {
+            id.to_string()
+        } else {
+            "None".to_string()
+        };
+        write!(
+            f,
             "{}: id({}), uuid({})",
             self.get_kind(),
             id_string,
@@ -913,11 +1594,254 @@ impl DependsOnFilter {
     }
 }
 
-impl CloneFilter for DependsOnFilter {
+impl CloneFilter for ParentFilter {
     fn clone_box(&self) -> Box<dyn Filter> {
-        Box::new(DependsOnFilter {
+        Box::new(ParentFilter {
             id: self.id.to_owned(),
             uuid: self.uuid.to_owned(),
         })
     }
 }
+
+/// Match the tasks that have subtasks
+#[derive(PartialEq, Deserialize, Serialize)]
+pub struct HasSubtasksFilter {}
+
+#[typetag::serde]
+impl Filter for HasSubtasksFilter {
+    fn validate_task(&self, task: &Task) -> bool {
+        !task.get_subtasks().is_empty()
+    }
+
+    fn add_children(&mut self, _: Box<dyn Filter>) {
+        unreachable!("Trying to add a child to a HasSubtasksFilter");
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn convert_id_to_uuid(&mut self, _id_to_uuid: &HashMap<usize, Uuid>) {}
+
+    fn iter(&self) -> Box<dyn Iterator<Item = &dyn Filter> + '_> {
+        Box::new(std::iter::once(self as &dyn Filter))
+    }
+}
+
+impl FilterKindGetter for HasSubtasksFilter {
+    fn get_kind(&self) -> FilterKind {
+        FilterKind::HasSubtasks
+    }
+}
+
+impl HasSubtasksFilter {
+    fn format_helper(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.get_kind())
+    }
+}
+
+impl CloneFilter for HasSubtasksFilter {
+    fn clone_box(&self) -> Box<dyn Filter> {
+        Box::new(HasSubtasksFilter {})
+    }
+}
+
+#[derive(PartialEq, Deserialize, Serialize)]
+pub struct RecurrenceFilter {
+    /// Match tasks that have exactly this recurrence. `None` matches the tasks
+    /// that are not recurring.
+    pub recurrence: Option<Recurrence>,
+}
+
+#[typetag::serde]
+impl Filter for RecurrenceFilter {
+    fn validate_task(&self, task: &Task) -> bool {
+        task.get_recurrence() == &self.recurrence
+    }
+
+    fn add_children(&mut self, _: Box<dyn Filter>) {
+        unreachable!("Trying to add a child to a RecurrenceFilter");
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn convert_id_to_uuid(&mut self, _id_to_uuid: &HashMap<usize, Uuid>) {}
+
+    fn iter(&self) -> Box<dyn Iterator<Item = &dyn Filter> + '_> {
+        Box::new(std::iter::once(self as &dyn Filter))
+    }
+}
+
+impl FilterKindGetter for RecurrenceFilter {
+    fn get_kind(&self) -> FilterKind {
+        FilterKind::Recurrence
+    }
+}
+
+impl RecurrenceFilter {
+    fn format_helper(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.recurrence {
+            Some(recurrence) => write!(f, "{}: {}", self.get_kind(), recurrence),
+            None => write!(f, "{}: none", self.get_kind()),
+        }
+    }
+}
+
+impl CloneFilter for RecurrenceFilter {
+    fn clone_box(&self) -> Box<dyn Filter> {
+        Box::new(RecurrenceFilter {
+            recurrence: self.recurrence.to_owned(),
+        })
+    }
+}
+
+#[derive(PartialEq, Deserialize, Serialize)]
+pub struct PriorityFilter {
+    /// `None` matches the tasks without a priority
+    pub priority: Option<TaskPriority>,
+    /// Match the tasks with a priority strictly higher than `priority`. Tasks
+    /// without a priority rank below every priority.
+    pub above: bool,
+}
+
+#[typetag::serde]
+impl Filter for PriorityFilter {
+    fn validate_task(&self, task: &Task) -> bool {
+        if self.above {
+            // `None` is lower than any `Some` value
+            task.get_priority() > &self.priority
+        } else {
+            task.get_priority() == &self.priority
+        }
+    }
+
+    fn add_children(&mut self, _: Box<dyn Filter>) {
+        unreachable!("Trying to add a child to a PriorityFilter");
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn convert_id_to_uuid(&mut self, _id_to_uuid: &HashMap<usize, Uuid>) {}
+
+    fn iter(&self) -> Box<dyn Iterator<Item = &dyn Filter> + '_> {
+        Box::new(std::iter::once(self as &dyn Filter))
+    }
+}
+
+impl FilterKindGetter for PriorityFilter {
+    fn get_kind(&self) -> FilterKind {
+        FilterKind::Priority
+    }
+}
+
+impl PriorityFilter {
+    fn format_helper(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let comparison = if self.above { "above " } else { "" };
+        match &self.priority {
+            Some(priority) => write!(f, "{}: {}{}", self.get_kind(), comparison, priority),
+            None => write!(f, "{}: {}none", self.get_kind(), comparison),
+        }
+    }
+}
+
+impl CloneFilter for PriorityFilter {
+    fn clone_box(&self) -> Box<dyn Filter> {
+        Box::new(PriorityFilter {
+            priority: self.priority,
+            above: self.above,
+        })
+    }
+}
+
+/// How the value of a UDA is compared to the value of a `UdaFilter`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum UdaComparison {
+    Equal,
+    Above,
+    Below,
+}
+
+#[derive(PartialEq, Deserialize, Serialize)]
+pub struct UdaFilter {
+    pub name: String,
+    pub definition: UdaDefinition,
+    /// Value as stored on the tasks. `None` matches the tasks without this UDA.
+    pub value: Option<String>,
+    pub comparison: UdaComparison,
+}
+
+#[typetag::serde]
+impl Filter for UdaFilter {
+    fn validate_task(&self, task: &Task) -> bool {
+        match (task.get_udas().get(&self.name), &self.value) {
+            (None, None) => self.comparison == UdaComparison::Equal,
+            (Some(task_value), Some(value)) => {
+                match self.definition.compare_values(task_value, value) {
+                    Some(ordering) => match self.comparison {
+                        UdaComparison::Equal => ordering == Ordering::Equal,
+                        UdaComparison::Above => ordering == Ordering::Greater,
+                        UdaComparison::Below => ordering == Ordering::Less,
+                    },
+                    None => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
+    fn add_children(&mut self, _: Box<dyn Filter>) {
+        unreachable!("Trying to add a child to a UdaFilter");
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn convert_id_to_uuid(&mut self, _id_to_uuid: &HashMap<usize, Uuid>) {}
+
+    fn iter(&self) -> Box<dyn Iterator<Item = &dyn Filter> + '_> {
+        Box::new(std::iter::once(self as &dyn Filter))
+    }
+}
+
+impl FilterKindGetter for UdaFilter {
+    fn get_kind(&self) -> FilterKind {
+        FilterKind::Uda
+    }
+}
+
+impl UdaFilter {
+    fn format_helper(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let comparison = match self.comparison {
+            UdaComparison::Equal => "",
+            UdaComparison::Above => "above ",
+            UdaComparison::Below => "below ",
+        };
+        match &self.value {
+            Some(value) => write!(
+                f,
+                "{}: {} {}{}",
+                self.get_kind(),
+                self.name,
+                comparison,
+                self.definition.format_value(value)
+            ),
+            None => write!(f, "{}: {} {}none", self.get_kind(), self.name, comparison),
+        }
+    }
+}
+
+impl CloneFilter for UdaFilter {
+    fn clone_box(&self) -> Box<dyn Filter> {
+        Box::new(UdaFilter {
+            name: self.name.to_owned(),
+            definition: self.definition.to_owned(),
+            value: self.value.to_owned(),
+            comparison: self.comparison,
+        })
+    }
+}
@@ -1,8 +1,11 @@
 use super::*;
-use crate::task::{Project, TaskData, TaskProperties, TaskStatus};
+use crate::task::{
+    LinkType, Project, Recurrence, TaskData, TaskPriority, TaskProperties, TaskStatus,
+    UdaDefinition, UdaType,
+};
 use all_asserts::{assert_false, assert_true};
 use chrono::{Duration, Local, NaiveTime, TimeZone};
-use filters_test::filters_impl::DateDueFilterType;
+use filters_test::filters_impl::{DateDueFilterType, UdaComparison};
 
 fn init() {
     let _ = env_logger::builder().is_test(true).try_init();
@@ -257,13 +260,85 @@ fn test_task_matches_status_filter() {
     assert_false!(other_filter.validate_task(&task));
 }
 
+#[test]
+fn test_waiting_filter() {
+    let mut task_data = TaskData::default();
+    let mut add = |value: &str, status: TaskStatus| {
+        task_data
+            .add_task(&TaskProperties::from(&[value.to_owned()]).unwrap(), status)
+            .unwrap()
+            .clone()
+    };
+    let waiting = add("foo wait:2026-11-05", TaskStatus::Pending);
+    let completed = add("baz wait:2026-11-05", TaskStatus::Completed);
+
+    // The wait date is compared to the time the filter was built at
+    let before = Local.with_ymd_and_hms(2026, 11, 3, 12, 0, 0).unwrap();
+    let after = Local.with_ymd_and_hms(2026, 11, 6, 12, 0, 0).unwrap();
+    let pending_filter = StatusFilter {
+        status: TaskStatus::Pending,
+    };
+    let completed_filter = StatusFilter {
+        status: TaskStatus::Completed,
+    };
+
+    // A waiting task keeps its status, 'status:pending' matches it all along
+    assert_true!(WaitingFilter { now: before }.validate_task(&waiting));
+    assert_true!(pending_filter.validate_task(&waiting));
+
+    assert_false!(WaitingFilter { now: after }.validate_task(&waiting));
+    assert_true!(pending_filter.validate_task(&waiting));
+
+    // Only the pending and active tasks can be waiting
+    assert_false!(WaitingFilter { now: before }.validate_task(&completed));
+    assert_true!(completed_filter.validate_task(&completed));
+}
+
+#[test]
+fn test_wait_and_scheduled_filters() {
+    let mut task_data = TaskData::default();
+    let task = task_data
+        .add_task(
+            &TaskProperties::from(&["foo wait:tomorrow scheduled:yesterday".to_owned()]).unwrap(),
+            TaskStatus::Pending,
+        )
+        .unwrap()
+        .clone();
+    let now = Local::now();
+
+    let wait = |type_when: DateDueFilterType| DateWaitFilter {
+        time: now,
+        type_when,
+    };
+    assert_true!(wait(DateDueFilterType::After).validate_task(&task));
+    assert_false!(wait(DateDueFilterType::Before).validate_task(&task));
+    assert_false!(wait(DateDueFilterType::Day).validate_task(&task));
+
+    let scheduled = |type_when: DateDueFilterType| DateScheduledFilter {
+        time: now,
+        type_when,
+    };
+    assert_false!(scheduled(DateDueFilterType::After).validate_task(&task));
+    assert_true!(scheduled(DateDueFilterType::Before).validate_task(&task));
+
+    let other = task_data
+        .add_task(
+            &TaskProperties::from(&["bar".to_owned()]).unwrap(),
+            TaskStatus::Pending,
+        )
+        .unwrap()
+        .clone();
+    assert_false!(wait(DateDueFilterType::Before).validate_task(&other));
+    assert_false!(scheduled(DateDueFilterType::Before).validate_task(&other));
+}
+
 #[test]
 fn test_filter_depends_on() {
     let depends_uuid = Uuid::new_v4();
     let mut task = Task::default();
     let mut props = TaskProperties::default();
-    props.add_depends_on(&crate::task::DependsOnIdentifier::Uuid(depends_uuid));
-    task.apply(&props).unwrap();
+    props.add_depends_on(&crate::task::TaskIdentifier::Uuid(depends_uuid));
+    task.apply(&props, Local::now()).unwrap();
 
     let filter = DependsOnFilter {
         uuid: Some(depends_uuid),
@@ -290,6 +365,91 @@ fn test_filter_depends_on() {
     assert_false!(filter.validate_task(&task));
 }
 
+#[test]
+fn test_filter_subtasks() {
+    let mut task_data = TaskData::default();
+    let parent = *task_data
+        .add_task(
+            &TaskProperties::from(&["release".to_owned()]).unwrap(),
+            TaskStatus::Pending,
+        )
+        .unwrap()
+        .get_uuid();
+    let child = *task_data
+        .add_task(
+            &TaskProperties::from(&[format!("write the notes parent:{}", parent)]).unwrap(),
+            TaskStatus::Pending,
+        )
+        .unwrap()
+        .get_uuid();
+    task_data.upkeep().unwrap();
+    let parent = &task_data.get_task_map()[&parent];
+    let child = &task_data.get_task_map()[&child];
+
+    let mut filter = ParentFilter {
+        id: parent.get_id(),
+        uuid: None,
+    };
+    assert_false!(filter.validate_task(child));
+    filter.convert_id_to_uuid(&HashMap::from([(1, *parent.get_uuid())]));
+    assert_true!(filter.validate_task(child));
+    assert_false!(filter.validate_task(parent));
+
+    let filter = HasSubtasksFilter {};
+    assert_true!(filter.validate_task(parent));
+    assert_false!(filter.validate_task(child));
+}
+
+#[test]
+fn test_filter_linked() {
+    let mut task_data = TaskData::default();
+    let original = *task_data
+        .add_task(
+            &TaskProperties::from(&["crash on start".to_owned()]).unwrap(),
+            TaskStatus::Pending,
+        )
+        .unwrap()
+        .get_uuid();
+    let duplicate = *task_data
+        .add_task(
+            &TaskProperties::from(&[format!(
+                "app does not start link:duplicates:{} link:relates-to:{}",
+                original, original
+            )])
+            .unwrap(),
+            TaskStatus::Pending,
+        )
+        .unwrap()
+        .get_uuid();
+    task_data.upkeep().unwrap();
+    let original = &task_data.get_task_map()[&original];
+    let duplicate = &task_data.get_task_map()[&duplicate];
+
+    let mut filter = LinkedFilter {
+        kind: LinkType::Duplicates,
+        id: original.get_id(),
+        uuid: None,
+    };
+    assert_false!(filter.validate_task(duplicate));
+    filter.convert_id_to_uuid(&HashMap::from([(1, *original.get_uuid())]));
+    assert_true!(filter.validate_task(duplicate));
+    assert_false!(filter.validate_task(original));
+
+    // A symmetric link matches from both of its ends
+    let filter = LinkedFilter {
+        kind: LinkType::RelatesTo,
+        id: None,
+        uuid: Some(*duplicate.get_uuid()),
+    };
+    assert_true!(filter.validate_task(original));
+    let filter = LinkedFilter {
+        kind: LinkType::FollowsUp,
+        id: None,
+        uuid: Some(*original.get_uuid()),
+    };
+    assert_false!(filter.validate_task(duplicate));
+}
+
 #[test]
 fn test_depends_on_convert_id_to_uuid_already_has_uuid() {
     let uuid = Uuid::new_v4();
@@ -452,10 +612,234 @@ fn test_validate_task() {
     t.set_summary("This is a task");
     assert_true!(f_xor.validate_task(&t));
 
-    t.delete();
+    t.delete(Local::now());
     let f_id = TaskIdFilter { id: 0 };
     assert_false!(f_id.validate_task(&t));
 
-    t.done();
+    t.done(Local::now());
     assert_false!(f_id.validate_task(&t));
 }
+
+#[test]
+fn test_recurrence_filter() {
+    let mut task_data = TaskData::default();
+    let task = task_data
+        .add_task(
+            &TaskProperties::from(&["foo recur:weekly".to_owned()]).unwrap(),
+            TaskStatus::Pending,
+        )
+        .unwrap()
+        .clone();
+
+    assert_true!(
+        RecurrenceFilter {
+            recurrence: Some(Recurrence::from_string("weekly").unwrap())
+        }
+        .validate_task(&task)
+    );
+    assert_false!(
+        RecurrenceFilter {
+            recurrence: Some(Recurrence::from_string("2w").unwrap())
+        }
+        .validate_task(&task)
+    );
+    assert_false!(RecurrenceFilter { recurrence: None }.validate_task(&task));
+}
+
+#[test]
+fn test_priority_filter() {
+    let mut task_data = TaskData::default();
+    let mut add = |value: &str| {
+        task_data
+            .add_task(
+                &TaskProperties::from(&[value.to_owned()]).unwrap(),
+                TaskStatus::Pending,
+            )
+            .unwrap()
+            .clone()
+    };
+    let high = add("foo priority:H");
+    let low = add("bar priority:L");
+    let none = add("baz");
+
+    let filter = |priority: Option<TaskPriority>, above: bool| PriorityFilter { priority, above };
+
+    assert_true!(filter(Some(TaskPriority::High), false).validate_task(&high));
+    assert_false!(filter(Some(TaskPriority::High), false).validate_task(&low));
+    assert_true!(filter(None, false).validate_task(&none));
+    assert_false!(filter(None, false).validate_task(&low));
+
+    assert_true!(filter(Some(TaskPriority::Medium), true).validate_task(&high));
+    assert_false!(filter(Some(TaskPriority::Medium), true).validate_task(&low));
+    assert_false!(filter(Some(TaskPriority::Medium), true).validate_task(&none));
+    assert_false!(filter(Some(TaskPriority::High), true).validate_task(&high));
+    // Any priority is above no priority at all
+    assert_true!(filter(None, true).validate_task(&low));
+    assert_false!(filter(None, true).validate_task(&none));
+}
+
+#[test]
+fn test_uda_filter() {
+    let mut task = Task::default();
+    let mut props = TaskProperties::default();
+    props.set_uda("estimate", Some("7200".to_string()));
+    task.apply(&props, Local::now()).unwrap();
+    let other = Task::default();
+
+    let filter = |value: Option<&str>, comparison: UdaComparison| UdaFilter {
+        name: "estimate".to_string(),
+        definition: UdaDefinition {
+            uda_type: UdaType::Duration,
+            values: Vec::new(),
+        },
+        value: value.map(|v| v.to_string()),
+        comparison,
+    };
+
+    assert_true!(filter(Some("7200"), UdaComparison::Equal).validate_task(&task));
+    assert_false!(filter(Some("3600"), UdaComparison::Equal).validate_task(&task));
+    assert_true!(filter(Some("3600"), UdaComparison::Above).validate_task(&task));
+    assert_false!(filter(Some("3600"), UdaComparison::Below).validate_task(&task));
+    assert_true!(filter(Some("86400"), UdaComparison::Below).validate_task(&task));
+
+    assert_false!(filter(Some("3600"), UdaComparison::Above).validate_task(&other));
+    assert_true!(filter(None, UdaComparison::Equal).validate_task(&other));
+    assert_false!(filter(None, UdaComparison::Equal).validate_task(&task));
+}
+
+#[test]
+fn test_mentions_waiting() {
+    let mentions = |line: &str| {
+        let values: Vec<String> = line.split_whitespace().map(|s| s.to_string()).collect();
+        mentions_waiting(&from(&values).unwrap())
+    };
+    assert_true!(mentions("status:waiting"));
+    assert_true!(mentions("+home and not status:waiting"));
+    assert_true!(mentions("(+home or status:waiting) and +garden"));
+    assert_false!(mentions("status:pending or status:active"));
+    assert_false!(mentions("wait.before:tomorrow"));
+    assert_false!(mentions(""));
+}
+
+#[test]
+fn test_to_indexed() {
+    let filter = from(&["status:pending +home or project:work".to_owned()]).unwrap();
+    assert_eq!(
+        to_indexed(&filter),
+        IndexedFilter::Or(vec![
+            IndexedFilter::And(vec![
+                IndexedFilter::Status(TaskStatus::Pending),
+                IndexedFilter::Tag {
+                    name: "home".to_owned(),
+                    include: true
+                },
+            ]),
+            IndexedFilter::Project("work".to_owned()),
+        ])
+    );
+
+    // Filters on non-indexed fields are relaxed
+    let filter = from(&["status:completed and foo".to_owned()]).unwrap();
+    assert_eq!(
+        to_indexed(&filter),
+        IndexedFilter::Status(TaskStatus::Completed)
+    );
+    let filter = from(&["status:completed or foo".to_owned()]).unwrap();
+    assert_eq!(to_indexed(&filter), IndexedFilter::All);
+    let filter = from(&["not +home".to_owned()]).unwrap();
+    assert_eq!(
+        to_indexed(&filter),
+        IndexedFilter::Tag {
+            name: "home".to_owned(),
+            include: false
+        }
+    );
+    let filter = from(&["not status:completed".to_owned()]).unwrap();
+    assert_eq!(to_indexed(&filter), IndexedFilter::All);
+    // Only a tag is negated, a compound filter matches fewer tasks than its
+    // relaxed form
+    let filter = from(&["not (+home and summary:zzz)".to_owned()]).unwrap();
+    assert_eq!(to_indexed(&filter), IndexedFilter::All);
+    let filter = from(&["not (+home or +garden)".to_owned()]).unwrap();
+    assert_eq!(to_indexed(&filter), IndexedFilter::All);
+    assert_eq!(to_indexed(&new_empty()), IndexedFilter::All);
+}
+
+#[test]
+fn test_not_filter() {
+    let mut task_data = TaskData::default();
+    let task = task_data
+        .add_task(
+            &TaskProperties::from(&["foo +urgent project:work".to_owned()]).unwrap(),
+            TaskStatus::Pending,
+        )
+        .unwrap()
+        .clone();
+
+    let not_filter = from(&["not project:work".to_owned()]).unwrap();
+    assert_false!(not_filter.validate_task(&task));
+    let not_filter = from(&["not (status:pending and +urgent)".to_owned()]).unwrap();
+    assert_false!(not_filter.validate_task(&task));
+    let not_filter = from(&["not (status:pending and +home)".to_owned()]).unwrap();
+    assert_true!(not_filter.validate_task(&task));
+
+    let serialized = serde_json::to_string(&not_filter).unwrap();
+    assert_true!(serialized.starts_with("{\"type\":\"NotFilter\",\"value\":{\"child\":"));
+    let deserialized: Box<dyn Filter> = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(&deserialized, &not_filter);
+    assert_eq!(&not_filter.clone(), &not_filter);
+    assert_true!(not_filter.to_string().starts_with("Not:\n|   And:"));
+}
+
+#[test]
+fn test_text_filters() {
+    let mut task_data = TaskData::default();
+    let mut task = task_data
+        .add_task(
+            &TaskProperties::from(&["Fix the parser project:work.bee".to_owned()]).unwrap(),
+            TaskStatus::Pending,
+        )
+        .unwrap()
+        .clone();
+    let mut props = TaskProperties::default();
+    props.set_annotate("Ask Alice about the lexer".to_owned());
+    props.set_description("The lexer splits `due:` in two.\n\nSee the *tokens*.");
+    task.apply(&props, Local::now()).unwrap();
+
+    let matches = |value: &str| from(&[value.to_owned()]).unwrap().validate_task(&task);
+    assert_true!(matches("summary:PARSER"));
+    assert_false!(matches("summary:alice"));
+    assert_true!(matches("annotation:alice"));
+    assert_false!(matches("annotation:parser"));
+
+    // A word searches all of them
+    assert_true!(matches("PARSER"));
+    assert_true!(matches("alice"));
+    assert_true!(matches("tokens"));
+    assert_false!(matches("bob"));
+
+    assert_true!(matches("/^fix.*parser$/"));
+    assert_true!(matches("/ask alice/"));
+    assert_false!(matches("/^ask bob/"));
+    assert_true!(matches("summary~:^fix"));
+    assert_false!(matches("summary~:^ask"));
+    assert_true!(matches("annotation~:(lexer|parser)$"));
+    assert_true!(matches("description:TOKENS"));
+    assert_false!(matches("description:alice"));
+    assert_true!(matches("description~:^the.lexer"));
+    assert_true!(matches("/in two/"));
+
+    assert_true!(matches("project:work"));
+    assert_false!(matches("project.is:work"));
+    assert_true!(matches("project.is:work.bee"));
+
+    let filter = from(&["summary~:^fix".to_owned()]).unwrap();
+    let serialized = serde_json::to_string(&filter).unwrap();
+    assert_eq!(
+        serialized,
+        "{\"type\":\"RegexFilter\",\"value\":{\"field\":\"Summary\",\"regex\":\"^fix\"}}"
+    );
+    let deserialized: Box<dyn Filter> = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(&deserialized, &filter);
+    assert_true!(deserialized.validate_task(&task));
+}
@@ -1,12 +1,20 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
 use uuid::Uuid;
 
-use crate::lexer::Lexer;
-use crate::task::{Project, TaskStatus};
+use crate::clock::{Clock, SystemClock};
+use crate::config::get_config;
+use crate::lexer::{Lexer, link_type_of_keyword};
+use crate::task::{Project, Recurrence, TaskPriority, TaskStatus, UdaDefinition, split_uda_word};
 
 use super::filters_impl::{
-    AndFilter, DateCreatedFilter, DateDueFilter, DateDueFilterType, DateEndFilter, DependsOnFilter,
-    FilterKind, OrFilter, ProjectFilter, StatusFilter, StringFilter, TagFilter, TaskIdFilter,
-    UuidFilter, XorFilter,
+    AndFilter, DateCreatedFilter, DateDueFilter, DateDueFilterType, DateEndFilter,
+    DateScheduledFilter, DateWaitFilter, DependsOnFilter, FilterKind, FilterRegex,
+    HasSubtasksFilter, LinkedFilter, NotFilter, OrFilter, ParentFilter, PriorityFilter,
+    ProjectFilter, ProjectIsFilter, RecurrenceFilter, RegexFilter, StatusFilter, StringFilter,
+    TagFilter, TaskIdFilter, TextField, TextFilter, UdaComparison, UdaFilter, UuidFilter,
+    WaitingFilter, XorFilter,
 };
 use super::{Filter, new_empty};
 
@@ -20,13 +28,22 @@ pub struct FilterParser {
     peek_token: Token,
     buffer_tokens: Vec<Token>,
     buffer_index: usize,
+    /// The UDAs that can be filtered on, by name
+    udas: BTreeMap<String, UdaDefinition>,
+    clock: Arc<dyn Clock>,
 }
 
+/// The operator the filter being parsed is an operand of.
+///
+/// From the one binding the tightest to the loosest, the operators are `not`, `and`
+/// (also implied between two terms), `or` and `xor`. `not` only applies to the term
+/// or the parenthesised group following it.
 #[derive(Clone, Copy, PartialEq)]
 enum ScopeOperator {
     Or,
     And,
     Xor,
+    Not,
     None,
 }
 
@@ -85,6 +102,10 @@ impl BaseParser for FilterParser {
     fn get_mut_lexer(&mut self) -> &mut Lexer {
         &mut self.lexer
     }
+
+    fn get_clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
 }
 
 impl FilterParser {
@@ -95,6 +116,8 @@ impl FilterParser {
             peek_token: Token::default(),
             buffer_tokens: Vec::default(),
             buffer_index: 0,
+            udas: get_config().udas.to_owned(),
+            clock: Arc::new(SystemClock),
         };
         parser.next_token();
         parser.next_token();
@@ -102,6 +125,33 @@ impl FilterParser {
         parser
     }
 
+    /// Use @clock for the relative dates instead of the system clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Read a regular expression up to the next blank. Parentheses are part of
+    /// it as long as they are balanced, so that a pattern can be put in a group.
+    fn read_pattern(&mut self) -> String {
+        let mut depth = 0;
+        let mut value = String::default();
+        loop {
+            match self.current_token.token_type {
+                TokenType::LeftParenthesis => depth += 1,
+                TokenType::RightParenthesis => depth -= 1,
+                _ => {}
+            }
+            value.push_str(&self.current_token.literal);
+            match self.peek_token.token_type {
+                TokenType::Blank | TokenType::Eof => break,
+                TokenType::RightParenthesis if depth == 0 => break,
+                _ => self.next_token(),
+            }
+        }
+        value
+    }
+
     pub fn parse_filter(&mut self) -> Result<Box<dyn Filter>, String> {
         let mut has_only_ids = true;
         let filter = self.parse_filter_impl(&0, ScopeOperator::None, &mut has_only_ids)?;
@@ -135,6 +185,9 @@ impl FilterParser {
         }
 
         while self.current_token.token_type != TokenType::Eof {
+            if scope_operator == ScopeOperator::Not && filter.get_kind() != FilterKind::Root {
+                return Ok(filter);
+            }
             match self.current_token.token_type {
                 TokenType::Eof => {}
                 TokenType::Blank => {
@@ -209,6 +262,35 @@ impl FilterParser {
                         ],
                     });
                 }
+                TokenType::OperatorNot => {
+                    *has_only_ids = false;
+                    self.next_token();
+                    self.skip_whitespace();
+                    let child = match self.current_token.token_type {
+                        TokenType::Eof
+                        | TokenType::RightParenthesis
+                        | TokenType::OperatorOr
+                        | TokenType::OperatorAnd
+                        | TokenType::OperatorXor => new_empty(),
+                        _ => self.parse_filter_impl(
+                            parenthesis_scope,
+                            ScopeOperator::Not,
+                            has_only_ids,
+                        )?,
+                    };
+                    if child.get_kind() == FilterKind::Root {
+                        return Err(err_msg_prefix
+                            + &format!(
+                                "Expected a filter following 'not', found '{}' (value: '{}')",
+                                self.current_token.token_type, self.current_token.literal
+                            ));
+                    }
+                    filter = add_to_current_filter(
+                        filter,
+                        Box::new(NotFilter { child }),
+                        &ScopeOperator::And,
+                    );
+                }
                 TokenType::RightParenthesis => {
                     if *parenthesis_scope == 0 {
                         return Err(err_msg_prefix + "Encountered ')' before encountering a '('");
@@ -256,16 +338,24 @@ impl FilterParser {
                             ));
                     }
 
-                    let status_filter = Box::new(StatusFilter {
-                        status: TaskStatus::from_string(&self.current_token.literal)
-                            .map_err(|err| err_msg_prefix.to_string() + &err)?,
-                    });
+                    let status_filter: Box<dyn Filter> =
+                        if self.current_token.literal.to_lowercase() == "waiting" {
+                            Box::new(WaitingFilter {
+                                now: self.clock.now(),
+                            })
+                        } else {
+                            Box::new(StatusFilter {
+                                status: TaskStatus::from_string(&self.current_token.literal)
+                                    .map_err(|err| err_msg_prefix.to_string() + &err)?,
+                            })
+                        };
                     filter = add_to_current_filter(filter, status_filter, &ScopeOperator::And);
 
                     self.next_token();
                 }
-                TokenType::ProjectPrefix => {
+                TokenType::ProjectPrefix | TokenType::ProjectIsPrefix => {
                     *has_only_ids = false;
+                    let exact = self.current_token.token_type == TokenType::ProjectIsPrefix;
                     self.next_token();
                     self.skip_whitespace();
                     if self.current_token.token_type != TokenType::WordString {
@@ -301,14 +391,139 @@ impl FilterParser {
                             ));
                     }
 
-                    let project_filter = Box::new(ProjectFilter {
-                        name: Project::from(project_name),
-                    });
+                    let project_filter: Box<dyn Filter> = if exact {
+                        Box::new(ProjectIsFilter {
+                            name: Project::from(project_name),
+                        })
+                    } else {
+                        Box::new(ProjectFilter {
+                            name: Project::from(project_name),
+                        })
+                    };
                     filter = add_to_current_filter(filter, project_filter, &ScopeOperator::And);
 
                     self.next_token();
                 }
-                TokenType::String | TokenType::WordString => {
+                TokenType::FilterSummary
+                | TokenType::FilterAnnotation
+                | TokenType::FilterDescription
+                | TokenType::FilterSummaryRegex
+                | TokenType::FilterAnnotationRegex
+                | TokenType::FilterDescriptionRegex => {
+                    *has_only_ids = false;
+                    let tok_type = self.current_token.token_type.clone();
+                    self.next_token();
+                    self.skip_whitespace();
+                    let is_regex = tok_type == TokenType::FilterSummaryRegex
+                        || tok_type == TokenType::FilterAnnotationRegex
+                        || tok_type == TokenType::FilterDescriptionRegex;
+                    match self.current_token.token_type {
+                        TokenType::LeftParenthesis if is_regex => {}
+                        TokenType::Eof
+                        | TokenType::LeftParenthesis
+                        | TokenType::RightParenthesis => {
+                            return Err(err_msg_prefix
+                                + &format!(
+                                    "Expected a text to search following a {}, found '{}' (value: '{}')",
+                                    tok_type,
+                                    self.current_token.token_type,
+                                    self.current_token.literal
+                                ));
+                        }
+                        _ => {}
+                    }
+
+                    let new_filter: Box<dyn Filter> = match tok_type {
+                        TokenType::FilterSummary => Box::new(TextFilter {
+                            field: TextField::Summary,
+                            value: self.read_until_blank(),
+                        }),
+                        TokenType::FilterAnnotation => Box::new(TextFilter {
+                            field: TextField::Annotation,
+                            value: self.read_until_blank(),
+                        }),
+                        TokenType::FilterDescription => Box::new(TextFilter {
+                            field: TextField::Description,
+                            value: self.read_until_blank(),
+                        }),
+                        TokenType::FilterSummaryRegex => Box::new(RegexFilter {
+                            field: TextField::Summary,
+                            regex: FilterRegex::new(&self.read_pattern())
+                                .map_err(|e| err_msg_prefix.to_string() + &e)?,
+                        }),
+                        TokenType::FilterAnnotationRegex => Box::new(RegexFilter {
+                            field: TextField::Annotation,
+                            regex: FilterRegex::new(&self.read_pattern())
+                                .map_err(|e| err_msg_prefix.to_string() + &e)?,
+                        }),
+                        TokenType::FilterDescriptionRegex => Box::new(RegexFilter {
+                            field: TextField::Description,
+                            regex: FilterRegex::new(&self.read_pattern())
+                                .map_err(|e| err_msg_prefix.to_string() + &e)?,
+                        }),
+                        _ => unreachable!(),
+                    };
+                    filter = add_to_current_filter(filter, new_filter, &ScopeOperator::And);
+
+                    self.next_token();
+                }
+                TokenType::Regex => {
+                    *has_only_ids = false;
+                    // Remove the delimiters, and unescape the ones within the pattern
+                    let literal = &self.current_token.literal;
+                    let pattern = literal[1..literal.len() - 1].replace("\\/", "/");
+                    let regex_filter = Box::new(RegexFilter {
+                        field: TextField::Any,
+                        regex: FilterRegex::new(&pattern)
+                            .map_err(|e| err_msg_prefix.to_string() + &e)?,
+                    });
+                    filter = add_to_current_filter(filter, regex_filter, &ScopeOperator::And);
+
+                    self.next_token();
+                }
+                TokenType::WordString
+                    if split_uda_word(&self.current_token.literal, &self.udas).is_some() =>
+                {
+                    *has_only_ids = false;
+                    let literal = self.current_token.literal.to_owned();
+                    let (name, modifier, value) = split_uda_word(&literal, &self.udas).unwrap();
+                    let comparison = match modifier {
+                        None => UdaComparison::Equal,
+                        Some("above") | Some("after") => UdaComparison::Above,
+                        Some("below") | Some("before") => UdaComparison::Below,
+                        Some(other) => {
+                            return Err(err_msg_prefix
+                                + &format!(
+                                    "Unknown comparison '{}' for the attribute '{}'. Use '{}:', '{}.above:' or '{}.below:'",
+                                    other, name, name, name, name
+                                ));
+                        }
+                    };
+                    let definition = self.udas[name].to_owned();
+                    let value = self
+                        .read_uda_value(value, &definition)
+                        .map_err(|e| err_msg_prefix.to_string() + &e)?;
+                    if value.is_none() && comparison != UdaComparison::Equal {
+                        return Err(err_msg_prefix
+                            + &format!("The attribute '{}' cannot be compared to 'none'", name));
+                    }
+                    filter = add_to_current_filter(
+                        filter,
+                        Box::new(UdaFilter {
+                            name: name.to_owned(),
+                            definition,
+                            value,
+                            comparison,
+                        }),
+                        &ScopeOperator::And,
+                    );
+                    self.next_token();
+                }
+                TokenType::String
+                | TokenType::WordString
+                | TokenType::Date
+                | TokenType::Link
+                | TokenType::Unlink => {
                     *has_only_ids = false;
                     filter = add_to_current_filter(
                         filter,
@@ -417,13 +632,158 @@ impl FilterParser {
 
                     self.next_token();
                 }
+                TokenType::FilterTokLinked => {
+                    *has_only_ids = false;
+                    let kind = link_type_of_keyword(&self.current_token.literal);
+                    self.next_token();
+                    self.skip_whitespace();
+
+                    let linked_filter = match self.current_token.token_type {
+                        TokenType::Int => Box::new(LinkedFilter {
+                            kind,
+                            id: Some(self.current_token.literal.parse::<usize>().unwrap()),
+                            uuid: None,
+                        }),
+                        TokenType::Uuid => Box::new(LinkedFilter {
+                            kind,
+                            uuid: Some(self.current_token.literal.parse::<Uuid>().unwrap()),
+                            id: None,
+                        }),
+                        _ => {
+                            return Err(err_msg_prefix
+                                + &format!(
+                                    "Expected a token of type Int or UUID following a TokenType::FilterTokLinked, found '{}' (value: '{}')",
+                                    self.current_token.token_type, self.current_token.literal
+                                ));
+                        }
+                    };
+                    filter = add_to_current_filter(filter, linked_filter, &ScopeOperator::And);
+
+                    self.next_token();
+                }
+                TokenType::Parent => {
+                    *has_only_ids = false;
+                    self.next_token();
+                    self.skip_whitespace();
+
+                    let parent_filter = match self.current_token.token_type {
+                        TokenType::Int => Box::new(ParentFilter {
+                            id: Some(self.current_token.literal.parse::<usize>().unwrap()),
+                            uuid: None,
+                        }),
+                        TokenType::Uuid => Box::new(ParentFilter {
+                            uuid: Some(self.current_token.literal.parse::<Uuid>().unwrap()),
+                            id: None,
+                        }),
+                        _ => {
+                            return Err(err_msg_prefix
+                                + &format!(
+                                    "Expected a token of type Int or UUID following a TokenType::Parent, found '{}' (value: '{}')",
+                                    self.current_token.token_type, self.current_token.literal
+                                ));
+                        }
+                    };
+                    filter = add_to_current_filter(filter, parent_filter, &ScopeOperator::And);
+
+                    self.next_token();
+                }
+                TokenType::FilterTokHas => {
+                    *has_only_ids = false;
+                    self.next_token();
+                    self.skip_whitespace();
+                    if self.current_token.token_type != TokenType::WordString
+                        || self.current_token.literal != "children"
+                    {
+                        return Err(err_msg_prefix
+                            + &format!(
+                                "Expected 'children' following 'has:', found '{}'",
+                                self.current_token.literal
+                            ));
+                    }
+                    filter = add_to_current_filter(
+                        filter,
+                        Box::new(HasSubtasksFilter {}),
+                        &ScopeOperator::And,
+                    );
+
+                    self.next_token();
+                }
+                TokenType::Recur => {
+                    *has_only_ids = false;
+                    self.next_token();
+                    self.skip_whitespace();
+                    match self.current_token.token_type {
+                        TokenType::Eof
+                        | TokenType::LeftParenthesis
+                        | TokenType::RightParenthesis => {
+                            return Err(err_msg_prefix
+                                + &format!(
+                                    "Expected a recurrence following a TokenType::Recur, found '{}' (value: '{}')",
+                                    self.current_token.token_type, self.current_token.literal
+                                ));
+                        }
+                        _ => {}
+                    }
+
+                    let value = self.read_until_blank();
+                    let recurrence = if value == "none" {
+                        None
+                    } else {
+                        Some(
+                            Recurrence::from_string(&value)
+                                .map_err(|e| err_msg_prefix.to_string() + &e)?,
+                        )
+                    };
+                    filter = add_to_current_filter(
+                        filter,
+                        Box::new(RecurrenceFilter { recurrence }),
+                        &ScopeOperator::And,
+                    );
+
+                    self.next_token();
+                }
+                TokenType::Priority | TokenType::PriorityAbove => {
+                    *has_only_ids = false;
+                    let above = self.current_token.token_type == TokenType::PriorityAbove;
+                    self.next_token();
+                    self.skip_whitespace();
+                    if self.current_token.token_type != TokenType::WordString {
+                        return Err(err_msg_prefix
+                            + &format!(
+                                "Expected a priority following a TokenType::Priority, found '{}' (value: '{}')",
+                                self.current_token.token_type, self.current_token.literal
+                            ));
+                    }
+
+                    let priority = if self.current_token.literal == "none" {
+                        None
+                    } else {
+                        Some(
+                            TaskPriority::from_string(&self.current_token.literal)
+                                .map_err(|e| err_msg_prefix.to_string() + &e)?,
+                        )
+                    };
+                    filter = add_to_current_filter(
+                        filter,
+                        Box::new(PriorityFilter { priority, above }),
+                        &ScopeOperator::And,
+                    );
+
+                    self.next_token();
+                }
                 TokenType::FilterTokDateEndBefore
                 | TokenType::FilterTokDateEndAfter
                 | TokenType::FilterTokDateCreatedBefore
                 | TokenType::FilterTokDateCreatedAfter
                 | TokenType::FilterTokDateDue
                 | TokenType::FilterTokDateDueAfter
-                | TokenType::FilterTokDateDueBefore => {
+                | TokenType::FilterTokDateDueBefore
+                | TokenType::FilterTokDateWait
+                | TokenType::FilterTokDateWaitBefore
+                | TokenType::FilterTokDateWaitAfter
+                | TokenType::FilterTokDateScheduled
+                | TokenType::FilterTokDateScheduledBefore
+                | TokenType::FilterTokDateScheduledAfter => {
                     *has_only_ids = false;
                     let before = self.current_token.token_type == TokenType::FilterTokDateEndBefore
                         || self.current_token.token_type == TokenType::FilterTokDateCreatedBefore;
@@ -431,9 +791,10 @@ impl FilterParser {
 
                     self.next_token();
                     self.skip_whitespace();
-                    if self.current_token.token_type != TokenType::WordString
-                        && self.current_token.token_type != TokenType::Int
-                    {
+                    if !matches!(
+                        self.current_token.token_type,
+                        TokenType::WordString | TokenType::Int | TokenType::Date
+                    ) {
                         return Err(err_msg_prefix
                             + &format!(
                                 "Expected a token of type String or Int following a TokenTypeFilterDateEnd, found '{}' (value: '{}')",
@@ -462,6 +823,30 @@ impl FilterParser {
                             time,
                             type_when: DateDueFilterType::After,
                         }),
+                        TokenType::FilterTokDateWait => Box::new(DateWaitFilter {
+                            time,
+                            type_when: DateDueFilterType::Day,
+                        }),
+                        TokenType::FilterTokDateWaitBefore => Box::new(DateWaitFilter {
+                            time,
+                            type_when: DateDueFilterType::Before,
+                        }),
+                        TokenType::FilterTokDateWaitAfter => Box::new(DateWaitFilter {
+                            time,
+                            type_when: DateDueFilterType::After,
+                        }),
+                        TokenType::FilterTokDateScheduled => Box::new(DateScheduledFilter {
+                            time,
+                            type_when: DateDueFilterType::Day,
+                        }),
+                        TokenType::FilterTokDateScheduledBefore => Box::new(DateScheduledFilter {
+                            time,
+                            type_when: DateDueFilterType::Before,
+                        }),
+                        TokenType::FilterTokDateScheduledAfter => Box::new(DateScheduledFilter {
+                            time,
+                            type_when: DateDueFilterType::After,
+                        }),
                         _ => unreachable!(),
                     };
 
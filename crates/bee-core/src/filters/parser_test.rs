@@ -1,8 +1,12 @@
+use crate::clock::FixedClock;
 use crate::filters;
 use all_asserts::assert_true;
 use chrono::{Duration, Local, NaiveTime, TimeZone};
+use std::sync::Arc;
 
-use crate::task::{Project, TaskStatus};
+use std::collections::BTreeMap;
+
+use crate::task::{LinkType, Project, TaskPriority, TaskStatus, UdaDefinition, UdaType};
 
 use super::*;
 
@@ -87,8 +91,9 @@ fn test_add_string_to_current_filter() {
 
 #[test]
 fn test_parse_filter() {
+    let now = Local::now();
     let lexer = Lexer::new("some status: completed or status:pending".to_string());
-    let mut p = FilterParser::new(lexer);
+    let mut p = FilterParser::new(lexer).with_clock(Arc::new(FixedClock::new(now)));
     let f = p.parse_filter().unwrap();
 
     let expected_filter: Box<dyn Filter> = Box::new(OrFilter {
@@ -153,6 +158,63 @@ fn test_parse_depends_on_filter() {
     assert_true!(p.parse_filter().is_err())
 }
 
+#[test]
+fn test_parse_subtask_filters() {
+    let lexer = Lexer::new("parent:3".to_string());
+    let mut p = FilterParser::new(lexer);
+    let expected_filter: Box<dyn Filter> = Box::new(ParentFilter {
+        id: Some(3),
+        uuid: None,
+    });
+    assert_eq!(&p.parse_filter().unwrap(), &expected_filter);
+
+    let new_uuid = Uuid::new_v4();
+    let lexer = Lexer::new(format!("parent: {}", new_uuid));
+    let mut p = FilterParser::new(lexer);
+    let expected_filter: Box<dyn Filter> = Box::new(ParentFilter {
+        id: None,
+        uuid: Some(new_uuid),
+    });
+    assert_eq!(&p.parse_filter().unwrap(), &expected_filter);
+
+    let lexer = Lexer::new("has:children".to_string());
+    let mut p = FilterParser::new(lexer);
+    let expected_filter: Box<dyn Filter> = Box::new(HasSubtasksFilter {});
+    assert_eq!(&p.parse_filter().unwrap(), &expected_filter);
+
+    for input in ["parent:abc", "has:parents"] {
+        let lexer = Lexer::new(input.to_string());
+        let mut p = FilterParser::new(lexer);
+        assert_true!(p.parse_filter().is_err(), "'{}' should not parse", input);
+    }
+}
+
+#[test]
+fn test_parse_linked_filter() {
+    let lexer = Lexer::new("linked.relates-to:3".to_string());
+    let mut p = FilterParser::new(lexer);
+    let expected_filter: Box<dyn Filter> = Box::new(LinkedFilter {
+        kind: LinkType::RelatesTo,
+        id: Some(3),
+        uuid: None,
+    });
+    assert_eq!(&p.parse_filter().unwrap(), &expected_filter);
+
+    let new_uuid = Uuid::new_v4();
+    let lexer = Lexer::new(format!("linked.follows-up: {}", new_uuid));
+    let mut p = FilterParser::new(lexer);
+    let expected_filter: Box<dyn Filter> = Box::new(LinkedFilter {
+        kind: LinkType::FollowsUp,
+        id: None,
+        uuid: Some(new_uuid),
+    });
+    assert_eq!(&p.parse_filter().unwrap(), &expected_filter);
+
+    let lexer = Lexer::new("linked.duplicates:abc".to_string());
+    let mut p = FilterParser::new(lexer);
+    assert_true!(p.parse_filter().is_err());
+}
+
 #[test]
 fn test_parse_project_filter() {
     let lexer = Lexer::new("project:ABC".to_string());
@@ -507,3 +569,285 @@ fn test_build_filter_dates() {
     });
     assert_eq!(&expected, &actual);
 }
+
+#[test]
+fn test_build_filter_wait_and_scheduled() {
+    let today_start = Local
+        .from_local_datetime(
+            &Local::now()
+                .date_naive()
+                .and_time(NaiveTime::from_hms_opt(0, 0, 0).unwrap()),
+        )
+        .single()
+        .unwrap();
+    let now = Local::now();
+    let from = |value: &str| {
+        filters::from_with_clock(&[value.to_string()], Arc::new(FixedClock::new(now))).unwrap()
+    };
+
+    let expected: Box<dyn Filter> = Box::new(AndFilter {
+        children: vec![
+            Box::new(WaitingFilter { now }),
+            Box::new(DateWaitFilter {
+                time: today_start,
+                type_when: DateDueFilterType::Before,
+            }),
+        ],
+    });
+    assert_eq!(&expected, &from("status:waiting wait.before:today"));
+
+    let expected: Box<dyn Filter> = Box::new(DateScheduledFilter {
+        time: today_start,
+        type_when: DateDueFilterType::After,
+    });
+    assert_eq!(&expected, &from("scheduled.after:today"));
+
+    let expected: Box<dyn Filter> = Box::new(DateWaitFilter {
+        time: today_start,
+        type_when: DateDueFilterType::Day,
+    });
+    assert_eq!(&expected, &from("wait:today"));
+}
+
+#[test]
+fn test_build_filter_recurrence() {
+    let actual: Box<dyn Filter> = filters::from(
+        &["recur:weekly +main"]
+            .iter()
+            .map(|&s| s.to_string())
+            .collect::<Vec<String>>(),
+    )
+    .unwrap();
+    let expected: Box<dyn Filter> = Box::new(AndFilter {
+        children: vec![
+            Box::new(RecurrenceFilter {
+                recurrence: Some(Recurrence::from_string("weekly").unwrap()),
+            }),
+            Box::new(TagFilter {
+                tag_name: "main".to_string(),
+                include: true,
+            }),
+        ],
+    });
+    assert_eq!(&expected, &actual);
+
+    let actual: Box<dyn Filter> = filters::from(&["recur:none".to_string()]).unwrap();
+    let expected: Box<dyn Filter> = Box::new(RecurrenceFilter { recurrence: None });
+    assert_eq!(&expected, &actual);
+
+    assert_true!(filters::from(&["recur:".to_string()]).is_err());
+    assert_true!(filters::from(&["recur:whenever".to_string()]).is_err());
+}
+
+#[test]
+fn test_build_filter_priority() {
+    let from = |value: &str| filters::from(&[value.to_string()]);
+
+    let expected: Box<dyn Filter> = Box::new(PriorityFilter {
+        priority: Some(TaskPriority::High),
+        above: false,
+    });
+    assert_eq!(&expected, &from("priority:H").unwrap());
+
+    let expected: Box<dyn Filter> = Box::new(AndFilter {
+        children: vec![
+            Box::new(PriorityFilter {
+                priority: Some(TaskPriority::Medium),
+                above: true,
+            }),
+            Box::new(TagFilter {
+                tag_name: "main".to_string(),
+                include: true,
+            }),
+        ],
+    });
+    assert_eq!(&expected, &from("priority.above:m +main").unwrap());
+
+    let expected: Box<dyn Filter> = Box::new(PriorityFilter {
+        priority: None,
+        above: false,
+    });
+    assert_eq!(&expected, &from("priority:none").unwrap());
+
+    assert_true!(from("priority:").is_err());
+    assert_true!(from("priority:urgent").is_err());
+}
+
+#[test]
+fn test_parse_uda_filter() {
+    let size = UdaDefinition {
+        uda_type: UdaType::Enum,
+        values: vec!["S".to_string(), "M".to_string(), "L".to_string()],
+    };
+    let parse = |value: &str| {
+        let mut parser = FilterParser::new(Lexer::new(value.to_string()));
+        parser.udas = BTreeMap::from([("size".to_string(), size.to_owned())]);
+        parser.parse_filter()
+    };
+    let uda_filter = |value: Option<&str>, comparison: UdaComparison| -> Box<dyn Filter> {
+        Box::new(UdaFilter {
+            name: "size".to_string(),
+            definition: size.to_owned(),
+            value: value.map(|v| v.to_string()),
+            comparison,
+        })
+    };
+
+    assert_eq!(
+        &parse("size:l").unwrap(),
+        &uda_filter(Some("L"), UdaComparison::Equal)
+    );
+    assert_eq!(
+        &parse("size:none").unwrap(),
+        &uda_filter(None, UdaComparison::Equal)
+    );
+    let expected: Box<dyn Filter> = Box::new(OrFilter {
+        children: vec![
+            uda_filter(Some("S"), UdaComparison::Above),
+            uda_filter(Some("M"), UdaComparison::Below),
+        ],
+    });
+    assert_eq!(&parse("size.above:S or size.below: M").unwrap(), &expected);
+
+    // Not a UDA
+    let expected: Box<dyn Filter> = Box::new(StringFilter {
+        value: "color:red".to_string(),
+    });
+    assert_eq!(&parse("color:red").unwrap(), &expected);
+
+    assert_true!(parse("size:XL").is_err());
+    assert_true!(parse("size.above:none").is_err());
+    assert_true!(parse("size.around:M").is_err());
+}
+
+#[test]
+fn test_build_filter_not() {
+    let now = Local::now();
+    let from = |value: &str| {
+        filters::from_with_clock(&[value.to_string()], Arc::new(FixedClock::new(now)))
+    };
+    let not = |child: Box<dyn Filter>| -> Box<dyn Filter> { Box::new(NotFilter { child }) };
+    let tag = |name: &str| -> Box<dyn Filter> {
+        Box::new(TagFilter {
+            include: true,
+            tag_name: name.to_owned(),
+        })
+    };
+
+    let expected = not(Box::new(ProjectFilter {
+        name: Project::from("work".to_owned()),
+    }));
+    assert_eq!(&expected, &from("not project:work").unwrap());
+
+    // 'not' applies to the whole parenthesised group
+    let expected = not(Box::new(AndFilter {
+        children: vec![
+            Box::new(StatusFilter {
+                status: TaskStatus::Pending,
+            }),
+            tag("urgent"),
+        ],
+    }));
+    assert_eq!(
+        &expected,
+        &from("not (status:pending and +urgent)").unwrap()
+    );
+
+    // 'not' binds tighter than 'and', which binds tighter than 'or'
+    let expected: Box<dyn Filter> = Box::new(OrFilter {
+        children: vec![
+            tag("one"),
+            Box::new(AndFilter {
+                children: vec![not(tag("two")), tag("three")],
+            }),
+        ],
+    });
+    assert_eq!(&expected, &from("+one or not +two +three").unwrap());
+
+    let expected: Box<dyn Filter> = Box::new(XorFilter {
+        children: vec![not(tag("one")), not(not(tag("two")))],
+    });
+    assert_eq!(&expected, &from("not +one xor not not +two").unwrap());
+
+    // IDs following a 'not' are not a list of IDs to select
+    let expected = not(Box::new(TaskIdFilter { id: 3 }));
+    assert_eq!(&expected, &from("not 3").unwrap());
+
+    // 'not' can still be part of a word
+    let expected: Box<dyn Filter> = Box::new(StringFilter {
+        value: "nothing".to_owned(),
+    });
+    assert_eq!(&expected, &from("nothing").unwrap());
+
+    assert_true!(from("not").is_err());
+    assert_true!(from("+one not").is_err());
+    assert_true!(from("not or +one").is_err());
+    assert_true!(from("(not)").is_err());
+}
+
+#[test]
+fn test_build_filter_text() {
+    let from = |value: &str| filters::from(&[value.to_string()]);
+    let regex = |field: TextField, pattern: &str| -> Box<dyn Filter> {
+        Box::new(RegexFilter {
+            field,
+            regex: FilterRegex::new(pattern).unwrap(),
+        })
+    };
+
+    let expected: Box<dyn Filter> = Box::new(AndFilter {
+        children: vec![
+            Box::new(TextFilter {
+                field: TextField::Summary,
+                value: "write-up".to_owned(),
+            }),
+            Box::new(TextFilter {
+                field: TextField::Annotation,
+                value: "call".to_owned(),
+            }),
+        ],
+    });
+    assert_eq!(
+        &expected,
+        &from("summary:write-up annotation:call").unwrap()
+    );
+
+    assert_eq!(
+        &regex(TextField::Any, "^fix (a|the) bug/"),
+        &from(r"/^fix (a|the) bug\//").unwrap()
+    );
+    assert_eq!(
+        &regex(TextField::Summary, "^(a|b)$"),
+        &from("summary~:^(a|b)$").unwrap()
+    );
+    assert_eq!(
+        &regex(TextField::Description, "^#+notes"),
+        &from("description~:^#+notes").unwrap()
+    );
+    let expected: Box<dyn Filter> = Box::new(TextFilter {
+        field: TextField::Description,
+        value: "rollback".to_owned(),
+    });
+    assert_eq!(&expected, &from("description:rollback").unwrap());
+
+    // The parentheses of a group are not part of the pattern
+    let expected: Box<dyn Filter> = Box::new(OrFilter {
+        children: vec![
+            regex(TextField::Annotation, "b(c)"),
+            Box::new(TagFilter {
+                include: true,
+                tag_name: "home".to_owned(),
+            }),
+        ],
+    });
+    assert_eq!(&expected, &from("(annotation~:b(c)) or +home").unwrap());
+
+    let expected: Box<dyn Filter> = Box::new(ProjectIsFilter {
+        name: Project::from("work.bee".to_owned()),
+    });
+    assert_eq!(&expected, &from("project.is:work.bee").unwrap());
+
+    assert_true!(from("summary:").is_err());
+    assert_true!(from("summary~:(").is_err());
+    assert_true!(from("/(/").is_err());
+}
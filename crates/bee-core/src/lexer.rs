@@ -3,6 +3,9 @@ use unicode_normalization::UnicodeNormalization;
 use unicode_segmentation::UnicodeSegmentation;
 use uuid::Uuid;
 
+use crate::parser::parse_iso_date;
+use crate::task::LinkType;
+
 #[derive(Debug, PartialEq, Default, Clone)]
 pub enum TokenType {
     FilterTokDateDue,
@@ -12,7 +15,28 @@ pub enum TokenType {
     FilterTokDateCreatedAfter,
     FilterTokDateEndBefore,
     FilterTokDateEndAfter,
+    FilterTokDateWait,
+    FilterTokDateWaitBefore,
+    FilterTokDateWaitAfter,
+    FilterTokDateScheduled,
+    FilterTokDateScheduledBefore,
+    FilterTokDateScheduledAfter,
     DependsOn,
+    Parent,
+    FilterTokHas,
+    Link,
+    Unlink,
+    FilterTokLinked,
+    Recur,
+    Priority,
+    PriorityAbove,
+    FilterSummary,
+    FilterSummaryRegex,
+    FilterAnnotation,
+    FilterAnnotationRegex,
+    FilterDescription,
+    FilterDescriptionRegex,
+    Regex,
     String,
     WordString,
     TagPlusPrefix,
@@ -20,14 +44,18 @@ pub enum TokenType {
     FilterStatus,
     Int,
     Uuid,
+    /// An ISO date such as `2026-11-03` or `2026-11-03T14:00`
+    Date,
     #[default]
     Eof,
     LeftParenthesis,
     RightParenthesis,
     ProjectPrefix,
+    ProjectIsPrefix,
     OperatorAnd,
     OperatorOr,
     OperatorXor,
+    OperatorNot,
     Blank,
 }
 impl std::fmt::Display for TokenType {
@@ -37,25 +65,49 @@ impl std::fmt::Display for TokenType {
             TokenType::FilterTokDateDueBefore => "FilterTokDateDueBefore",
             TokenType::FilterTokDateDueAfter => "FilterTokDateDueAfter",
             TokenType::DependsOn => "DependsOn",
+            TokenType::Parent => "Parent",
+            TokenType::FilterTokHas => "FilterTokHas",
+            TokenType::Link => "Link",
+            TokenType::Unlink => "Unlink",
+            TokenType::FilterTokLinked => "FilterTokLinked",
+            TokenType::Recur => "Recur",
+            TokenType::Priority => "Priority",
+            TokenType::PriorityAbove => "PriorityAbove",
+            TokenType::FilterSummary => "FilterSummary",
+            TokenType::FilterSummaryRegex => "FilterSummaryRegex",
+            TokenType::FilterAnnotation => "FilterAnnotation",
+            TokenType::FilterAnnotationRegex => "FilterAnnotationRegex",
+            TokenType::FilterDescription => "FilterDescription",
+            TokenType::FilterDescriptionRegex => "FilterDescriptionRegex",
+            TokenType::Regex => "Regex",
             TokenType::String => "String",
             TokenType::ProjectPrefix => "ProjectPrefix",
+            TokenType::ProjectIsPrefix => "ProjectIsPrefix",
             TokenType::WordString => "WordString",
             TokenType::TagPlusPrefix => "TagPlusPrefix",
             TokenType::TagMinusPrefix => "TagMinusPrefix",
             TokenType::FilterStatus => "FilterStatus",
             TokenType::Int => "Int",
             TokenType::Uuid => "Uuid",
+            TokenType::Date => "Date",
             TokenType::Eof => "Eof",
             TokenType::LeftParenthesis => "LeftParenthesis",
             TokenType::RightParenthesis => "RightParenthesis",
             TokenType::OperatorAnd => "OperatorAnd",
             TokenType::OperatorOr => "OperatorOr",
             TokenType::OperatorXor => "OperatorXor",
+            TokenType::OperatorNot => "OperatorNot",
             TokenType::Blank => "Blank",
             TokenType::FilterTokDateEndBefore => "FilterTokDateEndBefore",
             TokenType::FilterTokDateEndAfter => "FilterTokDateEndAfter",
             TokenType::FilterTokDateCreatedBefore => "FilterTokDateCreatedBefore",
             TokenType::FilterTokDateCreatedAfter => "FilterTokDateCreatedAfter",
+            TokenType::FilterTokDateWait => "FilterTokDateWait",
+            TokenType::FilterTokDateWaitBefore => "FilterTokDateWaitBefore",
+            TokenType::FilterTokDateWaitAfter => "FilterTokDateWaitAfter",
+            TokenType::FilterTokDateScheduled => "FilterTokDateScheduled",
+            TokenType::FilterTokDateScheduledBefore => "FilterTokDateScheduledBefore",
+            TokenType::FilterTokDateScheduledAfter => "FilterTokDateScheduledAfter",
         };
         write!(f, "{}", token_str)
     }
@@ -81,6 +133,17 @@ fn is_segment_character_str(ch: &str) -> bool {
         .all(|c| is_segment_character(&c))
 }
 
+/// The type of link named in the literal of a `Link`, `Unlink` or `FilterTokLinked`
+/// token, e.g. `link:relates-to:`
+pub(crate) fn link_type_of_keyword(literal: &str) -> LinkType {
+    let name = literal
+        .trim_end_matches(':')
+        .rsplit([':', '.'])
+        .next()
+        .unwrap_or_default();
+    LinkType::from_string(name).expect("The lexer only reads valid link types")
+}
+
 #[derive(Debug, Default)]
 pub struct Lexer {
     input: String,
@@ -144,6 +207,28 @@ impl Lexer {
         output_str
     }
 
+    // Method to find an ISO date at the current position, see `parse_iso_date`.
+    // Returns the date as written.
+    fn match_date(&self) -> Option<String> {
+        let rest = self
+            .input
+            .graphemes(true)
+            .skip(self.position)
+            .take(20)
+            .collect::<String>();
+        [19, 16, 10]
+            .into_iter()
+            .filter(|len| rest.is_char_boundary(*len))
+            .find(|len| {
+                parse_iso_date(&rest[..*len]).is_some()
+                    && rest[*len..]
+                        .chars()
+                        .next()
+                        .is_none_or(|c| is_segment_character(&c) || c == '+' || c == '-')
+            })
+            .map(|len| rest[..len].to_owned())
+    }
+
     // Method to check if the current substring is a valid UUID
     fn is_uuid(&self) -> bool {
         let end_pos = self.position + 36; // UUID length is 36
@@ -210,6 +295,19 @@ impl Lexer {
             .starts_with(word)
     }
 
+    // Method to match a keyword made of @prefix, the name of a link type and a ':',
+    // e.g. `link:relates-to:`, without consuming the input
+    fn match_link_keyword(&self, prefix: &str) -> Option<String> {
+        let rest = self
+            .input
+            .graphemes(true)
+            .skip(self.position)
+            .collect::<String>();
+        let (name, _) = rest.strip_prefix(prefix)?.split_once(':')?;
+        LinkType::from_string(name).ok()?;
+        Some(format!("{}{}:", prefix, name))
+    }
+
     fn read_next_word(&mut self) -> String {
         let mut output_str = String::default();
         while let Some(ch) = &self.ch {
@@ -229,6 +327,40 @@ impl Lexer {
         output_str
     }
 
+    // Find the position of the '/' closing the regular expression starting at the
+    // current '/'. A '/' can be escaped with a backslash.
+    fn find_regex_end(&self) -> Option<usize> {
+        let mut escaped = false;
+        for (idx, grapheme) in self
+            .input
+            .graphemes(true)
+            .enumerate()
+            .skip(self.position + 1)
+        {
+            match grapheme {
+                "/" if !escaped => return Some(idx),
+                "\\" => escaped = !escaped,
+                _ => escaped = false,
+            }
+        }
+        None
+    }
+
+    // Read a regular expression written between two '/', delimiters included
+    fn read_regex(&mut self) -> String {
+        let end = self
+            .find_regex_end()
+            .expect("Trying to read an unterminated regex");
+        let mut output_str = String::default();
+        while self.position <= end {
+            if let Some(c) = &self.ch {
+                output_str.push_str(c);
+            }
+            self.read_char();
+        }
+        output_str
+    }
+
     // This reads the word given as parameter. If the next characters do not
     // correspond to the word given, this will PANIC
     fn read_word(&mut self, word: &str) -> String {
@@ -278,6 +410,13 @@ impl Lexer {
                         token_type: TokenType::Uuid,
                     }
                 }
+                _ if let Some(date) = self.match_date() => {
+                    trace!("Token '{}' is a Date", date);
+                    Token {
+                        literal: self.read_word(&date),
+                        token_type: TokenType::Date,
+                    }
+                }
                 _ if self.is_digit() => {
                     trace!("Token '{}' is a digit", ch);
                     Token {
@@ -352,6 +491,23 @@ impl Lexer {
                         token_type,
                     }
                 }
+                _ if self.match_keyword("not") => {
+                    let mut literal_value = self.read_word("not");
+
+                    let token_type = match &self.ch {
+                        Some(c) if !is_segment_character_str(c) => {
+                            literal_value += &self.read_next_word();
+                            TokenType::WordString
+                        }
+                        _ => TokenType::OperatorNot,
+                    };
+
+                    trace!("Token '{}' is a {}", literal_value, token_type);
+                    Token {
+                        literal: literal_value,
+                        token_type,
+                    }
+                }
                 _ if self.match_keyword("status:") => Token {
                     literal: self.read_word("status:"),
                     token_type: TokenType::FilterStatus,
@@ -372,6 +528,42 @@ impl Lexer {
                     literal: self.read_word("end.before:"),
                     token_type: TokenType::FilterTokDateEndBefore,
                 },
+                _ if self.match_keyword("project.is:") => Token {
+                    literal: self.read_word("project.is:"),
+                    token_type: TokenType::ProjectIsPrefix,
+                },
+                _ if self.match_keyword("summary:") => Token {
+                    literal: self.read_word("summary:"),
+                    token_type: TokenType::FilterSummary,
+                },
+                _ if self.match_keyword("summary~:") => Token {
+                    literal: self.read_word("summary~:"),
+                    token_type: TokenType::FilterSummaryRegex,
+                },
+                _ if self.match_keyword("annotation:") => Token {
+                    literal: self.read_word("annotation:"),
+                    token_type: TokenType::FilterAnnotation,
+                },
+                _ if self.match_keyword("annotation~:") => Token {
+                    literal: self.read_word("annotation~:"),
+                    token_type: TokenType::FilterAnnotationRegex,
+                },
+                _ if self.match_keyword("description:") => Token {
+                    literal: self.read_word("description:"),
+                    token_type: TokenType::FilterDescription,
+                },
+                _ if self.match_keyword("description~:") => Token {
+                    literal: self.read_word("description~:"),
+                    token_type: TokenType::FilterDescriptionRegex,
+                },
+                _ if ch == "/" && self.find_regex_end().is_some() => {
+                    let literal = self.read_regex();
+                    trace!("Token '{}' is a Regex", literal);
+                    Token {
+                        literal,
+                        token_type: TokenType::Regex,
+                    }
+                }
                 _ if self.match_keyword("project:") => Token {
                     literal: self.read_word("project:"),
                     token_type: TokenType::ProjectPrefix,
@@ -388,6 +580,30 @@ impl Lexer {
                     literal: self.read_word("due.after:"),
                     token_type: TokenType::FilterTokDateDueAfter,
                 },
+                _ if self.match_keyword("wait:") => Token {
+                    literal: self.read_word("wait:"),
+                    token_type: TokenType::FilterTokDateWait,
+                },
+                _ if self.match_keyword("wait.before:") => Token {
+                    literal: self.read_word("wait.before:"),
+                    token_type: TokenType::FilterTokDateWaitBefore,
+                },
+                _ if self.match_keyword("wait.after:") => Token {
+                    literal: self.read_word("wait.after:"),
+                    token_type: TokenType::FilterTokDateWaitAfter,
+                },
+                _ if self.match_keyword("scheduled:") => Token {
+                    literal: self.read_word("scheduled:"),
+                    token_type: TokenType::FilterTokDateScheduled,
+                },
+                _ if self.match_keyword("scheduled.before:") => Token {
+                    literal: self.read_word("scheduled.before:"),
+                    token_type: TokenType::FilterTokDateScheduledBefore,
+                },
+                _ if self.match_keyword("scheduled.after:") => Token {
+                    literal: self.read_word("scheduled.after:"),
+                    token_type: TokenType::FilterTokDateScheduledAfter,
+                },
                 _ if self.match_keyword("proj:") => Token {
                     literal: self.read_word("proj:"),
                     token_type: TokenType::ProjectPrefix,
@@ -396,6 +612,47 @@ impl Lexer {
                     literal: self.read_word("depends:"),
                     token_type: TokenType::DependsOn,
                 },
+                _ if self.match_keyword("parent:") => Token {
+                    literal: self.read_word("parent:"),
+                    token_type: TokenType::Parent,
+                },
+                _ if self.match_keyword("has:") => Token {
+                    literal: self.read_word("has:"),
+                    token_type: TokenType::FilterTokHas,
+                },
+                _ if self.match_link_keyword("link:").is_some() => {
+                    let keyword = self.match_link_keyword("link:").unwrap();
+                    Token {
+                        literal: self.read_word(&keyword),
+                        token_type: TokenType::Link,
+                    }
+                }
+                _ if self.match_link_keyword("unlink:").is_some() => {
+                    let keyword = self.match_link_keyword("unlink:").unwrap();
+                    Token {
+                        literal: self.read_word(&keyword),
+                        token_type: TokenType::Unlink,
+                    }
+                }
+                _ if self.match_link_keyword("linked.").is_some() => {
+                    let keyword = self.match_link_keyword("linked.").unwrap();
+                    Token {
+                        literal: self.read_word(&keyword),
+                        token_type: TokenType::FilterTokLinked,
+                    }
+                }
+                _ if self.match_keyword("recur:") => Token {
+                    literal: self.read_word("recur:"),
+                    token_type: TokenType::Recur,
+                },
+                _ if self.match_keyword("priority:") => Token {
+                    literal: self.read_word("priority:"),
+                    token_type: TokenType::Priority,
+                },
+                _ if self.match_keyword("priority.above:") => Token {
+                    literal: self.read_word("priority.above:"),
+                    token_type: TokenType::PriorityAbove,
+                },
                 _ if ch == ")" => {
                     self.read_char();
                     Token {
@@ -137,6 +137,37 @@ fn test_lexer() {
     let tok = lexer.next_token().unwrap();
     assert_eq!(tok.literal, "depends:");
     assert_eq!(tok.token_type, TokenType::DependsOn);
+
+    let mut lexer = Lexer::new("parent:".to_string());
+    let tok = lexer.next_token().unwrap();
+    assert_eq!(tok.literal, "parent:");
+    assert_eq!(tok.token_type, TokenType::Parent);
+
+    let mut lexer = Lexer::new("has:".to_string());
+    let tok = lexer.next_token().unwrap();
+    assert_eq!(tok.literal, "has:");
+    assert_eq!(tok.token_type, TokenType::FilterTokHas);
+
+    let mut lexer =
+        Lexer::new("link:relates-to:4 unlink:follows-up: linked.duplicates:".to_string());
+    let tok = lexer.next_token().unwrap();
+    assert_eq!(tok.literal, "link:relates-to:");
+    assert_eq!(tok.token_type, TokenType::Link);
+    let tok = lexer.next_token().unwrap();
+    assert_eq!(tok.token_type, TokenType::Int);
+    lexer.next_token().unwrap();
+    let tok = lexer.next_token().unwrap();
+    assert_eq!(tok.literal, "unlink:follows-up:");
+    assert_eq!(tok.token_type, TokenType::Unlink);
+    lexer.next_token().unwrap();
+    let tok = lexer.next_token().unwrap();
+    assert_eq!(tok.literal, "linked.duplicates:");
+    assert_eq!(tok.token_type, TokenType::FilterTokLinked);
+
+    // Only the known link types make a keyword
+    let mut lexer = Lexer::new("link:https:".to_string());
+    let tok = lexer.next_token().unwrap();
+    assert_eq!(tok.token_type, TokenType::WordString);
 }
 
 #[test]
@@ -156,6 +187,16 @@ fn test_lexer_operators() {
     assert_eq!(tok.literal, "xor");
     assert_eq!(tok.token_type, TokenType::OperatorXor);
 
+    let mut lexer = Lexer::new("not".to_string());
+    let tok = lexer.next_token().unwrap();
+    assert_eq!(tok.literal, "not");
+    assert_eq!(tok.token_type, TokenType::OperatorNot);
+
+    let mut lexer = Lexer::new("nothing".to_string());
+    let tok = lexer.next_token().unwrap();
+    assert_eq!(tok.literal, "nothing");
+    assert_eq!(tok.token_type, TokenType::WordString);
+
     let mut lexer = Lexer::new("ands".to_string());
     let tok = lexer.next_token().unwrap();
     assert_eq!(tok.literal, "ands");
@@ -225,3 +266,79 @@ fn test_lexer_with_spaces() {
     assert_eq!(tok.literal, "\n");
     assert_eq!(tok.token_type, TokenType::Blank);
 }
+
+#[test]
+fn test_lexer_text_filters() {
+    let mut lexer = Lexer::new(
+        "summary:foo annotation~:ba(r) project.is:work description:x description~:y".to_string(),
+    );
+    let expected = [
+        ("summary:", TokenType::FilterSummary),
+        ("foo", TokenType::WordString),
+        (" ", TokenType::Blank),
+        ("annotation~:", TokenType::FilterAnnotationRegex),
+        ("ba", TokenType::WordString),
+        ("(", TokenType::LeftParenthesis),
+        ("r", TokenType::WordString),
+        (")", TokenType::RightParenthesis),
+        (" ", TokenType::Blank),
+        ("project.is:", TokenType::ProjectIsPrefix),
+        ("work", TokenType::WordString),
+        (" ", TokenType::Blank),
+        ("description:", TokenType::FilterDescription),
+        ("x", TokenType::WordString),
+        (" ", TokenType::Blank),
+        ("description~:", TokenType::FilterDescriptionRegex),
+        ("y", TokenType::WordString),
+    ];
+    for (literal, token_type) in expected {
+        let tok = lexer.next_token().unwrap();
+        assert_eq!(tok.literal, literal);
+        assert_eq!(tok.token_type, token_type);
+    }
+
+    let mut lexer = Lexer::new(r"/fo\/o (bar)/ /tmp".to_string());
+    let tok = lexer.next_token().unwrap();
+    assert_eq!(tok.literal, r"/fo\/o (bar)/");
+    assert_eq!(tok.token_type, TokenType::Regex);
+    lexer.next_token().unwrap();
+    // Without a closing '/', this is not a regex
+    let tok = lexer.next_token().unwrap();
+    assert_eq!(tok.literal, "/tmp");
+    assert_eq!(tok.token_type, TokenType::String);
+}
+
+#[test]
+fn test_lexer_dates() {
+    let mut lexer = Lexer::new(
+        "due:2026-11-03T14:00 2026-11-03+1d (2026-11-03T14:00:30) 2026-13-01 2026-11-03x"
+            .to_string(),
+    );
+    let expected = [
+        ("due:", TokenType::FilterTokDateDue),
+        ("2026-11-03T14:00", TokenType::Date),
+        (" ", TokenType::Blank),
+        ("2026-11-03", TokenType::Date),
+        ("+", TokenType::TagPlusPrefix),
+        ("1", TokenType::Int),
+        ("d", TokenType::WordString),
+        (" ", TokenType::Blank),
+        ("(", TokenType::LeftParenthesis),
+        ("2026-11-03T14:00:30", TokenType::Date),
+        (")", TokenType::RightParenthesis),
+        (" ", TokenType::Blank),
+        // Not valid dates
+        ("2026", TokenType::Int),
+        ("-", TokenType::TagMinusPrefix),
+        ("13", TokenType::Int),
+        ("-", TokenType::TagMinusPrefix),
+        ("01", TokenType::Int),
+        (" ", TokenType::Blank),
+        ("2026", TokenType::Int),
+    ];
+    for (literal, token_type) in expected {
+        let tok = lexer.next_token().unwrap();
+        assert_eq!(tok.literal, literal);
+        assert_eq!(tok.token_type, token_type);
+    }
+}
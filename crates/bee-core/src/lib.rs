@@ -1,5 +1,7 @@
+pub mod clock;
 pub mod config;
 pub mod filters;
+pub mod sort;
 pub mod task;
 
 mod lexer;
@@ -10,13 +12,30 @@ use std::collections::HashMap;
 use config::ReportConfig;
 use task::Task;
 
+/// A task along with the tasks shown under it in a tree of tasks
+#[derive(Debug, PartialEq)]
+pub struct TaskTreeNode<'a> {
+    pub task: &'a Task,
+    pub children: Vec<TaskTreeNode<'a>>,
+}
+
 pub trait Printer {
     fn print_list_of_tasks(
         &self,
         tasks: Vec<&Task>,
         report_kind: &ReportConfig,
     ) -> Result<(), String>;
-    fn print_task_info(&self, task: &Task) -> Result<(), String>;
+
+    /// Print the tasks of the trees under @roots, with the columns of @report_kind
+    fn print_tree_of_tasks(
+        &self,
+        _roots: Vec<TaskTreeNode>,
+        _report_kind: &ReportConfig,
+    ) -> Result<(), String> {
+        Err("Showing a tree of tasks is not supported".to_string())
+    }
+    /// Print the details of @task. @subtasks are its subtasks that could be found.
+    fn print_task_info(&self, task: &Task, subtasks: Vec<&Task>) -> Result<(), String>;
 
     /// Print the help for all the possible actions. This can also have a couple more named sections.
     ///
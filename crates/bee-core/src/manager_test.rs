@@ -1,6 +1,7 @@
 use all_asserts::assert_true;
 use chrono::{Duration, Local};
 
+use crate::clock::FixedClock;
 use crate::filters;
 
 use super::*;
@@ -89,68 +90,79 @@ fn test_task_data_deserialize() {
 }
 
 #[test]
-fn test_update_task_property_depends_on_none() {
+fn test_update_task_property_identifiers_none() {
     let data = TaskData::default();
     let props = TaskProperties::default();
 
-    let result = data.update_task_property_depends_on(&props).unwrap();
+    let result = data.update_task_property_identifiers(&props).unwrap();
 
-    assert_eq!(result.depends_on, None);
+    assert_true!(result.links.is_empty());
 }
 
 #[test]
-fn test_update_task_property_depends_on_uuid() {
+fn test_update_task_property_identifiers_uuid() {
     let mut data = TaskData::default();
     let uuid = Uuid::new_v4();
     data.insert_id_to_uuid(1, uuid);
 
     let props = TaskProperties {
-        depends_on: Some(vec![DependsOnIdentifier::Uuid(uuid)]),
+        links: vec![LinkChange::Add(
+            LinkType::DependsOn,
+            TaskIdentifier::Uuid(uuid),
+        )],
         ..TaskProperties::default()
     };
 
-    let result = data.update_task_property_depends_on(&props).unwrap();
+    let result = data.update_task_property_identifiers(&props).unwrap();
 
-    assert_eq!(
-        result.depends_on,
-        Some(vec![DependsOnIdentifier::Uuid(uuid)])
-    );
+    assert_eq!(result.links, props.links);
 }
 
 #[test]
-fn test_update_task_property_depends_on_usize() {
+fn test_update_task_property_identifiers_usize() {
     let mut data = TaskData::default();
     let uuid = Uuid::new_v4();
     data.insert_id_to_uuid(1, uuid);
 
     let props = TaskProperties {
-        depends_on: Some(vec![DependsOnIdentifier::Usize(1)]),
+        links: vec![
+            LinkChange::Add(LinkType::DependsOn, TaskIdentifier::Usize(1)),
+            LinkChange::Remove(LinkType::RelatesTo, TaskIdentifier::Usize(1)),
+            LinkChange::Clear(LinkType::Duplicates),
+        ],
         ..TaskProperties::default()
     };
 
-    let result = data.update_task_property_depends_on(&props).unwrap();
+    let result = data.update_task_property_identifiers(&props).unwrap();
 
     assert_eq!(
-        result.depends_on,
-        Some(vec![DependsOnIdentifier::Uuid(uuid)])
+        result.links,
+        vec![
+            LinkChange::Add(LinkType::DependsOn, TaskIdentifier::Uuid(uuid)),
+            LinkChange::Remove(LinkType::RelatesTo, TaskIdentifier::Uuid(uuid)),
+            LinkChange::Clear(LinkType::Duplicates),
+        ]
     );
 }
 
 #[test]
-fn test_update_task_property_depends_on_usize_not_found() {
+fn test_update_task_property_identifiers_usize_not_found() {
     let data = TaskData::default();
     let props = TaskProperties {
-        depends_on: Some(vec![DependsOnIdentifier::Usize(1)]),
+        links: vec![LinkChange::Add(
+            LinkType::DependsOn,
+            TaskIdentifier::Usize(1),
+        )],
         ..TaskProperties::default()
     };
 
-    let result = data.update_task_property_depends_on(&props);
+    let result = data.update_task_property_identifiers(&props);
 
     assert!(result.is_err());
     assert_true!(result.is_err());
 }
 
-// Make sure we load the extra tasks from the blocking and the depends_on fields
+// Make sure we load the extra tasks from the links and the backlinks
 #[test]
 fn test_filter_taskdata() {
     let mut data = TaskData::default();
@@ -159,13 +171,13 @@ fn test_filter_taskdata() {
     let task1 = Task {
         status: TaskStatus::Pending,
         uuid: task1_uuid,
-        depends_on: vec![task2_uuid],
+        links: BTreeMap::from([(LinkType::DependsOn, vec![task2_uuid])]),
         ..Task::default()
     };
     let task2 = Task {
         status: TaskStatus::Pending,
         uuid: task2_uuid,
-        blocking: vec![task1_uuid],
+        backlinks: BTreeMap::from([(LinkType::DependsOn, vec![task1_uuid])]),
         ..Task::default()
     };
     data.tasks.insert(task1.uuid.to_owned(), task1.clone());
@@ -186,6 +198,43 @@ fn test_filter_taskdata() {
     assert_eq!(new_data.get_extra_tasks().get(&task1_uuid).unwrap(), &task1);
 }
 
+// The tasks linked to the filtered ones through other tasks are loaded as well
+#[test]
+fn test_filter_taskdata_transitive() {
+    let mut data = TaskData::default();
+    let mut uuids = Vec::new();
+    for summary in ["first", "second", "third", "fourth", "unrelated"] {
+        let mut props = TaskProperties::from(&[summary.to_owned()]).unwrap();
+        // Every task but the unrelated one depends on the previous one
+        if let Some(previous) = uuids.last()
+            && summary != "unrelated"
+        {
+            props.add_depends_on(&TaskIdentifier::Uuid(*previous));
+        }
+        let uuid = *data
+            .add_task(&props, TaskStatus::Pending)
+            .unwrap()
+            .get_uuid();
+        uuids.push(uuid);
+    }
+    data.upkeep().unwrap();
+
+    // From the middle of the chain, both ends are reached
+    let filter = filters::from(&[uuids[2].to_string()]).unwrap();
+    let new_data = data.filter(&filter);
+    assert_eq!(new_data.get_task_map().len(), 1);
+    let mut extra_uuids: Vec<Uuid> = new_data.get_extra_tasks().keys().cloned().collect();
+    extra_uuids.sort_by_key(|uuid| data.tasks[uuid].date_created);
+    assert_eq!(extra_uuids, vec![uuids[0], uuids[1], uuids[3]]);
+
+    // The tasks referenced by the properties come with the tasks they are linked to
+    let filter = filters::from(&[uuids[4].to_string()]).unwrap();
+    let mut new_data = data.filter(&filter);
+    assert_true!(new_data.get_extra_tasks().is_empty());
+    new_data.insert_linked_extra_tasks(&data, &[uuids[0]]);
+    assert_eq!(new_data.get_extra_tasks().len(), 4);
+}
+
 #[test]
 fn test_upkeep() {
     let mut data = TaskData::default();
@@ -197,7 +246,7 @@ fn test_upkeep() {
         status: TaskStatus::Pending,
         uuid: task1_uuid,
         date_created: now,
-        depends_on: vec![task2_uuid],
+        links: BTreeMap::from([(LinkType::DependsOn, vec![task2_uuid])]),
         ..Task::default()
     };
     let task2 = Task {
@@ -214,28 +263,429 @@ fn test_upkeep() {
     assert_eq!(data.tasks.get(&task2_uuid).unwrap().id, None);
 
     // Check that we are updating the blocking status
-    assert_true!(data.tasks.get(&task2_uuid).unwrap().blocking.is_empty());
+    assert_true!(
+        data.tasks
+            .get(&task2_uuid)
+            .unwrap()
+            .get_blocking()
+            .is_empty()
+    );
 
     let _ = data.upkeep();
 
     assert_eq!(data.tasks.get(&task1_uuid).unwrap().id, Some(1));
     assert_eq!(data.tasks.get(&task2_uuid).unwrap().id, Some(2));
 
-    assert_eq!(data.tasks.get(&task2_uuid).unwrap().blocking.len(), 1);
-    assert_eq!(data.tasks.get(&task2_uuid).unwrap().blocking[0], task1_uuid);
+    assert_eq!(data.tasks.get(&task2_uuid).unwrap().get_blocking().len(), 1);
+    assert_eq!(
+        data.tasks.get(&task2_uuid).unwrap().get_blocking()[0],
+        task1_uuid
+    );
 
     // Make sure that the delete and done functions correctly erase the blocking / done status
     // and that upkeep also checks that we need to update when task is complete
     let tmp_task2 = data.tasks.get_mut(&task2_uuid).unwrap().clone();
-    data.tasks.get_mut(&task2_uuid).unwrap().done();
+    data.tasks.get_mut(&task2_uuid).unwrap().done(Local::now());
 
     let _ = data.upkeep();
-    assert_true!(data.tasks.get(&task1_uuid).unwrap().depends_on.is_empty());
+    assert_true!(
+        data.tasks
+            .get(&task1_uuid)
+            .unwrap()
+            .get_depends()
+            .is_empty()
+    );
 
     data.tasks.insert(tmp_task2.uuid, tmp_task2);
     let _ = data.upkeep();
-    data.tasks.get_mut(&task1_uuid).unwrap().depends_on = vec![task2_uuid];
-    data.tasks.get_mut(&task2_uuid).unwrap().delete();
+    data.tasks
+        .get_mut(&task1_uuid)
+        .unwrap()
+        .insert_link(LinkType::DependsOn, task2_uuid);
+    data.tasks
+        .get_mut(&task2_uuid)
+        .unwrap()
+        .delete(Local::now());
     let _ = data.upkeep();
-    assert_true!(data.tasks.get(&task1_uuid).unwrap().depends_on.is_empty());
+    assert_true!(
+        data.tasks
+            .get(&task1_uuid)
+            .unwrap()
+            .get_depends()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_task_done_recurring() {
+    let mut data = TaskData::default();
+    let mut props =
+        TaskProperties::from(&["pay rent recur:monthly due:today priority:H".to_owned()]).unwrap();
+    props.set_description("Transfer to the account of the agency");
+    props.set_uda("estimate", Some("7200".to_owned()));
+    props.set_uda("customer", Some("landlord".to_owned()));
+    let task = data.add_task(&props, TaskStatus::Pending).unwrap().clone();
+    let task_due = task.get_date_due().unwrap();
+
+    let next_instance = data.task_done(task.get_uuid()).unwrap().clone();
+    assert_eq!(
+        data.tasks.get(task.get_uuid()).unwrap().status,
+        TaskStatus::Completed
+    );
+    assert_eq!(next_instance.status, TaskStatus::Pending);
+    assert_true!(next_instance.id.is_some());
+    assert_eq!(next_instance.summary, "pay rent");
+    assert_eq!(next_instance.recurrence, task.recurrence);
+    assert_eq!(next_instance.recurrence_template, Some(task.uuid));
+    assert_eq!(
+        next_instance.date_due,
+        task_due.checked_add_months(chrono::Months::new(1))
+    );
+
+    assert_eq!(next_instance.priority, Some(TaskPriority::High));
+    assert_eq!(next_instance.get_udas(), task.get_udas());
+    assert_eq!(next_instance.get_udas()["customer"], "landlord");
+    assert_eq!(
+        next_instance.description.as_deref(),
+        Some("Transfer to the account of the agency")
+    );
+    assert_eq!(next_instance.date_wait, None);
+
+    // The following instances are all linked to the first task of the series
+    let third_instance = data.task_done(&next_instance.uuid).unwrap().clone();
+    assert_eq!(third_instance.recurrence_template, Some(task.uuid));
+
+    // The wait and scheduled dates move along with the due date
+    let third_due = third_instance.date_due.unwrap();
+    let props = TaskProperties {
+        date_wait: Some(Some(third_due - Duration::days(3))),
+        date_scheduled: Some(Some(third_due - Duration::days(1))),
+        ..TaskProperties::default()
+    };
+    data.apply(&third_instance.uuid, &props).unwrap();
+    let fourth_instance = data.task_done(&third_instance.uuid).unwrap().clone();
+    let fourth_due = fourth_instance.date_due.unwrap();
+    assert_eq!(
+        fourth_instance.date_wait,
+        Some(fourth_due - Duration::days(3))
+    );
+    assert_eq!(
+        fourth_instance.date_scheduled,
+        Some(fourth_due - Duration::days(1))
+    );
+
+    // Completing a task that is already completed doesn't create a new instance
+    assert_true!(data.task_done(&next_instance.uuid).is_none());
+
+    // Non recurring tasks don't create anything
+    let other = data
+        .add_task(
+            &TaskProperties::from(&["one off task".to_owned()]).unwrap(),
+            TaskStatus::Pending,
+        )
+        .unwrap()
+        .clone();
+    assert_true!(data.task_done(other.get_uuid()).is_none());
+    assert_eq!(data.tasks.len(), 5);
+}
+
+#[test]
+fn test_apply_rejects_dependency_cycle() {
+    let mut data = TaskData::default();
+    let first = *data
+        .add_task(
+            &TaskProperties::from(&["first".to_owned()]).unwrap(),
+            TaskStatus::Pending,
+        )
+        .unwrap()
+        .get_uuid();
+    let second = *data
+        .add_task(
+            &TaskProperties::from(&[format!("second depends:{}", first)]).unwrap(),
+            TaskStatus::Pending,
+        )
+        .unwrap()
+        .get_uuid();
+    let third = *data
+        .add_task(
+            &TaskProperties::from(&[format!("third depends:{}", second)]).unwrap(),
+            TaskStatus::Pending,
+        )
+        .unwrap()
+        .get_uuid();
+
+    let result = data.apply(
+        &first,
+        &TaskProperties::from(&[format!("depends:{}", third)]).unwrap(),
+    );
+    assert_eq!(
+        result,
+        Err(
+            "Circular dependency: 1 'first' -> 3 'third' -> 2 'second' -> 1 'first'. \
+             Tasks cannot depend on each other."
+                .to_owned()
+        )
+    );
+    assert_true!(data.tasks.get(&first).unwrap().get_depends().is_empty());
+
+    // A task cannot depend on itself
+    assert_true!(
+        data.apply(
+            &second,
+            &TaskProperties::from(&[format!("depends:{}", second)]).unwrap()
+        )
+        .is_err()
+    );
+    assert_eq!(data.tasks.get(&second).unwrap().get_depends(), &[first]);
+
+    // Dependencies that don't form a cycle are still accepted
+    data.apply(
+        &third,
+        &TaskProperties::from(&[format!("depends:{}", first)]).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(data.tasks.get(&third).unwrap().get_depends().len(), 2);
+}
+
+#[test]
+fn test_repair_dangling_links() {
+    let missing = Uuid::new_v4();
+    let existing = Task {
+        uuid: Uuid::new_v4(),
+        status: TaskStatus::Pending,
+        date_created: Local::now() - Duration::try_seconds(2).unwrap(),
+        ..Task::default()
+    };
+    let broken = Task {
+        uuid: Uuid::new_v4(),
+        status: TaskStatus::Pending,
+        links: BTreeMap::from([(LinkType::DependsOn, vec![missing, existing.uuid])]),
+        backlinks: BTreeMap::from([(LinkType::DependsOn, vec![missing])]),
+        parent: Some(missing),
+        ..Task::default()
+    };
+    let mut data: TaskData = vec![existing.clone(), broken.clone()].into_iter().collect();
+
+    // The missing dependency is reported instead of panicking
+    assert_true!(data.clone().upkeep().is_err());
+
+    let repairs = data.repair_dangling_links();
+    assert_eq!(repairs.len(), 3);
+    let repaired = &data.get_task_map()[&broken.uuid];
+    assert_eq!(repaired.get_depends(), &[existing.uuid]);
+    assert_true!(repaired.get_blocking().is_empty());
+    assert_eq!(repaired.parent, None);
+    assert_eq!(data.get_task_map()[&existing.uuid], existing);
+
+    assert_true!(data.upkeep().is_ok());
+    assert_true!(data.repair_dangling_links().is_empty());
+}
+
+// A task blocking several tasks knows about all of them at once
+#[test]
+fn test_upkeep_blocking_several_tasks() {
+    let mut data = TaskData::default();
+    let blocker = *data
+        .add_task(
+            &TaskProperties::from(&["blocker".to_owned()]).unwrap(),
+            TaskStatus::Pending,
+        )
+        .unwrap()
+        .get_uuid();
+    for summary in ["first", "second", "third"] {
+        data.add_task(
+            &TaskProperties::from(&[format!("{} depends:{}", summary, blocker)]).unwrap(),
+            TaskStatus::Pending,
+        )
+        .unwrap();
+    }
+    data.upkeep().unwrap();
+    assert_eq!(data.tasks[&blocker].get_blocking().len(), 3);
+}
+
+// Every type of link is seen from the task it goes to
+#[test]
+fn test_upkeep_backlinks() {
+    let mut data = TaskData::default();
+    let bug = *data
+        .add_task(
+            &TaskProperties::from(&["bug".to_owned()]).unwrap(),
+            TaskStatus::Pending,
+        )
+        .unwrap()
+        .get_uuid();
+    let report = *data
+        .add_task(
+            &TaskProperties::from(&[format!(
+                "report link:duplicates:{} link:follows-up:{}",
+                bug, bug
+            )])
+            .unwrap(),
+            TaskStatus::Pending,
+        )
+        .unwrap()
+        .get_uuid();
+    data.upkeep().unwrap();
+    assert_eq!(
+        data.tasks[&bug].get_backlinks(LinkType::Duplicates),
+        &[report]
+    );
+    assert_eq!(
+        data.tasks[&bug].get_backlinks(LinkType::FollowsUp),
+        &[report]
+    );
+    assert_true!(data.tasks[&bug].get_blocking().is_empty());
+
+    // Unlike dependencies, the other links are kept once the linked task is done
+    data.task_done(&bug);
+    data.upkeep().unwrap();
+    assert_eq!(data.tasks[&report].get_links(LinkType::Duplicates), &[bug]);
+
+    data.apply(
+        &report,
+        &TaskProperties::from(&[format!("unlink:duplicates:{}", bug)]).unwrap(),
+    )
+    .unwrap();
+    data.upkeep().unwrap();
+    assert_true!(
+        data.tasks[&bug]
+            .get_backlinks(LinkType::Duplicates)
+            .is_empty()
+    );
+    assert_eq!(
+        data.tasks[&bug].get_backlinks(LinkType::FollowsUp),
+        &[report]
+    );
+}
+
+fn add_subtask(data: &mut TaskData, summary: &str, parent: Option<Uuid>) -> Uuid {
+    let arguments = match parent {
+        Some(parent) => format!("{} parent:{}", summary, parent),
+        None => summary.to_owned(),
+    };
+    *data
+        .add_task(
+            &TaskProperties::from(&[arguments]).unwrap(),
+            TaskStatus::Pending,
+        )
+        .unwrap()
+        .get_uuid()
+}
+
+#[test]
+fn test_upkeep_subtasks() {
+    let mut data = TaskData::default();
+    let parent = add_subtask(&mut data, "release", None);
+    let first = add_subtask(&mut data, "first", Some(parent));
+    let second = add_subtask(&mut data, "second", Some(parent));
+    let third = add_subtask(&mut data, "third", Some(parent));
+    data.upkeep().unwrap();
+    assert_eq!(data.tasks[&parent].sub, vec![first, second, third]);
+    assert_eq!(data.tasks[&parent].progress, Some(0));
+    assert_eq!(data.tasks[&first].progress, None);
+
+    // The deleted subtasks don't count
+    data.task_done(&first);
+    data.task_delete(&third);
+    data.upkeep().unwrap();
+    assert_eq!(data.tasks[&parent].sub, vec![first, second]);
+    assert_eq!(data.tasks[&parent].progress, Some(50));
+
+    // Removing the parent of a task updates its former parent
+    data.apply(
+        &second,
+        &TaskProperties::from(&["parent:none".to_owned()]).unwrap(),
+    )
+    .unwrap();
+    data.upkeep().unwrap();
+    assert_eq!(data.tasks[&parent].sub, vec![first]);
+    assert_eq!(data.tasks[&parent].progress, Some(100));
+
+    // The parent must exist
+    data.tasks.get_mut(&second).unwrap().parent = Some(Uuid::new_v4());
+    assert_true!(data.upkeep().is_err());
+}
+
+#[test]
+fn test_apply_rejects_parent_cycle() {
+    let mut data = TaskData::default();
+    let parent = add_subtask(&mut data, "release", None);
+    let child = add_subtask(&mut data, "build", Some(parent));
+    let grandchild = add_subtask(&mut data, "compile", Some(child));
+
+    for subtask in [parent, grandchild] {
+        let result = data.apply(
+            &parent,
+            &TaskProperties::from(&[format!("parent:{}", subtask)]).unwrap(),
+        );
+        assert_eq!(
+            result,
+            Err(
+                "The task 'release' cannot be a subtask of itself or of one of its subtasks."
+                    .to_owned()
+            )
+        );
+        assert_eq!(data.tasks[&parent].parent, None);
+    }
+}
+
+#[test]
+fn test_get_parent_to_complete() {
+    let mut data = TaskData::default();
+    let parent = add_subtask(&mut data, "release", None);
+    let first = add_subtask(&mut data, "first", Some(parent));
+    let second = add_subtask(&mut data, "second", Some(parent));
+    data.upkeep().unwrap();
+
+    data.task_done(&first);
+    assert_eq!(data.get_parent_to_complete(&first), None);
+    data.task_done(&second);
+    assert_eq!(data.get_parent_to_complete(&second), Some(parent));
+    assert_eq!(data.get_parent_to_complete(&parent), None);
+
+    // A parent that is already completed is left as it is
+    data.task_done(&parent);
+    assert_eq!(data.get_parent_to_complete(&second), None);
+}
+
+#[test]
+fn test_upkeep_ids_with_fixed_clock() {
+    // Every task is created at the same time
+    let mut data = TaskData::default();
+    data.set_clock(Arc::new(FixedClock::new(Local::now())));
+    let uuids: Vec<Uuid> = ["first", "second", "third", "fourth", "fifth"]
+        .iter()
+        .map(|summary| {
+            let task = data
+                .add_task(
+                    &TaskProperties::from(&[summary.to_string()]).unwrap(),
+                    TaskStatus::Pending,
+                )
+                .unwrap();
+            *task.get_uuid()
+        })
+        .collect();
+    let ids = |data: &TaskData| -> Vec<Option<usize>> {
+        uuids
+            .iter()
+            .map(|uuid| data.get_task_map()[uuid].get_id())
+            .collect()
+    };
+    let expected: Vec<Option<usize>> = (1..=5).map(Some).collect();
+    assert_eq!(ids(&data), expected);
+
+    for _ in 0..10 {
+        data.upkeep().unwrap();
+        assert_eq!(ids(&data), expected);
+    }
+
+    // The IDs are kept once the tasks are written and read again
+    let mut data: TaskData = serde_json::from_str(&serde_json::to_string(&data).unwrap()).unwrap();
+    data.upkeep().unwrap();
+    assert_eq!(ids(&data), expected);
+
+    // The tasks after a completed one move up, in the same order
+    data.task_done(&uuids[1]);
+    data.upkeep().unwrap();
+    assert_eq!(ids(&data), vec![Some(1), None, Some(2), Some(3), Some(4)]);
 }
@@ -1,40 +1,153 @@
 use log::debug;
 use std::fmt::Debug;
 
-use chrono::{DateTime, Duration, Local, NaiveTime, TimeDelta, TimeZone};
+use chrono::{
+    DateTime, Datelike, Days, Duration, Local, Months, NaiveDate, NaiveDateTime, NaiveTime,
+    TimeDelta, TimeZone, Weekday,
+};
 
+use crate::clock::Clock;
 use crate::lexer::{Lexer, Token, TokenType};
+use crate::task::{UdaDefinition, UdaType};
 
-fn matches_year_string(input: &str) -> bool {
+pub(crate) fn matches_year_string(input: &str) -> bool {
     input == "y" || input == "year" || input == "years"
 }
 
-fn matches_month_string(input: &str) -> bool {
+pub(crate) fn matches_month_string(input: &str) -> bool {
     input == "mo" || input == "month" || input == "months"
 }
 
-fn matches_week_string(input: &str) -> bool {
+pub(crate) fn matches_week_string(input: &str) -> bool {
     input == "w" || input == "week" || input == "weeks"
 }
 
-fn matches_day_string(input: &str) -> bool {
+pub(crate) fn matches_day_string(input: &str) -> bool {
     input == "d" || input == "day" || input == "days"
 }
 
-fn matches_hour_string(input: &str) -> bool {
+pub(crate) fn matches_hour_string(input: &str) -> bool {
     input == "h" || input == "hour" || input == "hours"
 }
 
-fn matches_minute_string(input: &str) -> bool {
+pub(crate) fn matches_minute_string(input: &str) -> bool {
     input == "m" || input == "minute" || input == "minutes"
 }
 
-fn matches_second_string(input: &str) -> bool {
+pub(crate) fn matches_second_string(input: &str) -> bool {
     input == "s" || input == "second" || input == "seconds"
 }
 
-fn get_day_duration_from_string(number: i64, value: &str) -> TimeDelta {
-    Duration::try_days(number * value.parse::<i64>().unwrap().to_owned()).unwrap()
+pub(crate) fn weekday_from_string(input: &str) -> Result<Weekday, String> {
+    match input {
+        "mon" | "monday" => Ok(Weekday::Mon),
+        "tue" | "tuesday" => Ok(Weekday::Tue),
+        "wed" | "wednesday" => Ok(Weekday::Wed),
+        "thu" | "thursday" => Ok(Weekday::Thu),
+        "fri" | "friday" => Ok(Weekday::Fri),
+        "sat" | "saturday" => Ok(Weekday::Sat),
+        "sun" | "sunday" => Ok(Weekday::Sun),
+        _ => Err(format!("Invalid day of the week '{}'", input)),
+    }
+}
+
+/// Parse a date written as `2026-11-03`, `2026-11-03T14:00` or `2026-11-03T14:00:30`.
+/// A date without a time is at midnight.
+pub(crate) fn parse_iso_date(input: &str) -> Option<NaiveDateTime> {
+    if input.get(4..5) != Some("-") || input.get(7..8) != Some("-") {
+        return None;
+    }
+    match input.len() {
+        10 => NaiveDate::parse_from_str(input, "%Y-%m-%d")
+            .ok()
+            .map(|date| date.and_time(NaiveTime::MIN)),
+        16 => NaiveDateTime::parse_from_str(input, "%Y-%m-%dT%H:%M").ok(),
+        19 => NaiveDateTime::parse_from_str(input, "%Y-%m-%dT%H:%M:%S").ok(),
+        _ => None,
+    }
+}
+
+fn to_local_time(time: NaiveDateTime) -> Result<DateTime<Local>, String> {
+    Local.from_local_datetime(&time).earliest().ok_or(format!(
+        "The time '{}' does not exist in the local timezone",
+        time
+    ))
+}
+
+fn start_of_day(date: NaiveDate) -> Result<DateTime<Local>, String> {
+    to_local_time(date.and_time(NaiveTime::MIN))
+}
+
+fn end_of_day(date: NaiveDate) -> Result<DateTime<Local>, String> {
+    to_local_time(date.and_hms_opt(23, 59, 59).unwrap())
+}
+
+/// The first and last days of the week, month, quarter or year containing @date,
+/// the period being given as in the `sow`, `som`, `soq` and `soy` anchors. Weeks
+/// start on Monday.
+fn period_bounds(date: NaiveDate, period: &str) -> (NaiveDate, NaiveDate) {
+    let (first_day, months) = match period {
+        "w" => {
+            let first_day = date - Days::new(date.weekday().num_days_from_monday().into());
+            return (first_day, first_day + Days::new(6));
+        }
+        "m" => (date.with_day(1).unwrap(), 1),
+        "q" => (
+            NaiveDate::from_ymd_opt(date.year(), (date.month0() / 3) * 3 + 1, 1).unwrap(),
+            3,
+        ),
+        "y" => (NaiveDate::from_ymd_opt(date.year(), 1, 1).unwrap(), 12),
+        _ => unreachable!("Unknown period '{}'", period),
+    };
+    (first_day, first_day + Months::new(months) - Days::new(1))
+}
+
+/// The first @weekday on or after @today. With @next_week, this is the @weekday
+/// of the week following the one of @today instead.
+fn date_of_weekday(today: NaiveDate, weekday: Weekday, next_week: bool) -> NaiveDate {
+    let days_from_monday = today.weekday().num_days_from_monday();
+    let offset = if next_week {
+        7 - days_from_monday + weekday.num_days_from_monday()
+    } else {
+        (7 + weekday.num_days_from_monday() - days_from_monday) % 7
+    };
+    today + Days::new(offset.into())
+}
+
+/// An amount of time given in a date expression, such as `3 months`. Days, weeks,
+/// months and years follow the calendar, so one month after January 31st is the
+/// last day of February.
+enum DateOffset {
+    Days(u64),
+    Months(u32),
+    Exact(TimeDelta),
+}
+
+impl DateOffset {
+    fn from_count_and_unit(count: u32, unit: &str) -> Option<DateOffset> {
+        let offset = match unit {
+            _ if matches_year_string(unit) => DateOffset::Months(12 * count),
+            _ if matches_month_string(unit) => DateOffset::Months(count),
+            _ if matches_week_string(unit) => DateOffset::Days(7 * u64::from(count)),
+            _ if matches_day_string(unit) => DateOffset::Days(count.into()),
+            _ if matches_hour_string(unit) => DateOffset::Exact(Duration::hours(count.into())),
+            _ if matches_minute_string(unit) => DateOffset::Exact(Duration::minutes(count.into())),
+            _ if matches_second_string(unit) => DateOffset::Exact(Duration::seconds(count.into())),
+            _ => return None,
+        };
+        Some(offset)
+    }
+
+    fn apply(&self, time: DateTime<Local>, forward: bool) -> Option<DateTime<Local>> {
+        match (self, forward) {
+            (DateOffset::Days(days), true) => time.checked_add_days(Days::new(*days)),
+            (DateOffset::Days(days), false) => time.checked_sub_days(Days::new(*days)),
+            (DateOffset::Months(months), true) => time.checked_add_months(Months::new(*months)),
+            (DateOffset::Months(months), false) => time.checked_sub_months(Months::new(*months)),
+            (DateOffset::Exact(delta), true) => time.checked_add_signed(*delta),
+            (DateOffset::Exact(delta), false) => time.checked_sub_signed(*delta),
+        }
+    }
 }
 
 pub trait BaseParser: Debug {
@@ -52,6 +165,10 @@ pub trait BaseParser: Debug {
 
     fn get_mut_lexer(&mut self) -> &mut Lexer;
 
+    /// The clock giving the time relative dates such as `today` or `3 days ago`
+    /// are computed from
+    fn get_clock(&self) -> &dyn Clock;
+
     fn back_token(&mut self) {
         if self.get_buffer_index() == 0 {
             panic!("Error: Trying to call 'back_token' too many times!");
@@ -115,10 +232,88 @@ pub trait BaseParser: Debug {
         blank_count
     }
 
+    /// Read the current token and all the ones directly following it (i.e. not separated by
+    /// a blank or a parenthesis) as a single string.
+    ///
+    /// The current token is left on the last token that was read.
+    fn read_until_blank(&mut self) -> String {
+        let mut value = self.get_current_token().literal.to_owned();
+        while !matches!(
+            self.get_peek_token().token_type,
+            TokenType::Blank
+                | TokenType::Eof
+                | TokenType::LeftParenthesis
+                | TokenType::RightParenthesis
+        ) {
+            value.push_str(&self.get_peek_token().literal);
+            self.next_token();
+        }
+        value
+    }
+
+    /// Read the value of a UDA given as `<name>:<value>`. The current token is the
+    /// word containing the name of the UDA, and @value is the part of that word
+    /// after the colon. When it is empty, the value is read from the next tokens.
+    ///
+    /// Returns `None` if the value is `none`. The current token is left on the last
+    /// token of the value.
+    fn read_uda_value(
+        &mut self,
+        value: &str,
+        definition: &UdaDefinition,
+    ) -> Result<Option<String>, String> {
+        let value = if value.is_empty() {
+            self.next_token();
+            self.skip_whitespace();
+            if matches!(
+                self.get_current_token().token_type,
+                TokenType::Eof | TokenType::LeftParenthesis | TokenType::RightParenthesis
+            ) {
+                return Err(format!(
+                    "Expected a value for the attribute, found '{}'",
+                    self.get_current_token().literal
+                ));
+            }
+            if definition.uda_type == UdaType::Date && self.get_current_token().literal != "none" {
+                return Ok(Some(self.read_date_expr()?.to_rfc3339()));
+            }
+            self.read_until_blank()
+        } else {
+            let mut value = value.to_owned();
+            while !matches!(
+                self.get_peek_token().token_type,
+                TokenType::Blank
+                    | TokenType::Eof
+                    | TokenType::LeftParenthesis
+                    | TokenType::RightParenthesis
+            ) {
+                value.push_str(&self.get_peek_token().literal);
+                self.next_token();
+            }
+            value
+        };
+
+        if value == "none" {
+            return Ok(None);
+        }
+        definition.parse_value(&value).map(Some)
+    }
+
+    /// Read a date expression. It starts with one of:
+    /// - `now`, `today`, `tomorrow`, `yesterday` or `eod` (today at 18:00)
+    /// - an ISO date such as `2026-11-03` or `2026-11-03T14:00`
+    /// - a day of the week such as `friday`, the closest one from today (included),
+    ///   or `next friday`, the one of the next week
+    /// - the start or end of the current week, month, quarter or year: `sow`, `eow`,
+    ///   `som`, `eom`, `soq`, `eoq`, `soy` and `eoy`
+    /// - `in 3 days` or `3 days ago`
+    ///
+    /// It can then be followed by offsets such as `+ 1w - 2h`.
     fn read_date_expr(&mut self) -> Result<DateTime<Local>, String> {
         debug!("Reading date expression");
+        let now = self.get_clock().now();
         let mut time = None;
-        let mut try_time = Local::now();
+        let mut try_time = now;
         let mut first = true;
         let mut expect_duration = false;
         let mut in_keyword = false;
@@ -144,53 +339,20 @@ pub trait BaseParser: Debug {
                     backtrace_tokens += self.skip_whitespace();
                     let unit_token = self.get_current_token().to_owned();
                     // expect a duration here
-                    let duration = match unit_token.literal {
-                        _ if matches_year_string(unit_token.literal.as_str()) => {
-                            get_day_duration_from_string(365, number_token.literal.as_str())
-                        }
-                        _ if matches_month_string(unit_token.literal.as_str()) => {
-                            get_day_duration_from_string(30, number_token.literal.as_str())
-                        }
-                        _ if matches_week_string(unit_token.literal.as_str()) => {
-                            get_day_duration_from_string(7, number_token.literal.as_str())
-                        }
-                        _ if matches_day_string(unit_token.literal.as_str()) => {
-                            get_day_duration_from_string(1, number_token.literal.as_str())
-                        }
-                        _ if matches_hour_string(unit_token.literal.as_str()) => {
-                            Duration::try_hours(
-                                number_token.literal.parse::<i64>().unwrap().to_owned(),
-                            )
-                            .unwrap()
-                        }
-                        _ if matches_minute_string(unit_token.literal.as_str()) => {
-                            Duration::try_minutes(
-                                number_token.literal.parse::<i64>().unwrap().to_owned(),
-                            )
-                            .unwrap()
-                        }
-                        _ if matches_second_string(unit_token.literal.as_str()) => {
-                            Duration::try_seconds(
-                                number_token.literal.parse::<i64>().unwrap().to_owned(),
-                            )
-                            .unwrap()
-                        }
-                        _ => {
-                            break;
-                        }
+                    let count = number_token
+                        .literal
+                        .parse::<u32>()
+                        .map_err(|_| format!("invalid number '{}'", number_token.literal))?;
+                    let Some(offset) = DateOffset::from_count_and_unit(count, &unit_token.literal)
+                    else {
+                        break;
                     };
-                    if first {
-                        try_time = Local::now() - duration;
+                    try_time = if first {
+                        offset.apply(now, false)
                     } else {
-                        match cur_scope {
-                            Scope::Minus => {
-                                try_time -= duration;
-                            }
-                            Scope::Plus => {
-                                try_time += duration;
-                            }
-                        }
+                        offset.apply(try_time, cur_scope == Scope::Plus)
                     }
+                    .ok_or("the date expression is out of range".to_string())?;
 
                     expect_duration = false;
                     time = Some(try_time.to_owned());
@@ -235,6 +397,21 @@ pub trait BaseParser: Debug {
                     backtrace_tokens += 1;
                     self.next_token();
                 }
+                TokenType::Date => {
+                    if !first {
+                        break;
+                    }
+                    first = false;
+
+                    debug!("Read Date token '{}'", self.get_current_token().literal);
+                    try_time = to_local_time(
+                        parse_iso_date(&self.get_current_token().literal)
+                            .expect("The lexer only reads valid dates"),
+                    )?;
+                    time = Some(try_time.to_owned());
+                    backtrace_tokens = 0;
+                    self.next_token();
+                }
                 // This is a specific time
                 TokenType::WordString => {
                     if !first {
@@ -246,44 +423,55 @@ pub trait BaseParser: Debug {
                         "Read WordString token '{}'",
                         self.get_current_token().literal
                     );
-                    let now = Local::now();
-                    let today_start = Local
-                        .from_local_datetime(
-                            &now.date_naive()
-                                .and_time(NaiveTime::from_hms_opt(0, 0, 0).unwrap()),
-                        )
-                        .single()
-                        .unwrap();
+                    let today = now.date_naive();
                     match self.get_current_token().literal.as_str() {
                         "now" => {
                             try_time = now;
                         }
                         "today" => {
-                            try_time = today_start;
+                            try_time = start_of_day(today)?;
                         }
                         "tomorrow" => {
-                            try_time = today_start + Duration::try_days(1).unwrap();
+                            try_time = start_of_day(today + Days::new(1))?;
                         }
                         "yesterday" => {
-                            try_time = today_start - Duration::try_days(1).unwrap();
+                            try_time = start_of_day(today - Days::new(1))?;
                         }
                         "eod" => {
-                            try_time = today_start + Duration::try_hours(18).unwrap();
+                            try_time = to_local_time(today.and_hms_opt(18, 0, 0).unwrap())?;
+                        }
+                        anchor @ ("sow" | "som" | "soq" | "soy") => {
+                            try_time = start_of_day(period_bounds(today, &anchor[2..]).0)?;
+                        }
+                        anchor @ ("eow" | "eom" | "eoq" | "eoy") => {
+                            try_time = end_of_day(period_bounds(today, &anchor[2..]).1)?;
+                        }
+                        "next" => {
+                            self.next_token();
+                            self.skip_whitespace();
+                            let weekday = weekday_from_string(&self.get_current_token().literal)
+                                .map_err(|e| format!("'next' must be followed by a day: {}", e))?;
+                            try_time = start_of_day(date_of_weekday(today, weekday, true))?;
                         }
                         "in" => {
                             expect_duration = true;
+                            cur_scope = Scope::Plus;
                             self.next_token();
                             backtrace_tokens += 1 + self.skip_whitespace();
                             in_keyword = true;
                             continue;
                         }
-                        // last week
-                        _ => {
-                            return Err(format!(
-                                "unexpected token '{}' found in invalid date expression",
-                                self.get_current_token().literal
-                            ));
-                        }
+                        literal => match weekday_from_string(literal) {
+                            Ok(weekday) => {
+                                try_time = start_of_day(date_of_weekday(today, weekday, false))?;
+                            }
+                            Err(_) => {
+                                return Err(format!(
+                                    "unexpected token '{}' found in invalid date expression",
+                                    literal
+                                ));
+                            }
+                        },
                     }
 
                     time = Some(try_time.to_owned());
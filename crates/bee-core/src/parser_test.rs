@@ -1,16 +1,20 @@
 use super::*;
+use crate::clock::{FixedClock, SystemClock};
+use all_asserts::assert_true;
+use std::sync::Arc;
 
 fn init() {
     let _ = env_logger::builder().is_test(true).try_init();
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct MockParser {
     lexer: Lexer,
     current_token: Token,
     peek_token: Token,
     buffer_tokens: Vec<Token>,
     buffer_index: usize,
+    clock: Arc<dyn Clock>,
 }
 
 impl MockParser {
@@ -21,12 +25,20 @@ impl MockParser {
             peek_token: Token::default(),
             buffer_tokens: Vec::default(),
             buffer_index: 0,
+            clock: Arc::new(SystemClock),
         };
         parser.next_token();
         parser.next_token();
         parser.buffer_index = 0;
         parser
     }
+
+    /// A parser for which now is always @now
+    pub fn with_now(input: &str, now: DateTime<Local>) -> MockParser {
+        let mut parser = MockParser::new(Lexer::new(input.to_string()));
+        parser.clock = Arc::new(FixedClock::new(now));
+        parser
+    }
 }
 
 impl BaseParser for MockParser {
@@ -61,184 +73,197 @@ impl BaseParser for MockParser {
     fn get_mut_lexer(&mut self) -> &mut Lexer {
         &mut self.lexer
     }
+
+    fn get_clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+}
+
+fn local(year: i32, month: u32, day: u32, hour: u32, min: u32, sec: u32) -> DateTime<Local> {
+    Local
+        .with_ymd_and_hms(year, month, day, hour, min, sec)
+        .unwrap()
+}
+
+/// Wednesday, January 28th 2026 at 14:30
+fn fixed_now() -> DateTime<Local> {
+    local(2026, 1, 28, 14, 30, 0)
+}
+
+fn read_date(input: &str) -> DateTime<Local> {
+    MockParser::with_now(input, fixed_now())
+        .read_date_expr()
+        .unwrap()
 }
 
 #[test]
 fn test_read_date_expr() {
     init();
-    let now = Local::now();
-    let today_start = Local
-        .from_local_datetime(
-            &now.date_naive()
-                .and_time(NaiveTime::from_hms_opt(0, 0, 0).unwrap()),
-        )
-        .single()
-        .unwrap();
-
-    let lexer = Lexer::new("today".to_string());
-    let mut p = MockParser::new(lexer);
-
+    let now = fixed_now();
+    let today_start = local(2026, 1, 28, 0, 0, 0);
+
+    assert_eq!(read_date("now"), now);
+    assert_eq!(read_date("today"), today_start);
+    assert_eq!(read_date("tomorrow"), local(2026, 1, 29, 0, 0, 0));
+    assert_eq!(read_date("yesterday"), local(2026, 1, 27, 0, 0, 0));
+    assert_eq!(read_date("eod"), local(2026, 1, 28, 18, 0, 0));
+
+    assert_eq!(read_date("today - 1h"), local(2026, 1, 27, 23, 0, 0));
+    assert_eq!(read_date("today - 1m"), local(2026, 1, 27, 23, 59, 0));
+    assert_eq!(read_date("today - 1s"), local(2026, 1, 27, 23, 59, 59));
+    assert_eq!(read_date("today-11d"), local(2026, 1, 17, 0, 0, 0));
+    assert_eq!(read_date("today - 1d"), local(2026, 1, 27, 0, 0, 0));
+    assert_eq!(read_date("today - 1d + 1d"), today_start);
+    assert_eq!(read_date("today - 2w"), local(2026, 1, 14, 0, 0, 0));
+    assert_eq!(read_date("today - 3 months"), local(2025, 10, 28, 0, 0, 0));
+    assert_eq!(read_date("today - 3 year"), local(2023, 1, 28, 0, 0, 0));
+    assert_eq!(read_date("now + 1mo + 2h"), local(2026, 2, 28, 16, 30, 0));
+
+    assert_eq!(read_date("in 3 days"), local(2026, 1, 31, 14, 30, 0));
+    assert_eq!(read_date("in 2 weeks"), local(2026, 2, 11, 14, 30, 0));
+    assert_eq!(read_date("2 months ago"), local(2025, 11, 28, 14, 30, 0));
+
+    // 'ago' does not apply to 'in'
+    let mut p = MockParser::with_now("in 3 days ago", now);
     let res = p.read_date_expr().unwrap();
-    assert_eq!(res, today_start);
-
-    let lexer = Lexer::new("tomorrow".to_string());
-    let mut p = MockParser::new(lexer);
+    assert_eq!(res, local(2026, 1, 31, 14, 30, 0));
+    assert_eq!(p.current_token.token_type, TokenType::Blank);
+    p.next_token();
+    assert_eq!(p.current_token.token_type, TokenType::WordString);
+    assert_eq!(p.current_token.literal, "ago".to_owned());
 
+    // Ensure we stop after seeing 'ago'
+    let mut p = MockParser::with_now("3 years ago today", now);
     let res = p.read_date_expr().unwrap();
-    assert_eq!(
-        res.to_rfc2822(),
-        (today_start + Duration::try_days(1).unwrap()).to_rfc2822()
-    );
-
-    let lexer = Lexer::new("yesterday".to_string());
-    let mut p = MockParser::new(lexer);
+    assert_eq!(res, local(2023, 1, 28, 14, 30, 0));
+    p.skip_whitespace();
+    assert_eq!(p.current_token.token_type, TokenType::WordString);
+    assert_eq!(p.current_token.literal, "today".to_owned());
 
+    let mut p = MockParser::with_now("today -foo", now);
     let res = p.read_date_expr().unwrap();
-    assert_eq!(
-        res.to_rfc2822(),
-        (today_start - Duration::try_days(1).unwrap()).to_rfc2822()
-    );
-
-    let lexer = Lexer::new("eod".to_string());
-    let mut p = MockParser::new(lexer);
+    assert_eq!(res, today_start);
+    assert_eq!(p.current_token.token_type, TokenType::Blank);
+    p.next_token();
+    assert_eq!(p.current_token.token_type, TokenType::TagMinusPrefix);
+    assert_eq!(p.peek_token.token_type, TokenType::WordString);
+    assert_eq!(p.peek_token.literal, "foo".to_owned());
+}
 
-    let res = p.read_date_expr().unwrap();
+#[test]
+fn test_read_date_expr_calendar() {
+    init();
+    // Months and years keep the day of the month when it exists
     assert_eq!(
-        res.to_rfc2822(),
-        (today_start + Duration::try_hours(18).unwrap()).to_rfc2822()
+        read_date("2026-01-31 + 1 month"),
+        local(2026, 2, 28, 0, 0, 0)
     );
-
-    let lexer = Lexer::new("now".to_string());
-    let mut p = MockParser::new(lexer);
-
-    let res = p.read_date_expr().unwrap();
-    // This format doesn't print smaller units than seconds
-    assert_eq!(res.to_rfc2822(), now.to_rfc2822());
-
-    let lexer = Lexer::new("today - 1h".to_string());
-    let mut p = MockParser::new(lexer);
-
-    let res = p.read_date_expr().unwrap();
-    // This format doesn't print smaller units than seconds
     assert_eq!(
-        res.to_rfc2822(),
-        (today_start - Duration::try_hours(1).unwrap()).to_rfc2822()
+        read_date("2024-01-31 + 1 month"),
+        local(2024, 2, 29, 0, 0, 0)
     );
-
-    let lexer = Lexer::new("today - 1m".to_string());
-    let mut p = MockParser::new(lexer);
-
-    let res = p.read_date_expr().unwrap();
-    // This format doesn't print smaller units than seconds
     assert_eq!(
-        res.to_rfc2822(),
-        (today_start - Duration::try_minutes(1).unwrap()).to_rfc2822()
+        read_date("2026-03-31 - 1 month"),
+        local(2026, 2, 28, 0, 0, 0)
     );
-
-    let lexer = Lexer::new("today - 1s".to_string());
-    let mut p = MockParser::new(lexer);
-
-    let res = p.read_date_expr().unwrap();
-    // This format doesn't print smaller units than seconds
+    assert_eq!(read_date("2024-02-29 + 1y"), local(2025, 2, 28, 0, 0, 0));
     assert_eq!(
-        res.to_rfc2822(),
-        (today_start - Duration::try_seconds(1).unwrap()).to_rfc2822()
+        read_date("2026-05-31 + 1 month"),
+        local(2026, 6, 30, 0, 0, 0)
     );
-
-    let lexer = Lexer::new("today-11d".to_string());
-    let mut p = MockParser::new(lexer);
-
-    let res = p.read_date_expr().unwrap();
-    // This format doesn't print smaller units than seconds
-    assert_eq!(
-        res.to_rfc2822(),
-        (today_start - Duration::try_days(11).unwrap()).to_rfc2822()
-    );
-
-    let lexer = Lexer::new("today - 1d".to_string());
-    let mut p = MockParser::new(lexer);
-
-    let res = p.read_date_expr().unwrap();
-    // This format doesn't print smaller units than seconds
-    assert_eq!(
-        res.to_rfc2822(),
-        (today_start - Duration::try_days(1).unwrap()).to_rfc2822()
-    );
-
-    let lexer = Lexer::new("today - 1d + 1d".to_string());
-    let mut p = MockParser::new(lexer);
-
-    let res = p.read_date_expr().unwrap();
-    // This format doesn't print smaller units than seconds
-    assert_eq!(res.to_rfc2822(), today_start.to_rfc2822());
-
-    let lexer = Lexer::new("today - 2w".to_string());
-    let mut p = MockParser::new(lexer);
-
-    let res = p.read_date_expr().unwrap();
-    // This format doesn't print smaller units than seconds
     assert_eq!(
-        res.to_rfc2822(),
-        (today_start - Duration::try_days(14).unwrap()).to_rfc2822()
+        read_date("2026-12-15 + 2 months"),
+        local(2027, 2, 15, 0, 0, 0)
     );
+    assert_eq!(read_date("2026-01-31 + 4w"), local(2026, 2, 28, 0, 0, 0));
+}
 
-    let lexer = Lexer::new("today - 3 months".to_string());
-    let mut p = MockParser::new(lexer);
-
-    let res = p.read_date_expr().unwrap();
-    // This format doesn't print smaller units than seconds
+#[test]
+fn test_read_date_expr_iso() {
+    init();
+    assert_eq!(read_date("2026-11-03"), local(2026, 11, 3, 0, 0, 0));
+    assert_eq!(read_date("2026-11-03T14:00"), local(2026, 11, 3, 14, 0, 0));
     assert_eq!(
-        res.to_rfc2822(),
-        (today_start - Duration::try_days(90).unwrap()).to_rfc2822()
+        read_date("2026-11-03T14:00:30"),
+        local(2026, 11, 3, 14, 0, 30)
     );
+    assert_eq!(read_date("2026-11-03 + 2d"), local(2026, 11, 5, 0, 0, 0));
+    assert_eq!(read_date("2026-11-03+1h"), local(2026, 11, 3, 1, 0, 0));
 
-    let lexer = Lexer::new("in 3 days ago".to_string());
-    let mut p = MockParser::new(lexer);
+    // Not a valid date, so this is not a date token
+    let mut p = MockParser::with_now("2026-02-30", fixed_now());
+    assert_eq!(p.current_token.token_type, TokenType::Int);
+    assert_true!(p.read_date_expr().is_err());
 
-    let res = p.read_date_expr().unwrap();
-    // This format doesn't print smaller units than seconds
-    assert_eq!(
-        res.to_rfc2822(),
-        (now - Duration::try_days(3).unwrap()).to_rfc2822()
-    );
-    assert_eq!(p.current_token.token_type, TokenType::Blank);
-    p.next_token();
-    assert_eq!(p.current_token.token_type, TokenType::WordString);
-    assert_eq!(p.current_token.literal, "ago".to_owned());
-
-    let lexer = Lexer::new("today - 3 year".to_string());
-    let mut p = MockParser::new(lexer);
+    let mut p = MockParser::with_now("2026-11-03 foo", fixed_now());
+    assert_eq!(p.read_date_expr().unwrap(), local(2026, 11, 3, 0, 0, 0));
+    p.skip_whitespace();
+    assert_eq!(p.current_token.literal, "foo".to_owned());
+}
 
-    let res = p.read_date_expr().unwrap();
-    // This format doesn't print smaller units than seconds
-    assert_eq!(
-        res.to_rfc2822(),
-        (today_start - Duration::try_days(365 * 3).unwrap()).to_rfc2822()
+#[test]
+fn test_read_date_expr_weekdays() {
+    init();
+    // Today is a Wednesday
+    assert_eq!(read_date("wednesday"), local(2026, 1, 28, 0, 0, 0));
+    assert_eq!(read_date("thursday"), local(2026, 1, 29, 0, 0, 0));
+    assert_eq!(read_date("fri"), local(2026, 1, 30, 0, 0, 0));
+    assert_eq!(read_date("sunday"), local(2026, 2, 1, 0, 0, 0));
+    assert_eq!(read_date("monday"), local(2026, 2, 2, 0, 0, 0));
+    assert_eq!(read_date("tuesday"), local(2026, 2, 3, 0, 0, 0));
+
+    // The day of the next week
+    assert_eq!(read_date("next monday"), local(2026, 2, 2, 0, 0, 0));
+    assert_eq!(read_date("next wednesday"), local(2026, 2, 4, 0, 0, 0));
+    assert_eq!(read_date("next friday"), local(2026, 2, 6, 0, 0, 0));
+    assert_eq!(read_date("next sun"), local(2026, 2, 8, 0, 0, 0));
+    assert_eq!(read_date("next friday + 9h"), local(2026, 2, 6, 9, 0, 0));
+
+    let sunday = local(2026, 2, 1, 10, 0, 0);
+    let read_on_sunday = |input: &str| {
+        MockParser::with_now(input, sunday)
+            .read_date_expr()
+            .unwrap()
+    };
+    assert_eq!(read_on_sunday("sunday"), local(2026, 2, 1, 0, 0, 0));
+    assert_eq!(read_on_sunday("saturday"), local(2026, 2, 7, 0, 0, 0));
+    assert_eq!(read_on_sunday("next monday"), local(2026, 2, 2, 0, 0, 0));
+
+    assert_true!(
+        MockParser::with_now("next week", fixed_now())
+            .read_date_expr()
+            .is_err()
     );
-
-    // Ensure we stop after seeing 'ago'
-    let lexer = Lexer::new("3 years ago today".to_string());
-    let mut p = MockParser::new(lexer);
-
-    let res = p.read_date_expr().unwrap();
-    // This format doesn't print smaller units than seconds
-    assert_eq!(
-        res.to_rfc2822(),
-        (now - Duration::try_days(365 * 3).unwrap()).to_rfc2822()
+    assert_true!(
+        MockParser::with_now("someday", fixed_now())
+            .read_date_expr()
+            .is_err()
     );
-    p.skip_whitespace();
-    assert_eq!(p.current_token.token_type, TokenType::WordString);
-    assert_eq!(p.current_token.literal, "today".to_owned());
-
-    let lexer = Lexer::new("today -foo".to_string());
-    let mut p = MockParser::new(lexer);
+}
 
-    let res = p.read_date_expr().unwrap();
-    // This format doesn't print smaller units than seconds
-    assert_eq!(res.to_rfc2822(), today_start.to_rfc2822());
-    assert_eq!(p.current_token.token_type, TokenType::Blank);
-    p.next_token();
-    assert_eq!(p.current_token.token_type, TokenType::TagMinusPrefix);
-    assert_eq!(p.peek_token.token_type, TokenType::WordString);
-    assert_eq!(p.peek_token.literal, "foo".to_owned());
+#[test]
+fn test_read_date_expr_anchors() {
+    init();
+    assert_eq!(read_date("sow"), local(2026, 1, 26, 0, 0, 0));
+    assert_eq!(read_date("eow"), local(2026, 2, 1, 23, 59, 59));
+    assert_eq!(read_date("som"), local(2026, 1, 1, 0, 0, 0));
+    assert_eq!(read_date("eom"), local(2026, 1, 31, 23, 59, 59));
+    assert_eq!(read_date("soq"), local(2026, 1, 1, 0, 0, 0));
+    assert_eq!(read_date("eoq"), local(2026, 3, 31, 23, 59, 59));
+    assert_eq!(read_date("soy"), local(2026, 1, 1, 0, 0, 0));
+    assert_eq!(read_date("eoy"), local(2026, 12, 31, 23, 59, 59));
+    assert_eq!(read_date("eom + 1d"), local(2026, 2, 1, 23, 59, 59));
+
+    let read_at = |input: &str, now: DateTime<Local>| {
+        MockParser::with_now(input, now).read_date_expr().unwrap()
+    };
+    let leap_day = local(2028, 2, 29, 8, 0, 0);
+    assert_eq!(read_at("sow", leap_day), local(2028, 2, 28, 0, 0, 0));
+    assert_eq!(read_at("eom", leap_day), local(2028, 2, 29, 23, 59, 59));
+    assert_eq!(read_at("eoq", leap_day), local(2028, 3, 31, 23, 59, 59));
+
+    let november = local(2026, 11, 15, 8, 0, 0);
+    assert_eq!(read_at("soq", november), local(2026, 10, 1, 0, 0, 0));
+    assert_eq!(read_at("eoq", november), local(2026, 12, 31, 23, 59, 59));
+    assert_eq!(read_at("eom", november), local(2026, 11, 30, 23, 59, 59));
 }
@@ -0,0 +1,245 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+
+use crate::config::{default_sort, get_config};
+use crate::task::Task;
+
+/// What the tasks of a report can be sorted by
+#[derive(Debug, Clone, PartialEq)]
+pub enum SortField {
+    Id,
+    Urgency,
+    Due,
+    Scheduled,
+    Wait,
+    Created,
+    Completed,
+    Project,
+    Priority,
+    Summary,
+    Status,
+    /// A user defined attribute, given as `uda.<name>`
+    Uda(String),
+}
+
+impl SortField {
+    /// Read a field by its name. The dates can also be given by the name of
+    /// their column, e.g. `date_due`.
+    pub fn from_string(input: &str) -> Result<SortField, String> {
+        let name = input.to_lowercase();
+        if let Some(uda) = name.strip_prefix("uda.")
+            && !uda.is_empty()
+        {
+            return Ok(SortField::Uda(uda.to_owned()));
+        }
+        match name.strip_prefix("date_").unwrap_or(&name) {
+            "id" => Ok(SortField::Id),
+            "urgency" => Ok(SortField::Urgency),
+            "due" => Ok(SortField::Due),
+            "scheduled" => Ok(SortField::Scheduled),
+            "wait" => Ok(SortField::Wait),
+            "created" => Ok(SortField::Created),
+            "completed" => Ok(SortField::Completed),
+            "project" => Ok(SortField::Project),
+            "priority" => Ok(SortField::Priority),
+            "summary" => Ok(SortField::Summary),
+            "status" => Ok(SortField::Status),
+            _ => Err(format!(
+                "Invalid sort field '{}'. Valid fields are 'id', 'urgency', 'due', 'scheduled', \
+                'wait', 'created', 'completed', 'project', 'priority', 'summary', 'status' \
+                and 'uda.<name>'",
+                input
+            )),
+        }
+    }
+}
+
+impl fmt::Display for SortField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SortField::Id => write!(f, "id"),
+            SortField::Urgency => write!(f, "urgency"),
+            SortField::Due => write!(f, "due"),
+            SortField::Scheduled => write!(f, "scheduled"),
+            SortField::Wait => write!(f, "wait"),
+            SortField::Created => write!(f, "created"),
+            SortField::Completed => write!(f, "completed"),
+            SortField::Project => write!(f, "project"),
+            SortField::Priority => write!(f, "priority"),
+            SortField::Summary => write!(f, "summary"),
+            SortField::Status => write!(f, "status"),
+            SortField::Uda(name) => write!(f, "uda.{}", name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+/// A field to sort the tasks by, written as the name of the field followed by
+/// `+` for the ascending order or `-` for the descending one, e.g. `due+`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct SortKey {
+    pub field: SortField,
+    pub direction: SortDirection,
+}
+
+impl SortKey {
+    pub fn from_string(input: &str) -> Result<SortKey, String> {
+        let input = input.trim();
+        let (field, direction) = if let Some(field) = input.strip_suffix('+') {
+            (field, SortDirection::Ascending)
+        } else if let Some(field) = input.strip_suffix('-') {
+            (field, SortDirection::Descending)
+        } else {
+            return Err(format!(
+                "Invalid sort key '{}'. It must end with '+' for the ascending order \
+                or '-' for the descending one, e.g. 'due+'",
+                input
+            ));
+        };
+        Ok(SortKey {
+            field: SortField::from_string(field)?,
+            direction,
+        })
+    }
+
+    /// Compare the tasks on the field of this key. A task without a value for
+    /// it comes after the tasks having one, whatever the direction.
+    pub fn compare(&self, lhs: &Task, rhs: &Task) -> Ordering {
+        match &self.field {
+            SortField::Id => self.compare_values(lhs.get_id(), rhs.get_id(), Ord::cmp),
+            SortField::Urgency => self.compare_values(
+                lhs.get_computed_urgency(),
+                rhs.get_computed_urgency(),
+                Ord::cmp,
+            ),
+            SortField::Due => {
+                self.compare_values(*lhs.get_date_due(), *rhs.get_date_due(), Ord::cmp)
+            }
+            SortField::Scheduled => self.compare_values(
+                *lhs.get_date_scheduled(),
+                *rhs.get_date_scheduled(),
+                Ord::cmp,
+            ),
+            SortField::Wait => {
+                self.compare_values(*lhs.get_date_wait(), *rhs.get_date_wait(), Ord::cmp)
+            }
+            SortField::Created => self.compare_values(
+                Some(lhs.get_date_created()),
+                Some(rhs.get_date_created()),
+                Ord::cmp,
+            ),
+            SortField::Completed => self.compare_values(
+                *lhs.get_date_completed(),
+                *rhs.get_date_completed(),
+                Ord::cmp,
+            ),
+            SortField::Project => self.compare_values(
+                lhs.get_project().as_ref().map(|p| p.get_name()),
+                rhs.get_project().as_ref().map(|p| p.get_name()),
+                Ord::cmp,
+            ),
+            SortField::Priority => {
+                self.compare_values(*lhs.get_priority(), *rhs.get_priority(), Ord::cmp)
+            }
+            SortField::Summary => self.compare_values(
+                Some(lhs.get_summary().to_lowercase()),
+                Some(rhs.get_summary().to_lowercase()),
+                Ord::cmp,
+            ),
+            SortField::Status => {
+                self.compare_values(Some(lhs.get_status()), Some(rhs.get_status()), Ord::cmp)
+            }
+            // The values are compared as strings when the UDA is not defined anymore
+            SortField::Uda(name) => {
+                let definition = get_config().udas.get(name);
+                self.compare_values(
+                    lhs.get_udas().get(name),
+                    rhs.get_udas().get(name),
+                    |l, r| match definition {
+                        Some(definition) => {
+                            definition.compare_values(l, r).unwrap_or_else(|| l.cmp(r))
+                        }
+                        None => l.cmp(r),
+                    },
+                )
+            }
+        }
+    }
+
+    fn compare_values<T>(
+        &self,
+        lhs: Option<T>,
+        rhs: Option<T>,
+        compare: impl Fn(&T, &T) -> Ordering,
+    ) -> Ordering {
+        match (lhs, rhs) {
+            (Some(lhs), Some(rhs)) => match self.direction {
+                SortDirection::Ascending => compare(&lhs, &rhs),
+                SortDirection::Descending => compare(&rhs, &lhs),
+            },
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+}
+
+impl TryFrom<String> for SortKey {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        SortKey::from_string(&value)
+    }
+}
+
+impl fmt::Display for SortKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.direction {
+            SortDirection::Ascending => write!(f, "{}+", self.field),
+            SortDirection::Descending => write!(f, "{}-", self.field),
+        }
+    }
+}
+
+/// Read the sort keys given to `sort:`, separated by commas, e.g. `due+,urgency-`
+pub fn parse_sort_keys(input: &str) -> Result<Vec<SortKey>, String> {
+    if input.trim().is_empty() {
+        return Err("No sort key given to 'sort:', e.g. 'sort:due+'".to_string());
+    }
+    input.split(',').map(SortKey::from_string).collect()
+}
+
+/// Keys breaking the ties between the tasks equal on the keys of a report: the
+/// default ones, then the ID
+static TIE_BREAK_KEYS: Lazy<Vec<SortKey>> = Lazy::new(|| {
+    let mut keys = default_sort();
+    keys.push(SortKey {
+        field: SortField::Id,
+        direction: SortDirection::Ascending,
+    });
+    keys
+});
+
+/// Compare the tasks on each of the @keys in turn, then on the default keys and on
+/// the ID. The tasks that are equal on all of them are ordered by UUID, so that no
+/// two tasks are ever equal.
+pub fn compare_tasks(lhs: &Task, rhs: &Task, keys: &[SortKey]) -> Ordering {
+    keys.iter()
+        .chain(TIE_BREAK_KEYS.iter())
+        .map(|key| key.compare(lhs, rhs))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or_else(|| lhs.get_uuid().cmp(rhs.get_uuid()))
+}
+
+#[cfg(test)]
+#[path = "sort_test.rs"]
+mod sort_test;
@@ -0,0 +1,142 @@
+use all_asserts::assert_true;
+use chrono::{Local, TimeZone};
+use std::sync::Arc;
+
+use super::*;
+use crate::clock::FixedClock;
+use crate::task::{TaskData, TaskProperties, TaskStatus};
+
+fn sorted_summaries(data: &TaskData, keys: &str) -> Vec<String> {
+    let keys = parse_sort_keys(keys).unwrap();
+    let mut tasks: Vec<&Task> = data.get_task_map().values().collect();
+    tasks.sort_by(|lhs, rhs| compare_tasks(lhs, rhs, &keys));
+    tasks.iter().map(|t| t.get_summary().to_owned()).collect()
+}
+
+fn add_task(data: &mut TaskData, arguments: &str) {
+    data.add_task(
+        &TaskProperties::from(&[arguments.to_owned()]).unwrap(),
+        TaskStatus::Pending,
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_parse_sort_keys() {
+    assert_eq!(
+        parse_sort_keys("due+,Urgency-,date_created+,uda.size-"),
+        Ok(vec![
+            SortKey {
+                field: SortField::Due,
+                direction: SortDirection::Ascending,
+            },
+            SortKey {
+                field: SortField::Urgency,
+                direction: SortDirection::Descending,
+            },
+            SortKey {
+                field: SortField::Created,
+                direction: SortDirection::Ascending,
+            },
+            SortKey {
+                field: SortField::Uda("size".to_owned()),
+                direction: SortDirection::Descending,
+            },
+        ])
+    );
+    assert_eq!(
+        SortKey::from_string("project-").unwrap().to_string(),
+        "project-"
+    );
+
+    assert_true!(parse_sort_keys("").is_err());
+    assert_true!(parse_sort_keys("due").is_err());
+    assert_true!(parse_sort_keys("due+,").is_err());
+    assert_true!(parse_sort_keys("someday+").is_err());
+    assert_true!(parse_sort_keys("uda.+").is_err());
+}
+
+#[test]
+fn test_sort_missing_values() {
+    let mut data = TaskData::default();
+    add_task(&mut data, "later due:2026-11-20");
+    add_task(&mut data, "no due date");
+    add_task(&mut data, "sooner due:2026-11-05");
+
+    // The tasks without a due date come last in both directions
+    assert_eq!(
+        sorted_summaries(&data, "due+"),
+        vec!["sooner", "later", "no due date"]
+    );
+    assert_eq!(
+        sorted_summaries(&data, "due-"),
+        vec!["later", "sooner", "no due date"]
+    );
+}
+
+#[test]
+fn test_sort_several_keys() {
+    let mut data = TaskData::default();
+    add_task(&mut data, "b project:work priority:L");
+    add_task(&mut data, "a project:home priority:H");
+    add_task(&mut data, "c project:work priority:H");
+    add_task(&mut data, "d");
+
+    assert_eq!(
+        sorted_summaries(&data, "project+,priority-"),
+        vec!["a", "c", "b", "d"]
+    );
+    assert_eq!(
+        sorted_summaries(&data, "priority-,summary-"),
+        vec!["c", "a", "b", "d"]
+    );
+    assert_eq!(
+        sorted_summaries(&data, "summary+"),
+        vec!["a", "b", "c", "d"]
+    );
+}
+
+#[test]
+fn test_sort_ties() {
+    let now = Local.with_ymd_and_hms(2026, 11, 3, 14, 0, 0).unwrap();
+    let mut data = TaskData::default();
+    data.set_clock(Arc::new(FixedClock::new(now)));
+    add_task(&mut data, "first");
+    add_task(&mut data, "second");
+    add_task(&mut data, "urgent priority:H");
+    data.set_clock(Arc::new(FixedClock::new(
+        now + chrono::Duration::minutes(1),
+    )));
+    add_task(&mut data, "recent");
+    data.upkeep().unwrap();
+
+    // The tasks equal on the given keys are sorted by urgency, then the most
+    // recent first, then by ID
+    assert_eq!(
+        sorted_summaries(&data, "project+"),
+        vec!["urgent", "recent", "first", "second"]
+    );
+}
+
+#[test]
+fn test_sort_equal_tasks() {
+    let now = Local.with_ymd_and_hms(2026, 11, 3, 14, 0, 0).unwrap();
+    let mut data = TaskData::default();
+    data.set_clock(Arc::new(FixedClock::new(now)));
+    for _ in 0..2 {
+        data.add_task(
+            &TaskProperties::from(&["same".to_owned()]).unwrap(),
+            TaskStatus::Completed,
+        )
+        .unwrap();
+    }
+    let tasks: Vec<&Task> = data.get_task_map().values().collect();
+    let keys = parse_sort_keys("summary+,created-").unwrap();
+
+    // Tasks equal on every key and without an ID are still ordered, by UUID
+    assert_eq!(
+        compare_tasks(tasks[0], tasks[1], &keys),
+        tasks[0].get_uuid().cmp(tasks[1].get_uuid())
+    );
+    assert_eq!(compare_tasks(tasks[0], tasks[0], &keys), Ordering::Equal);
+}
@@ -1,9 +1,16 @@
+mod import;
+mod link;
+mod priority;
+mod recurrence;
 mod task_prop_parser;
+mod tracking;
+mod uda;
+mod urgency;
 
 use log::trace;
 use task_prop_parser::TaskPropertyParser;
 
-use std::{cmp::Ordering, collections::HashSet, fmt};
+use std::{cmp::Ordering, collections::HashSet, fmt, sync::Arc};
 
 use chrono::prelude::DateTime;
 use serde_json::Value;
@@ -11,11 +18,23 @@ use uuid::Uuid;
 
 use chrono::Local;
 use serde::{Deserialize, Deserializer, Serialize, ser::Serializer};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
+use crate::clock::{Clock, SystemClock};
 use crate::filters::Filter;
 use crate::lexer::Lexer;
 
+pub use import::{ImportOutcome, ImportedTask};
+pub use link::{LinkChange, LinkType};
+pub use priority::TaskPriority;
+pub use recurrence::{Recurrence, RecurrenceUnit};
+pub use task_prop_parser::parse_date_expression;
+pub use tracking::TimeInterval;
+pub(crate) use uda::split_uda_word;
+pub use uda::{UdaDefinition, UdaType, format_duration};
+pub use urgency::UrgencyTerm;
+use urgency::{DEFAULT_AGE_WINDOW, DueCurve, UrgencyTerms, age_urgency, format_date};
+
 #[path = "task_test.rs"]
 #[cfg(test)]
 mod task_test;
@@ -64,7 +83,7 @@ impl fmt::Display for TaskStatus {
 }
 
 #[derive(Clone, PartialEq, Debug, serde::Serialize, serde::Deserialize)]
-pub enum DependsOnIdentifier {
+pub enum TaskIdentifier {
     Usize(usize),
     Uuid(Uuid),
 }
@@ -84,11 +103,31 @@ pub struct TaskProperties {
     annotation: Option<String>,
     /// Replace all of this task's annotations with the given vector
     annotations: Option<Vec<TaskAnnotation>>,
+    /// `Some(None)` removes the description of a task
+    #[serde(default)]
+    description: Option<Option<String>>,
     active_status: Option<bool>,
     project: Option<Project>,
+    /// `Some(None)` removes the due date of a task, and the same goes for the
+    /// wait and scheduled dates
     #[serde(default)]
-    date_due: Option<DateTime<chrono::Local>>,
-    depends_on: Option<Vec<DependsOnIdentifier>>,
+    date_due: Option<Option<DateTime<chrono::Local>>>,
+    #[serde(default)]
+    date_wait: Option<Option<DateTime<chrono::Local>>>,
+    #[serde(default)]
+    date_scheduled: Option<Option<DateTime<chrono::Local>>>,
+    /// Links to add or remove, in the order they were given
+    #[serde(default)]
+    links: Vec<LinkChange>,
+    /// `Some(None)` makes the task a top-level task again
+    parent: Option<Option<TaskIdentifier>>,
+    /// `Some(None)` removes the recurrence of a task
+    recurrence: Option<Option<Recurrence>>,
+    /// `Some(None)` removes the priority of a task
+    priority: Option<Option<TaskPriority>>,
+    /// Values of user defined attributes. `None` removes the attribute from a task
+    #[serde(default)]
+    udas: BTreeMap<String, Option<String>>,
 }
 
 // We implement a specific function for annotate because we cannot know how to differenciate
@@ -106,13 +145,23 @@ impl TaskProperties {
         self.summary = Some(summary.to_string());
     }
 
-    pub fn add_depends_on(&mut self, identifier: &DependsOnIdentifier) {
-        if self.depends_on.is_none() {
-            self.depends_on = Some(Vec::new());
-        }
-        if let Some(depends) = &mut self.depends_on {
-            depends.push(identifier.clone());
-        }
+    /// Set the description of the task. An empty description removes it.
+    pub fn set_description(&mut self, description: &str) {
+        let description = description.trim();
+        self.description = Some((!description.is_empty()).then(|| description.to_owned()));
+    }
+
+    /// Set the value of a UDA, as stored on the tasks. `None` removes the UDA.
+    pub fn set_uda(&mut self, name: &str, value: Option<String>) {
+        self.udas.insert(name.to_owned(), value);
+    }
+
+    pub fn add_depends_on(&mut self, identifier: &TaskIdentifier) {
+        self.add_link_change(LinkChange::Add(LinkType::DependsOn, identifier.clone()));
+    }
+
+    pub fn add_link_change(&mut self, change: LinkChange) {
+        self.links.push(change);
     }
 
     /// Sets the vector of tags that should be removed
@@ -133,16 +182,29 @@ impl TaskProperties {
     }
 
     pub fn from(values: &[String]) -> Result<TaskProperties, String> {
+        TaskProperties::from_with_clock(values, Arc::new(SystemClock))
+    }
+
+    /// Parse the properties, the relative dates being relative to the time of @clock
+    pub fn from_with_clock(
+        values: &[String],
+        clock: Arc<dyn Clock>,
+    ) -> Result<TaskProperties, String> {
         let lexer = Lexer::new(values.join(" "));
-        let mut parser = TaskPropertyParser::new(lexer);
+        let mut parser = TaskPropertyParser::new(lexer).with_clock(clock);
         parser.parse_task_properties()
     }
 
-    pub fn get_referenced_tasks(&self) -> Vec<DependsOnIdentifier> {
-        match &self.depends_on {
-            Some(deps) => deps.to_owned(),
-            None => Vec::default(),
+    pub fn get_referenced_tasks(&self) -> Vec<TaskIdentifier> {
+        let mut referenced: Vec<TaskIdentifier> = self
+            .links
+            .iter()
+            .filter_map(|change| change.get_target().cloned())
+            .collect();
+        if let Some(Some(parent)) = &self.parent {
+            referenced.push(parent.to_owned());
         }
+        referenced
     }
 }
 
@@ -155,6 +217,13 @@ pub struct TaskAnnotation {
 }
 
 impl TaskAnnotation {
+    pub fn new(value: &str, time: DateTime<chrono::Local>) -> TaskAnnotation {
+        TaskAnnotation {
+            value: value.to_owned(),
+            time,
+        }
+    }
+
     pub fn get_value(&self) -> &String {
         &self.value
     }
@@ -175,12 +244,17 @@ pub struct TaskHistory {
 }
 
 #[derive(Default, Clone, serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Hash)]
+#[serde(remote = "Self")]
 pub struct Task {
     id: Option<usize>,
     status: TaskStatus,
     uuid: Uuid,
     summary: String,
 
+    /// Long-form text about the task, written in markdown
+    #[serde(default)]
+    description: Option<String>,
+
     #[serde(default)]
     annotations: Vec<TaskAnnotation>,
 
@@ -191,27 +265,120 @@ pub struct Task {
     #[serde(default)]
     date_completed: Option<DateTime<chrono::Local>>,
 
+    /// List of the UUIDs of the subtasks of this task. It is kept up to date
+    /// from the parent of the other tasks, the deleted subtasks are left out.
+    #[serde(default)]
     sub: Vec<Uuid>,
 
-    /// List of the UUIDs this task depends on
+    /// UUID of the task this task is a subtask of
+    #[serde(default)]
+    parent: Option<Uuid>,
+
+    /// Percentage of the subtasks that are completed, if the task has subtasks
+    #[serde(default)]
+    progress: Option<u8>,
+
+    /// UUIDs of the tasks this task is linked to, by type of link
     #[serde(default)]
-    depends_on: Vec<Uuid>,
+    links: BTreeMap<LinkType, Vec<Uuid>>,
 
+    /// UUIDs of the tasks linked to this task, by type of link. They are kept
+    /// up to date from the links of the other tasks.
     #[serde(default)]
-    blocking: Vec<Uuid>,
+    backlinks: BTreeMap<LinkType, Vec<Uuid>>,
+
+    /// Dependencies as they were stored before they became links. They are
+    /// moved to the links when the task is read.
+    #[serde(default, rename = "depends_on", skip_serializing)]
+    legacy_depends_on: Vec<Uuid>,
 
     project: Option<Project>,
 
     #[serde(default)]
     date_due: Option<DateTime<chrono::Local>>,
 
+    /// The task is hidden from the pending tasks until this date
+    #[serde(default)]
+    date_wait: Option<DateTime<chrono::Local>>,
+
+    /// Date from which the task can be worked on. Its urgency goes up once
+    /// this date is reached.
+    #[serde(default)]
+    date_scheduled: Option<DateTime<chrono::Local>>,
+
+    #[serde(default)]
+    priority: Option<TaskPriority>,
+
+    /// When set, completing this task will create the next instance of it
+    #[serde(default)]
+    recurrence: Option<Recurrence>,
+
+    /// UUID of the first task of a recurring series. This is only set on the
+    /// instances that were created when completing a recurring task.
+    #[serde(default)]
+    recurrence_template: Option<Uuid>,
+
     /// Urgency score that will be computed depending on the other fields of the task
     #[serde(default)]
     urgency: Option<i64>,
 
+    /// What the urgency is made of. It is computed along with the urgency.
+    #[serde(skip)]
+    urgency_terms: Vec<UrgencyTerm>,
+
     /// All the events that have happened to a task after its creation
     #[serde(default)]
     history: Vec<TaskHistory>,
+
+    /// The periods during which the task was active
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    intervals: Vec<TimeInterval>,
+
+    /// Values of the user defined attributes, as described by `UdaDefinition`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    udas: BTreeMap<String, String>,
+
+    /// Fields of a task imported from another application that have no
+    /// equivalent in bee, kept as JSON values
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    imported_fields: BTreeMap<String, String>,
+}
+
+/// The UUID of a task given to TaskProperties, once its ID has been resolved by TaskData
+fn resolved_uuid(identifier: &TaskIdentifier) -> Uuid {
+    match identifier {
+        TaskIdentifier::Usize(_) => {
+            unreachable!(
+                "We should not have a usize here. \
+                We should have converted it to a UUID before applying \
+                the properties to the task."
+            );
+        }
+        TaskIdentifier::Uuid(uuid) => *uuid,
+    }
+}
+
+impl Serialize for Task {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Task::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Task {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut task = Task::deserialize(deserializer)?;
+        let legacy_depends_on = std::mem::take(&mut task.legacy_depends_on);
+        for uuid in legacy_depends_on {
+            task.insert_link(LinkType::DependsOn, uuid);
+        }
+        Ok(task)
+    }
 }
 
 impl PartialOrd for Task {
@@ -239,19 +406,31 @@ impl Task {
         self.id
     }
 
-    pub fn get_urgency(&mut self) -> Result<i64, String> {
+    pub fn get_urgency(&mut self, now: DateTime<Local>) -> Result<i64, String> {
         if let Some(urgency) = self.urgency {
             return Ok(urgency);
         }
 
-        self.compute_urgency()
+        self.compute_urgency(now)
+    }
+
+    /// The urgency as computed by the last upkeep, if it was
+    pub fn get_computed_urgency(&self) -> Option<i64> {
+        self.urgency
     }
 
-    fn compute_urgency(&mut self) -> Result<i64, String> {
-        let mut urgency: i64 = 0;
+    /// Compute the urgency of the task and record the terms it is made of
+    fn compute_urgency(&mut self, now: DateTime<Local>) -> Result<i64, String> {
+        let mut terms = UrgencyTerms::default();
         let mut blocking_coef = 1;
         let mut depends_coef = -1;
         let mut active_status_coef = 10;
+        let mut scheduled_coef = 5;
+        let mut annotations_coef = 0;
+        let mut age_coef = 0;
+        let mut age_window = DEFAULT_AGE_WINDOW;
+        let mut due_curve = DueCurve::default();
+        let mut priority_coefs = HashMap::<TaskPriority, i64>::new();
 
         let conf = crate::config::get_config();
         for coef_field in conf.coefficients.iter() {
@@ -259,10 +438,10 @@ impl Task {
                 "tag" => {
                     if let Some(tag_value) = &coef_field.value {
                         if self.tags.contains(tag_value) {
-                            urgency += coef_field.coefficient;
+                            terms.add("tag", Some(tag_value.to_owned()), coef_field.coefficient);
                         }
                     } else {
-                        urgency += coef_field.coefficient;
+                        terms.add("tag", None, coef_field.coefficient);
                     }
                 }
                 "depends" => {
@@ -274,32 +453,168 @@ impl Task {
                 "active_status" => {
                     active_status_coef = coef_field.coefficient;
                 }
+                "scheduled" => {
+                    scheduled_coef = coef_field.coefficient;
+                }
+                "annotations" => {
+                    annotations_coef = coef_field.coefficient;
+                }
+                "age" => {
+                    age_coef = coef_field.coefficient;
+                    age_window =
+                        urgency::get_window(coef_field, DEFAULT_AGE_WINDOW).map_err(|e| {
+                            format!(
+                                "Error parsing the coefficient field in the configuration file. {}",
+                                e
+                            )
+                        })?;
+                }
+                "due" => {
+                    due_curve = DueCurve::from_coefficient(coef_field).map_err(|e| {
+                        format!(
+                            "Error parsing the coefficient field in the configuration file. {}",
+                            e
+                        )
+                    })?;
+                }
+                // The value is a prefix of the project, without it the coefficient
+                // applies to every task with a project
+                "project" => {
+                    let matched = match (&self.project, &coef_field.value) {
+                        (Some(project), Some(prefix)) => project
+                            .get_name()
+                            .starts_with(prefix)
+                            .then(|| prefix.to_owned()),
+                        (Some(project), None) => Some(project.get_name().to_owned()),
+                        (None, _) => None,
+                    };
+                    if let Some(value) = matched {
+                        terms.add("project", Some(value), coef_field.coefficient);
+                    }
+                }
+                field if field.starts_with("uda.") => {
+                    let name = &field["uda.".len()..];
+                    let definition = conf.udas.get(name).ok_or(format!(
+                        "Error parsing the coefficient field in the configuration file. \
+                            '{}' is not a known UDA",
+                        name
+                    ))?;
+                    let task_value = match self.udas.get(name) {
+                        Some(value) => value,
+                        None => continue,
+                    };
+                    // Without a value, the coefficient applies as soon as the task has the UDA
+                    let matches = match &coef_field.value {
+                        Some(value) => {
+                            let value = definition.parse_value(value).map_err(|e| {
+                                format!(
+                                    "Error parsing the coefficient field in the configuration file. {}",
+                                    e
+                                )
+                            })?;
+                            definition.compare_values(task_value, &value) == Some(Ordering::Equal)
+                        }
+                        None => true,
+                    };
+                    if matches {
+                        terms.add(field, Some(task_value.to_owned()), coef_field.coefficient);
+                    }
+                }
+                "priority" => {
+                    let priority = match &coef_field.value {
+                        Some(value) => TaskPriority::from_string(value).map_err(|e| {
+                            format!(
+                                "Error parsing the coefficient field in the configuration file. {}",
+                                e
+                            )
+                        })?,
+                        None => {
+                            return Err(
+                                "Error parsing the coefficient field in the configuration file. \
+                                The 'priority' field requires a 'value' ('H', 'M' or 'L')"
+                                    .to_string(),
+                            );
+                        }
+                    };
+                    priority_coefs.insert(priority, coef_field.coefficient);
+                }
                 _ => {
                     return Err(format!(
                         "Error parsing the coefficient field in the configuration file. \
-                            '{}' is not a valid 'field' name. Valid field names are: 'tag', 'depends', 'blocking'",
+                            '{}' is not a valid 'field' name. Valid field names are: 'tag', 'project', 'depends', \
+                            'blocking', 'active_status', 'scheduled', 'priority', 'due', 'age', \
+                            'annotations', 'uda.<name>'",
                         coef_field.field
                     ));
                 }
             }
         }
 
-        urgency += self.blocking.len() as i64 * blocking_coef;
-        urgency += self.depends_on.len() as i64 * depends_coef;
+        let blocking = self.get_blocking().len();
+        terms.add(
+            "blocking",
+            Some(blocking.to_string()),
+            blocking as i64 * blocking_coef,
+        );
+        let depends = self.get_depends().len();
+        terms.add(
+            "depends",
+            Some(depends.to_string()),
+            depends as i64 * depends_coef,
+        );
 
         if self.status == TaskStatus::Active {
-            urgency += active_status_coef;
+            terms.add("active_status", None, active_status_coef);
         }
 
-        // Compute number of days remaining until the due date
-        if let Some(date_due) = self.date_due {
-            let now = Local::now();
-            let days = date_due.signed_duration_since(now).num_days();
-            urgency += days;
+        if let Some(scheduled) = self.date_scheduled
+            && scheduled <= now
+        {
+            terms.add("scheduled", Some(format_date(&scheduled)), scheduled_coef);
+        }
+
+        if let Some(priority) = &self.priority {
+            terms.add(
+                "priority",
+                Some(priority.to_string()),
+                priority_coefs
+                    .get(priority)
+                    .copied()
+                    .unwrap_or_else(|| priority.default_coefficient()),
+            );
         }
 
+        if let Some(date_due) = self.date_due {
+            terms.add(
+                "due",
+                Some(format_date(&date_due)),
+                due_curve.urgency(date_due, now).round() as i64,
+            );
+        }
+        terms.add(
+            "age",
+            Some(format!(
+                "{} days",
+                now.signed_duration_since(self.date_created).num_days()
+            )),
+            age_urgency(age_coef, age_window, self.date_created, now).round() as i64,
+        );
+        terms.add(
+            "annotations",
+            Some(self.annotations.len().to_string()),
+            self.annotations.len() as i64 * annotations_coef,
+        );
+
+        let urgency = terms.total();
+        self.urgency_terms = terms.into_terms();
         self.urgency = Some(urgency);
-        Ok(self.urgency.unwrap())
+        Ok(urgency)
+    }
+
+    /// The terms the urgency is made of, as computed by the last upkeep. Only the
+    /// terms that change the urgency are kept.
+    pub fn get_urgency_terms(&self) -> &Vec<UrgencyTerm> {
+        &self.urgency_terms
     }
 
     pub fn get_history(&self) -> &Vec<TaskHistory> {
@@ -310,12 +625,91 @@ impl Task {
         &self.annotations
     }
 
-    pub fn get_blocking(&self) -> &Vec<Uuid> {
-        &self.blocking
+    pub fn get_description(&self) -> &Option<String> {
+        &self.description
+    }
+
+    pub fn get_intervals(&self) -> &Vec<TimeInterval> {
+        &self.intervals
+    }
+
+    /// Total time during which the task was active, including the time since it
+    /// was started if it still is
+    pub fn get_tracked_time(&self, now: DateTime<Local>) -> chrono::TimeDelta {
+        self.intervals
+            .iter()
+            .map(|interval| interval.duration(now))
+            .sum()
     }
 
-    pub fn get_depends(&self) -> &Vec<Uuid> {
-        &self.depends_on
+    /// Close the interval of time opened when the task was started, if any
+    fn stop_tracking(&mut self, time: DateTime<Local>) {
+        if let Some(interval) = self.intervals.last_mut()
+            && interval.is_open()
+        {
+            interval.end = Some(time);
+        }
+    }
+
+    /// The tasks that depend on this task
+    pub fn get_blocking(&self) -> &[Uuid] {
+        self.get_backlinks(LinkType::DependsOn)
+    }
+
+    /// The tasks this task depends on
+    pub fn get_depends(&self) -> &[Uuid] {
+        self.get_links(LinkType::DependsOn)
+    }
+
+    /// The tasks this task is linked to with a link of type @kind
+    pub fn get_links(&self, kind: LinkType) -> &[Uuid] {
+        self.links.get(&kind).map_or(&[], |uuids| uuids.as_slice())
+    }
+
+    /// The tasks linked to this task with a link of type @kind
+    pub fn get_backlinks(&self, kind: LinkType) -> &[Uuid] {
+        self.backlinks
+            .get(&kind)
+            .map_or(&[], |uuids| uuids.as_slice())
+    }
+
+    /// Whether this task is linked to the task with @uuid by a link of type @kind.
+    /// Symmetric links are followed in both directions.
+    pub fn is_linked_to(&self, kind: LinkType, uuid: &Uuid) -> bool {
+        self.get_links(kind).contains(uuid)
+            || (kind.is_symmetric() && self.get_backlinks(kind).contains(uuid))
+    }
+
+    /// Link this task to the task with @uuid, returns false if the link existed
+    fn insert_link(&mut self, kind: LinkType, uuid: Uuid) -> bool {
+        let uuids = self.links.entry(kind).or_default();
+        if uuids.contains(&uuid) {
+            return false;
+        }
+        uuids.push(uuid);
+        true
+    }
+
+    /// Remove the links of type @kind that don't match @keep
+    fn retain_links<F: FnMut(&Uuid) -> bool>(&mut self, kind: LinkType, keep: F) {
+        if let Some(uuids) = self.links.get_mut(&kind) {
+            uuids.retain(keep);
+            if uuids.is_empty() {
+                self.links.remove(&kind);
+            }
+        }
+    }
+
+    pub fn get_subtasks(&self) -> &Vec<Uuid> {
+        &self.sub
+    }
+
+    pub fn get_parent(&self) -> &Option<Uuid> {
+        &self.parent
+    }
+
+    pub fn get_progress(&self) -> Option<u8> {
+        self.progress
     }
 
     pub fn get_summary(&self) -> &str {
@@ -351,33 +745,114 @@ impl Task {
         &self.date_due
     }
 
+    pub fn get_date_wait(&self) -> &Option<DateTime<Local>> {
+        &self.date_wait
+    }
+
+    pub fn get_date_scheduled(&self) -> &Option<DateTime<Local>> {
+        &self.date_scheduled
+    }
+
+    /// A pending or active task is waiting until its wait date is reached
+    pub fn is_waiting(&self, now: DateTime<Local>) -> bool {
+        matches!(self.status, TaskStatus::Pending | TaskStatus::Active)
+            && self.date_wait.is_some_and(|wait| wait > now)
+    }
+
+    pub fn get_priority(&self) -> &Option<TaskPriority> {
+        &self.priority
+    }
+
     pub fn get_uuid(&self) -> &Uuid {
         &self.uuid
     }
 
+    pub fn get_recurrence(&self) -> &Option<Recurrence> {
+        &self.recurrence
+    }
+
+    pub fn get_recurrence_template(&self) -> &Option<Uuid> {
+        &self.recurrence_template
+    }
+
+    pub fn get_udas(&self) -> &BTreeMap<String, String> {
+        &self.udas
+    }
+
+    pub fn get_imported_fields(&self) -> &BTreeMap<String, String> {
+        &self.imported_fields
+    }
+
     /// Send back a list of the UUID that this task knows about or refers to
     pub fn get_extra_uuid(&self) -> Vec<Uuid> {
-        let mut uuids = [self.depends_on.to_owned(), self.blocking.to_owned()].concat();
+        let mut uuids: Vec<Uuid> = self
+            .links
+            .values()
+            .chain(self.backlinks.values())
+            .flatten()
+            .chain(&self.sub)
+            .cloned()
+            .collect();
+        uuids.extend(self.parent);
         uuids.sort_unstable();
         uuids.dedup();
         uuids
     }
 
-    pub fn apply(&mut self, props: &TaskProperties) -> Result<(), String> {
+    /// Apply the changes of @props to the task, @now being the time of the changes
+    pub fn apply(&mut self, props: &TaskProperties, now: DateTime<Local>) -> Result<(), String> {
         if let Some(summary) = &props.summary {
             self.history.push(TaskHistory {
-                time: Local::now(),
+                time: now,
                 value: format!("Summary changed from '{}' to '{}'.", self.summary, summary),
             });
             self.summary = summary.clone();
         }
 
+        if let Some(description) = &props.description
+            && *description != self.description
+        {
+            self.history.push(TaskHistory {
+                time: now,
+                value: match description {
+                    Some(_) => "Description changed".to_string(),
+                    None => "Description removed".to_string(),
+                },
+            });
+            self.description = description.clone();
+        }
+
         if let Some(date_due) = &props.date_due {
             self.history.push(TaskHistory {
-                time: Local::now(),
-                value: format!("Due date set to {}", date_due),
+                time: now,
+                value: match date_due {
+                    Some(date) => format!("Due date set to {}", date),
+                    None => "Due date removed".to_string(),
+                },
+            });
+            self.date_due = date_due.to_owned();
+        }
+
+        if let Some(date_wait) = &props.date_wait {
+            self.history.push(TaskHistory {
+                time: now,
+                value: match date_wait {
+                    Some(date) => format!("Wait date set to {}", date),
+                    None => "Wait date removed".to_string(),
+                },
             });
-            self.date_due = Some(date_due.to_owned());
+            self.date_wait = date_wait.to_owned();
+        }
+
+        if let Some(date_scheduled) = &props.date_scheduled {
+            self.history.push(TaskHistory {
+                time: now,
+                value: match date_scheduled {
+                    Some(date) => format!("Scheduled date set to {}", date),
+                    None => "Scheduled date removed".to_string(),
+                },
+            });
+            self.date_scheduled = date_scheduled.to_owned();
         }
 
         if let Some(active) = &props.active_status {
@@ -389,8 +864,9 @@ impl Task {
                     ));
                 }
                 self.status = TaskStatus::Active;
+                self.intervals.push(TimeInterval::start(now));
                 self.history.push(TaskHistory {
-                    time: Local::now(),
+                    time: now,
                     value: "Status changed from 'PENDING' to 'ACTIVE'".to_string(),
                 });
             } else {
@@ -401,8 +877,9 @@ impl Task {
                     ));
                 }
                 self.status = TaskStatus::Pending;
+                self.stop_tracking(now);
                 self.history.push(TaskHistory {
-                    time: Local::now(),
+                    time: now,
                     value: "Status changed from 'ACTIVE' to 'PENDING'".to_string(),
                 });
             }
@@ -411,21 +888,62 @@ impl Task {
         if let Some(status) = &props.status {
             if &self.status != status {
                 self.history.push(TaskHistory {
-                    time: Local::now(),
+                    time: now,
                     value: format!("Status changed from '{}' to '{}'", self.status, status),
                 });
+                if self.status == TaskStatus::Active {
+                    self.stop_tracking(now);
+                } else if *status == TaskStatus::Active {
+                    self.intervals.push(TimeInterval::start(now));
+                }
             }
             self.status = status.to_owned();
         }
 
         if let Some(proj) = &props.project {
             self.history.push(TaskHistory {
-                time: Local::now(),
+                time: now,
                 value: format!("Project set to '{}'", proj),
             });
             self.project = Some(proj.to_owned());
         }
 
+        if let Some(recurrence) = &props.recurrence {
+            self.history.push(TaskHistory {
+                time: now,
+                value: match recurrence {
+                    Some(r) => format!("Recurrence set to '{}'", r),
+                    None => "Recurrence removed".to_string(),
+                },
+            });
+            self.recurrence = recurrence.to_owned();
+        }
+
+        if let Some(priority) = &props.priority {
+            self.history.push(TaskHistory {
+                time: now,
+                value: match priority {
+                    Some(p) => format!("Priority set to '{}'", p),
+                    None => "Priority removed".to_string(),
+                },
+            });
+            self.priority = priority.to_owned();
+        }
+
+        for (name, value) in &props.udas {
+            self.history.push(TaskHistory {
+                time: now,
+                value: match value {
+                    Some(v) => format!("Attribute '{}' set to '{}'", name, v),
+                    None => format!("Attribute '{}' removed", name),
+                },
+            });
+            match value {
+                Some(v) => self.udas.insert(name.to_owned(), v.to_owned()),
+                None => self.udas.remove(name),
+            };
+        }
+
         if let Some(tags) = &props.tags_remove {
             let s: HashSet<String> = tags.iter().cloned().collect();
             let mut removed_tags: Vec<String> = Vec::new();
@@ -440,7 +958,7 @@ impl Task {
 
             if !removed_tags.is_empty() {
                 self.history.push(TaskHistory {
-                    time: Local::now(),
+                    time: now,
                     value: format!("Removed tag(s) '{}'", removed_tags.join(", ")),
                 });
             }
@@ -455,7 +973,7 @@ impl Task {
                 new_tags.difference(&existing_tags).cloned().collect();
             if !tags_added.is_empty() {
                 self.history.push(TaskHistory {
-                    time: Local::now(),
+                    time: now,
                     value: format!(
                         "Added tag(s) '{}'",
                         tags_added
@@ -470,59 +988,76 @@ impl Task {
 
         if let Some(ann) = &props.annotation {
             self.history.push(TaskHistory {
-                time: Local::now(),
+                time: now,
                 value: format!("Added an annotation '{}'", ann),
             });
             self.annotations.push(TaskAnnotation {
                 value: ann.to_string(),
-                time: Local::now(),
+                time: now,
             });
         }
 
         if let Some(annotations) = &props.annotations {
             self.history.push(TaskHistory {
-                time: Local::now(),
+                time: now,
                 value: "The list of annotations have been changed".to_string(),
             });
             self.annotations = annotations.to_owned();
         }
 
-        if let Some(depends_on) = &props.depends_on {
-            let mut deps_set = HashSet::<Uuid>::new();
-
-            // If the vector is empty, it's because we want to cancel all dependencies
-            // for the task. In which case just don't add any. This will update the
-            // task to not have any dependencies
-            if !depends_on.is_empty() {
-                self.depends_on.iter().for_each(|uuid| {
-                    deps_set.insert(uuid.to_owned());
-                });
-            }
-            for dep in depends_on {
-                match dep {
-                    DependsOnIdentifier::Usize(_) => {
-                        unreachable!(
-                            "We should not have a usize here. \
-                            We should have converted it to a UUID before applying \
-                            the properties to the task."
-                        );
+        for change in &props.links {
+            match change {
+                LinkChange::Add(kind, target) => {
+                    let uuid = resolved_uuid(target);
+                    if uuid == self.uuid {
+                        return Err(format!(
+                            "The task '{}' cannot be linked to itself.",
+                            self.summary
+                        ));
                     }
-                    DependsOnIdentifier::Uuid(uuid) => {
-                        if deps_set.contains(uuid) {
-                            continue;
-                        }
+                    if self.insert_link(*kind, uuid) {
+                        self.history.push(TaskHistory {
+                            time: now,
+                            value: format!("Added a '{}' link to '{}'", kind, uuid),
+                        });
+                    }
+                }
+                LinkChange::Remove(kind, target) => {
+                    let uuid = resolved_uuid(target);
+                    if self.get_links(*kind).contains(&uuid) {
+                        self.history.push(TaskHistory {
+                            time: now,
+                            value: format!("Removed the '{}' link to '{}'", kind, uuid),
+                        });
+                        self.retain_links(*kind, |linked| *linked != uuid);
+                    }
+                }
+                LinkChange::Clear(kind) => {
+                    if !self.get_links(*kind).is_empty() {
                         self.history.push(TaskHistory {
-                            time: Local::now(),
-                            value: format!("Added a UUID to depend on: '{}'", uuid),
+                            time: now,
+                            value: format!("Removed all the '{}' links", kind),
                         });
-                        self.depends_on.push(uuid.to_owned());
-                        deps_set.insert(uuid.to_owned());
+                        self.links.remove(kind);
                     }
                 }
             }
-            self.depends_on = deps_set.into_iter().collect();
         }
-        self.compute_urgency()?;
+
+        if let Some(parent) = &props.parent {
+            let parent = parent.as_ref().map(resolved_uuid);
+            if parent != self.parent {
+                self.history.push(TaskHistory {
+                    time: now,
+                    value: match parent {
+                        Some(uuid) => format!("Parent set to '{}'", uuid),
+                        None => "Parent removed".to_string(),
+                    },
+                });
+            }
+            self.parent = parent;
+        }
+        self.compute_urgency(now)?;
         Ok(())
     }
 
@@ -540,26 +1075,73 @@ impl Task {
         }
     }
 
-    pub fn delete(&mut self) {
+    pub fn delete(&mut self, now: DateTime<Local>) {
         self.history.push(TaskHistory {
-            time: Local::now(),
+            time: now,
             value: "Deleted task.".to_string(),
         });
+        self.stop_tracking(now);
         self.status = TaskStatus::Deleted;
         self.id = None;
         self.urgency = None;
+        self.urgency_terms.clear();
     }
 
-    pub fn done(&mut self) {
-        let current_time = Local::now();
+    /// Mark the task as completed.
+    ///
+    /// If the task is recurring, this returns the next instance of it. It is up
+    /// to the caller to store it.
+    pub fn done(&mut self, now: DateTime<Local>) -> Option<Task> {
+        let was_open = matches!(self.status, TaskStatus::Pending | TaskStatus::Active);
         self.history.push(TaskHistory {
-            time: current_time,
+            time: now,
             value: "Marked task as done".to_string(),
         });
+        self.stop_tracking(now);
         self.status = TaskStatus::Completed;
-        self.date_completed = Some(current_time);
+        self.date_completed = Some(now);
         self.id = None;
         self.urgency = None;
+        self.urgency_terms.clear();
+
+        if !was_open {
+            return None;
+        }
+        self.get_next_recurrence(now)
+    }
+
+    fn get_next_recurrence(&self, completed: DateTime<Local>) -> Option<Task> {
+        let recurrence = self.recurrence.as_ref()?;
+        let previous_due = self.date_due.unwrap_or(completed);
+        let date_due = recurrence.next_due(previous_due, completed)?;
+        // The wait and scheduled dates keep the same distance to the due date
+        let shift = date_due - previous_due;
+        let template = self.recurrence_template.unwrap_or(self.uuid);
+
+        Some(Task {
+            uuid: Uuid::new_v4(),
+            status: TaskStatus::Pending,
+            summary: self.summary.to_owned(),
+            description: self.description.to_owned(),
+            tags: self.tags.to_owned(),
+            project: self.project.to_owned(),
+            priority: self.priority.to_owned(),
+            udas: self.udas.to_owned(),
+            date_created: completed,
+            date_due: Some(date_due),
+            date_wait: self.date_wait.map(|date| date + shift),
+            date_scheduled: self.date_scheduled.map(|date| date + shift),
+            recurrence: Some(recurrence.to_owned()),
+            recurrence_template: Some(template),
+            history: vec![TaskHistory {
+                time: completed,
+                value: format!(
+                    "Created as the next instance of recurring task '{}'",
+                    template
+                ),
+            }],
+            ..Task::default()
+        })
     }
 }
 
@@ -584,7 +1166,7 @@ impl fmt::Display for Project {
     }
 }
 
-#[derive(Default, Clone)]
+#[derive(Clone)]
 pub struct TaskData {
     /// All the active tasks in this manager. This refers as tasks that should directly be
     /// modified.
@@ -601,6 +1183,22 @@ pub struct TaskData {
     /// Those are the tasks not required by the filters, but that might be needed
     /// when processing the action because they are linked to the filters
     extra_tasks: HashMap<Uuid, Task>,
+
+    /// Gives the time of the changes made to the tasks
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for TaskData {
+    fn default() -> Self {
+        TaskData {
+            tasks: HashMap::default(),
+            undos: HashMap::default(),
+            id_to_uuid: HashMap::default(),
+            max_id: 0,
+            extra_tasks: HashMap::default(),
+            clock: Arc::new(SystemClock),
+        }
+    }
 }
 
 impl TaskData {
@@ -608,6 +1206,14 @@ impl TaskData {
         &self.tasks
     }
 
+    pub fn get_clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
     pub fn get_id_to_uuid(&self) -> &HashMap<usize, Uuid> {
         &self.id_to_uuid
     }
@@ -629,12 +1235,102 @@ impl TaskData {
     }
 
     pub fn apply(&mut self, task_uuid: &Uuid, props: &TaskProperties) -> Result<(), String> {
-        if props.depends_on.is_none() {
-            return self.tasks.get_mut(task_uuid).unwrap().apply(props);
+        if props.links.is_empty() && props.parent.is_none() {
+            return self
+                .tasks
+                .get_mut(task_uuid)
+                .unwrap()
+                .apply(props, self.clock.now());
         }
 
-        let my_props = self.update_task_property_depends_on(props)?;
-        self.tasks.get_mut(task_uuid).unwrap().apply(&my_props)
+        let my_props = self.update_task_property_identifiers(props)?;
+        let task = self.tasks.get_mut(task_uuid).unwrap();
+        let task_before = task.to_owned();
+        task.apply(&my_props, self.clock.now())?;
+
+        if let Err(e) = self
+            .check_dependency_cycle(task_uuid)
+            .and_then(|_| self.check_parent_cycle(task_uuid))
+        {
+            self.tasks.insert(*task_uuid, task_before);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Return an error if the task with @uuid is its own parent, directly or through
+    /// the parents of its parent. Only the tasks loaded in this TaskData are followed.
+    pub fn check_parent_cycle(&self, uuid: &Uuid) -> Result<(), String> {
+        let get_task = |uuid: &Uuid| self.tasks.get(uuid).or_else(|| self.extra_tasks.get(uuid));
+        let mut visited = HashSet::new();
+        let mut current = get_task(uuid).and_then(|t| t.parent);
+        while let Some(parent_uuid) = current {
+            if parent_uuid == *uuid {
+                let summary = get_task(uuid).map_or("", |t| t.get_summary());
+                return Err(format!(
+                    "The task '{}' cannot be a subtask of itself or of one of its subtasks.",
+                    summary
+                ));
+            }
+            if !visited.insert(parent_uuid) {
+                break;
+            }
+            current = get_task(&parent_uuid).and_then(|t| t.parent);
+        }
+        Ok(())
+    }
+
+    /// Return an error describing the cycle if the task with @uuid depends on itself,
+    /// directly or through other tasks. Only the tasks loaded in this TaskData are
+    /// followed.
+    pub fn check_dependency_cycle(&self, uuid: &Uuid) -> Result<(), String> {
+        let path = match self.find_dependency_path(uuid, uuid, &mut HashSet::new()) {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        let description = path
+            .iter()
+            .map(
+                |uuid| match self.tasks.get(uuid).or_else(|| self.extra_tasks.get(uuid)) {
+                    Some(task) => match task.id {
+                        Some(id) => format!("{} '{}'", id, task.summary),
+                        None => format!("{} '{}'", task.uuid, task.summary),
+                    },
+                    None => uuid.to_string(),
+                },
+            )
+            .collect::<Vec<String>>()
+            .join(" -> ");
+        Err(format!(
+            "Circular dependency: {}. Tasks cannot depend on each other.",
+            description
+        ))
+    }
+
+    /// Find a chain of dependencies going from the task @from to the task @to
+    fn find_dependency_path(
+        &self,
+        from: &Uuid,
+        to: &Uuid,
+        visited: &mut HashSet<Uuid>,
+    ) -> Option<Vec<Uuid>> {
+        let task = self
+            .tasks
+            .get(from)
+            .or_else(|| self.extra_tasks.get(from))?;
+        for dep_uuid in task.get_depends() {
+            if dep_uuid == to {
+                return Some(vec![*from, *to]);
+            }
+            if visited.insert(*dep_uuid)
+                && let Some(mut path) = self.find_dependency_path(dep_uuid, to, visited)
+            {
+                path.insert(0, *from);
+                return Some(path);
+            }
+        }
+        None
     }
 
     pub fn get_owned(&self, uuid: &Uuid) -> Option<Task> {
@@ -656,57 +1352,100 @@ impl TaskData {
         &self.undos
     }
 
-    pub fn task_done(&mut self, uuid: &Uuid) {
-        self.tasks.get_mut(uuid).unwrap().done();
+    /// Complete the task with the given UUID. If it was a recurring task, its next
+    /// instance is added to the tasks and returned. Its ID only holds until the next
+    /// upkeep, which renumbers the tasks now that the completed one has none.
+    pub fn task_done(&mut self, uuid: &Uuid) -> Option<&Task> {
+        let mut next_instance = self.tasks.get_mut(uuid).unwrap().done(self.clock.now())?;
+        self.max_id += 1;
+        next_instance.id = Some(self.max_id);
+
+        let next_uuid = next_instance.uuid;
+        self.tasks.insert(next_uuid, next_instance);
+        self.tasks.get(&next_uuid)
     }
 
     pub fn task_delete(&mut self, uuid: &Uuid) {
-        self.tasks.get_mut(uuid).unwrap().delete();
+        self.tasks.get_mut(uuid).unwrap().delete(self.clock.now());
     }
 
-    /// Turns the ID to UUIDs in the depends_on vector of TaskProperties
+    /// Turns the ID to UUIDs in the links and in the parent of TaskProperties
     /// This also copies the TaskProperties to a owned object
-    fn update_task_property_depends_on(
+    fn update_task_property_identifiers(
         &self,
         props: &TaskProperties,
     ) -> Result<TaskProperties, String> {
-        if props.depends_on.is_none() {
-            return Ok(props.clone());
-        }
-
-        // Update the depends_on vector from the ID to use UUID instead
         let mut my_props: TaskProperties = props.clone();
-        let mut new_depends_on = Vec::<DependsOnIdentifier>::new();
-
-        if let Some(deps) = &my_props.depends_on {
-            for dep in deps {
-                match dep {
-                    DependsOnIdentifier::Uuid(uuid) => {
-                        new_depends_on.push(DependsOnIdentifier::Uuid(uuid.to_owned()))
+        my_props.links = props
+            .links
+            .iter()
+            .map(|change| {
+                Ok(match change {
+                    LinkChange::Add(kind, target) => {
+                        LinkChange::Add(*kind, self.to_uuid_identifier(target)?)
                     }
-                    DependsOnIdentifier::Usize(id) => {
-                        new_depends_on.push(DependsOnIdentifier::Uuid(
-                            self.id_to_uuid
-                                .get(id)
-                                .ok_or(format!(
-                                    "The given id {} doesn't correspond to any known task.",
-                                    &id
-                                ))?
-                                .to_owned(),
-                        ));
+                    LinkChange::Remove(kind, target) => {
+                        LinkChange::Remove(*kind, self.to_uuid_identifier(target)?)
                     }
-                }
-            }
+                    LinkChange::Clear(kind) => LinkChange::Clear(*kind),
+                })
+            })
+            .collect::<Result<_, String>>()?;
+        if let Some(Some(parent)) = &props.parent {
+            my_props.parent = Some(Some(self.to_uuid_identifier(parent)?));
         }
-        my_props.depends_on = Some(new_depends_on);
         Ok(my_props)
     }
 
+    fn to_uuid_identifier(&self, identifier: &TaskIdentifier) -> Result<TaskIdentifier, String> {
+        match identifier {
+            TaskIdentifier::Uuid(uuid) => Ok(TaskIdentifier::Uuid(uuid.to_owned())),
+            TaskIdentifier::Usize(id) => Ok(TaskIdentifier::Uuid(
+                self.id_to_uuid
+                    .get(id)
+                    .ok_or(format!(
+                        "The given id {} doesn't correspond to any known task.",
+                        &id
+                    ))?
+                    .to_owned(),
+            )),
+        }
+    }
+
+    /// The UUID of the parent of the task with @uuid, if every subtask of this parent
+    /// is completed while the parent itself is still pending or active
+    pub fn get_parent_to_complete(&self, uuid: &Uuid) -> Option<Uuid> {
+        let get_task = |uuid: &Uuid| self.tasks.get(uuid).or_else(|| self.extra_tasks.get(uuid));
+        let parent = get_task(uuid)?
+            .parent
+            .and_then(|parent| get_task(&parent))?;
+        if !matches!(parent.status, TaskStatus::Pending | TaskStatus::Active) {
+            return None;
+        }
+        // A subtask that is not loaded is not known to be completed
+        let all_completed = !parent.sub.is_empty()
+            && parent
+                .sub
+                .iter()
+                .all(|child| get_task(child).is_some_and(|t| t.status == TaskStatus::Completed));
+        all_completed.then_some(parent.uuid)
+    }
+
+    /// Turn the extra task with @uuid into one of the tasks of this TaskData, so
+    /// that the changes made to it are kept
+    pub fn promote_extra_task(&mut self, uuid: &Uuid) {
+        if let Some(task) = self.extra_tasks.remove(uuid) {
+            self.tasks.insert(*uuid, task);
+        }
+    }
+
     pub fn upkeep(&mut self) -> Result<(), String> {
         let mut vec: Vec<_> = self.tasks.values().by_ref().collect();
 
-        // Set the ID of the tasks by sorting them by date_created
-        vec.sort_by(|lhs, rhs| lhs.date_created.cmp(&rhs.date_created));
+        // Set the ID of the tasks by sorting them by date_created. The tasks created at
+        // the same time keep the order of their previous IDs, the new ones coming last,
+        // so that the IDs do not change from one run to the next.
+        vec.sort_by_key(|t| (t.date_created, t.id.is_none(), t.id, t.uuid));
         let uuids: Vec<Uuid> = vec.iter().map(|t| t.uuid).collect();
         let mut i = 1;
         for cur_uuid in uuids {
@@ -722,97 +1461,156 @@ impl TaskData {
             }
         }
 
+        let now = self.clock.now();
         for t in self.tasks.values_mut() {
-            t.compute_urgency()?;
+            t.compute_urgency(now)?;
         }
 
-        // Update dependency status if a depended class is done / deleted
-
-        let mut task_depends_to_update = HashMap::<Uuid, Vec<Uuid>>::default();
+        // Drop the dependencies on the tasks that are done / deleted
+        let mut finished_dependencies = HashMap::<Uuid, Vec<Uuid>>::default();
         for task in self.tasks.values() {
-            let mut dependencies_to_update = HashSet::<Uuid>::default();
-            let deps_set_before: HashSet<Uuid> = task.depends_on.iter().cloned().collect();
-            for dep_uuid in &deps_set_before {
-                if let Some(t) = self.tasks.get(dep_uuid) {
-                    match t.status {
-                        TaskStatus::Pending | TaskStatus::Active => {}
-                        TaskStatus::Completed | TaskStatus::Deleted => {
-                            dependencies_to_update.insert(*dep_uuid);
-                        }
-                    }
-                } else if let Some(t) = self.extra_tasks.get(dep_uuid) {
-                    match t.status {
-                        TaskStatus::Pending | TaskStatus::Active => {}
-                        TaskStatus::Completed | TaskStatus::Deleted => {
-                            dependencies_to_update.insert(*dep_uuid);
-                        }
+            for (kind, uuids) in &task.links {
+                for linked_uuid in uuids {
+                    let Some(t) = self
+                        .tasks
+                        .get(linked_uuid)
+                        .or_else(|| self.extra_tasks.get(linked_uuid))
+                    else {
+                        trace!("We have {} task", self.tasks.len());
+                        trace!("tasks are: {:?}", self.tasks);
+                        return Err(match kind {
+                            LinkType::DependsOn => format!(
+                                "The task '{}' ({}) depends on the task {}, which does not exist.",
+                                task.summary, task.uuid, linked_uuid
+                            ),
+                            _ => format!(
+                                "The task '{}' ({}) has a '{}' link to the task {}, which does not exist.",
+                                task.summary, task.uuid, kind, linked_uuid
+                            ),
+                        });
+                    };
+                    if *kind == LinkType::DependsOn
+                        && matches!(t.status, TaskStatus::Completed | TaskStatus::Deleted)
+                    {
+                        finished_dependencies
+                            .entry(task.uuid)
+                            .or_default()
+                            .push(*linked_uuid);
                     }
-                } else {
-                    trace!("We have {} task", self.tasks.len());
-                    trace!("tasks are: {:?}", self.tasks);
-                    unreachable!(
-                        "We were unable to find the task associated with uuid {:?} during upkeep phase",
-                        dep_uuid
-                    );
                 }
             }
-
-            let deps_set_after: HashSet<Uuid> = deps_set_before
-                .difference(&dependencies_to_update)
-                .cloned()
-                .collect();
-            task_depends_to_update.insert(task.uuid, deps_set_after.into_iter().collect());
         }
-        task_depends_to_update
-            .into_iter()
-            .for_each(|(task_uuid, deps_uuids)| {
-                self.tasks.get_mut(&task_uuid).unwrap().depends_on = deps_uuids;
-            });
+        for (task_uuid, finished) in finished_dependencies {
+            self.tasks
+                .get_mut(&task_uuid)
+                .unwrap()
+                .retain_links(LinkType::DependsOn, |uuid| !finished.contains(uuid));
+        }
 
-        // Add the blocking UUID when being referred by depends_on
-        let mut blockers_uuid = HashMap::new();
+        // Rebuild the backlinks of each task from the links of the other tasks
+        let mut backlinks = HashMap::<Uuid, BTreeMap<LinkType, Vec<Uuid>>>::default();
         for task in self.tasks.values() {
-            for blocking_uuid in &task.depends_on {
-                blockers_uuid.insert(blocking_uuid.to_owned(), task.uuid.to_owned());
+            for (kind, uuids) in &task.links {
+                for linked_uuid in uuids {
+                    backlinks
+                        .entry(*linked_uuid)
+                        .or_default()
+                        .entry(*kind)
+                        .or_default()
+                        .push(task.uuid);
+                }
             }
         }
-        for (blocker_uuid, blocked_uuid) in blockers_uuid {
-            let t = self.tasks.get_mut(&blocker_uuid).unwrap();
-            t.blocking.push(blocked_uuid);
-            t.blocking.sort_unstable();
-            t.blocking.dedup();
+        for task in self.tasks.values_mut() {
+            let mut task_backlinks = backlinks.remove(&task.uuid).unwrap_or_default();
+            // The dependencies on a finished task are dropped, it keeps the tasks it
+            // was blocking
+            if matches!(task.status, TaskStatus::Completed | TaskStatus::Deleted) {
+                task_backlinks
+                    .entry(LinkType::DependsOn)
+                    .or_default()
+                    .extend(task.get_blocking());
+            }
+            task_backlinks.retain(|_, uuids| !uuids.is_empty());
+            for uuids in task_backlinks.values_mut() {
+                uuids.sort_unstable();
+                uuids.dedup();
+            }
+            task.backlinks = task_backlinks;
         }
 
-        // Update blocking status for tasks
-        // For each blocking task, this looks up the tasks depending on it and keeps
-        // the blocking list up to date.
-        let blockers_uuid: Vec<_> = self
-            .tasks
-            .values()
-            .filter(|t| {
-                !t.blocking.is_empty()
-                    && t.status != TaskStatus::Deleted
-                    && t.status != TaskStatus::Completed
+        // Rebuild the subtasks of each task from the parent of the other tasks
+        let mut subtasks = HashMap::<Uuid, Vec<&Task>>::default();
+        for task in self.tasks.values() {
+            let Some(parent_uuid) = &task.parent else {
+                continue;
+            };
+            if !self.tasks.contains_key(parent_uuid) && !self.extra_tasks.contains_key(parent_uuid)
+            {
+                return Err(format!(
+                    "The task '{}' ({}) is a subtask of the task {}, which does not exist.",
+                    task.summary, task.uuid, parent_uuid
+                ));
+            }
+            if task.status != TaskStatus::Deleted {
+                subtasks.entry(*parent_uuid).or_default().push(task);
+            }
+        }
+        let subtasks: HashMap<Uuid, (Vec<Uuid>, Option<u8>)> = subtasks
+            .into_iter()
+            .map(|(parent_uuid, mut children)| {
+                children.sort_by_key(|t| (t.date_created, t.uuid));
+                let completed = children
+                    .iter()
+                    .filter(|t| t.status == TaskStatus::Completed)
+                    .count();
+                let progress = (completed * 100 / children.len()) as u8;
+                let uuids = children.iter().map(|t| t.uuid).collect();
+                (parent_uuid, (uuids, Some(progress)))
             })
-            .map(|t| t.uuid)
             .collect();
-        for blocker_uuid in blockers_uuid {
-            let mut blocker_task = self.tasks.get_mut(&blocker_uuid).unwrap().to_owned();
-            let mut new_blocking_uuids = Vec::new();
+        for task in self.tasks.values_mut() {
+            (task.sub, task.progress) = subtasks.get(&task.uuid).cloned().unwrap_or_default();
+        }
 
-            for blocked_uuid in &blocker_task.blocking {
-                let blocked_task = self.tasks.get(blocked_uuid).unwrap();
+        Ok(())
+    }
 
-                if blocked_task.depends_on.contains(&blocker_uuid) {
-                    new_blocking_uuids.push(*blocked_uuid);
+    /// Remove the links and backlinks that refer to a task which
+    /// doesn't exist. Return a description of every link that was removed.
+    pub fn repair_dangling_links(&mut self) -> Vec<String> {
+        let existing: HashSet<Uuid> = self
+            .tasks
+            .keys()
+            .chain(self.extra_tasks.keys())
+            .cloned()
+            .collect();
+        let mut repairs = Vec::new();
+        let mut uuids: Vec<Uuid> = self.tasks.keys().cloned().collect();
+        uuids.sort_by_key(|uuid| (self.tasks[uuid].date_created, *uuid));
+        for uuid in uuids {
+            let task = self.tasks.get_mut(&uuid).unwrap();
+            for (direction, links) in [("to", &mut task.links), ("from", &mut task.backlinks)] {
+                for (kind, uuids) in links.iter_mut() {
+                    for missing in uuids.iter().filter(|u| !existing.contains(u)) {
+                        repairs.push(format!(
+                            "The task '{}' ({}) had a '{}' link {} the missing task {}.",
+                            task.summary, task.uuid, kind, direction, missing
+                        ));
+                    }
+                    uuids.retain(|u| existing.contains(u));
                 }
+                links.retain(|_, uuids| !uuids.is_empty());
+            }
+            if let Some(missing) = task.parent.filter(|u| !existing.contains(u)) {
+                repairs.push(format!(
+                    "The task '{}' ({}) was a subtask of the missing task {}.",
+                    task.summary, task.uuid, missing
+                ));
+                task.parent = None;
             }
-            blocker_task.blocking = new_blocking_uuids;
-            self.tasks
-                .insert(blocker_task.uuid.to_owned(), blocker_task);
         }
-
-        Ok(())
+        repairs
     }
 
     #[allow(clippy::borrowed_box)]
@@ -822,24 +1620,55 @@ impl TaskData {
             ..TaskData::clone(self)
         };
 
-        let mut extra_tasks = Vec::new();
         for (key, task) in &self.tasks {
             if filter.validate_task(task) {
                 new_data.tasks.insert(key.to_owned(), task.to_owned());
-                for uuid_dep in &task.depends_on {
-                    extra_tasks.push(self.tasks.get(uuid_dep).unwrap());
-                }
-                for uuid_dep in &task.blocking {
-                    extra_tasks.push(self.tasks.get(uuid_dep).unwrap());
-                }
             }
         }
 
-        for task in extra_tasks {
-            new_data.extra_tasks.insert(task.uuid, task.to_owned());
+        let selected: Vec<Uuid> = new_data.tasks.keys().cloned().collect();
+        new_data.insert_linked_extra_tasks(self, &selected);
+        new_data
+    }
+
+    /// Add the tasks of @all_tasks with the given @uuids as extra tasks, along with
+    /// every task that can be reached from them by following the dependencies and
+    /// the blocked tasks. The tasks that are already loaded are kept as they are.
+    pub fn insert_linked_extra_tasks(&mut self, all_tasks: &TaskData, uuids: &[Uuid]) {
+        for uuid in all_tasks.get_linked_closure(uuids) {
+            if self.tasks.contains_key(&uuid) || self.extra_tasks.contains_key(&uuid) {
+                continue;
+            }
+            if let Some(task) = all_tasks
+                .tasks
+                .get(&uuid)
+                .or_else(|| all_tasks.extra_tasks.get(&uuid))
+            {
+                self.extra_tasks.insert(uuid, task.to_owned());
+            }
         }
+    }
 
-        new_data
+    /// The UUIDs of the tasks linked to the ones with @uuids, directly or through
+    /// other tasks, including @uuids. The links to missing tasks are ignored.
+    fn get_linked_closure(&self, uuids: &[Uuid]) -> HashSet<Uuid> {
+        let mut closure: HashSet<Uuid> = uuids.iter().cloned().collect();
+        let mut to_visit: Vec<Uuid> = uuids.to_vec();
+        while let Some(uuid) = to_visit.pop() {
+            let Some(task) = self
+                .tasks
+                .get(&uuid)
+                .or_else(|| self.extra_tasks.get(&uuid))
+            else {
+                continue;
+            };
+            for linked in task.get_extra_uuid() {
+                if closure.insert(linked) {
+                    to_visit.push(linked);
+                }
+            }
+        }
+        closure
     }
 
     pub fn add_task(
@@ -862,12 +1691,15 @@ impl TaskData {
             TaskStatus::Completed | TaskStatus::Deleted => None,
         };
 
+        let now = self.clock.now();
         let date_completed: Option<DateTime<chrono::Local>> = match status {
             TaskStatus::Pending | TaskStatus::Active => None,
-            TaskStatus::Completed | TaskStatus::Deleted => Some(Local::now()),
+            TaskStatus::Completed | TaskStatus::Deleted => Some(now),
         };
 
-        let date_due = props.date_due.as_ref().map(|date| date.to_owned());
+        let date_due = props.date_due.flatten();
+        let date_wait = props.date_wait.flatten();
+        let date_scheduled = props.date_scheduled.flatten();
 
         let project = props.project.to_owned();
 
@@ -881,25 +1713,33 @@ impl TaskData {
             None => Vec::default(),
         };
 
-        let depends_on = match &props.depends_on {
-            Some(_) => {
-                let my_props = self.update_task_property_depends_on(props)?;
-                let mut deps_uuid: Vec<Uuid> = Vec::new();
-                for item in my_props.depends_on.unwrap() {
-                    match item {
-                        DependsOnIdentifier::Usize(_) => {
-                            unreachable!(
-                                "We should not have a usize here. \
-                            We should have converted it to a UUID before applying \
-                            the properties to the task."
-                            );
-                        }
-                        DependsOnIdentifier::Uuid(item_uuid) => deps_uuid.push(item_uuid),
+        let mut links = BTreeMap::<LinkType, Vec<Uuid>>::new();
+        for change in self.update_task_property_identifiers(props)?.links {
+            match change {
+                LinkChange::Add(kind, target) => {
+                    let uuids = links.entry(kind).or_default();
+                    let uuid = resolved_uuid(&target);
+                    if !uuids.contains(&uuid) {
+                        uuids.push(uuid);
                     }
                 }
-                deps_uuid
+                LinkChange::Remove(kind, target) => {
+                    let uuid = resolved_uuid(&target);
+                    links.entry(kind).or_default().retain(|u| *u != uuid);
+                }
+                LinkChange::Clear(kind) => {
+                    links.remove(&kind);
+                }
             }
-            None => Vec::default(),
+        }
+        links.retain(|_, uuids| !uuids.is_empty());
+
+        let parent = match &props.parent {
+            Some(Some(parent)) => match self.to_uuid_identifier(parent)? {
+                TaskIdentifier::Uuid(uuid) => Some(uuid),
+                TaskIdentifier::Usize(_) => unreachable!("The ID was converted to a UUID"),
+            },
+            _ => None,
         };
 
         let t = Task {
@@ -908,11 +1748,22 @@ impl TaskData {
             status,
             tags,
             uuid: Uuid::new_v4(),
-            date_created: Local::now(),
+            date_created: now,
             date_completed,
             date_due,
+            date_wait,
+            date_scheduled,
             project,
-            depends_on,
+            links,
+            parent,
+            description: props.description.to_owned().flatten(),
+            recurrence: props.recurrence.to_owned().flatten(),
+            priority: props.priority.flatten(),
+            udas: props
+                .udas
+                .iter()
+                .filter_map(|(name, value)| Some((name.to_owned(), value.to_owned()?)))
+                .collect(),
             ..Task::default()
         };
         let owned_uuid = t.get_uuid().to_owned();
@@ -927,7 +1778,7 @@ impl Serialize for TaskData {
         S: Serializer,
     {
         let mut tasks: Vec<&Task> = self.tasks.values().collect();
-        tasks.sort_by(|lhs, rhs| lhs.date_created.cmp(&rhs.date_created));
+        tasks.sort_by_key(|t| (t.date_created, t.uuid));
         tasks.serialize(serializer)
     }
 }
@@ -938,8 +1789,13 @@ impl<'de> Deserialize<'de> for TaskData {
         D: Deserializer<'de>,
     {
         let tasks: Vec<Task> = Deserialize::deserialize(deserializer)?;
+        Ok(tasks.into_iter().collect())
+    }
+}
 
-        let task_map: HashMap<Uuid, Task> = tasks
+impl FromIterator<Task> for TaskData {
+    fn from_iter<I: IntoIterator<Item = Task>>(iter: I) -> Self {
+        let task_map: HashMap<Uuid, Task> = iter
             .into_iter()
             .map(|t| (t.get_uuid().to_owned(), t))
             .collect();
@@ -949,11 +1805,11 @@ impl<'de> Deserialize<'de> for TaskData {
             .max()
             .unwrap_or(0);
 
-        Ok(TaskData {
+        TaskData {
             tasks: task_map,
             max_id,
             ..TaskData::default()
-        })
+        }
     }
 }
 
@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Local};
+use uuid::Uuid;
+
+use super::{
+    LinkType, Project, Task, TaskAnnotation, TaskData, TaskHistory, TaskPriority, TaskStatus,
+};
+
+/// Fields of a task coming from another application, e.g. Taskwarrior.
+///
+/// The UUID of the task is kept, so that importing the same task again updates
+/// the bee task instead of creating a duplicate.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ImportedTask {
+    pub uuid: Uuid,
+    pub summary: String,
+    pub status: TaskStatus,
+    pub date_created: DateTime<Local>,
+    pub date_completed: Option<DateTime<Local>>,
+    pub date_due: Option<DateTime<Local>>,
+    pub date_wait: Option<DateTime<Local>>,
+    pub date_scheduled: Option<DateTime<Local>>,
+    pub project: Option<Project>,
+    pub priority: Option<TaskPriority>,
+    pub tags: Vec<String>,
+    pub annotations: Vec<TaskAnnotation>,
+    pub depends_on: Vec<Uuid>,
+    /// Fields without an equivalent in bee, as JSON values
+    pub imported_fields: BTreeMap<String, String>,
+}
+
+/// What importing a task did to the tasks of a `TaskData`
+#[derive(Debug, PartialEq)]
+pub enum ImportOutcome {
+    Created,
+    /// The task already existed, it is given in the state it was before the import
+    Updated(Box<Task>),
+    Unchanged,
+}
+
+impl TaskData {
+    /// Create the task described by @imported, or update the existing task with
+    /// the same UUID. The existing task can either be one of the tasks or one of
+    /// the extra tasks.
+    pub fn import_task(&mut self, imported: ImportedTask) -> ImportOutcome {
+        let existing = self
+            .tasks
+            .get(&imported.uuid)
+            .or_else(|| self.extra_tasks.get(&imported.uuid))
+            .cloned();
+
+        let mut task = existing.to_owned().unwrap_or_default();
+        task.uuid = imported.uuid;
+        task.summary = imported.summary;
+        task.status = imported.status;
+        task.date_created = imported.date_created;
+        task.date_completed = imported.date_completed;
+        task.date_due = imported.date_due;
+        task.date_wait = imported.date_wait;
+        task.date_scheduled = imported.date_scheduled;
+        task.project = imported.project;
+        task.priority = imported.priority;
+        task.tags = imported.tags;
+        task.annotations = imported.annotations;
+        if imported.depends_on.is_empty() {
+            task.links.remove(&LinkType::DependsOn);
+        } else {
+            task.links.insert(LinkType::DependsOn, imported.depends_on);
+        }
+        task.imported_fields = imported.imported_fields;
+
+        let outcome = match existing {
+            Some(before) if before == task => return ImportOutcome::Unchanged,
+            Some(before) => {
+                task.history.push(TaskHistory {
+                    time: self.clock.now(),
+                    value: "Task updated by an import".to_string(),
+                });
+                ImportOutcome::Updated(Box::new(before))
+            }
+            None => ImportOutcome::Created,
+        };
+
+        self.extra_tasks.remove(&task.uuid);
+        self.tasks.insert(task.uuid, task);
+        outcome
+    }
+}
@@ -0,0 +1,99 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::TaskIdentifier;
+
+/// The kind of relation a link makes between two tasks. A link goes from the
+/// task holding it to another task, the other task sees it as a backlink.
+///
+/// It is (de)serialised as the name the user gives to `link:`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LinkType {
+    /// The task cannot be done before the linked task. The linked task is
+    /// blocking this task.
+    DependsOn,
+    RelatesTo,
+    Duplicates,
+    FollowsUp,
+}
+
+impl LinkType {
+    pub const ALL: [LinkType; 4] = [
+        LinkType::DependsOn,
+        LinkType::RelatesTo,
+        LinkType::Duplicates,
+        LinkType::FollowsUp,
+    ];
+
+    pub fn from_string(input: &str) -> Result<LinkType, String> {
+        LinkType::ALL
+            .into_iter()
+            .find(|kind| kind.to_string() == input.to_lowercase())
+            .ok_or(format!(
+                "Invalid link type '{}'. Valid link types are {}",
+                input,
+                LinkType::ALL
+                    .iter()
+                    .map(|kind| format!("'{}'", kind))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ))
+    }
+
+    /// A link of a symmetric type means the same thing from both of its ends
+    pub fn is_symmetric(&self) -> bool {
+        matches!(self, LinkType::RelatesTo)
+    }
+
+    /// How the link is described from the task holding it
+    pub fn label(&self) -> &'static str {
+        match self {
+            LinkType::DependsOn => "Depends on",
+            LinkType::RelatesTo => "Relates to",
+            LinkType::Duplicates => "Duplicates",
+            LinkType::FollowsUp => "Follows up",
+        }
+    }
+
+    /// How the link is described from the linked task
+    pub fn inverse_label(&self) -> &'static str {
+        match self {
+            LinkType::DependsOn => "Blocking",
+            LinkType::RelatesTo => "Relates to",
+            LinkType::Duplicates => "Duplicated by",
+            LinkType::FollowsUp => "Followed up by",
+        }
+    }
+}
+
+impl fmt::Display for LinkType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LinkType::DependsOn => write!(f, "depends-on"),
+            LinkType::RelatesTo => write!(f, "relates-to"),
+            LinkType::Duplicates => write!(f, "duplicates"),
+            LinkType::FollowsUp => write!(f, "follows-up"),
+        }
+    }
+}
+
+/// A change to the links of a task, as given by `link:`, `unlink:` or `depends:`
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum LinkChange {
+    Add(LinkType, TaskIdentifier),
+    Remove(LinkType, TaskIdentifier),
+    /// Remove every link of this type
+    Clear(LinkType),
+}
+
+impl LinkChange {
+    /// The task the link goes to, if the change is about a single link
+    pub fn get_target(&self) -> Option<&TaskIdentifier> {
+        match self {
+            LinkChange::Add(_, target) | LinkChange::Remove(_, target) => Some(target),
+            LinkChange::Clear(_) => None,
+        }
+    }
+}
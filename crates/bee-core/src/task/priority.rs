@@ -0,0 +1,49 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// How important a task is. Priorities are ordered from `Low` to `High`.
+///
+/// It is (de)serialised as the letter the user gives to `priority:`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum TaskPriority {
+    #[serde(rename = "L")]
+    Low,
+    #[serde(rename = "M")]
+    Medium,
+    #[serde(rename = "H")]
+    High,
+}
+
+impl TaskPriority {
+    pub fn from_string(input: &str) -> Result<TaskPriority, String> {
+        match input.to_lowercase().as_str() {
+            "h" | "high" => Ok(TaskPriority::High),
+            "m" | "medium" => Ok(TaskPriority::Medium),
+            "l" | "low" => Ok(TaskPriority::Low),
+            _ => Err(format!(
+                "Invalid priority '{}'. Valid priorities are 'H', 'M' and 'L'",
+                input
+            )),
+        }
+    }
+
+    /// Urgency coefficient used when none is given in the configuration
+    pub fn default_coefficient(&self) -> i64 {
+        match self {
+            TaskPriority::High => 6,
+            TaskPriority::Medium => 4,
+            TaskPriority::Low => 2,
+        }
+    }
+}
+
+impl fmt::Display for TaskPriority {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TaskPriority::High => write!(f, "H"),
+            TaskPriority::Medium => write!(f, "M"),
+            TaskPriority::Low => write!(f, "L"),
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use std::fmt;
+
+use chrono::{DateTime, Datelike, Days, Local, Months, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::parser::{
+    matches_day_string, matches_month_string, matches_week_string, matches_year_string,
+    weekday_from_string,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RecurrenceUnit {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl RecurrenceUnit {
+    fn from_string(input: &str) -> Result<RecurrenceUnit, String> {
+        match input {
+            _ if matches_day_string(input) => Ok(RecurrenceUnit::Day),
+            _ if matches_week_string(input) => Ok(RecurrenceUnit::Week),
+            _ if matches_month_string(input) => Ok(RecurrenceUnit::Month),
+            _ if matches_year_string(input) => Ok(RecurrenceUnit::Year),
+            _ => Err(format!("Invalid recurrence unit '{}'", input)),
+        }
+    }
+
+    /// Add `count` of this unit to `time`. Months and years follow the calendar,
+    /// so adding one month to January 31st lands on the last day of February.
+    fn add_to(&self, time: DateTime<Local>, count: u32) -> Option<DateTime<Local>> {
+        match self {
+            RecurrenceUnit::Day => time.checked_add_days(Days::new(count.into())),
+            RecurrenceUnit::Week => time.checked_add_days(Days::new(7 * u64::from(count))),
+            RecurrenceUnit::Month => time.checked_add_months(Months::new(count)),
+            RecurrenceUnit::Year => time.checked_add_months(Months::new(12 * count)),
+        }
+    }
+}
+
+impl fmt::Display for RecurrenceUnit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RecurrenceUnit::Day => write!(f, "d"),
+            RecurrenceUnit::Week => write!(f, "w"),
+            RecurrenceUnit::Month => write!(f, "mo"),
+            RecurrenceUnit::Year => write!(f, "y"),
+        }
+    }
+}
+
+/// Describe when the next instance of a recurring task is due.
+///
+/// It is (de)serialised as the same string the user gives to `recur:`, e.g.
+/// `weekly`, `2w`, `mon,thu`, `1st,15th` or `after:3d`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Recurrence {
+    /// Every `count` units after the previous due date
+    Every { count: u32, unit: RecurrenceUnit },
+    /// On each of the given days of the week
+    Weekdays(Vec<Weekday>),
+    /// On each of the given days of the month. Months that do not have
+    /// such a day are skipped.
+    DaysOfMonth(Vec<u32>),
+    /// `count` units after the previous instance was completed
+    AfterCompletion { count: u32, unit: RecurrenceUnit },
+}
+
+const WORKING_DAYS: [Weekday; 5] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+];
+
+fn ordinal_suffix(day: u32) -> &'static str {
+    match (day % 10, day % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    }
+}
+
+fn day_of_month_from_string(input: &str) -> Result<u32, String> {
+    let digits_len = input.chars().take_while(|c| c.is_ascii_digit()).count();
+    let (number, suffix) = input.split_at(digits_len);
+    let day = number
+        .parse::<u32>()
+        .map_err(|_| format!("Invalid day of the month '{}'", input))?;
+    if !(1..=31).contains(&day) || suffix != ordinal_suffix(day) {
+        return Err(format!("Invalid day of the month '{}'", input));
+    }
+    Ok(day)
+}
+
+/// Parse expressions such as `3d` or `2 weeks` into a count and a unit
+fn count_and_unit_from_string(input: &str) -> Result<(u32, RecurrenceUnit), String> {
+    let digits_len = input.chars().take_while(|c| c.is_ascii_digit()).count();
+    let (number, unit) = input.split_at(digits_len);
+    let count = number
+        .parse::<u32>()
+        .map_err(|_| format!("Invalid recurrence '{}'", input))?;
+    if count == 0 {
+        return Err(format!(
+            "Invalid recurrence '{}': the interval cannot be 0",
+            input
+        ));
+    }
+    Ok((count, RecurrenceUnit::from_string(unit.trim())?))
+}
+
+impl Recurrence {
+    pub fn from_string(input: &str) -> Result<Recurrence, String> {
+        let input = input.trim().to_lowercase();
+        match input.as_str() {
+            "daily" => {
+                return Ok(Recurrence::Every {
+                    count: 1,
+                    unit: RecurrenceUnit::Day,
+                });
+            }
+            "weekly" => {
+                return Ok(Recurrence::Every {
+                    count: 1,
+                    unit: RecurrenceUnit::Week,
+                });
+            }
+            "monthly" => {
+                return Ok(Recurrence::Every {
+                    count: 1,
+                    unit: RecurrenceUnit::Month,
+                });
+            }
+            "yearly" => {
+                return Ok(Recurrence::Every {
+                    count: 1,
+                    unit: RecurrenceUnit::Year,
+                });
+            }
+            "weekdays" => return Ok(Recurrence::Weekdays(WORKING_DAYS.to_vec())),
+            _ => {}
+        }
+
+        if let Some(duration) = input.strip_prefix("after:") {
+            let (count, unit) = count_and_unit_from_string(duration)?;
+            return Ok(Recurrence::AfterCompletion { count, unit });
+        }
+
+        if input.starts_with(|c: char| c.is_ascii_digit()) && !input.contains(',') {
+            // Either a single day of the month ('15th') or an interval ('2w')
+            if let Ok(day) = day_of_month_from_string(&input) {
+                return Ok(Recurrence::DaysOfMonth(vec![day]));
+            }
+            let (count, unit) = count_and_unit_from_string(&input)?;
+            return Ok(Recurrence::Every { count, unit });
+        }
+
+        let items: Vec<&str> = input.split(',').map(|s| s.trim()).collect();
+        if items
+            .iter()
+            .all(|s| s.starts_with(|c: char| c.is_ascii_digit()))
+        {
+            let mut days = items
+                .into_iter()
+                .map(day_of_month_from_string)
+                .collect::<Result<Vec<u32>, String>>()?;
+            days.sort_unstable();
+            days.dedup();
+            return Ok(Recurrence::DaysOfMonth(days));
+        }
+
+        let mut weekdays = items
+            .into_iter()
+            .map(weekday_from_string)
+            .collect::<Result<Vec<Weekday>, String>>()
+            .map_err(|e| format!("Invalid recurrence '{}'. {}", input, e))?;
+        weekdays.sort_unstable_by_key(|d| d.num_days_from_monday());
+        weekdays.dedup();
+        Ok(Recurrence::Weekdays(weekdays))
+    }
+
+    /// Compute when the instance following the one due at `previous_due` should be due,
+    /// given that the previous instance was completed at `completed`.
+    ///
+    /// Occurrences that fall before the completion time are skipped, so completing
+    /// a daily task three days late doesn't create a backlog of overdue instances.
+    pub fn next_due(
+        &self,
+        previous_due: DateTime<Local>,
+        completed: DateTime<Local>,
+    ) -> Option<DateTime<Local>> {
+        match self {
+            Recurrence::Every { count, unit } => {
+                let mut next = unit.add_to(previous_due, *count)?;
+                while next <= completed {
+                    next = unit.add_to(next, *count)?;
+                }
+                Some(next)
+            }
+            Recurrence::AfterCompletion { count, unit } => unit.add_to(completed, *count),
+            Recurrence::Weekdays(days) => {
+                Self::next_matching_day(previous_due, completed, |d| days.contains(&d.weekday()))
+            }
+            Recurrence::DaysOfMonth(days) => {
+                Self::next_matching_day(previous_due, completed, |d| days.contains(&d.day()))
+            }
+        }
+    }
+
+    fn next_matching_day<F>(
+        previous_due: DateTime<Local>,
+        completed: DateTime<Local>,
+        matches: F,
+    ) -> Option<DateTime<Local>>
+    where
+        F: Fn(&DateTime<Local>) -> bool,
+    {
+        let mut next = previous_due.checked_add_days(Days::new(1))?;
+        while !matches(&next) || next <= completed {
+            next = next.checked_add_days(Days::new(1))?;
+        }
+        Some(next)
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Recurrence::Every { count: 1, unit } => match unit {
+                RecurrenceUnit::Day => write!(f, "daily"),
+                RecurrenceUnit::Week => write!(f, "weekly"),
+                RecurrenceUnit::Month => write!(f, "monthly"),
+                RecurrenceUnit::Year => write!(f, "yearly"),
+            },
+            Recurrence::Every { count, unit } => write!(f, "{}{}", count, unit),
+            Recurrence::Weekdays(days) if days.as_slice() == WORKING_DAYS => {
+                write!(f, "weekdays")
+            }
+            Recurrence::Weekdays(days) => write!(
+                f,
+                "{}",
+                days.iter()
+                    .map(|d| d.to_string().to_lowercase())
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ),
+            Recurrence::DaysOfMonth(days) => write!(
+                f,
+                "{}",
+                days.iter()
+                    .map(|d| format!("{}{}", d, ordinal_suffix(*d)))
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ),
+            Recurrence::AfterCompletion { count, unit } => write!(f, "after:{}{}", count, unit),
+        }
+    }
+}
+
+impl TryFrom<String> for Recurrence {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Recurrence::from_string(&value)
+    }
+}
+
+impl From<Recurrence> for String {
+    fn from(value: Recurrence) -> Self {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+#[path = "recurrence_test.rs"]
+mod recurrence_test;
@@ -0,0 +1,115 @@
+use all_asserts::assert_true;
+use chrono::TimeZone;
+
+use super::*;
+
+fn local_time(year: i32, month: u32, day: u32, hour: u32) -> DateTime<Local> {
+    Local
+        .with_ymd_and_hms(year, month, day, hour, 0, 0)
+        .single()
+        .unwrap()
+}
+
+#[test]
+fn test_recurrence_from_string() {
+    assert_eq!(
+        Recurrence::from_string("weekly"),
+        Ok(Recurrence::Every {
+            count: 1,
+            unit: RecurrenceUnit::Week
+        })
+    );
+    assert_eq!(
+        Recurrence::from_string("3mo"),
+        Ok(Recurrence::Every {
+            count: 3,
+            unit: RecurrenceUnit::Month
+        })
+    );
+    assert_eq!(
+        Recurrence::from_string("after:2days"),
+        Ok(Recurrence::AfterCompletion {
+            count: 2,
+            unit: RecurrenceUnit::Day
+        })
+    );
+    assert_eq!(
+        Recurrence::from_string("fri,mon"),
+        Ok(Recurrence::Weekdays(vec![Weekday::Mon, Weekday::Fri]))
+    );
+    assert_eq!(
+        Recurrence::from_string("weekdays"),
+        Ok(Recurrence::Weekdays(WORKING_DAYS.to_vec()))
+    );
+    assert_eq!(
+        Recurrence::from_string("15th,1st"),
+        Ok(Recurrence::DaysOfMonth(vec![1, 15]))
+    );
+    assert_eq!(
+        Recurrence::from_string("22nd"),
+        Ok(Recurrence::DaysOfMonth(vec![22]))
+    );
+
+    assert_true!(Recurrence::from_string("0d").is_err());
+    assert_true!(Recurrence::from_string("3").is_err());
+    assert_true!(Recurrence::from_string("32nd").is_err());
+    assert_true!(Recurrence::from_string("2st").is_err());
+    assert_true!(Recurrence::from_string("mon,foo").is_err());
+    assert_true!(Recurrence::from_string("after:weekly").is_err());
+}
+
+#[test]
+fn test_recurrence_display_round_trip() {
+    for value in [
+        "daily", "weekly", "monthly", "yearly", "2w", "10d", "weekdays", "mon,thu", "1st,22nd",
+        "after:3d",
+    ] {
+        let recurrence = Recurrence::from_string(value).unwrap();
+        assert_eq!(recurrence.to_string(), value);
+    }
+
+    let recurrence = Recurrence::from_string("monthly").unwrap();
+    assert_eq!(serde_json::to_string(&recurrence).unwrap(), "\"monthly\"");
+    assert_eq!(
+        serde_json::from_str::<Recurrence>("\"monthly\"").unwrap(),
+        recurrence
+    );
+}
+
+#[test]
+fn test_recurrence_next_due() {
+    let due = local_time(2024, 1, 31, 9);
+
+    // Months follow the calendar
+    let monthly = Recurrence::from_string("monthly").unwrap();
+    assert_eq!(
+        monthly.next_due(due, local_time(2024, 1, 30, 12)),
+        Some(local_time(2024, 2, 29, 9))
+    );
+
+    // Occurrences that have already passed are skipped
+    let weekly = Recurrence::from_string("weekly").unwrap();
+    assert_eq!(
+        weekly.next_due(due, local_time(2024, 2, 10, 12)),
+        Some(local_time(2024, 2, 14, 9))
+    );
+
+    // 2024-01-31 is a Wednesday
+    let weekdays = Recurrence::from_string("mon,fri").unwrap();
+    assert_eq!(
+        weekdays.next_due(due, local_time(2024, 1, 31, 12)),
+        Some(local_time(2024, 2, 2, 9))
+    );
+
+    let days_of_month = Recurrence::from_string("30th").unwrap();
+    assert_eq!(
+        days_of_month.next_due(due, local_time(2024, 1, 31, 12)),
+        Some(local_time(2024, 3, 30, 9))
+    );
+
+    let after = Recurrence::from_string("after:2d").unwrap();
+    assert_eq!(
+        after.next_due(due, local_time(2024, 2, 5, 12)),
+        Some(local_time(2024, 2, 7, 12))
+    );
+}
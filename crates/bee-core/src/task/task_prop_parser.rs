@@ -1,21 +1,32 @@
+use std::collections::BTreeMap;
 use std::fmt::Debug;
+use std::sync::Arc;
 
+use chrono::{DateTime, Local};
 use log::debug;
 use uuid::Uuid;
 
 use crate::{
-    lexer::{Lexer, Token, TokenType},
+    clock::{Clock, SystemClock},
+    config::get_config,
+    lexer::{Lexer, Token, TokenType, link_type_of_keyword},
     parser::BaseParser,
-    task::{DependsOnIdentifier, Project, TaskProperties, TaskStatus},
+    task::{
+        LinkChange, LinkType, Project, Recurrence, TaskIdentifier, TaskPriority, TaskProperties,
+        TaskStatus, UdaDefinition, split_uda_word,
+    },
 };
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct TaskPropertyParser {
     lexer: Lexer,
     current_token: Token,
     peek_token: Token,
     buffer_tokens: Vec<Token>,
     buffer_index: usize,
+    /// The UDAs that can be set, by name
+    udas: BTreeMap<String, UdaDefinition>,
+    clock: Arc<dyn Clock>,
 }
 
 impl BaseParser for TaskPropertyParser {
@@ -50,6 +61,10 @@ impl BaseParser for TaskPropertyParser {
     fn get_mut_lexer(&mut self) -> &mut Lexer {
         &mut self.lexer
     }
+
+    fn get_clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
 }
 
 macro_rules! process_tag_prefix {
@@ -79,6 +94,25 @@ macro_rules! process_tag_prefix {
     };
 }
 
+/// Parse a whole date expression such as `tomorrow` or `2 days ago`, relative to
+/// the time given by @clock
+pub fn parse_date_expression(
+    value: &str,
+    clock: Arc<dyn Clock>,
+) -> Result<DateTime<Local>, String> {
+    let mut parser = TaskPropertyParser::new(Lexer::new(value.to_owned())).with_clock(clock);
+    parser.skip_whitespace();
+    let time = parser.read_date_expr()?;
+    parser.skip_whitespace();
+    if parser.current_token.token_type != TokenType::Eof {
+        return Err(format!(
+            "unexpected token '{}' found in invalid date expression",
+            parser.current_token.literal
+        ));
+    }
+    Ok(time)
+}
+
 impl TaskPropertyParser {
     pub fn new(lexer: Lexer) -> TaskPropertyParser {
         let mut parser = TaskPropertyParser {
@@ -87,6 +121,8 @@ impl TaskPropertyParser {
             peek_token: Token::default(),
             buffer_tokens: Vec::default(),
             buffer_index: 0,
+            udas: get_config().udas.to_owned(),
+            clock: Arc::new(SystemClock),
         };
         parser.next_token();
         parser.next_token();
@@ -94,6 +130,30 @@ impl TaskPropertyParser {
         parser
     }
 
+    /// Use @clock for the relative dates instead of the system clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Read the task a link goes to, following @keyword. `None` is returned for
+    /// the word 'none', which removes the links.
+    fn read_link_target(&self, keyword: &str) -> Result<Option<TaskIdentifier>, String> {
+        match self.current_token.token_type {
+            TokenType::Uuid => Ok(Some(TaskIdentifier::Uuid(
+                Uuid::parse_str(&self.current_token.literal).unwrap(),
+            ))),
+            TokenType::Int => Ok(Some(TaskIdentifier::Usize(
+                self.current_token.literal.parse::<usize>().unwrap(),
+            ))),
+            TokenType::WordString if self.current_token.literal == "none" => Ok(None),
+            _ => Err(format!(
+                "Expected a token of type Uuid or Int following a {}, found '{}' (value: '{}')",
+                keyword, self.current_token.token_type, self.current_token.literal
+            )),
+        }
+    }
+
     pub fn parse_task_properties(&mut self) -> Result<TaskProperties, String> {
         let err_msg_prefix: String = "could not parse the task property expression. ".to_string();
         let mut props = TaskProperties::default();
@@ -103,20 +163,52 @@ impl TaskPropertyParser {
 
         while self.current_token.token_type != TokenType::Eof {
             match self.current_token.token_type {
+                TokenType::WordString
+                    if matches!(
+                        split_uda_word(&self.current_token.literal, &self.udas),
+                        Some((_, None, _))
+                    ) =>
+                {
+                    let literal = self.current_token.literal.to_owned();
+                    let (name, _, value) = split_uda_word(&literal, &self.udas).unwrap();
+                    let definition = self.udas[name].to_owned();
+                    let value = self
+                        .read_uda_value(value, &definition)
+                        .map_err(|e| err_msg_prefix.to_string() + &e)?;
+                    props.udas.insert(name.to_owned(), value);
+                    self.next_token();
+                }
                 TokenType::OperatorOr
                 | TokenType::Blank
                 | TokenType::Int
                 | TokenType::Uuid
+                | TokenType::Date
                 | TokenType::String
                 | TokenType::WordString
                 | TokenType::OperatorAnd
                 | TokenType::OperatorXor
+                | TokenType::OperatorNot
                 | TokenType::FilterTokDateDueBefore
                 | TokenType::FilterTokDateDueAfter
                 | TokenType::FilterTokDateCreatedBefore
                 | TokenType::FilterTokDateCreatedAfter
                 | TokenType::FilterTokDateEndBefore
                 | TokenType::FilterTokDateEndAfter
+                | TokenType::FilterTokDateWaitBefore
+                | TokenType::FilterTokDateWaitAfter
+                | TokenType::FilterTokDateScheduledBefore
+                | TokenType::FilterTokDateScheduledAfter
+                | TokenType::FilterSummary
+                | TokenType::FilterSummaryRegex
+                | TokenType::FilterAnnotation
+                | TokenType::FilterAnnotationRegex
+                | TokenType::FilterDescription
+                | TokenType::FilterDescriptionRegex
+                | TokenType::ProjectIsPrefix
+                | TokenType::PriorityAbove
+                | TokenType::FilterTokHas
+                | TokenType::FilterTokLinked
+                | TokenType::Regex
                 | TokenType::LeftParenthesis
                 | TokenType::RightParenthesis => {
                     if let Some(summary) = props.summary {
@@ -195,46 +287,147 @@ impl TaskPropertyParser {
                     self.next_token();
                     self.skip_whitespace();
 
-                    let mut new_depends_on = match &props.depends_on {
-                        None => Vec::default(),
-                        Some(values) => values.to_owned(),
+                    let change = match self.read_link_target("TokenTypeDependsOn") {
+                        Ok(Some(target)) => LinkChange::Add(LinkType::DependsOn, target),
+                        Ok(None) => LinkChange::Clear(LinkType::DependsOn),
+                        Err(e) => return Err(err_msg_prefix + &e),
                     };
-                    match self.current_token.token_type {
-                        TokenType::Uuid => {
-                            new_depends_on.push(DependsOnIdentifier::Uuid(
-                                Uuid::parse_str(&self.current_token.literal).unwrap(),
-                            ));
-                        }
-                        TokenType::Int => {
-                            new_depends_on.push(DependsOnIdentifier::Usize(
-                                self.current_token.literal.parse::<usize>().unwrap(),
-                            ));
-                        }
-                        _ if self.current_token.token_type == TokenType::WordString
-                            && self.current_token.literal == *"none" =>
-                        {
-                            props.depends_on = Some(Vec::new());
+                    props.add_link_change(change);
+                    self.next_token();
+                }
+                TokenType::Link => {
+                    let kind = link_type_of_keyword(&self.current_token.literal);
+                    self.next_token();
+                    self.skip_whitespace();
+
+                    let change = match self.read_link_target("TokenTypeLink") {
+                        Ok(Some(target)) => LinkChange::Add(kind, target),
+                        Ok(None) => LinkChange::Clear(kind),
+                        Err(e) => return Err(err_msg_prefix + &e),
+                    };
+                    props.add_link_change(change);
+                    self.next_token();
+                }
+                TokenType::Unlink => {
+                    let kind = link_type_of_keyword(&self.current_token.literal);
+                    self.next_token();
+                    self.skip_whitespace();
+
+                    let change = match self.read_link_target("TokenTypeUnlink") {
+                        Ok(Some(target)) => LinkChange::Remove(kind, target),
+                        Ok(None) => {
+                            return Err(err_msg_prefix
+                                + &format!(
+                                    "Use 'link:{}:none' to remove all the '{}' links",
+                                    kind, kind
+                                ));
                         }
+                        Err(e) => return Err(err_msg_prefix + &e),
+                    };
+                    props.add_link_change(change);
+                    self.next_token();
+                }
+                TokenType::Parent => {
+                    self.next_token();
+                    self.skip_whitespace();
+
+                    props.parent = match self.current_token.token_type {
+                        TokenType::Uuid => Some(Some(TaskIdentifier::Uuid(
+                            Uuid::parse_str(&self.current_token.literal).unwrap(),
+                        ))),
+                        TokenType::Int => Some(Some(TaskIdentifier::Usize(
+                            self.current_token.literal.parse::<usize>().unwrap(),
+                        ))),
+                        TokenType::WordString if self.current_token.literal == "none" => Some(None),
                         _ => {
                             return Err(err_msg_prefix
                                 + &format!(
-                                    "Expected a token of type Uuid or Int following a TokenTypeDependsOn, found '{}' (value: '{}')",
+                                    "Expected a token of type Uuid or Int following a TokenTypeParent, found '{}' (value: '{}')",
+                                    self.current_token.token_type, self.current_token.literal
+                                ));
+                        }
+                    };
+                    self.next_token();
+                }
+                TokenType::Recur => {
+                    self.next_token();
+                    self.skip_whitespace();
+
+                    match self.current_token.token_type {
+                        TokenType::Eof
+                        | TokenType::LeftParenthesis
+                        | TokenType::RightParenthesis => {
+                            return Err(err_msg_prefix
+                                + &format!(
+                                    "Expected a recurrence following a TokenTypeRecur, found '{}' (value: '{}')",
                                     self.current_token.token_type, self.current_token.literal
                                 ));
                         }
+                        _ => {}
                     }
-                    if !new_depends_on.is_empty() {
-                        props.depends_on = Some(new_depends_on);
+
+                    let value = self.read_until_blank();
+                    props.recurrence = if value == "none" {
+                        Some(None)
+                    } else {
+                        Some(Some(
+                            Recurrence::from_string(&value)
+                                .map_err(|e| err_msg_prefix.to_string() + &e)?,
+                        ))
+                    };
+                    self.next_token();
+                }
+                TokenType::Priority => {
+                    self.next_token();
+                    self.skip_whitespace();
+
+                    if self.current_token.token_type != TokenType::WordString {
+                        return Err(err_msg_prefix
+                            + &format!(
+                                "Expected a priority following a TokenTypePriority, found '{}' (value: '{}')",
+                                self.current_token.token_type, self.current_token.literal
+                            ));
                     }
+
+                    props.priority = if self.current_token.literal == "none" {
+                        Some(None)
+                    } else {
+                        Some(Some(
+                            TaskPriority::from_string(&self.current_token.literal)
+                                .map_err(|e| err_msg_prefix.to_string() + &e)?,
+                        ))
+                    };
                     self.next_token();
                 }
-                TokenType::FilterTokDateDue => {
+                TokenType::FilterTokDateDue
+                | TokenType::FilterTokDateWait
+                | TokenType::FilterTokDateScheduled => {
+                    let tok_type = self.current_token.token_type.clone();
                     self.next_token();
                     self.skip_whitespace();
 
-                    if self.current_token.token_type != TokenType::WordString
-                        && self.current_token.token_type != TokenType::Int
+                    // 'none', or no value at all, removes the date
+                    let is_empty = self.current_token.token_type == TokenType::Eof;
+                    if is_empty
+                        || (self.current_token.token_type == TokenType::WordString
+                            && self.current_token.literal == "none")
                     {
+                        match tok_type {
+                            TokenType::FilterTokDateDue => props.date_due = Some(None),
+                            TokenType::FilterTokDateWait => props.date_wait = Some(None),
+                            TokenType::FilterTokDateScheduled => props.date_scheduled = Some(None),
+                            _ => unreachable!(),
+                        }
+                        if !is_empty {
+                            self.next_token();
+                        }
+                        continue;
+                    }
+
+                    if !matches!(
+                        self.current_token.token_type,
+                        TokenType::WordString | TokenType::Int | TokenType::Date
+                    ) {
                         return Err(err_msg_prefix
                             + &format!(
                                 "Expected a token of type String or Int following a TokenTypeFilterDateEnd, found '{}' (value: '{}')",
@@ -243,7 +436,14 @@ impl TaskPropertyParser {
                     }
 
                     let time = self.read_date_expr()?;
-                    props.date_due = Some(time);
+                    match tok_type {
+                        TokenType::FilterTokDateDue => props.date_due = Some(Some(time)),
+                        TokenType::FilterTokDateWait => props.date_wait = Some(Some(time)),
+                        TokenType::FilterTokDateScheduled => {
+                            props.date_scheduled = Some(Some(time))
+                        }
+                        _ => unreachable!(),
+                    }
                     self.next_token();
                 }
                 TokenType::Eof => unreachable!("We should not be trying to read EOF"),
@@ -1,7 +1,12 @@
 use all_asserts::assert_true;
-use chrono::{Local, NaiveTime, TimeZone};
+use chrono::{Duration, Local, NaiveTime, TimeZone};
+use std::sync::Arc;
+
+use std::collections::BTreeMap;
 
 use super::*;
+use crate::clock::{Clock, FixedClock};
+use crate::task::{UdaDefinition, UdaType};
 
 fn from_string(value: &str) -> TaskProperties {
     let lexer = Lexer::new(value.to_owned());
@@ -104,7 +109,7 @@ fn test_task_properties_parser() {
             project: Some(Project {
                 name: "proj.a.b.c".to_string()
             }),
-            date_due: Some(today_start),
+            date_due: Some(Some(today_start)),
             ..TaskProperties::default()
         }
     );
@@ -114,24 +119,27 @@ fn test_task_properties_parser() {
 
     let tp = from_string("depends:6");
     let props = TaskProperties {
-        depends_on: Some(vec![DependsOnIdentifier::Usize(6)]),
+        links: vec![LinkChange::Add(
+            LinkType::DependsOn,
+            TaskIdentifier::Usize(6),
+        )],
         ..TaskProperties::default()
     };
     assert_eq!(tp, props);
 
     let tp = from_string("depends:6 depends:7");
     let props = TaskProperties {
-        depends_on: Some(vec![
-            DependsOnIdentifier::Usize(6),
-            DependsOnIdentifier::Usize(7),
-        ]),
+        links: vec![
+            LinkChange::Add(LinkType::DependsOn, TaskIdentifier::Usize(6)),
+            LinkChange::Add(LinkType::DependsOn, TaskIdentifier::Usize(7)),
+        ],
         ..TaskProperties::default()
     };
     assert_eq!(tp, props);
 
     let tp = from_string("depends:none");
     let props = TaskProperties {
-        depends_on: Some(vec![]),
+        links: vec![LinkChange::Clear(LinkType::DependsOn)],
         ..TaskProperties::default()
     };
     assert_eq!(tp, props);
@@ -139,11 +147,251 @@ fn test_task_properties_parser() {
     let uuid1 = Uuid::new_v4();
     let tp = from_string(format!("depends:6 depends:{}", uuid1).as_str());
     let props = TaskProperties {
-        depends_on: Some(vec![
-            DependsOnIdentifier::Usize(6),
-            DependsOnIdentifier::Uuid(uuid1),
-        ]),
+        links: vec![
+            LinkChange::Add(LinkType::DependsOn, TaskIdentifier::Usize(6)),
+            LinkChange::Add(LinkType::DependsOn, TaskIdentifier::Uuid(uuid1)),
+        ],
+        ..TaskProperties::default()
+    };
+    assert_eq!(tp, props);
+}
+
+#[test]
+fn test_links() {
+    let tp = from_string("fix the login link:duplicates:4 link:relates-to:5");
+    let props = TaskProperties {
+        summary: Some("fix the login".to_owned()),
+        links: vec![
+            LinkChange::Add(LinkType::Duplicates, TaskIdentifier::Usize(4)),
+            LinkChange::Add(LinkType::RelatesTo, TaskIdentifier::Usize(5)),
+        ],
+        ..TaskProperties::default()
+    };
+    assert_eq!(tp, props);
+
+    let uuid = Uuid::new_v4();
+    let tp = from_string(format!("unlink:follows-up:{} link:depends-on:none", uuid).as_str());
+    assert_eq!(
+        tp.links,
+        vec![
+            LinkChange::Remove(LinkType::FollowsUp, TaskIdentifier::Uuid(uuid)),
+            LinkChange::Clear(LinkType::DependsOn),
+        ]
+    );
+    assert_eq!(tp.get_referenced_tasks(), vec![TaskIdentifier::Uuid(uuid)]);
+
+    // An unknown link type is part of the summary
+    let tp = from_string("read link:https://example.org");
+    assert_eq!(tp.summary, Some("read link:https://example.org".to_owned()));
+    assert_true!(tp.links.is_empty());
+
+    let lexer = Lexer::new("unlink:relates-to:none".to_string());
+    let mut parser = TaskPropertyParser::new(lexer);
+    assert_true!(parser.parse_task_properties().is_err());
+}
+
+#[test]
+fn test_recurrence() {
+    let tp = from_string("water the plants recur:weekly +home");
+    let props = TaskProperties {
+        summary: Some("water the plants".to_owned()),
+        tags_add: Some(vec!["home".to_owned()]),
+        recurrence: Some(Some(Recurrence::from_string("weekly").unwrap())),
+        ..TaskProperties::default()
+    };
+    assert_eq!(tp, props);
+
+    let tp = from_string("recur:2w");
+    assert_eq!(
+        tp.recurrence,
+        Some(Some(Recurrence::from_string("2w").unwrap()))
+    );
+
+    let tp = from_string("recur: 1st,15th");
+    assert_eq!(
+        tp.recurrence,
+        Some(Some(Recurrence::from_string("1st,15th").unwrap()))
+    );
+
+    let tp = from_string("recur:none");
+    assert_eq!(tp.recurrence, Some(None));
+
+    let lexer = Lexer::new("recur:sometimes".to_string());
+    let mut parser = TaskPropertyParser::new(lexer);
+    assert_true!(parser.parse_task_properties().is_err());
+}
+
+#[test]
+fn test_parent() {
+    let tp = from_string("write the tests parent:12");
+    let props = TaskProperties {
+        summary: Some("write the tests".to_owned()),
+        parent: Some(Some(TaskIdentifier::Usize(12))),
         ..TaskProperties::default()
     };
     assert_eq!(tp, props);
+
+    let uuid = Uuid::new_v4();
+    let tp = from_string(format!("parent:{}", uuid).as_str());
+    assert_eq!(tp.parent, Some(Some(TaskIdentifier::Uuid(uuid))));
+    assert_eq!(tp.get_referenced_tasks(), vec![TaskIdentifier::Uuid(uuid)]);
+
+    let tp = from_string("parent:none");
+    assert_eq!(tp.parent, Some(None));
+
+    let lexer = Lexer::new("parent:someone".to_string());
+    let mut parser = TaskPropertyParser::new(lexer);
+    assert_true!(parser.parse_task_properties().is_err());
+}
+
+#[test]
+fn test_priority() {
+    let tp = from_string("write the report priority:H +work");
+    let props = TaskProperties {
+        summary: Some("write the report".to_owned()),
+        tags_add: Some(vec!["work".to_owned()]),
+        priority: Some(Some(TaskPriority::High)),
+        ..TaskProperties::default()
+    };
+    assert_eq!(tp, props);
+
+    let tp = from_string("priority: low");
+    assert_eq!(tp.priority, Some(Some(TaskPriority::Low)));
+
+    let tp = from_string("priority:none");
+    assert_eq!(tp.priority, Some(None));
+
+    // Only the filter uses 'priority.above:'
+    let tp = from_string("priority.above:M");
+    assert_eq!(tp.summary, Some("priority.above:M".to_owned()));
+    assert_eq!(tp.priority, None);
+
+    let lexer = Lexer::new("priority:urgent".to_string());
+    let mut parser = TaskPropertyParser::new(lexer);
+    assert_true!(parser.parse_task_properties().is_err());
+}
+
+#[test]
+fn test_wait_and_scheduled() {
+    let today_start = Local
+        .from_local_datetime(
+            &Local::now()
+                .date_naive()
+                .and_time(NaiveTime::from_hms_opt(0, 0, 0).unwrap()),
+        )
+        .single()
+        .unwrap();
+
+    let tp = from_string("renew passport wait:today scheduled: today");
+    let props = TaskProperties {
+        summary: Some("renew passport".to_owned()),
+        date_wait: Some(Some(today_start)),
+        date_scheduled: Some(Some(today_start)),
+        ..TaskProperties::default()
+    };
+    assert_eq!(tp, props);
+
+    // Only the filters use 'wait.before:' and 'scheduled.after:'
+    let tp = from_string("wait.before:today");
+    assert_eq!(tp.summary, Some("wait.before:today".to_owned()));
+    assert_eq!(tp.date_wait, None);
+
+    // 'none', or no value, removes the date
+    let tp = from_string("wait:none +home");
+    assert_eq!(tp.date_wait, Some(None));
+    assert_eq!(tp.tags_add, Some(vec!["home".to_owned()]));
+    assert_eq!(tp.summary, None);
+
+    let tp = from_string("scheduled:none due:none");
+    assert_eq!(tp.date_scheduled, Some(None));
+    assert_eq!(tp.date_due, Some(None));
+
+    let tp = from_string("+home wait:");
+    assert_eq!(tp.date_wait, Some(None));
+
+    let lexer = Lexer::new("wait:+home".to_string());
+    let mut parser = TaskPropertyParser::new(lexer);
+    assert_true!(parser.parse_task_properties().is_err());
+}
+
+#[test]
+fn test_udas() {
+    let parse = |value: &str| {
+        let mut parser = TaskPropertyParser::new(Lexer::new(value.to_owned()));
+        parser.udas = BTreeMap::from([
+            (
+                "estimate".to_string(),
+                UdaDefinition {
+                    uda_type: UdaType::Duration,
+                    values: Vec::new(),
+                },
+            ),
+            (
+                "ticket".to_string(),
+                UdaDefinition {
+                    uda_type: UdaType::String,
+                    values: Vec::new(),
+                },
+            ),
+        ]);
+        parser.parse_task_properties()
+    };
+
+    let tp = parse("fix the login estimate:2h ticket:ABC-12").unwrap();
+    assert_eq!(tp.summary, Some("fix the login".to_owned()));
+    assert_eq!(
+        tp.udas,
+        BTreeMap::from([
+            ("estimate".to_string(), Some("7200".to_string())),
+            ("ticket".to_string(), Some("ABC-12".to_string())),
+        ])
+    );
+
+    let tp = parse("ticket: XYZ estimate:none").unwrap();
+    assert_eq!(
+        tp.udas,
+        BTreeMap::from([
+            ("estimate".to_string(), None),
+            ("ticket".to_string(), Some("XYZ".to_string())),
+        ])
+    );
+
+    // Words that only look like a UDA stay in the summary
+    let tp = parse("size:L estimate.above:1h").unwrap();
+    assert_eq!(tp.summary, Some("size:L estimate.above:1h".to_owned()));
+
+    assert_true!(parse("estimate:soon").is_err());
+    assert_true!(parse("estimate:").is_err());
+}
+
+#[test]
+fn test_parse_date_expression() {
+    let now = Local.with_ymd_and_hms(2026, 11, 3, 14, 0, 0).unwrap();
+    let clock: Arc<dyn Clock> = Arc::new(FixedClock::new(now));
+    let today = Local.with_ymd_and_hms(2026, 11, 3, 0, 0, 0).unwrap();
+    assert_eq!(parse_date_expression("today", clock.clone()), Ok(today));
+    assert_eq!(
+        parse_date_expression(" yesterday ", clock.clone()),
+        Ok(today - Duration::days(1))
+    );
+    assert_eq!(
+        parse_date_expression("2 days ago", clock.clone()),
+        Ok(now - Duration::days(2))
+    );
+    assert_eq!(
+        parse_date_expression("2026-11-03T14:00 + 1d", clock.clone()),
+        Ok(Local.with_ymd_and_hms(2026, 11, 4, 14, 0, 0).unwrap())
+    );
+
+    // A date in the summary stays in it
+    let tp = from_string("book the room for 2026-11-03 due:2026-11-01");
+    assert_eq!(tp.summary, Some("book the room for 2026-11-03".to_owned()));
+    assert_eq!(
+        tp.date_due,
+        Some(Some(Local.with_ymd_and_hms(2026, 11, 1, 0, 0, 0).unwrap()))
+    );
+
+    assert_true!(parse_date_expression("", clock.clone()).is_err());
+    assert_true!(parse_date_expression("today +work", clock.clone()).is_err());
+    assert_true!(parse_date_expression("someday", clock).is_err());
 }
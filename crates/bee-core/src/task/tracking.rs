@@ -0,0 +1,79 @@
+use chrono::{DateTime, Days, Local, NaiveDate, NaiveTime, TimeDelta, TimeZone};
+use serde::{Deserialize, Serialize};
+
+/// A period during which a task was active, from the moment it was started to
+/// the moment it was stopped. `end` is `None` while the task is still active.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct TimeInterval {
+    pub start: DateTime<Local>,
+    #[serde(default)]
+    pub end: Option<DateTime<Local>>,
+}
+
+impl TimeInterval {
+    pub fn start(time: DateTime<Local>) -> TimeInterval {
+        TimeInterval {
+            start: time,
+            end: None,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.end.is_none()
+    }
+
+    /// Time spent in this interval. An open interval lasts until @now.
+    pub fn duration(&self, now: DateTime<Local>) -> TimeDelta {
+        (self.end.unwrap_or(now) - self.start).max(TimeDelta::zero())
+    }
+
+    /// Time spent in this interval between @from and @to. An open interval
+    /// lasts until @now.
+    pub fn duration_between(
+        &self,
+        from: DateTime<Local>,
+        to: DateTime<Local>,
+        now: DateTime<Local>,
+    ) -> TimeDelta {
+        let start = self.start.max(from);
+        let end = self.end.unwrap_or(now).min(to);
+        (end - start).max(TimeDelta::zero())
+    }
+
+    /// Time spent in this interval on each day, between @from and @to. Days
+    /// without any time spent are left out.
+    pub fn split_by_day(
+        &self,
+        from: DateTime<Local>,
+        to: DateTime<Local>,
+        now: DateTime<Local>,
+    ) -> Vec<(NaiveDate, TimeDelta)> {
+        let mut days = Vec::new();
+        let end = self.end.unwrap_or(now).min(to);
+        let mut day = self.start.max(from).date_naive();
+        while day <= end.date_naive() {
+            let next_day = day.checked_add_days(Days::new(1)).unwrap();
+            let duration = self.duration_between(
+                start_of_day(day).max(from),
+                start_of_day(next_day).min(to),
+                now,
+            );
+            if duration > TimeDelta::zero() {
+                days.push((day, duration));
+            }
+            day = next_day;
+        }
+        days
+    }
+}
+
+fn start_of_day(day: NaiveDate) -> DateTime<Local> {
+    Local
+        .from_local_datetime(&day.and_time(NaiveTime::MIN))
+        .earliest()
+        .unwrap_or_else(|| day.and_time(NaiveTime::MIN).and_utc().with_timezone(&Local))
+}
+
+#[cfg(test)]
+#[path = "tracking_test.rs"]
+mod tracking_test;
@@ -0,0 +1,84 @@
+use chrono::TimeZone;
+
+use super::*;
+
+fn local_time(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+    Local
+        .with_ymd_and_hms(2024, 3, day, hour, minute, 0)
+        .single()
+        .unwrap()
+}
+
+fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+}
+
+#[test]
+fn test_interval_duration() {
+    let interval = TimeInterval {
+        start: local_time(4, 9, 0),
+        end: Some(local_time(4, 10, 30)),
+    };
+    assert_eq!(
+        interval.duration(local_time(5, 0, 0)),
+        TimeDelta::minutes(90)
+    );
+
+    let open = TimeInterval::start(local_time(4, 9, 0));
+    assert!(open.is_open());
+    assert_eq!(open.duration(local_time(4, 9, 45)), TimeDelta::minutes(45));
+}
+
+#[test]
+fn test_interval_duration_between() {
+    let interval = TimeInterval {
+        start: local_time(4, 9, 0),
+        end: Some(local_time(4, 12, 0)),
+    };
+    let now = local_time(6, 0, 0);
+    assert_eq!(
+        interval.duration_between(local_time(4, 10, 0), local_time(4, 11, 0), now),
+        TimeDelta::hours(1)
+    );
+    assert_eq!(
+        interval.duration_between(local_time(4, 13, 0), local_time(4, 14, 0), now),
+        TimeDelta::zero()
+    );
+}
+
+#[test]
+fn test_interval_split_by_day() {
+    let interval = TimeInterval {
+        start: local_time(4, 22, 0),
+        end: Some(local_time(6, 1, 30)),
+    };
+    let now = local_time(10, 0, 0);
+    assert_eq!(
+        interval.split_by_day(local_time(1, 0, 0), local_time(10, 0, 0), now),
+        vec![
+            (date(4), TimeDelta::hours(2)),
+            (date(5), TimeDelta::hours(24)),
+            (date(6), TimeDelta::minutes(90)),
+        ]
+    );
+    assert_eq!(
+        interval.split_by_day(local_time(5, 12, 0), local_time(6, 1, 0), now),
+        vec![
+            (date(5), TimeDelta::hours(12)),
+            (date(6), TimeDelta::hours(1))
+        ]
+    );
+
+    let open = TimeInterval::start(local_time(6, 23, 0));
+    assert_eq!(
+        open.split_by_day(
+            local_time(1, 0, 0),
+            local_time(10, 0, 0),
+            local_time(7, 0, 30)
+        ),
+        vec![
+            (date(6), TimeDelta::hours(1)),
+            (date(7), TimeDelta::minutes(30))
+        ]
+    );
+}
@@ -0,0 +1,238 @@
+use std::{cmp::Ordering, collections::BTreeMap};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use super::task_prop_parser::TaskPropertyParser;
+use crate::lexer::Lexer;
+use crate::parser::{
+    BaseParser, matches_day_string, matches_hour_string, matches_minute_string,
+    matches_month_string, matches_second_string, matches_week_string, matches_year_string,
+};
+
+/// Names that cannot be used for a UDA because they already are a field of a task
+/// or a keyword of the filters
+const RESERVED_NAMES: [&str; 17] = [
+    "annotation",
+    "created",
+    "depends",
+    "description",
+    "due",
+    "end",
+    "id",
+    "priority",
+    "proj",
+    "project",
+    "recur",
+    "scheduled",
+    "status",
+    "summary",
+    "tags",
+    "uuid",
+    "wait",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UdaType {
+    String,
+    Number,
+    Date,
+    /// A number of seconds, given as e.g. `2h`, `1d` or `1h30m`
+    Duration,
+    /// One of the values listed in the definition of the UDA
+    Enum,
+}
+
+/// Declaration of a user defined attribute (UDA), from the `[core.uda.<name>]`
+/// section of the configuration.
+///
+/// UDA values are kept on the tasks as strings. Numbers and enum values are
+/// stored as given, dates as RFC 3339 and durations as a number of seconds.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct UdaDefinition {
+    #[serde(rename = "type")]
+    pub uda_type: UdaType,
+    /// Allowed values of an enum UDA, from the lowest to the highest
+    #[serde(default)]
+    pub values: Vec<String>,
+}
+
+impl UdaDefinition {
+    /// Check that the UDA called @name can be used
+    pub fn validate(&self, name: &str) -> Result<(), String> {
+        if !name.starts_with(|c: char| c.is_ascii_alphabetic())
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(format!(
+                "Invalid UDA name '{}'. It must start with a letter and only contain letters, \
+                digits, '_' and '-'",
+                name
+            ));
+        }
+        if RESERVED_NAMES.contains(&name) {
+            return Err(format!(
+                "Invalid UDA name '{}'. It is already the name of a task field",
+                name
+            ));
+        }
+        if self.uda_type == UdaType::Enum && self.values.is_empty() {
+            return Err(format!(
+                "The enum UDA '{}' requires a list of 'values'",
+                name
+            ));
+        }
+        Ok(())
+    }
+
+    /// Turn the @value given by the user into the value stored on the tasks
+    pub fn parse_value(&self, value: &str) -> Result<String, String> {
+        match self.uda_type {
+            UdaType::String => Ok(value.to_owned()),
+            UdaType::Number => value
+                .parse::<f64>()
+                .map(|_| value.to_owned())
+                .map_err(|_| format!("'{}' is not a number", value)),
+            UdaType::Date => {
+                let mut parser = TaskPropertyParser::new(Lexer::new(value.to_owned()));
+                Ok(parser.read_date_expr()?.to_rfc3339())
+            }
+            UdaType::Duration => parse_duration(value)
+                .map(|seconds| seconds.to_string())
+                .ok_or(format!(
+                    "'{}' is not a duration, e.g. '2h' or '1h30m'",
+                    value
+                )),
+            UdaType::Enum => self
+                .values
+                .iter()
+                .find(|allowed| allowed.eq_ignore_ascii_case(value))
+                .cloned()
+                .ok_or(format!(
+                    "'{}' is not one of the allowed values: {}",
+                    value,
+                    self.values.join(", ")
+                )),
+        }
+    }
+
+    /// Compare two stored values of this UDA. Dates are compared by day, and
+    /// enum values by their position in the list of allowed values.
+    pub fn compare_values(&self, lhs: &str, rhs: &str) -> Option<Ordering> {
+        match self.uda_type {
+            UdaType::String => Some(lhs.to_lowercase().cmp(&rhs.to_lowercase())),
+            UdaType::Number => lhs
+                .parse::<f64>()
+                .ok()?
+                .partial_cmp(&rhs.parse::<f64>().ok()?),
+            UdaType::Date => {
+                let lhs = DateTime::parse_from_rfc3339(lhs)
+                    .ok()?
+                    .with_timezone(&Local);
+                let rhs = DateTime::parse_from_rfc3339(rhs)
+                    .ok()?
+                    .with_timezone(&Local);
+                Some(lhs.date_naive().cmp(&rhs.date_naive()))
+            }
+            UdaType::Duration => Some(lhs.parse::<i64>().ok()?.cmp(&rhs.parse::<i64>().ok()?)),
+            UdaType::Enum => {
+                let position = |value: &str| self.values.iter().position(|v| v == value);
+                Some(position(lhs)?.cmp(&position(rhs)?))
+            }
+        }
+    }
+
+    /// Human readable version of a stored value
+    pub fn format_value(&self, value: &str) -> String {
+        match self.uda_type {
+            UdaType::Date => match DateTime::parse_from_rfc3339(value) {
+                Ok(date) => date
+                    .with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string(),
+                Err(_) => value.to_owned(),
+            },
+            UdaType::Duration => match value.parse::<i64>() {
+                Ok(seconds) => format_duration(seconds),
+                Err(_) => value.to_owned(),
+            },
+            UdaType::String | UdaType::Number | UdaType::Enum => value.to_owned(),
+        }
+    }
+}
+
+/// If @word is `<name>:<value>` or `<name>.<modifier>:<value>` with `<name>` one of
+/// @udas, return the name, the modifier and the value. The value can be empty.
+pub(crate) fn split_uda_word<'a>(
+    word: &'a str,
+    udas: &BTreeMap<String, UdaDefinition>,
+) -> Option<(&'a str, Option<&'a str>, &'a str)> {
+    let (key, value) = word.split_once(':')?;
+    let (name, modifier) = match key.split_once('.') {
+        Some((name, modifier)) => (name, Some(modifier)),
+        None => (key, None),
+    };
+    if udas.contains_key(name) {
+        Some((name, modifier, value))
+    } else {
+        None
+    }
+}
+
+/// Parse a duration made of one or more `<number><unit>`, e.g. `1h30m`, into
+/// a number of seconds. A number without a unit is a number of seconds.
+fn parse_duration(value: &str) -> Option<i64> {
+    if let Ok(seconds) = value.parse::<i64>() {
+        return Some(seconds);
+    }
+
+    let mut total = 0;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let number_len = rest.find(|c: char| !c.is_ascii_digit())?;
+        let unit_len = rest[number_len..]
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len() - number_len);
+        let number = rest[..number_len].parse::<i64>().ok()?;
+        let unit = &rest[number_len..number_len + unit_len];
+        let unit_seconds = match unit {
+            _ if matches_year_string(unit) => 365 * 86400,
+            _ if matches_month_string(unit) => 30 * 86400,
+            _ if matches_week_string(unit) => 7 * 86400,
+            _ if matches_day_string(unit) => 86400,
+            _ if matches_hour_string(unit) => 3600,
+            _ if matches_minute_string(unit) || unit == "min" => 60,
+            _ if matches_second_string(unit) => 1,
+            _ => return None,
+        };
+        total += number * unit_seconds;
+        rest = &rest[number_len + unit_len..];
+    }
+    Some(total)
+}
+
+/// Human readable version of a number of seconds, e.g. `1h30m`
+pub fn format_duration(seconds: i64) -> String {
+    if seconds == 0 {
+        return "0s".to_string();
+    }
+    let mut out = String::default();
+    let mut rest = seconds.abs();
+    for (unit, unit_seconds) in [("d", 86400), ("h", 3600), ("m", 60), ("s", 1)] {
+        if rest >= unit_seconds {
+            out += &format!("{}{}", rest / unit_seconds, unit);
+            rest %= unit_seconds;
+        }
+    }
+    if seconds < 0 {
+        format!("-{}", out)
+    } else {
+        out
+    }
+}
+
+#[cfg(test)]
+#[path = "uda_test.rs"]
+mod uda_test;
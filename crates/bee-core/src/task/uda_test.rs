@@ -0,0 +1,91 @@
+use all_asserts::{assert_false, assert_true};
+use chrono::{NaiveTime, TimeZone};
+
+use super::*;
+
+fn definition(uda_type: UdaType, values: &[&str]) -> UdaDefinition {
+    UdaDefinition {
+        uda_type,
+        values: values.iter().map(|v| v.to_string()).collect(),
+    }
+}
+
+#[test]
+fn test_validate() {
+    let string = definition(UdaType::String, &[]);
+    assert_true!(string.validate("ticket").is_ok());
+    assert_true!(string.validate("customer_name").is_ok());
+    assert_false!(string.validate("due").is_ok());
+    assert_false!(string.validate("2nd").is_ok());
+    assert_false!(string.validate("ticket.id").is_ok());
+
+    assert_false!(definition(UdaType::Enum, &[]).validate("size").is_ok());
+    assert_true!(
+        definition(UdaType::Enum, &["S", "L"])
+            .validate("size")
+            .is_ok()
+    );
+}
+
+#[test]
+fn test_parse_value() {
+    let number = definition(UdaType::Number, &[]);
+    assert_eq!(number.parse_value("2.5"), Ok("2.5".to_string()));
+    assert_true!(number.parse_value("two").is_err());
+
+    let duration = definition(UdaType::Duration, &[]);
+    assert_eq!(duration.parse_value("2h"), Ok("7200".to_string()));
+    assert_eq!(duration.parse_value("1h30min"), Ok("5400".to_string()));
+    assert_eq!(duration.parse_value("1d"), Ok("86400".to_string()));
+    assert_eq!(duration.parse_value("90"), Ok("90".to_string()));
+    assert_true!(duration.parse_value("2 hours").is_err());
+    assert_true!(duration.parse_value("h").is_err());
+
+    let size = definition(UdaType::Enum, &["S", "M", "L"]);
+    assert_eq!(size.parse_value("m"), Ok("M".to_string()));
+    assert_true!(size.parse_value("XL").is_err());
+
+    let date = definition(UdaType::Date, &[]);
+    let tomorrow = Local
+        .from_local_datetime(
+            &(Local::now().date_naive() + chrono::Days::new(1))
+                .and_time(NaiveTime::from_hms_opt(0, 0, 0).unwrap()),
+        )
+        .single()
+        .unwrap();
+    assert_eq!(date.parse_value("tomorrow"), Ok(tomorrow.to_rfc3339()));
+    assert_true!(date.parse_value("someday").is_err());
+}
+
+#[test]
+fn test_compare_and_format_values() {
+    let size = definition(UdaType::Enum, &["S", "M", "L"]);
+    assert_eq!(size.compare_values("L", "M"), Some(Ordering::Greater));
+    assert_eq!(size.compare_values("S", "M"), Some(Ordering::Less));
+
+    let number = definition(UdaType::Number, &[]);
+    assert_eq!(number.compare_values("10", "9.5"), Some(Ordering::Greater));
+
+    let string = definition(UdaType::String, &[]);
+    assert_eq!(string.compare_values("ABC", "abc"), Some(Ordering::Equal));
+
+    let duration = definition(UdaType::Duration, &[]);
+    assert_eq!(duration.compare_values("60", "3600"), Some(Ordering::Less));
+    assert_eq!(duration.format_value("5400"), "1h30m");
+    assert_eq!(duration.format_value("0"), "0s");
+}
+
+#[test]
+fn test_split_uda_word() {
+    let udas = BTreeMap::from([("estimate".to_string(), definition(UdaType::Duration, &[]))]);
+    assert_eq!(
+        split_uda_word("estimate:2h", &udas),
+        Some(("estimate", None, "2h"))
+    );
+    assert_eq!(
+        split_uda_word("estimate.above:", &udas),
+        Some(("estimate", Some("above"), ""))
+    );
+    assert_eq!(split_uda_word("size:L", &udas), None);
+    assert_eq!(split_uda_word("estimate", &udas), None);
+}
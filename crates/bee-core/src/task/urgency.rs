@@ -0,0 +1,129 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::config::CoeffientField;
+
+const SECONDS_PER_DAY: f64 = 86400.0;
+
+/// Number of days after which the whole `age` coefficient applies, unless the
+/// coefficient gives a `window`
+pub const DEFAULT_AGE_WINDOW: i64 = 365;
+
+/// Part of the urgency of a task given by one coefficient
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct UrgencyTerm {
+    /// Name of the coefficient field, e.g. `tag` or `uda.size`
+    pub name: String,
+    /// What the coefficient matched, e.g. the tag or the due date
+    pub value: Option<String>,
+    pub amount: i64,
+}
+
+/// The terms of an urgency being computed
+#[derive(Default)]
+pub(super) struct UrgencyTerms {
+    terms: Vec<UrgencyTerm>,
+}
+
+impl UrgencyTerms {
+    /// Add a term, unless it does not change the urgency
+    pub fn add(&mut self, name: &str, value: Option<String>, amount: i64) {
+        if amount != 0 {
+            self.terms.push(UrgencyTerm {
+                name: name.to_owned(),
+                value,
+                amount,
+            });
+        }
+    }
+
+    pub fn total(&self) -> i64 {
+        self.terms.iter().map(|term| term.amount).sum()
+    }
+
+    pub fn into_terms(self) -> Vec<UrgencyTerm> {
+        self.terms
+    }
+}
+
+/// How a date matched by a coefficient is shown in its term
+pub(super) fn format_date(date: &DateTime<Local>) -> String {
+    date.format("%Y-%m-%d %H:%M").to_string()
+}
+
+/// How the due date of a task adds to its urgency. Nothing is added until
+/// @window days before the due date, then the urgency grows linearly up to @max
+/// on the due date. It keeps growing at the same pace once the task is overdue,
+/// up to @overdue_max.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DueCurve {
+    pub window: i64,
+    pub max: i64,
+    pub overdue_max: i64,
+}
+
+impl Default for DueCurve {
+    fn default() -> Self {
+        DueCurve {
+            window: 14,
+            max: 12,
+            overdue_max: 18,
+        }
+    }
+}
+
+impl DueCurve {
+    /// Read the curve of a `due` coefficient, whose `coefficient` is the urgency
+    /// of a task on its due date. An overdue task gets half of it more by default.
+    pub fn from_coefficient(coef_field: &CoeffientField) -> Result<DueCurve, String> {
+        Ok(DueCurve {
+            window: get_window(coef_field, DueCurve::default().window)?,
+            max: coef_field.coefficient,
+            overdue_max: coef_field
+                .overdue_max
+                .unwrap_or(coef_field.coefficient + coef_field.coefficient / 2),
+        })
+    }
+
+    pub fn urgency(&self, date_due: DateTime<Local>, now: DateTime<Local>) -> f64 {
+        let days_left = date_due.signed_duration_since(now).num_seconds() as f64 / SECONDS_PER_DAY;
+        let window = self.window as f64;
+        if days_left >= window {
+            return 0.0;
+        }
+        let urgency = self.max as f64 * (window - days_left) / window;
+        if days_left < 0.0 {
+            urgency.min(self.overdue_max as f64)
+        } else {
+            urgency
+        }
+    }
+}
+
+/// Urgency given by the age of a task: it grows linearly from nothing when the
+/// task is created to @coefficient once the task is @window days old
+pub fn age_urgency(
+    coefficient: i64,
+    window: i64,
+    date_created: DateTime<Local>,
+    now: DateTime<Local>,
+) -> f64 {
+    let age = now.signed_duration_since(date_created).num_seconds() as f64 / SECONDS_PER_DAY;
+    coefficient as f64 * (age / window as f64).clamp(0.0, 1.0)
+}
+
+/// The `window` of the coefficient, or @default if it has none
+pub fn get_window(coef_field: &CoeffientField, default: i64) -> Result<i64, String> {
+    match coef_field.window {
+        Some(window) if window <= 0 => Err(format!(
+            "The 'window' of the '{}' coefficient must be a positive number of days, got {}",
+            coef_field.field, window
+        )),
+        Some(window) => Ok(window),
+        None => Ok(default),
+    }
+}
+
+#[cfg(test)]
+#[path = "urgency_test.rs"]
+mod urgency_test;
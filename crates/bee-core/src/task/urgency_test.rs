@@ -0,0 +1,77 @@
+use all_asserts::assert_true;
+use chrono::{Duration, TimeZone};
+
+use super::*;
+
+fn now() -> DateTime<Local> {
+    Local.with_ymd_and_hms(2026, 11, 3, 14, 0, 0).unwrap()
+}
+
+fn due_coefficient(
+    coefficient: i64,
+    window: Option<i64>,
+    overdue_max: Option<i64>,
+) -> CoeffientField {
+    CoeffientField {
+        field: "due".to_string(),
+        coefficient,
+        window,
+        overdue_max,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_due_curve() {
+    let curve = DueCurve {
+        window: 10,
+        max: 20,
+        overdue_max: 30,
+    };
+    let urgency = |days: i64| curve.urgency(now() + Duration::days(days), now());
+
+    assert_eq!(urgency(300), 0.0);
+    assert_eq!(urgency(10), 0.0);
+    assert_eq!(urgency(5), 10.0);
+    assert_eq!(urgency(0), 20.0);
+    assert_eq!(urgency(-2), 24.0);
+    assert_eq!(urgency(-5), 30.0);
+    assert_eq!(urgency(-300), 30.0);
+
+    // A task overdue is always more urgent than a task due later
+    assert_true!(urgency(-1) > urgency(1));
+    assert_true!(urgency(1) > urgency(300));
+}
+
+#[test]
+fn test_due_curve_from_coefficient() {
+    assert_eq!(
+        DueCurve::from_coefficient(&due_coefficient(8, None, None)),
+        Ok(DueCurve {
+            window: 14,
+            max: 8,
+            overdue_max: 12,
+        })
+    );
+    assert_eq!(
+        DueCurve::from_coefficient(&due_coefficient(8, Some(7), Some(9))),
+        Ok(DueCurve {
+            window: 7,
+            max: 8,
+            overdue_max: 9,
+        })
+    );
+    assert_true!(DueCurve::from_coefficient(&due_coefficient(8, Some(0), None)).is_err());
+    assert_true!(DueCurve::from_coefficient(&due_coefficient(8, Some(-3), None)).is_err());
+}
+
+#[test]
+fn test_age_urgency() {
+    let urgency = |days: i64| age_urgency(4, 100, now() - Duration::days(days), now());
+    assert_eq!(urgency(0), 0.0);
+    assert_eq!(urgency(50), 2.0);
+    assert_eq!(urgency(100), 4.0);
+    assert_eq!(urgency(1000), 4.0);
+    // A task created in the future is not older than a new one
+    assert_eq!(urgency(-10), 0.0);
+}
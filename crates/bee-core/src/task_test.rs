@@ -61,7 +61,7 @@ fn test_apply_active() {
 
     assert_true!(task.get_history().is_empty());
     assert_eq!(task.get_status(), &TaskStatus::Pending);
-    let _ = task.apply(&props);
+    let _ = task.apply(&props, Local::now());
     assert_eq!(task.get_status(), &TaskStatus::Active);
     assert_false!(task.get_history().is_empty());
 
@@ -72,7 +72,7 @@ fn test_apply_active() {
 
     assert_true!(task.get_history().is_empty());
     assert_eq!(task.get_status(), &TaskStatus::Deleted);
-    let res = task.apply(&props);
+    let res = task.apply(&props, Local::now());
     assert_true!(res.is_err());
 
     let mut task = Task {
@@ -82,7 +82,7 @@ fn test_apply_active() {
 
     assert_true!(task.get_history().is_empty());
     assert_eq!(task.get_status(), &TaskStatus::Completed);
-    let res = task.apply(&props);
+    let res = task.apply(&props, Local::now());
     assert_true!(res.is_err());
 
     let mut task = Task {
@@ -93,7 +93,7 @@ fn test_apply_active() {
     // Assert that history is still empty
     assert_true!(task.get_history().is_empty());
     assert_eq!(task.get_status(), &TaskStatus::Active);
-    let _ = task.apply(&props);
+    let _ = task.apply(&props, Local::now());
     assert_eq!(task.get_status(), &TaskStatus::Active);
     assert_true!(task.get_history().is_empty());
 }
@@ -111,7 +111,7 @@ fn test_apply_stop() {
 
     assert_true!(task.get_history().is_empty());
     assert_eq!(task.get_status(), &TaskStatus::Active);
-    let _ = task.apply(&props);
+    let _ = task.apply(&props, Local::now());
     assert_eq!(task.get_status(), &TaskStatus::Pending);
     assert_false!(task.get_history().is_empty());
 
@@ -122,7 +122,7 @@ fn test_apply_stop() {
 
     assert_true!(task.get_history().is_empty());
     assert_eq!(task.get_status(), &TaskStatus::Deleted);
-    let res = task.apply(&props);
+    let res = task.apply(&props, Local::now());
     assert_true!(res.is_err());
 
     let mut task = Task {
@@ -132,7 +132,7 @@ fn test_apply_stop() {
 
     assert_true!(task.get_history().is_empty());
     assert_eq!(task.get_status(), &TaskStatus::Completed);
-    let res = task.apply(&props);
+    let res = task.apply(&props, Local::now());
     assert_true!(res.is_err());
 
     let mut task = Task {
@@ -143,11 +143,50 @@ fn test_apply_stop() {
     // Assert that history is still empty
     assert_true!(task.get_history().is_empty());
     assert_eq!(task.get_status(), &TaskStatus::Pending);
-    let _ = task.apply(&props);
+    let _ = task.apply(&props, Local::now());
     assert_eq!(task.get_status(), &TaskStatus::Pending);
     assert_true!(task.get_history().is_empty());
 }
 
+#[test]
+fn test_apply_start_stop_records_intervals() {
+    let mut task = setup_task();
+    let mut props = TaskProperties::default();
+
+    props.set_active_status(true);
+    task.apply(&props, Local::now()).unwrap();
+    assert_eq!(task.get_intervals().len(), 1);
+    assert_true!(task.get_intervals()[0].is_open());
+
+    props.set_active_status(false);
+    task.apply(&props, Local::now()).unwrap();
+    assert_eq!(task.get_intervals().len(), 1);
+    assert_false!(task.get_intervals()[0].is_open());
+
+    props.set_active_status(true);
+    task.apply(&props, Local::now()).unwrap();
+    task.done(Local::now());
+    assert_eq!(task.get_intervals().len(), 2);
+    assert_false!(task.get_intervals()[1].is_open());
+
+    let now = Local.with_ymd_and_hms(2026, 11, 3, 14, 0, 0).unwrap();
+    let task = Task {
+        intervals: vec![
+            TimeInterval {
+                start: now - Duration::hours(3),
+                end: Some(now - Duration::hours(2)),
+            },
+            TimeInterval {
+                start: now - Duration::minutes(30),
+                end: None,
+            },
+        ],
+        ..Default::default()
+    };
+    // The open interval counts until now
+    assert_eq!(task.get_tracked_time(now).num_minutes(), 90);
+}
+
 #[test]
 fn test_apply_project() {
     let mut task = setup_task();
@@ -158,11 +197,118 @@ fn test_apply_project() {
     props.project = Some(new_proj.clone());
 
     assert_true!(task.get_history().is_empty());
-    let _ = task.apply(&props);
+    let _ = task.apply(&props, Local::now());
     assert_eq!(task.project, Some(new_proj));
     assert_false!(task.get_history().is_empty());
 }
 
+#[test]
+fn test_apply_priority() {
+    let mut task = setup_task();
+    let mut props = setup_task_property();
+    let urgency_before = task.get_urgency(Local::now()).unwrap();
+
+    props.priority = Some(Some(TaskPriority::High));
+    task.apply(&props, Local::now()).unwrap();
+    assert_eq!(task.get_priority(), &Some(TaskPriority::High));
+    assert_eq!(
+        task.get_urgency(Local::now()).unwrap(),
+        urgency_before + TaskPriority::High.default_coefficient()
+    );
+    assert_false!(task.get_history().is_empty());
+
+    props.priority = Some(None);
+    task.apply(&props, Local::now()).unwrap();
+    assert_eq!(task.get_priority(), &None);
+    assert_eq!(task.get_urgency(Local::now()).unwrap(), urgency_before);
+}
+
+#[test]
+fn test_apply_scheduled() {
+    let mut task = setup_task();
+    let mut props = setup_task_property();
+    let urgency_before = task.get_urgency(Local::now()).unwrap();
+
+    // The urgency does not change until the scheduled date is reached
+    props.date_scheduled = Some(Some(Local::now() + Duration::try_days(2).unwrap()));
+    task.apply(&props, Local::now()).unwrap();
+    assert_eq!(task.get_urgency(Local::now()).unwrap(), urgency_before);
+    assert_false!(task.get_history().is_empty());
+
+    props.date_scheduled = Some(Some(Local::now() - Duration::try_days(2).unwrap()));
+    task.apply(&props, Local::now()).unwrap();
+    assert_eq!(task.get_urgency(Local::now()).unwrap(), urgency_before + 5);
+
+    props.date_scheduled = Some(None);
+    task.apply(&props, Local::now()).unwrap();
+    assert_eq!(task.get_date_scheduled(), &None);
+    assert_eq!(task.get_urgency(Local::now()).unwrap(), urgency_before);
+}
+
+#[test]
+fn test_urgency_due() {
+    let now = Local.with_ymd_and_hms(2026, 11, 3, 14, 0, 0).unwrap();
+    let urgency_due_in = |days: i64| {
+        let mut task = setup_task();
+        task.date_created = now;
+        task.date_due = Some(now + Duration::days(days));
+        task.get_urgency(now).unwrap()
+    };
+    let mut task = setup_task();
+    task.date_created = now;
+    let urgency_without_due = task.get_urgency(now).unwrap();
+
+    // The default curve goes up to 12 on the due date, and to 18 once overdue
+    assert_eq!(urgency_due_in(300), urgency_without_due);
+    assert_eq!(urgency_due_in(7), urgency_without_due + 6);
+    assert_eq!(urgency_due_in(0), urgency_without_due + 12);
+    assert_eq!(urgency_due_in(-300), urgency_without_due + 18);
+    assert_true!(urgency_due_in(-1) > urgency_due_in(300));
+}
+
+#[test]
+fn test_urgency_terms() {
+    let now = Local.with_ymd_and_hms(2026, 11, 3, 14, 0, 0).unwrap();
+    let mut task = setup_task();
+    task.date_created = now;
+    assert_eq!(task.get_urgency(now).unwrap(), 0);
+    assert_true!(task.get_urgency_terms().is_empty());
+
+    let mut task = setup_task();
+    task.date_created = now;
+    task.status = TaskStatus::Active;
+    task.priority = Some(TaskPriority::High);
+    task.date_due = Some(now);
+    let term = |name: &str, value: Option<&str>, amount: i64| UrgencyTerm {
+        name: name.to_owned(),
+        value: value.map(|v| v.to_owned()),
+        amount,
+    };
+    let urgency = task.get_urgency(now).unwrap();
+    assert_eq!(
+        task.get_urgency_terms(),
+        &vec![
+            term("active_status", None, 10),
+            term(
+                "priority",
+                Some("H"),
+                TaskPriority::High.default_coefficient()
+            ),
+            term("due", Some("2026-11-03 14:00"), 12),
+        ]
+    );
+    assert_eq!(
+        urgency,
+        task.get_urgency_terms()
+            .iter()
+            .map(|t| t.amount)
+            .sum::<i64>()
+    );
+
+    task.done(now);
+    assert_true!(task.get_urgency_terms().is_empty());
+}
+
 #[test]
 fn test_apply_summary() {
     let mut task = setup_task();
@@ -170,7 +316,7 @@ fn test_apply_summary() {
     props.summary = Some("New summary".to_string());
 
     assert_true!(task.get_history().is_empty());
-    let _ = task.apply(&props);
+    let _ = task.apply(&props, Local::now());
     assert_eq!(task.summary, "New summary");
     assert_false!(task.get_history().is_empty());
 }
@@ -183,7 +329,7 @@ fn test_apply_status() {
 
     assert_true!(task.get_history().is_empty());
     assert_eq!(task.status, TaskStatus::Pending);
-    let _ = task.apply(&props);
+    let _ = task.apply(&props, Local::now());
     assert_eq!(task.status, TaskStatus::Completed);
     assert_false!(task.get_history().is_empty());
 }
@@ -196,7 +342,7 @@ fn test_apply_tags_add() {
 
     // Test adding a new tag
     assert_true!(task.get_history().is_empty());
-    let _ = task.apply(&props);
+    let _ = task.apply(&props, Local::now());
     task.tags.sort();
     assert_eq!(task.tags, vec!["initial_tag1", "initial_tag2", "new_tag"]);
     assert_false!(task.get_history().is_empty());
@@ -207,7 +353,7 @@ fn test_apply_tags_add() {
 
     // Test adding an existing tag
     assert_true!(task.get_history().is_empty());
-    let _ = task.apply(&props);
+    let _ = task.apply(&props, Local::now());
     task.tags.sort();
     assert_true!(task.get_history().is_empty());
 
@@ -217,7 +363,7 @@ fn test_apply_tags_add() {
 
     // Test adding a mix of existing and new tags
     assert_true!(task.get_history().is_empty());
-    let _ = task.apply(&props);
+    let _ = task.apply(&props, Local::now());
     task.tags.sort();
     assert_false!(task.get_history().is_empty());
     assert_true!(
@@ -237,7 +383,7 @@ fn test_apply_tags_remove() {
 
     // Test removing an existing tag
     assert_true!(task.get_history().is_empty());
-    let _ = task.apply(&props);
+    let _ = task.apply(&props, Local::now());
     assert_eq!(task.tags, vec!["initial_tag1"]);
     assert_false!(task.get_history().is_empty());
     assert_true!(
@@ -254,7 +400,7 @@ fn test_apply_tags_remove() {
 
     // Test removing a non-existing tag
     assert_true!(task.get_history().is_empty());
-    let _ = task.apply(&props);
+    let _ = task.apply(&props, Local::now());
     assert_true!(task.get_history().is_empty());
 }
 
@@ -266,7 +412,7 @@ fn test_apply_annotation() {
 
     assert_true!(task.get_history().is_empty());
     assert_true!(task.annotations.is_empty());
-    let _ = task.apply(&props);
+    let _ = task.apply(&props, Local::now());
     assert_false!(task.annotations.is_empty());
     assert_false!(task.get_history().is_empty());
     assert_eq!(
@@ -275,6 +421,28 @@ fn test_apply_annotation() {
     );
 }
 
+#[test]
+fn test_apply_description() {
+    let mut task = setup_task();
+    let mut props = TaskProperties::default();
+    props.set_description("  # Steps\n\n1. Open the app\n");
+    task.apply(&props, Local::now()).unwrap();
+    assert_eq!(
+        task.get_description().as_deref(),
+        Some("# Steps\n\n1. Open the app")
+    );
+    assert_eq!(task.get_history().len(), 1);
+
+    // Setting the same description again is not a change
+    task.apply(&props, Local::now()).unwrap();
+    assert_eq!(task.get_history().len(), 1);
+
+    props.set_description("\n");
+    task.apply(&props, Local::now()).unwrap();
+    assert_eq!(task.get_description(), &None);
+    assert_eq!(task.get_history().len(), 2);
+}
+
 #[test]
 fn test_apply_combined() {
     let mut task = setup_task();
@@ -284,7 +452,7 @@ fn test_apply_combined() {
     props.tags_add = Some(vec!["additional_tag".to_string()]);
 
     assert_true!(task.get_history().is_empty());
-    let _ = task.apply(&props);
+    let _ = task.apply(&props, Local::now());
     assert_false!(task.get_history().is_empty());
     assert_eq!(task.summary, "Updated summary");
     assert_eq!(task.tags, vec!["initial_tag2", "additional_tag"]);
@@ -296,7 +464,7 @@ fn test_apply_no_change() {
     let props = TaskProperties::default(); // Assumes no change
 
     assert_true!(task.get_history().is_empty());
-    let _ = task.apply(&props);
+    let _ = task.apply(&props, Local::now());
     assert_true!(task.get_history().is_empty());
     assert_eq!(task.summary, "Initial summary");
     assert_eq!(task.tags, vec!["initial_tag1", "initial_tag2"]);
@@ -308,37 +476,86 @@ fn test_apply_depends_on() {
     let mut props = TaskProperties::default();
     let uuid_1 = Uuid::new_v4();
     let uuid_2 = Uuid::new_v4();
-    props.depends_on = Some(vec![DependsOnIdentifier::Uuid(uuid_1)]);
+    props.add_depends_on(&TaskIdentifier::Uuid(uuid_1));
 
-    assert_true!(task.depends_on.is_empty());
+    assert_true!(task.get_depends().is_empty());
     assert_true!(task.get_history().is_empty());
-    let _ = task.apply(&props);
+    let _ = task.apply(&props, Local::now());
     assert_false!(task.get_history().is_empty());
-    assert_eq!(task.depends_on.len(), 1);
+    assert_eq!(task.get_depends().len(), 1);
     // Evene if we apply if a second time we still have a single value because it's the same uuid
     assert_eq!(task.get_history().len(), 1);
-    let _ = task.apply(&props);
+    let _ = task.apply(&props, Local::now());
     assert_eq!(task.get_history().len(), 1);
-    assert_eq!(task.depends_on.len(), 1);
-    assert_eq!(task.depends_on.first().unwrap(), &uuid_1);
-
-    props.depends_on = Some(vec![
-        DependsOnIdentifier::Uuid(uuid_1),
-        DependsOnIdentifier::Uuid(uuid_2),
-    ]);
-    assert_eq!(task.depends_on.len(), 1);
-    let _ = task.apply(&props);
-    assert_eq!(task.depends_on.len(), 2);
-    assert_true!(
-        task.depends_on.first().unwrap() == &uuid_1 || task.depends_on.first().unwrap() == &uuid_2
-    );
-    assert_true!(
-        task.depends_on.last().unwrap() == &uuid_1 || task.depends_on.last().unwrap() == &uuid_2
+    assert_eq!(task.get_depends(), &[uuid_1]);
+
+    props.add_depends_on(&TaskIdentifier::Uuid(uuid_2));
+    assert_eq!(task.get_depends().len(), 1);
+    let _ = task.apply(&props, Local::now());
+    assert_eq!(task.get_depends(), &[uuid_1, uuid_2]);
+}
+
+#[test]
+fn test_apply_links() {
+    let mut task = setup_task();
+    let uuid_1 = Uuid::new_v4();
+    let uuid_2 = Uuid::new_v4();
+
+    let mut props = TaskProperties::default();
+    props.add_link_change(LinkChange::Add(
+        LinkType::RelatesTo,
+        TaskIdentifier::Uuid(uuid_1),
+    ));
+    props.add_link_change(LinkChange::Add(
+        LinkType::Duplicates,
+        TaskIdentifier::Uuid(uuid_2),
+    ));
+    task.apply(&props, Local::now()).unwrap();
+    assert_eq!(task.get_links(LinkType::RelatesTo), &[uuid_1]);
+    assert_eq!(task.get_links(LinkType::Duplicates), &[uuid_2]);
+    assert_true!(task.is_linked_to(LinkType::RelatesTo, &uuid_1));
+    assert_false!(task.is_linked_to(LinkType::RelatesTo, &uuid_2));
+
+    let mut props = TaskProperties::default();
+    props.add_link_change(LinkChange::Remove(
+        LinkType::RelatesTo,
+        TaskIdentifier::Uuid(uuid_1),
+    ));
+    props.add_link_change(LinkChange::Clear(LinkType::Duplicates));
+    task.apply(&props, Local::now()).unwrap();
+    assert_true!(task.get_links(LinkType::RelatesTo).is_empty());
+    assert_true!(task.get_links(LinkType::Duplicates).is_empty());
+    assert_eq!(task.get_history().len(), 4);
+
+    let mut props = TaskProperties::default();
+    props.add_link_change(LinkChange::Add(
+        LinkType::FollowsUp,
+        TaskIdentifier::Uuid(*task.get_uuid()),
+    ));
+    assert_true!(task.apply(&props, Local::now()).is_err());
+}
+
+#[test]
+fn test_deserialize_legacy_depends_on() {
+    let uuid = Uuid::new_v4();
+    let task: Task = serde_json::from_str(&format!(
+        r#"{{"id": 1, "status": "Pending", "uuid": "{}", "summary": "old task",
+            "tags": [], "date_created": "2024-01-01T10:00:00+00:00", "project": null,
+            "depends_on": ["{}"], "blocking": []}}"#,
+        Uuid::new_v4(),
+        uuid
+    ))
+    .unwrap();
+    assert_eq!(task.get_depends(), &[uuid]);
+
+    let value = serde_json::to_value(&task).unwrap();
+    assert_true!(value.get("depends_on").is_none());
+    assert_eq!(
+        value["links"]["depends-on"],
+        serde_json::json!([uuid.to_string()])
     );
-    assert_ne!(
-        task.depends_on.first().unwrap(),
-        task.depends_on.last().unwrap()
-    )
+    let task_again: Task = serde_json::from_value(value).unwrap();
+    assert_eq!(task_again, task);
 }
 
 #[test]
@@ -417,7 +634,7 @@ fn test_sort_tasks() {
         )
         .single()
         .unwrap();
-    let mut tasks = vec![
+    let mut tasks = [
         Task {
             id: Some(2),
             urgency: Some(2),
@@ -436,7 +653,7 @@ fn test_sort_tasks() {
     assert_eq!(tasks[0].id, Some(1));
     assert_eq!(tasks[1].id, Some(2));
 
-    let mut tasks = vec![
+    let mut tasks = [
         Task {
             id: Some(2),
             urgency: Some(2),
@@ -455,7 +672,7 @@ fn test_sort_tasks() {
     assert_eq!(tasks[0].id, Some(1));
     assert_eq!(tasks[1].id, Some(2));
 
-    let mut tasks = vec![
+    let mut tasks = [
         Task {
             id: Some(2),
             urgency: None,
@@ -474,7 +691,7 @@ fn test_sort_tasks() {
     assert_eq!(tasks[0].id, Some(1));
     assert_eq!(tasks[1].id, Some(2));
 
-    let mut tasks = vec![
+    let mut tasks = [
         Task {
             id: Some(2),
             urgency: None,
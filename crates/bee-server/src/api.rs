@@ -0,0 +1,293 @@
+use log::{debug, info};
+use percent_encoding::percent_decode_str;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use bee_actions::{ActionRegistry, command_parser::ParsedCommand};
+use bee_core::{
+    Printer,
+    clock::Clock,
+    config::{ReportConfig, get_config},
+    filters::{self, Filter},
+    task::{Task, TaskData, TaskProperties},
+};
+use bee_storage::storage::{Store, StoreError};
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[cfg(test)]
+#[path = "api_test.rs"]
+mod api_test;
+
+/// The endpoints of the API
+#[derive(Debug, PartialEq, Eq)]
+pub enum Route {
+    /// `GET /tasks?filter=<filter>`
+    List { filter: String },
+    /// `GET /tasks/<uuid>`
+    Get(Uuid),
+    /// `POST /tasks`
+    Add,
+    /// `PATCH /tasks/<uuid>`
+    Modify(Uuid),
+    /// `POST /tasks/<uuid>/done`
+    Done(Uuid),
+    /// `DELETE /tasks/<uuid>`
+    Delete(Uuid),
+    /// `POST /undo`
+    Undo,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ApiError {
+    pub status: u16,
+    pub message: String,
+}
+
+impl ApiError {
+    fn bad_request(message: String) -> Self {
+        ApiError {
+            status: 400,
+            message,
+        }
+    }
+
+    fn not_found(message: String) -> Self {
+        ApiError {
+            status: 404,
+            message,
+        }
+    }
+}
+
+impl From<StoreError> for ApiError {
+    fn from(error: StoreError) -> Self {
+        let status = match error {
+            StoreError::InvalidTasks(_)
+            | StoreError::InvalidChange(_)
+            | StoreError::UnknownTask(_) => 400,
+            StoreError::Io { .. } | StoreError::Parse { .. } | StoreError::Database(_) => 500,
+        };
+        ApiError {
+            status,
+            message: error.to_string(),
+        }
+    }
+}
+
+/// Body of the requests adding or modifying a task
+#[derive(Deserialize, Debug)]
+struct PropertiesBody {
+    /// Same as the arguments of the 'add' and 'modify' actions, e.g.
+    /// `"write the report +work due:friday"`
+    arguments: String,
+}
+
+/// Find the route of a request from its @method and @url (path and query string)
+pub fn parse_route(method: &str, url: &str) -> Result<Route, ApiError> {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+    let parse_uuid = |value: &str| {
+        Uuid::parse_str(value)
+            .map_err(|_| ApiError::bad_request(format!("Invalid task UUID '{}'.", value)))
+    };
+
+    match (method, segments.as_slice()) {
+        ("GET", ["tasks"]) => Ok(Route::List {
+            filter: get_query_parameter(query, "filter").unwrap_or_default(),
+        }),
+        ("GET", ["tasks", uuid]) => Ok(Route::Get(parse_uuid(uuid)?)),
+        ("POST", ["tasks"]) => Ok(Route::Add),
+        ("PATCH", ["tasks", uuid]) => Ok(Route::Modify(parse_uuid(uuid)?)),
+        ("POST", ["tasks", uuid, "done"]) => Ok(Route::Done(parse_uuid(uuid)?)),
+        ("DELETE", ["tasks", uuid]) => Ok(Route::Delete(parse_uuid(uuid)?)),
+        ("POST", ["undo"]) => Ok(Route::Undo),
+        _ => Err(ApiError::not_found(format!(
+            "No endpoint for {} {}.",
+            method, path
+        ))),
+    }
+}
+
+/// Decoded value of the parameter @name in the @query string
+fn get_query_parameter(query: &str, name: &str) -> Option<String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| {
+            percent_decode_str(&value.replace('+', " "))
+                .decode_utf8_lossy()
+                .into_owned()
+        })
+}
+
+/// Run the request to @route with the given @body and return the JSON answer.
+///
+/// Lists of tasks are serialised like the 'export' action does, and a single
+/// task for `GET /tasks/<uuid>`. The modifying endpoints answer with the tasks
+/// they changed. @clock gives the time of the changes and of the relative dates.
+pub fn handle<S: Store>(
+    route: &Route,
+    body: &str,
+    clock: Arc<dyn Clock>,
+) -> Result<String, ApiError> {
+    info!("Handling {:?}", route);
+    match route {
+        Route::List { filter } => {
+            let filter = filters::from_with_clock(std::slice::from_ref(filter), clock.clone())
+                .map_err(ApiError::bad_request)?;
+            let tasks = load_tasks::<S>(&filter, clock)?;
+            Ok(serde_json::to_string_pretty(&tasks).unwrap())
+        }
+        Route::Get(uuid) => {
+            let tasks = load_tasks::<S>(&uuid_filter(uuid), clock)?;
+            match tasks.get_task_map().get(uuid) {
+                Some(task) => Ok(serde_json::to_string_pretty(task).unwrap()),
+                None => Err(ApiError::not_found(format!("Task {} not found.", uuid))),
+            }
+        }
+        Route::Add => {
+            let body = parse_body(body)?;
+            let tasks = run_action::<S>("add", None, vec![body.arguments], clock)?;
+            Ok(to_json(&tasks))
+        }
+        Route::Modify(uuid) => {
+            let body = parse_body(body)?;
+            let tasks = run_action::<S>("modify", Some(*uuid), vec![body.arguments], clock)?;
+            Ok(to_json(&tasks))
+        }
+        Route::Done(uuid) => Ok(to_json(&run_action::<S>(
+            "done",
+            Some(*uuid),
+            vec![],
+            clock,
+        )?)),
+        Route::Delete(uuid) => Ok(to_json(&run_action::<S>(
+            "delete",
+            Some(*uuid),
+            vec![],
+            clock,
+        )?)),
+        Route::Undo => Ok(to_json(&run_action::<S>("undo", None, vec![], clock)?)),
+    }
+}
+
+fn parse_body(body: &str) -> Result<PropertiesBody, ApiError> {
+    serde_json::from_str(body).map_err(|e| {
+        ApiError::bad_request(format!(
+            "Invalid body, expected {{\"arguments\": \"...\"}}: {}",
+            e
+        ))
+    })
+}
+
+fn to_json(tasks: &[Task]) -> String {
+    let data: TaskData = tasks.iter().cloned().collect();
+    serde_json::to_string_pretty(&data).unwrap()
+}
+
+fn uuid_filter(uuid: &Uuid) -> Box<dyn Filter> {
+    filters::from(&[uuid.to_string()]).expect("A UUID is a valid filter")
+}
+
+#[allow(clippy::borrowed_box)]
+fn load_tasks<S: Store>(
+    filter: &Box<dyn Filter>,
+    clock: Arc<dyn Clock>,
+) -> Result<TaskData, ApiError> {
+    S::load_tasks(Some(filter), None, clock).map_err(ApiError::from)
+}
+
+/// Run the action called @action_name on the task @uuid, or on all the tasks
+/// if there is none, the same way the command line does. Return the tasks
+/// changed by the action, as they were written.
+fn run_action<S: Store>(
+    action_name: &str,
+    uuid: Option<Uuid>,
+    arguments: Vec<String>,
+    clock: Arc<dyn Clock>,
+) -> Result<Vec<Task>, ApiError> {
+    let undo_count = get_config().undo_depth;
+    let undos = S::load_undos(undo_count)?;
+
+    let props = TaskProperties::from_with_clock(&arguments, clock.clone())
+        .map_err(ApiError::bad_request)?;
+    let filter = uuid.map(|uuid| uuid_filter(&uuid));
+    let mut tasks = S::load_tasks(filter.as_ref(), Some(props), clock)?;
+    if let Some(uuid) = uuid
+        && !tasks.get_task_map().contains_key(&uuid)
+    {
+        return Err(ApiError::not_found(format!("Task {} not found.", uuid)));
+    }
+    for undo_action in &undos {
+        tasks.set_undos(&undo_action.tasks);
+    }
+    let tasks_before: HashMap<Uuid, Task> = tasks
+        .get_task_map()
+        .iter()
+        .chain(tasks.get_extra_tasks().iter())
+        .map(|(uuid, task)| (*uuid, task.clone()))
+        .collect();
+
+    let command = ParsedCommand {
+        command: action_name.to_string(),
+        arguments,
+        ..Default::default()
+    };
+    let mut action = ActionRegistry::get_action_from_command_parser(&command);
+    action.set_tasks(tasks);
+    action.set_undos(undos);
+    action
+        .do_action(&ServerPrinter)
+        .map_err(ApiError::bad_request)?;
+
+    let changed_uuids: Vec<Uuid> = action
+        .get_tasks()
+        .get_task_map()
+        .iter()
+        .chain(action.get_tasks().get_extra_tasks().iter())
+        .filter(|(uuid, task)| tasks_before.get(uuid) != Some(task))
+        .map(|(uuid, _)| *uuid)
+        .collect();
+    debug!("The action changed {} tasks", changed_uuids.len());
+
+    let written = S::write_tasks(action.get_tasks())?;
+    S::log_undo(undo_count, action.get_undos().to_owned(), undo_count)?;
+
+    Ok(changed_uuids
+        .iter()
+        .filter_map(|uuid| written.get_owned(uuid))
+        .collect())
+}
+
+/// The actions run by the server only give information messages, which are logged
+struct ServerPrinter;
+
+impl Printer for ServerPrinter {
+    fn print_list_of_tasks(&self, _: Vec<&Task>, _: &ReportConfig) -> Result<(), String> {
+        Err("Listing tasks is not supported by the server".to_string())
+    }
+
+    fn print_task_info(&self, _: &Task, _: Vec<&Task>) -> Result<(), String> {
+        Err("Showing a task is not supported by the server".to_string())
+    }
+
+    fn show_help(&self, _: &HashMap<String, String>) -> Result<(), String> {
+        Err("The help is not supported by the server".to_string())
+    }
+
+    fn show_information_message(&self, message: &str) {
+        info!("{}", message);
+    }
+
+    fn error(&self, message: &str) {
+        log::error!("{}", message);
+    }
+
+    fn print_raw(&self, message: &str) {
+        debug!("{}", message);
+    }
+}
@@ -0,0 +1,162 @@
+use all_asserts::*;
+use uuid::Uuid;
+
+use super::*;
+
+const UUID: &str = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+
+#[test]
+fn test_parse_route() {
+    let uuid = Uuid::parse_str(UUID).unwrap();
+
+    assert_eq!(
+        parse_route("GET", "/tasks"),
+        Ok(Route::List {
+            filter: String::new()
+        })
+    );
+    assert_eq!(
+        parse_route("GET", &format!("/tasks/{}", UUID)),
+        Ok(Route::Get(uuid))
+    );
+    assert_eq!(parse_route("POST", "/tasks"), Ok(Route::Add));
+    assert_eq!(
+        parse_route("PATCH", &format!("/tasks/{}", UUID)),
+        Ok(Route::Modify(uuid))
+    );
+    assert_eq!(
+        parse_route("POST", &format!("/tasks/{}/done", UUID)),
+        Ok(Route::Done(uuid))
+    );
+    assert_eq!(
+        parse_route("DELETE", &format!("/tasks/{}/", UUID)),
+        Ok(Route::Delete(uuid))
+    );
+    assert_eq!(parse_route("POST", "/undo"), Ok(Route::Undo));
+}
+
+#[test]
+fn test_parse_route_errors() {
+    assert_eq!(parse_route("GET", "/tasks/12").unwrap_err().status, 400);
+    assert_eq!(parse_route("PUT", "/tasks").unwrap_err().status, 404);
+    assert_eq!(parse_route("GET", "/projects").unwrap_err().status, 404);
+}
+
+#[test]
+fn test_parse_route_filter() {
+    assert_eq!(
+        parse_route("GET", "/tasks?filter=%2Bwork+project%3Abee&other=1"),
+        Ok(Route::List {
+            filter: "+work project:bee".to_string()
+        })
+    );
+    assert_eq!(
+        parse_route("GET", "/tasks?other=1&filter=status:pending"),
+        Ok(Route::List {
+            filter: "status:pending".to_string()
+        })
+    );
+}
+
+#[test]
+fn test_parse_body() {
+    assert_eq!(
+        parse_body(r#"{"arguments": "write docs +work"}"#)
+            .unwrap()
+            .arguments,
+        "write docs +work"
+    );
+    assert_true!(parse_body("write docs").is_err());
+    assert_true!(parse_body("{}").is_err());
+}
+
+#[test]
+fn test_handle_actions() {
+    use bee_core::clock::FixedClock;
+    use bee_core::task::TaskStatus;
+    use bee_storage::storage::JsonStore;
+    use chrono::{Local, TimeZone};
+
+    // The only test using the store, so that nothing else reads the variable
+    let data_home = tempfile::tempdir().unwrap();
+    unsafe { std::env::set_var("BEE_DATA_HOME", data_home.path()) };
+    let now = Local.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap();
+    let clock: Arc<dyn Clock> = Arc::new(FixedClock::new(now));
+    let handle = |route: Route, body: &str| handle::<JsonStore>(&route, body, clock.clone());
+    let stored = || JsonStore::load_raw_tasks().unwrap();
+
+    handle(
+        Route::Add,
+        r#"{"arguments": "write docs +work due:tomorrow"}"#,
+    )
+    .unwrap();
+    let tasks = stored();
+    assert_eq!(tasks.get_task_map().len(), 1);
+    let task = tasks.get_task_map().values().next().unwrap().clone();
+    let uuid = *task.get_uuid();
+    assert_eq!(task.get_summary(), "write docs");
+    assert_eq!(task.get_tags(), &vec!["work".to_string()]);
+    assert_eq!(task.get_date_created(), &now);
+    assert_eq!(
+        task.get_date_due(),
+        &Some(Local.with_ymd_and_hms(2026, 3, 3, 0, 0, 0).unwrap())
+    );
+
+    // The relative dates of the filters are relative to the clock as well
+    let list = |filter: &str| {
+        handle(
+            Route::List {
+                filter: filter.to_string(),
+            },
+            "",
+        )
+        .unwrap()
+    };
+    assert_true!(list("due:tomorrow").contains("write docs"));
+    assert_false!(list("due:today").contains("write docs"));
+
+    handle(Route::Modify(uuid), r#"{"arguments": "write the docs"}"#).unwrap();
+    assert_eq!(
+        stored().get_task_map()[&uuid].get_summary(),
+        "write the docs"
+    );
+
+    handle(Route::Done(uuid), "").unwrap();
+    assert_eq!(
+        stored().get_task_map()[&uuid].get_status(),
+        &TaskStatus::Completed
+    );
+
+    handle(Route::Undo, "").unwrap();
+    assert_eq!(
+        stored().get_task_map()[&uuid].get_status(),
+        &TaskStatus::Pending
+    );
+
+    handle(Route::Delete(uuid), "").unwrap();
+    assert_eq!(
+        stored().get_task_map()[&uuid].get_status(),
+        &TaskStatus::Deleted
+    );
+
+    // The errors keep the tasks as they are
+    let unknown = Uuid::parse_str(UUID).unwrap();
+    assert_eq!(handle(Route::Done(unknown), "").unwrap_err().status, 404);
+    assert_eq!(
+        handle(Route::Modify(uuid), "write docs")
+            .unwrap_err()
+            .status,
+        400
+    );
+    assert_eq!(
+        handle(Route::Add, r#"{"arguments": "due:someday"}"#)
+            .unwrap_err()
+            .status,
+        400
+    );
+    assert_eq!(stored().get_task_map().len(), 1);
+    assert_eq!(
+        stored().get_task_map()[&uuid].get_status(),
+        &TaskStatus::Deleted
+    );
+}
@@ -0,0 +1,95 @@
+mod api;
+
+use bee_core::{
+    clock::{Clock, clock_from_env},
+    config::{StorageBackend, get_config, load_config},
+};
+use bee_storage::{
+    sqlite_store::SqliteStore,
+    storage::{JsonStore, Store, lock_data},
+};
+use log::{error, info};
+use tiny_http::{Header, Request, Response, Server};
+
+use std::process::exit;
+use std::sync::Arc;
+
+use crate::api::{ApiError, handle, parse_route};
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:8080";
+
+fn main() {
+    env_logger::init();
+
+    if let Err(msg) = load_config() {
+        eprintln!("Error: {}", msg);
+        exit(1);
+    }
+    // `BEE_NOW` fixes the time of every request, as it does for the command line
+    let clock = match clock_from_env() {
+        Ok(clock) => clock,
+        Err(msg) => {
+            eprintln!("Error: {}", msg);
+            exit(1);
+        }
+    };
+
+    let address = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_ADDRESS.to_string());
+    let server = match Server::http(&address) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("Error: Unable to listen on {}: {}", address, e);
+            exit(1);
+        }
+    };
+    println!("Listening on http://{}", address);
+
+    match get_config().storage {
+        StorageBackend::Json => serve::<JsonStore>(server, clock),
+        StorageBackend::Sqlite => serve::<SqliteStore>(server, clock),
+    }
+}
+
+/// Answer the requests one at a time, so that two writes of the server can never
+/// interleave. @clock gives the time of the changes.
+fn serve<S: Store>(server: Server, clock: Arc<dyn Clock>) {
+    for request in server.incoming_requests() {
+        answer::<S>(request, clock.clone());
+    }
+}
+
+fn answer<S: Store>(mut request: Request, clock: Arc<dyn Clock>) {
+    info!("{} {}", request.method(), request.url());
+    let mut body = String::new();
+    let result = match request.as_reader().read_to_string(&mut body) {
+        Ok(_) => parse_route(request.method().as_str(), request.url()).and_then(|route| {
+            // The command line might be writing the tasks at the same time
+            let _lock = lock_data().map_err(ApiError::from)?;
+            handle::<S>(&route, &body, clock)
+        }),
+        Err(e) => Err(ApiError {
+            status: 400,
+            message: format!("Unable to read the request body: {}", e),
+        }),
+    };
+
+    let (status, content) = match result {
+        Ok(content) => (200, content),
+        Err(e) => {
+            error!("{}", e.message);
+            (
+                e.status,
+                serde_json::json!({ "error": e.message }).to_string(),
+            )
+        }
+    };
+    let header = Header::from_bytes("Content-Type", "application/json").unwrap();
+    let response = Response::from_string(content)
+        .with_status_code(status)
+        .with_header(header);
+    if let Err(e) = request.respond(response) {
+        error!("Unable to send the response: {}", e);
+    }
+}
@@ -1 +1,2 @@
+pub mod sqlite_store;
 pub mod storage;
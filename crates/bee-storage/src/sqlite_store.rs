@@ -0,0 +1,439 @@
+use log::debug;
+use rusqlite::{Connection, params, params_from_iter, types::Value};
+use uuid::Uuid;
+
+use bee_actions::ActionUndo;
+use bee_core::{
+    clock::Clock,
+    filters::{self, Filter, IndexedFilter},
+    task::{Task, TaskData, TaskIdentifier, TaskProperties},
+};
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::storage::{
+    Store, StoreError, create_parent_dir, find_data_file, find_logged_file,
+    get_changed_dependencies, get_sqlite_file_path, select_tasks,
+};
+
+#[cfg(test)]
+#[path = "sqlite_store_test.rs"]
+mod sqlite_store_test;
+
+/// The whole task is stored as JSON in the `data` column. The other columns
+/// duplicate the fields we filter on the most, so that they can be indexed.
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS tasks (
+    uuid TEXT PRIMARY KEY NOT NULL,
+    id INTEGER,
+    status TEXT NOT NULL,
+    project TEXT,
+    date_due INTEGER,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS tasks_status ON tasks(status);
+CREATE INDEX IF NOT EXISTS tasks_project ON tasks(project);
+CREATE INDEX IF NOT EXISTS tasks_date_due ON tasks(date_due);
+
+CREATE TABLE IF NOT EXISTS task_tags (
+    uuid TEXT NOT NULL,
+    tag TEXT NOT NULL,
+    PRIMARY KEY (uuid, tag)
+);
+CREATE INDEX IF NOT EXISTS task_tags_tag ON task_tags(tag);
+
+CREATE TABLE IF NOT EXISTS undos (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    data TEXT NOT NULL
+);
+";
+
+/// Tasks that are neither completed nor deleted. They are always loaded, as we need
+/// all of them to number the tasks.
+const OPEN_TASKS_CLAUSE: &str = "status IN ('pending', 'active')";
+
+#[derive(Default)]
+pub struct SqliteStore {}
+
+impl Store for SqliteStore {
+    #[allow(clippy::borrowed_box)]
+    fn load_tasks(
+        filter: Option<&Box<dyn Filter>>,
+        props: Option<TaskProperties>,
+        clock: Arc<dyn Clock>,
+    ) -> Result<TaskData, StoreError> {
+        debug!(
+            "Loading tasks using filter:\n{}",
+            &filter.unwrap_or(&filters::new_empty()).to_string()
+        );
+        let conn = open_connection()?;
+        load_tasks_from(&conn, filter, props, clock)
+    }
+
+    fn write_tasks(data: &TaskData) -> Result<TaskData, StoreError> {
+        let mut conn = open_connection()?;
+        write_tasks_to(&mut conn, data)
+    }
+
+    fn load_undos(last_count: usize) -> Result<Vec<ActionUndo>, StoreError> {
+        let conn = open_connection()?;
+        load_undos_from(&conn, last_count)
+    }
+
+    fn log_undo(
+        count: usize,
+        updated_undos: Vec<ActionUndo>,
+        history_depth: usize,
+    ) -> Result<(), StoreError> {
+        let mut conn = open_connection()?;
+        log_undo_to(&mut conn, count, updated_undos, history_depth)
+    }
+
+    fn load_raw_tasks() -> Result<TaskData, StoreError> {
+        let conn = open_connection()?;
+        Ok(query_tasks(&conn, "1", Vec::new())?.into_values().collect())
+    }
+}
+
+fn db_error(e: rusqlite::Error) -> StoreError {
+    StoreError::Database(e.to_string())
+}
+
+/// Open the database, creating it if needed. When it is created, the tasks and
+/// undo log of the JSON backend are imported into it.
+fn open_connection() -> Result<Connection, StoreError> {
+    let path = get_sqlite_file_path();
+    let is_new = !Path::new(&path).exists();
+    if is_new {
+        create_parent_dir(&path)?;
+        debug!("Creating task database at {}", path);
+    }
+
+    let mut conn = Connection::open(&path).map_err(db_error)?;
+    conn.execute_batch(SCHEMA).map_err(db_error)?;
+
+    if is_new {
+        let tasks_json = match find_data_file() {
+            Ok(file) => Some(fs::read_to_string(&file).map_err(|e| StoreError::io(&file, e))?),
+            Err(_) => None,
+        };
+        let undos_json = match find_logged_file() {
+            Ok(file) => Some(fs::read_to_string(&file).map_err(|e| StoreError::io(&file, e))?),
+            Err(_) => None,
+        };
+        migrate_from_json(&mut conn, tasks_json.as_deref(), undos_json.as_deref())?;
+    }
+
+    Ok(conn)
+}
+
+/// Import the content of the JSON backend files into an empty database
+fn migrate_from_json(
+    conn: &mut Connection,
+    tasks_json: Option<&str>,
+    undos_json: Option<&str>,
+) -> Result<(), StoreError> {
+    let tx = conn.transaction().map_err(db_error)?;
+
+    if let Some(content) = tasks_json.filter(|c| !c.trim().is_empty()) {
+        let data: TaskData = serde_json::from_str(content).map_err(|e| {
+            StoreError::Database(format!("Unable to read the JSON tasks to migrate: {}", e))
+        })?;
+        debug!("Migrating {} tasks from JSON", data.get_task_map().len());
+        for task in data.get_task_map().values() {
+            upsert_task(&tx, task)?;
+        }
+    }
+
+    if let Some(content) = undos_json.filter(|c| !c.trim().is_empty()) {
+        let undos: Vec<ActionUndo> = serde_json::from_str(content).map_err(|e| {
+            StoreError::Database(format!(
+                "Unable to read the JSON undo log to migrate: {}",
+                e
+            ))
+        })?;
+        debug!("Migrating {} undos from JSON", undos.len());
+        for undo in &undos {
+            insert_undo(&tx, undo)?;
+        }
+    }
+
+    tx.commit().map_err(db_error)
+}
+
+#[allow(clippy::borrowed_box)]
+fn load_tasks_from(
+    conn: &Connection,
+    filter: Option<&Box<dyn Filter>>,
+    props: Option<TaskProperties>,
+    clock: Arc<dyn Clock>,
+) -> Result<TaskData, StoreError> {
+    let indexed_filter = filter.map_or(IndexedFilter::All, filters::to_indexed);
+    let mut params = Vec::new();
+    let clause = where_clause(&indexed_filter, &mut params);
+    let mut tasks = query_tasks(
+        conn,
+        &format!("{} OR {}", OPEN_TASKS_CLAUSE, clause),
+        params,
+    )?;
+
+    // The tasks referenced by the properties might be neither open nor matched
+    // by the filter
+    let mut referenced_uuids = Vec::new();
+    if let Some(props) = &props {
+        for task_identifier in props.get_referenced_tasks() {
+            if let TaskIdentifier::Uuid(uuid) = task_identifier {
+                referenced_uuids.push(uuid);
+            }
+        }
+    }
+    for task in load_tasks_by_uuid(conn, &referenced_uuids)? {
+        tasks.insert(*task.get_uuid(), task);
+    }
+    load_linked_tasks(conn, &mut tasks)?;
+
+    let mut data: TaskData = tasks.into_values().collect();
+    data.set_clock(clock);
+    data.upkeep().map_err(StoreError::InvalidTasks)?;
+    select_tasks(data, filter, props)
+}
+
+fn write_tasks_to(conn: &mut Connection, data: &TaskData) -> Result<TaskData, StoreError> {
+    let written_uuids: Vec<Uuid> = data.get_task_map().keys().cloned().collect();
+    let mut tasks = query_tasks(conn, OPEN_TASKS_CLAUSE, Vec::new())?;
+    for task in load_tasks_by_uuid(conn, &written_uuids)? {
+        tasks.insert(*task.get_uuid(), task);
+    }
+    let changed_dependencies = get_changed_dependencies(&tasks, data);
+    // The tasks that are not linked anymore need their backlinks updated too
+    load_linked_tasks(conn, &mut tasks)?;
+    for task in data.get_task_map().values() {
+        tasks.insert(*task.get_uuid(), task.clone());
+    }
+    load_linked_tasks(conn, &mut tasks)?;
+
+    let mut stored_tasks: TaskData = tasks.into_values().collect();
+    stored_tasks.set_clock(data.get_clock().clone());
+    stored_tasks.upkeep().map_err(StoreError::InvalidTasks)?;
+    for uuid in changed_dependencies {
+        stored_tasks
+            .check_dependency_cycle(&uuid)
+            .map_err(StoreError::InvalidChange)?;
+    }
+
+    let tx = conn.transaction().map_err(db_error)?;
+    for task in stored_tasks.get_task_map().values() {
+        upsert_task(&tx, task)?;
+    }
+    tx.commit().map_err(db_error)?;
+
+    Ok(stored_tasks)
+}
+
+fn load_undos_from(conn: &Connection, last_count: usize) -> Result<Vec<ActionUndo>, StoreError> {
+    let mut stmt = conn
+        .prepare("SELECT data FROM undos ORDER BY seq DESC LIMIT ?1")
+        .map_err(db_error)?;
+    let rows = stmt
+        .query_map(params![last_count as i64], |row| row.get::<_, String>(0))
+        .map_err(db_error)?;
+
+    let mut undos = Vec::new();
+    for row in rows {
+        let undo: ActionUndo = serde_json::from_str(&row.map_err(db_error)?).map_err(|e| {
+            StoreError::Database(format!("Unable to read an undo from the database: {}", e))
+        })?;
+        undos.push(undo);
+    }
+    undos.reverse();
+    Ok(undos)
+}
+
+/// Replace the last @count undos with @updated_undos, keeping at most @history_depth undos
+fn log_undo_to(
+    conn: &mut Connection,
+    count: usize,
+    updated_undos: Vec<ActionUndo>,
+    history_depth: usize,
+) -> Result<(), StoreError> {
+    let tx = conn.transaction().map_err(db_error)?;
+    tx.execute(
+        "DELETE FROM undos WHERE seq IN (SELECT seq FROM undos ORDER BY seq DESC LIMIT ?1)",
+        params![count as i64],
+    )
+    .map_err(db_error)?;
+    for undo in &updated_undos {
+        insert_undo(&tx, undo)?;
+    }
+    tx.execute(
+        "DELETE FROM undos WHERE seq NOT IN (SELECT seq FROM undos ORDER BY seq DESC LIMIT ?1)",
+        params![history_depth as i64],
+    )
+    .map_err(db_error)?;
+    tx.commit().map_err(db_error)
+}
+
+fn insert_undo(conn: &Connection, undo: &ActionUndo) -> Result<(), StoreError> {
+    let undo_json = serde_json::to_string(undo).expect("Failed to serialize undo to JSON");
+    conn.execute("INSERT INTO undos (data) VALUES (?1)", params![undo_json])
+        .map_err(db_error)?;
+    Ok(())
+}
+
+fn upsert_task(conn: &Connection, task: &Task) -> Result<(), StoreError> {
+    let uuid = task.get_uuid().to_string();
+    let task_json = serde_json::to_string(task).expect("Failed to serialize task to JSON");
+    conn.execute(
+        "INSERT INTO tasks (uuid, id, status, project, date_due, data)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT(uuid) DO UPDATE SET
+            id = excluded.id,
+            status = excluded.status,
+            project = excluded.project,
+            date_due = excluded.date_due,
+            data = excluded.data",
+        params![
+            uuid,
+            task.get_id().map(|id| id as i64),
+            task.get_status().to_string(),
+            task.get_project().as_ref().map(|p| p.get_name().to_owned()),
+            task.get_date_due().map(|d| d.timestamp()),
+            task_json,
+        ],
+    )
+    .map_err(db_error)?;
+
+    conn.execute("DELETE FROM task_tags WHERE uuid = ?1", params![uuid])
+        .map_err(db_error)?;
+    for tag in task.get_tags() {
+        conn.execute(
+            "INSERT OR IGNORE INTO task_tags (uuid, tag) VALUES (?1, ?2)",
+            params![uuid, tag],
+        )
+        .map_err(db_error)?;
+    }
+    Ok(())
+}
+
+fn query_tasks(
+    conn: &Connection,
+    clause: &str,
+    params: Vec<Value>,
+) -> Result<HashMap<Uuid, Task>, StoreError> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT data FROM tasks WHERE {}", clause))
+        .map_err(db_error)?;
+    let rows = stmt
+        .query_map(params_from_iter(params), |row| row.get::<_, String>(0))
+        .map_err(db_error)?;
+
+    let mut tasks = HashMap::new();
+    for row in rows {
+        let task: Task = serde_json::from_str(&row.map_err(db_error)?).map_err(|e| {
+            StoreError::Database(format!("Unable to read a task from the database: {}", e))
+        })?;
+        tasks.insert(*task.get_uuid(), task);
+    }
+    Ok(tasks)
+}
+
+fn load_tasks_by_uuid(conn: &Connection, uuids: &[Uuid]) -> Result<Vec<Task>, StoreError> {
+    if uuids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let placeholders = vec!["?"; uuids.len()].join(", ");
+    let params = uuids.iter().map(|u| Value::Text(u.to_string())).collect();
+    Ok(
+        query_tasks(conn, &format!("uuid IN ({})", placeholders), params)?
+            .into_values()
+            .collect(),
+    )
+}
+
+/// Add to @tasks all the tasks that can be reached from them through their links,
+/// so that they can be kept up to date.
+fn load_linked_tasks(conn: &Connection, tasks: &mut HashMap<Uuid, Task>) -> Result<(), StoreError> {
+    let mut requested: HashSet<Uuid> = tasks.keys().cloned().collect();
+    let mut to_load: Vec<Uuid> = tasks
+        .values()
+        .flat_map(|t| t.get_extra_uuid())
+        .filter(|uuid| requested.insert(*uuid))
+        .collect();
+
+    while !to_load.is_empty() {
+        let loaded = load_tasks_by_uuid(conn, &to_load)?;
+        to_load = loaded
+            .iter()
+            .flat_map(|t| t.get_extra_uuid())
+            .filter(|uuid| requested.insert(*uuid))
+            .collect();
+        for task in loaded {
+            tasks.insert(*task.get_uuid(), task);
+        }
+    }
+    Ok(())
+}
+
+/// Turn @filter into an SQL expression, pushing its parameters in @params
+fn where_clause(filter: &IndexedFilter, params: &mut Vec<Value>) -> String {
+    match filter {
+        IndexedFilter::All => "1".to_string(),
+        IndexedFilter::And(children) => format!(
+            "({})",
+            children
+                .iter()
+                .map(|child| where_clause(child, params))
+                .collect::<Vec<String>>()
+                .join(" AND ")
+        ),
+        IndexedFilter::Or(children) => format!(
+            "({})",
+            children
+                .iter()
+                .map(|child| where_clause(child, params))
+                .collect::<Vec<String>>()
+                .join(" OR ")
+        ),
+        IndexedFilter::Status(status) => {
+            params.push(Value::Text(status.to_string()));
+            "status = ?".to_string()
+        }
+        IndexedFilter::Project(name) => {
+            let escaped = name
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            params.push(Value::Text(format!("{}%", escaped)));
+            "project LIKE ? ESCAPE '\\'".to_string()
+        }
+        IndexedFilter::Tag { name, include } => {
+            params.push(Value::Text(name.to_owned()));
+            let operator = if *include { "IN" } else { "NOT IN" };
+            format!(
+                "uuid {} (SELECT uuid FROM task_tags WHERE tag = ?)",
+                operator
+            )
+        }
+        IndexedFilter::Id(id) => {
+            params.push(Value::Integer(*id as i64));
+            "id = ?".to_string()
+        }
+        IndexedFilter::Uuid(uuid) => {
+            params.push(Value::Text(uuid.to_string()));
+            "uuid = ?".to_string()
+        }
+        // Due dates are stored in seconds, so these comparisons include the whole
+        // second the filter time falls in.
+        IndexedFilter::DueBefore(time) => {
+            params.push(Value::Integer(time.timestamp()));
+            "date_due <= ?".to_string()
+        }
+        IndexedFilter::DueAfter(time) => {
+            params.push(Value::Integer(time.timestamp()));
+            "date_due >= ?".to_string()
+        }
+    }
+}
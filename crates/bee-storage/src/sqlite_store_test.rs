@@ -0,0 +1,253 @@
+use super::*;
+
+use all_asserts::{assert_false, assert_true};
+use bee_actions::ActionUndoType;
+use bee_core::clock::{Clock, SystemClock};
+use bee_core::task::TaskStatus;
+
+fn open_test_connection() -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(SCHEMA).unwrap();
+    conn
+}
+
+fn add_task(data: &mut TaskData, args: &str) -> Uuid {
+    *data
+        .add_task(
+            &TaskProperties::from(&[args.to_owned()]).unwrap(),
+            TaskStatus::Pending,
+        )
+        .unwrap()
+        .get_uuid()
+}
+
+#[test]
+fn test_write_and_load_tasks() {
+    let mut conn = open_test_connection();
+
+    let mut data = TaskData::default();
+    let pending_uuid = add_task(&mut data, "pending task +home");
+    let done_uuid = add_task(&mut data, "completed task +home project:work.bee");
+    let other_done_uuid = add_task(&mut data, "other completed task +garden");
+    data.task_done(&done_uuid);
+    data.task_done(&other_done_uuid);
+    write_tasks_to(&mut conn, &data).unwrap();
+
+    let loaded = load_tasks_from(&conn, None, None, Arc::new(SystemClock)).unwrap();
+    assert_eq!(loaded.get_task_map().len(), 3);
+    assert_eq!(
+        loaded.get_task_map().get(&pending_uuid).unwrap().get_id(),
+        Some(1)
+    );
+
+    let filter = filters::from(&["status:completed +home".to_owned()]).unwrap();
+    let loaded = load_tasks_from(&conn, Some(&filter), None, Arc::new(SystemClock)).unwrap();
+    assert_eq!(
+        loaded.get_task_map().keys().collect::<Vec<_>>(),
+        vec![&done_uuid]
+    );
+
+    let filter = filters::from(&["project:work".to_owned()]).unwrap();
+    let loaded = load_tasks_from(&conn, Some(&filter), None, Arc::new(SystemClock)).unwrap();
+    assert_eq!(
+        loaded.get_task_map().keys().collect::<Vec<_>>(),
+        vec![&done_uuid]
+    );
+
+    // Modifying a task only touches that task
+    let mut modified = loaded.get_owned(&done_uuid).unwrap();
+    modified.set_summary("new summary");
+    let mut modified_data = TaskData::default();
+    modified_data.set_task(modified);
+    let stored = write_tasks_to(&mut conn, &modified_data).unwrap();
+    assert_false!(stored.get_task_map().contains_key(&other_done_uuid));
+
+    let loaded = load_tasks_from(&conn, None, None, Arc::new(SystemClock)).unwrap();
+    assert_eq!(loaded.get_task_map().len(), 3);
+    assert_eq!(
+        loaded.get_task_map().get(&done_uuid).unwrap().get_summary(),
+        "new summary"
+    );
+}
+
+#[test]
+fn test_only_candidate_tasks_are_read() {
+    let mut conn = open_test_connection();
+
+    let mut data = TaskData::default();
+    add_task(&mut data, "pending task");
+    let done_uuid = add_task(&mut data, "completed task +home");
+    let other_done_uuid = add_task(&mut data, "other completed task");
+    data.task_done(&done_uuid);
+    data.task_done(&other_done_uuid);
+    write_tasks_to(&mut conn, &data).unwrap();
+
+    let mut params = Vec::new();
+    let filter = filters::from(&["+home".to_owned()]).unwrap();
+    let clause = where_clause(&filters::to_indexed(&filter), &mut params);
+    let candidates = query_tasks(
+        &conn,
+        &format!("{} OR {}", OPEN_TASKS_CLAUSE, clause),
+        params,
+    )
+    .unwrap();
+    assert_eq!(candidates.len(), 2);
+    assert_true!(candidates.contains_key(&done_uuid));
+    assert_false!(candidates.contains_key(&other_done_uuid));
+}
+
+#[test]
+fn test_load_negated_compound_filter() {
+    let mut conn = open_test_connection();
+
+    let mut data = TaskData::default();
+    let first = add_task(&mut data, "first task +a");
+    let second = add_task(&mut data, "second task +a");
+    data.task_done(&first);
+    data.task_done(&second);
+    write_tasks_to(&mut conn, &data).unwrap();
+
+    // No task matches the group, so its negation matches all of them
+    let filter = filters::from(&["not (+a and summary:zzz)".to_owned()]).unwrap();
+    let loaded = load_tasks_from(&conn, Some(&filter), None, Arc::new(SystemClock)).unwrap();
+    assert_eq!(loaded.get_task_map().len(), 2);
+    assert_true!(loaded.get_task_map().contains_key(&first));
+    assert_true!(loaded.get_task_map().contains_key(&second));
+}
+
+#[test]
+fn test_load_linked_tasks() {
+    let mut conn = open_test_connection();
+
+    let mut data = TaskData::default();
+    let first_uuid = add_task(&mut data, "first +home");
+    let second_uuid = add_task(&mut data, &format!("second depends:{}", first_uuid));
+    let mut stored = write_tasks_to(&mut conn, &data).unwrap();
+    stored.task_done(&first_uuid);
+    let mut stored = write_tasks_to(&mut conn, &stored).unwrap();
+    stored.task_done(&second_uuid);
+    write_tasks_to(&mut conn, &stored).unwrap();
+
+    // The second task doesn't match the filter, but the first one is still
+    // blocking it
+    let filter = filters::from(&["status:completed +home".to_owned()]).unwrap();
+    let loaded = load_tasks_from(&conn, Some(&filter), None, Arc::new(SystemClock)).unwrap();
+    assert_eq!(
+        loaded.get_task_map().keys().collect::<Vec<_>>(),
+        vec![&first_uuid]
+    );
+    assert_true!(loaded.get_extra_tasks().contains_key(&second_uuid));
+}
+
+#[test]
+fn test_undo_log() {
+    let mut conn = open_test_connection();
+    let undo = |action_type: ActionUndoType| ActionUndo {
+        action_type,
+        ..Default::default()
+    };
+
+    log_undo_to(
+        &mut conn,
+        0,
+        vec![undo(ActionUndoType::Add), undo(ActionUndoType::Modify)],
+        10,
+    )
+    .unwrap();
+    let undos = load_undos_from(&conn, 1).unwrap();
+    assert_eq!(undos.len(), 1);
+    assert_eq!(undos[0].action_type, ActionUndoType::Modify);
+
+    // The last undo gets replaced
+    log_undo_to(&mut conn, 1, vec![undo(ActionUndoType::Add)], 10).unwrap();
+    let undos = load_undos_from(&conn, 10).unwrap();
+    assert_eq!(
+        undos.iter().map(|u| &u.action_type).collect::<Vec<_>>(),
+        vec![&ActionUndoType::Add, &ActionUndoType::Add]
+    );
+
+    // Only the most recent undos are kept
+    log_undo_to(
+        &mut conn,
+        0,
+        vec![undo(ActionUndoType::Modify), undo(ActionUndoType::Modify)],
+        3,
+    )
+    .unwrap();
+    let undos = load_undos_from(&conn, 10).unwrap();
+    assert_eq!(
+        undos.iter().map(|u| &u.action_type).collect::<Vec<_>>(),
+        vec![
+            &ActionUndoType::Add,
+            &ActionUndoType::Modify,
+            &ActionUndoType::Modify
+        ]
+    );
+
+    log_undo_to(&mut conn, 3, Vec::new(), 3).unwrap();
+    assert_true!(load_undos_from(&conn, 10).unwrap().is_empty());
+}
+
+#[test]
+fn test_migrate_from_json() {
+    let mut conn = open_test_connection();
+
+    let mut data = TaskData::default();
+    let uuid = add_task(&mut data, "migrated task +home");
+    data.upkeep().unwrap();
+    let tasks_json = serde_json::to_string(&data).unwrap();
+    let undos_json = serde_json::to_string(&vec![ActionUndo {
+        action_type: ActionUndoType::Add,
+        tasks: data.get_task_map().values().cloned().collect(),
+        ..Default::default()
+    }])
+    .unwrap();
+
+    migrate_from_json(&mut conn, Some(&tasks_json), Some(&undos_json)).unwrap();
+
+    let filter = filters::from(&["+home".to_owned()]).unwrap();
+    let loaded = load_tasks_from(&conn, Some(&filter), None, Arc::new(SystemClock)).unwrap();
+    assert_eq!(
+        loaded.get_task_map().keys().collect::<Vec<_>>(),
+        vec![&uuid]
+    );
+    assert_eq!(load_undos_from(&conn, 1).unwrap().len(), 1);
+
+    // Nothing to migrate
+    let mut conn = open_test_connection();
+    migrate_from_json(&mut conn, None, Some("")).unwrap();
+    assert_true!(
+        load_tasks_from(&conn, None, None, Arc::new(SystemClock))
+            .unwrap()
+            .get_task_map()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_write_rejects_dependency_cycle() {
+    let mut conn = open_test_connection();
+
+    let mut data = TaskData::default();
+    let first_uuid = add_task(&mut data, "first");
+    let second_uuid = add_task(&mut data, &format!("second depends:{}", first_uuid));
+    let stored = write_tasks_to(&mut conn, &data).unwrap();
+
+    // The task was loaded alone, so the cycle can only be seen when writing it
+    let mut first = stored.get_owned(&first_uuid).unwrap();
+    first
+        .apply(
+            &TaskProperties::from(&[format!("depends:{}", second_uuid)]).unwrap(),
+            SystemClock.now(),
+        )
+        .unwrap();
+    let mut modified_data = TaskData::default();
+    modified_data.set_task(first);
+    match write_tasks_to(&mut conn, &modified_data) {
+        Err(StoreError::InvalidChange(e)) => assert_true!(e.starts_with("Circular dependency: ")),
+        _ => panic!("The circular dependency was written"),
+    }
+
+    let loaded = load_tasks_from(&conn, None, None, Arc::new(SystemClock)).unwrap();
+    assert_true!(loaded.get_task_map()[&first_uuid].get_depends().is_empty());
+}
@@ -3,30 +3,128 @@ use uuid::Uuid;
 
 use bee_actions::ActionUndo;
 use bee_core::{
+    clock::Clock,
     filters::{self, Filter},
-    task::{DependsOnIdentifier, TaskData, TaskProperties},
+    task::{Task, TaskData, TaskIdentifier, TaskProperties},
 };
+use serde::de::DeserializeOwned;
 
 use std::collections::HashMap;
 use std::env;
+use std::fmt;
 use std::fs;
 use std::io;
-use std::io::Read;
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 #[path = "storage_test.rs"]
 mod storage_test;
 
+/// Why the tasks or the undo log could not be loaded or written
+#[derive(Debug)]
+pub enum StoreError {
+    /// A file could not be read or written
+    Io { path: String, error: io::Error },
+    /// A file is not valid JSON, or its content is not what bee expects
+    Parse {
+        path: String,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// The SQLite database returned an error
+    Database(String),
+    /// The tasks are not consistent with each other, e.g. a task depends on a
+    /// task which does not exist
+    InvalidTasks(String),
+    /// The changes made to the tasks are rejected, e.g. they create a circular
+    /// dependency
+    InvalidChange(String),
+    /// The properties given by the user refer to a task which does not exist,
+    /// given by its ID or UUID
+    UnknownTask(String),
+}
+
+impl StoreError {
+    pub(crate) fn io(path: &str, error: io::Error) -> Self {
+        StoreError::Io {
+            path: path.to_owned(),
+            error,
+        }
+    }
+
+    pub(crate) fn parse(path: &str, error: serde_json::Error) -> Self {
+        // The position is part of the message of serde_json, it is kept apart
+        let position = format!(" at line {} column {}", error.line(), error.column());
+        let message = error.to_string();
+        StoreError::Parse {
+            path: path.to_owned(),
+            line: error.line(),
+            column: error.column(),
+            message: message
+                .strip_suffix(&position)
+                .unwrap_or(&message)
+                .to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StoreError::Io { path, error } => write!(f, "Unable to access {}: {}", path, error),
+            StoreError::Parse {
+                path,
+                line,
+                column,
+                message,
+            } => write!(
+                f,
+                "Unable to parse {} at line {}, column {}: {}\n\
+                Fix the file by hand, or restore it from a backup.",
+                path, line, column, message
+            ),
+            StoreError::Database(message) => {
+                write!(f, "Error accessing the task database: {}", message)
+            }
+            StoreError::InvalidChange(message) => write!(f, "{}", message),
+            StoreError::UnknownTask(identifier) => {
+                write!(f, "There is no task with the identifier {}.", identifier)
+            }
+            StoreError::InvalidTasks(message) => {
+                write!(
+                    f,
+                    "{}\nRun 'bee doctor' to check the tasks and repair them.",
+                    message
+                )
+            }
+        }
+    }
+}
+
 pub trait Store {
+    /// Load the tasks matching @filter and the ones referenced by @props, @clock
+    /// giving the time of the changes made to them
     #[allow(clippy::borrowed_box)]
     fn load_tasks(
         filter: Option<&Box<dyn Filter>>,
         props: Option<TaskProperties>,
-    ) -> Result<TaskData, String>;
+        clock: Arc<dyn Clock>,
+    ) -> Result<TaskData, StoreError>;
     /// Will write the task and return the TaskData written
-    fn write_tasks(data: &TaskData) -> Result<TaskData, String>;
-    fn load_undos(last_count: usize) -> Vec<ActionUndo>;
-    fn log_undo(count: usize, updated_undos: Vec<ActionUndo>);
+    fn write_tasks(data: &TaskData) -> Result<TaskData, StoreError>;
+    fn load_undos(last_count: usize) -> Result<Vec<ActionUndo>, StoreError>;
+    /// Replace the last @count undos with @updated_undos, then drop the oldest undos
+    /// so that at most @history_depth of them are kept.
+    fn log_undo(
+        count: usize,
+        updated_undos: Vec<ActionUndo>,
+        history_depth: usize,
+    ) -> Result<(), StoreError>;
+    /// Load all the tasks as they are stored, without checking that they are
+    /// consistent. This is what 'bee doctor' repairs.
+    fn load_raw_tasks() -> Result<TaskData, StoreError>;
 }
 
 #[derive(Default)]
@@ -37,178 +135,247 @@ impl Store for JsonStore {
     fn load_tasks(
         filter: Option<&Box<dyn Filter>>,
         props: Option<TaskProperties>,
-    ) -> Result<TaskData, String> {
+        clock: Arc<dyn Clock>,
+    ) -> Result<TaskData, StoreError> {
         debug!(
             "Loading tasks using filter:\n{}",
             &filter.unwrap_or(&filters::new_empty()).to_string()
         );
-        let mut data = match find_data_file() {
-            Ok(data_file) => {
-                serde_json::from_str(&fs::read_to_string(data_file).expect("unable to read file"))
-                    .unwrap()
-            }
-            Err(_) => TaskData::default(),
-        };
-
-        data.upkeep()?;
-
-        // We need to keep some knowledge of how the ids map to the uuids
-        let mut id_to_uuid = HashMap::<usize, Uuid>::default();
-        for task in data
-            .get_task_map()
-            .values()
-            .filter(|t| t.get_id().is_some())
-        {
-            id_to_uuid.insert(task.get_id().unwrap(), *task.get_uuid());
-        }
-        for (id, uuid) in id_to_uuid.iter() {
-            data.insert_id_to_uuid(*id, *uuid);
-        }
-
-        // Load extra UUIDs from loaded tasks
-        let mut new_data = if let Some(filter) = filter {
-            let mut filter_mut = filter.clone();
-            filter_mut.convert_id_to_uuid(&id_to_uuid);
-            data.filter(&filter_mut)
-        } else {
-            data.to_owned()
-        };
-        debug!(
-            "Loaded {} tasks (out of {} total tasks).",
-            new_data.get_task_map().len(),
-            data.get_task_map().len()
-        );
-
-        // TODO: I need to get all the tasks that can be possibly reached from the
-        // filtered tasks. Currently I am only reaching the classes that are first
-        // degree neighbour of my filtered tasks.
-        // Allowing all tasks will allow to update their field accordingly when we're
-        // going dependency update and such.
-        let extra_uuids: Vec<_> = new_data
-            .get_task_map()
-            .values()
-            .flat_map(|task| task.get_extra_uuid())
-            .collect();
-
-        // Load extra uuids from the TaskProperties
-        if let Some(props) = props {
-            for task_identifier in props.get_referenced_tasks() {
-                match task_identifier {
-                    DependsOnIdentifier::Uuid(uuid) => {
-                        debug!("Adding extra task with uuid {} from TaskProperties", uuid);
-                        new_data.insert_extra_task(data.get_owned(&uuid).unwrap())
-                    }
-                    DependsOnIdentifier::Usize(id) => {
-                        if let Some(uuid) = id_to_uuid.get(&id) {
-                            debug!(
-                                "Adding extra task with id {} and uuid {} from TaskProperties",
-                                id, uuid
-                            );
-                            new_data.insert_extra_task(data.get_owned(uuid).unwrap())
-                        } else {
-                            unreachable!("Could not find task with id {}", id);
-                        }
-                    }
-                }
-            }
-        }
+        let mut data = Self::load_raw_tasks()?;
+        data.set_clock(clock);
+        data.upkeep().map_err(StoreError::InvalidTasks)?;
+        select_tasks(data, filter, props)
+    }
 
-        for uuid in extra_uuids {
-            let task = data.get_owned(&uuid).unwrap();
-            debug!(
-                "Adding extra task with id {:?} and uuid {} as extra task",
-                task.get_id(),
-                uuid
-            );
-            new_data.insert_extra_task(data.get_owned(&uuid).unwrap())
+    fn load_raw_tasks() -> Result<TaskData, StoreError> {
+        match find_data_file() {
+            Ok(data_file) => read_json_file(&data_file),
+            Err(_) => Ok(TaskData::default()),
         }
-
-        Ok(new_data)
     }
 
-    fn write_tasks(data: &TaskData) -> Result<TaskData, String> {
-        let mut stored_tasks = Self::load_tasks(None, None)?;
+    fn write_tasks(data: &TaskData) -> Result<TaskData, StoreError> {
+        // The tasks that were not loaded must be kept. Nothing can have changed them
+        // since they were loaded as long as the caller holds the lock from `lock_data`.
+        // They are only checked once @data is merged in, since it might repair them.
+        let mut stored_tasks = Self::load_raw_tasks()?;
+        stored_tasks.set_clock(data.get_clock().clone());
+        let changed_dependencies = get_changed_dependencies(stored_tasks.get_task_map(), data);
         for t in data.get_task_map().values() {
             stored_tasks.set_task(t.clone());
         }
-        stored_tasks.upkeep()?;
+        stored_tasks.upkeep().map_err(StoreError::InvalidTasks)?;
+        for uuid in changed_dependencies {
+            stored_tasks
+                .check_dependency_cycle(&uuid)
+                .map_err(StoreError::InvalidChange)?;
+        }
 
         let tasks_as_json =
             serde_json::to_string_pretty(&stored_tasks).expect("Failed to serialize tasks to JSON");
 
-        match find_data_file() {
-            Ok(_) => (),
-            Err(_) => create_path_if_not_exist(&get_data_file_path()),
-        }
-
-        let data_file = find_data_file().expect("Failed to find data file");
-
-        fs::write(data_file, tasks_as_json).expect("Could not write data file");
+        let data_file = find_data_file().unwrap_or_else(|_| get_data_file_path());
+        write_atomically(&data_file, &tasks_as_json)?;
 
         Ok(stored_tasks)
     }
 
-    fn load_undos(last_count: usize) -> Vec<ActionUndo> {
-        match find_logged_file() {
-            Ok(data_file) => {
-                let undos: Vec<ActionUndo> = serde_json::from_str(
-                    &fs::read_to_string(data_file).expect("unable to read file"),
-                )
-                .unwrap();
-                let len = undos.len();
-                if last_count >= len {
-                    undos[..].to_vec()
-                } else {
-                    undos[len - last_count..].to_vec()
-                }
-            }
+    fn load_undos(last_count: usize) -> Result<Vec<ActionUndo>, StoreError> {
+        let undos: Vec<ActionUndo> = match find_logged_file() {
+            Ok(data_file) => read_json_file(&data_file)?,
             Err(_) => Vec::default(),
+        };
+        let len = undos.len();
+        if last_count >= len {
+            Ok(undos)
+        } else {
+            Ok(undos[len - last_count..].to_vec())
         }
     }
 
-    fn log_undo(count: usize, updated_undos: Vec<ActionUndo>) {
-        // Assuming find_logged_file and create_path_if_not_exist functions exist
-        let data_file = match find_logged_file() {
-            Ok(file) => file,
-            Err(_) => {
-                create_path_if_not_exist(&get_logged_tasks_file_path());
-                find_logged_file().expect("Failed to find or create logged file")
-            }
-        };
-
-        let mut undos: Vec<ActionUndo> = Vec::new();
+    fn log_undo(
+        count: usize,
+        updated_undos: Vec<ActionUndo>,
+        history_depth: usize,
+    ) -> Result<(), StoreError> {
+        let data_file = find_logged_file().unwrap_or_else(|_| get_logged_tasks_file_path());
 
-        if let Ok(mut file) = fs::File::open(&data_file) {
-            let mut data = String::new();
-            file.read_to_string(&mut data)
-                .expect("Failed to read data file");
-            if !data.is_empty() {
-                undos = serde_json::from_str(&data).expect("Failed to parse JSON");
-            }
-        }
+        let mut undos: Vec<ActionUndo> = match find_logged_file() {
+            Ok(_) => read_json_file(&data_file)?,
+            Err(_) => Vec::new(),
+        };
 
         if undos.len() <= count {
             undos = updated_undos;
         } else {
             undos.splice(undos.len() - count.., updated_undos);
         }
+        if undos.len() > history_depth {
+            undos.drain(..undos.len() - history_depth);
+        }
 
         let updated_data = serde_json::to_string_pretty(&undos).expect("Failed to serialize data");
-        fs::write(&data_file, updated_data).expect("Failed to write to data file");
+        write_atomically(&data_file, &updated_data)
+    }
+}
+
+/// Deserialise the JSON file at @path. An empty file is read as the default value.
+fn read_json_file<T: DeserializeOwned + Default>(path: &str) -> Result<T, StoreError> {
+    let content = fs::read_to_string(path).map_err(|e| StoreError::io(path, e))?;
+    if content.trim().is_empty() {
+        return Ok(T::default());
+    }
+    serde_json::from_str(&content).map_err(|e| StoreError::parse(path, e))
+}
+
+/// Name of the active context. It is kept in the data directory whatever the
+/// storage backend.
+pub fn load_active_context() -> Result<Option<String>, StoreError> {
+    match find_context_file() {
+        Ok(file) => read_json_file(&file),
+        Err(_) => Ok(None),
     }
 }
 
-// Function to create a path if it doesn't exist
-fn create_path_if_not_exist(path: &str) {
-    fs::create_dir_all(
-        Path::new(path)
-            .parent()
-            .expect("Failed to get directory part of path"),
-    )
-    .expect("Failed to create directories");
-
-    fs::File::create(path).expect("Failed to create or truncate the file");
-    // File is automatically closed when it goes out of scope
+pub fn write_active_context(context: Option<&str>) -> Result<(), StoreError> {
+    let file = get_context_file_path();
+    let content = serde_json::to_string(&context).expect("Failed to serialize the context");
+    write_atomically(&file, &content)
+}
+
+/// Advisory lock on the data of bee, released when it is dropped. It is taken
+/// for the whole run of a command, so that another bee process cannot write the
+/// tasks between the moment they are loaded and the moment they are written.
+pub struct DataLock {
+    _file: fs::File,
+}
+
+/// Take the lock on the data of bee, waiting for the other bee processes to
+/// release it. This applies to every storage backend.
+pub fn lock_data() -> Result<DataLock, StoreError> {
+    lock_file(&get_lock_file_path())
+}
+
+fn lock_file(path: &str) -> Result<DataLock, StoreError> {
+    create_parent_dir(path)?;
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)
+        .map_err(|e| StoreError::io(path, e))?;
+    match file.try_lock() {
+        Ok(()) => (),
+        Err(fs::TryLockError::WouldBlock) => {
+            debug!("Waiting for another bee process to release {}", path);
+            file.lock().map_err(|e| StoreError::io(path, e))?;
+        }
+        Err(fs::TryLockError::Error(e)) => return Err(StoreError::io(path, e)),
+    }
+    Ok(DataLock { _file: file })
+}
+
+/// Replace the content of the file at @path with @content. The content is written
+/// to a temporary file which is then renamed, so that the file is never left
+/// half written.
+fn write_atomically(path: &str, content: &str) -> Result<(), StoreError> {
+    create_parent_dir(path)?;
+    let tmp_path = format!("{}.tmp", path);
+    let write_tmp = || -> io::Result<()> {
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()
+    };
+    write_tmp().map_err(|e| StoreError::io(&tmp_path, e))?;
+    fs::rename(&tmp_path, path).map_err(|e| StoreError::io(path, e))
+}
+
+/// Select the tasks matching @filter out of @data, which must hold every task the
+/// filtered tasks may refer to. The tasks referenced by @props and the ones linked
+/// to the filtered tasks, directly or through other tasks, are kept as extra tasks.
+#[allow(clippy::borrowed_box)]
+pub(crate) fn select_tasks(
+    mut data: TaskData,
+    filter: Option<&Box<dyn Filter>>,
+    props: Option<TaskProperties>,
+) -> Result<TaskData, StoreError> {
+    // We need to keep some knowledge of how the ids map to the uuids
+    let mut id_to_uuid = HashMap::<usize, Uuid>::default();
+    for task in data
+        .get_task_map()
+        .values()
+        .filter(|t| t.get_id().is_some())
+    {
+        id_to_uuid.insert(task.get_id().unwrap(), *task.get_uuid());
+    }
+    for (id, uuid) in id_to_uuid.iter() {
+        data.insert_id_to_uuid(*id, *uuid);
+    }
+
+    // The filtered tasks come with every task they are linked to
+    let mut new_data = if let Some(filter) = filter {
+        let mut filter_mut = filter.clone();
+        filter_mut.convert_id_to_uuid(&id_to_uuid);
+        data.filter(&filter_mut)
+    } else {
+        data.to_owned()
+    };
+    debug!(
+        "Loaded {} tasks (out of {} total tasks).",
+        new_data.get_task_map().len(),
+        data.get_task_map().len()
+    );
+
+    // Load extra uuids from the TaskProperties
+    let mut referenced_uuids = Vec::new();
+    if let Some(props) = props {
+        for task_identifier in props.get_referenced_tasks() {
+            let uuid = match task_identifier {
+                TaskIdentifier::Uuid(uuid) => uuid,
+                TaskIdentifier::Usize(id) => *id_to_uuid
+                    .get(&id)
+                    .ok_or(StoreError::UnknownTask(id.to_string()))?,
+            };
+            if data.get_owned(&uuid).is_none() {
+                return Err(StoreError::UnknownTask(uuid.to_string()));
+            }
+            debug!("Adding extra task with uuid {} from TaskProperties", uuid);
+            referenced_uuids.push(uuid);
+        }
+    }
+    new_data.insert_linked_extra_tasks(&data, &referenced_uuids);
+
+    Ok(new_data)
+}
+
+/// Get the UUIDs of the tasks in @data whose dependencies differ from the ones
+/// they have in @stored_tasks.
+///
+/// Only those need to be checked for circular dependencies, so that a cycle that
+/// was stored before they were detected doesn't prevent modifying its tasks.
+pub(crate) fn get_changed_dependencies(
+    stored_tasks: &HashMap<Uuid, Task>,
+    data: &TaskData,
+) -> Vec<Uuid> {
+    data.get_task_map()
+        .values()
+        .filter(|t| {
+            stored_tasks
+                .get(t.get_uuid())
+                .is_none_or(|stored| stored.get_depends() != t.get_depends())
+        })
+        .map(|t| *t.get_uuid())
+        .collect()
+}
+
+/// Create the directory containing @path if it doesn't exist
+pub(crate) fn create_parent_dir(path: &str) -> Result<(), StoreError> {
+    match Path::new(path).parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => {
+            fs::create_dir_all(dir).map_err(|e| StoreError::io(&dir.to_string_lossy(), e))
+        }
+        _ => Ok(()),
+    }
 }
 
 // FileSystem trait for abstracting file system operations
@@ -238,7 +405,7 @@ impl Env for RealEnv {
 }
 
 // Function to find data file
-fn find_data_file() -> Result<String, io::Error> {
+pub(crate) fn find_data_file() -> Result<String, io::Error> {
     get_data_file_impl(&RealFileSystem, &RealEnv, "bee-data.json", true)
 }
 
@@ -251,10 +418,26 @@ fn get_logged_tasks_file_path() -> String {
         .unwrap_or_default()
 }
 
-fn find_logged_file() -> Result<String, io::Error> {
+pub(crate) fn find_logged_file() -> Result<String, io::Error> {
     get_data_file_impl(&RealFileSystem, &RealEnv, "bee-logged-tasks.json", true)
 }
 
+fn get_lock_file_path() -> String {
+    get_data_file_impl(&RealFileSystem, &RealEnv, "bee-data.lock", false).unwrap_or_default()
+}
+
+fn find_context_file() -> Result<String, io::Error> {
+    get_data_file_impl(&RealFileSystem, &RealEnv, "bee-context.json", true)
+}
+
+fn get_context_file_path() -> String {
+    get_data_file_impl(&RealFileSystem, &RealEnv, "bee-context.json", false).unwrap_or_default()
+}
+
+pub(crate) fn get_sqlite_file_path() -> String {
+    get_data_file_impl(&RealFileSystem, &RealEnv, "bee-data.sqlite", false).unwrap_or_default()
+}
+
 // getDataFileImpl provides utility to find where we store the file on the filesystem
 fn get_data_file_impl<'a>(
     fs: &(impl FileSystem + 'a),
@@ -262,7 +445,12 @@ fn get_data_file_impl<'a>(
     filename: &str,
     find_file_only: bool,
 ) -> Result<String, io::Error> {
-    if filename != "bee-data.json" && filename != "bee-logged-tasks.json" {
+    if filename != "bee-data.json"
+        && filename != "bee-logged-tasks.json"
+        && filename != "bee-data.sqlite"
+        && filename != "bee-context.json"
+        && filename != "bee-data.lock"
+    {
         panic!("Invalid filename given to 'get_data_file_impl'");
     }
 
@@ -188,3 +188,63 @@ fn test_find_data_file_with_custom_data_home() {
     let path = get_data_file_impl(&mock_fs, &mock_env, "bee-data.json", true);
     assert_eq!(path.unwrap(), "/custom/bee/bee-data.json");
 }
+
+/// Unique path in the temporary directory for the test called @name
+#[cfg(test)]
+fn get_tmp_path(name: &str) -> String {
+    std::env::temp_dir()
+        .join(format!("bee-{}-{}", name, uuid::Uuid::new_v4()))
+        .join("file.json")
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[test]
+fn test_write_atomically() {
+    let path = get_tmp_path("write");
+    write_atomically(&path, "first").unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "first");
+
+    write_atomically(&path, "second").unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+    assert!(fs::metadata(format!("{}.tmp", path)).is_err());
+
+    fs::remove_dir_all(Path::new(&path).parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_lock_file() {
+    let path = get_tmp_path("lock");
+    let lock = lock_file(&path).unwrap();
+
+    let other = fs::OpenOptions::new().write(true).open(&path).unwrap();
+    assert!(other.try_lock().is_err());
+
+    drop(lock);
+    assert!(other.try_lock().is_ok());
+
+    fs::remove_dir_all(Path::new(&path).parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_read_json_file_errors() {
+    let path = get_tmp_path("parse");
+    match read_json_file::<Vec<Task>>(&path) {
+        Err(StoreError::Io {
+            path: error_path, ..
+        }) => assert_eq!(error_path, path),
+        other => panic!("Expected an IO error, got {:?}", other),
+    }
+
+    write_atomically(&path, "[\n  {\"uuid\": }\n]").unwrap();
+    match read_json_file::<Vec<Task>>(&path) {
+        Err(StoreError::Parse { line, column, .. }) => assert_eq!((line, column), (2, 12)),
+        other => panic!("Expected a parse error, got {:?}", other),
+    }
+
+    // An empty file is read as no task
+    write_atomically(&path, "").unwrap();
+    assert!(read_json_file::<Vec<Task>>(&path).unwrap().is_empty());
+
+    fs::remove_dir_all(Path::new(&path).parent().unwrap()).unwrap();
+}